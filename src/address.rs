@@ -0,0 +1,530 @@
+//! Bitcoin address encodings, checked locally: Base58Check for legacy
+//! addresses and extended keys, bech32 and bech32m for SegWit (BIP 173, BIP 350).
+//!
+//! Only mainnet addresses are accepted.
+
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use thiserror::Error;
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// Longest bech32 string BIP 173 allows
+const BECH32_MAX_LENGTH: usize = 90;
+const BECH32_CHECKSUM_LENGTH: usize = 6;
+
+const MAINNET_HRP: &str = "bc";
+/// Human-readable parts of test networks, rejected with a clearer error
+const TEST_HRPS: [&str; 2] = ["tb", "bcrt"];
+const P2PKH_VERSION: u8 = 0x00;
+const P2SH_VERSION: u8 = 0x05;
+/// Testnet P2PKH and P2SH version bytes
+const TEST_VERSIONS: [u8; 2] = [0x6f, 0xc4];
+
+/// Why an address was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AddressError {
+    #[error("'{0}' is not allowed in this encoding")]
+    BadCharacter(char),
+    #[error("Checksum does not match; check for typos")]
+    BadChecksum,
+    #[error("Mixes upper and lower case")]
+    MixedCase,
+    #[error("Wrong length for this address type")]
+    BadLength,
+    #[error("Testnet addresses are not supported")]
+    WrongNetwork,
+    #[error("SegWit version {0} must use the {1} checksum")]
+    WrongVariant(u8, Variant),
+    #[error("Not a Bitcoin address")]
+    Unknown,
+}
+
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+/// RIPEMD-160 of SHA-256, the hash in P2PKH, P2SH and P2WPKH outputs
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+/// Base58 of `payload` followed by its 4-byte double-SHA-256 checksum
+pub fn base58check_encode(payload: &[u8]) -> String {
+    let mut data = payload.to_vec();
+    data.extend_from_slice(&sha256d(payload)[..4]);
+
+    // Repeated division of the big-endian number by 58
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in &data {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&d| BASE58_ALPHABET[d as usize] as char),
+        )
+        .collect()
+}
+
+/// Payload of a Base58Check string, with the checksum verified and removed
+pub fn base58check_decode(input: &str) -> Result<Vec<u8>, AddressError> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in input.chars() {
+        let value = BASE58_ALPHABET
+            .iter()
+            .position(|&a| a as char == c)
+            .ok_or(AddressError::BadCharacter(c))? as u32;
+        let mut carry = value;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = input.chars().take_while(|&c| c == '1').count();
+    let mut data = vec![0u8; zeros];
+    data.extend(bytes.iter().rev());
+
+    if data.len() < 4 {
+        return Err(AddressError::BadLength);
+    }
+    let (payload, checksum) = data.split_at(data.len() - 4);
+    if sha256d(payload)[..4] != *checksum {
+        return Err(AddressError::BadChecksum);
+    }
+    Ok(payload.to_vec())
+}
+
+/// Bech32 checksum flavour: BIP 173 for SegWit v0, BIP 350 for v1 and later
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc8_30a3,
+        }
+    }
+
+    fn for_version(version: u8) -> Self {
+        if version == 0 {
+            Variant::Bech32
+        } else {
+            Variant::Bech32m
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Variant::Bech32 => "bech32",
+            Variant::Bech32m => "bech32m",
+        })
+    }
+}
+
+fn polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    values.into_iter().fold(1u32, |chk, value| {
+        let top = chk >> 25;
+        let chk = ((chk & 0x01ff_ffff) << 5) ^ value as u32;
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, g)| chk ^ g)
+    })
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.as_bytes();
+    bytes
+        .iter()
+        .map(|b| b >> 5)
+        .chain([0])
+        .chain(bytes.iter().map(|b| b & 31))
+        .collect()
+}
+
+/// Regroups bits, e.g. bytes into the 5-bit groups bech32 encodes
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let max = (1u32 << to) - 1;
+    let mut out = Vec::new();
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return None;
+        }
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+/// Bech32 string of 5-bit `data` under `hrp`
+pub fn bech32_encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let values: Vec<u8> = hrp_expand(hrp)
+        .into_iter()
+        .chain(data.iter().copied())
+        .chain([0; BECH32_CHECKSUM_LENGTH])
+        .collect();
+    let checksum = polymod(values) ^ variant.constant();
+    let checksum = (0..BECH32_CHECKSUM_LENGTH).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8);
+
+    let mut out = format!("{hrp}1");
+    out.extend(
+        data.iter()
+            .copied()
+            .chain(checksum)
+            .map(|d| BECH32_CHARSET[d as usize] as char),
+    );
+    out
+}
+
+/// Lowercase human-readable part, 5-bit data without the checksum, and
+/// which checksum matched
+fn bech32_decode(input: &str) -> Result<(String, Vec<u8>, Variant), AddressError> {
+    if input.len() > BECH32_MAX_LENGTH {
+        return Err(AddressError::BadLength);
+    }
+    let has_lower = input.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = input.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(AddressError::MixedCase);
+    }
+    let input = input.to_ascii_lowercase();
+    let separator = input.rfind('1').ok_or(AddressError::Unknown)?;
+    let (hrp, data) = (&input[..separator], &input[separator + 1..]);
+    if hrp.is_empty() || data.len() < BECH32_CHECKSUM_LENGTH {
+        return Err(AddressError::BadLength);
+    }
+
+    let data = data
+        .chars()
+        .map(|c| {
+            BECH32_CHARSET
+                .iter()
+                .position(|&b| b as char == c)
+                .map(|p| p as u8)
+                .ok_or(AddressError::BadCharacter(c))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    let residue = polymod(hrp_expand(hrp).into_iter().chain(data.iter().copied()));
+    let variant = [Variant::Bech32, Variant::Bech32m]
+        .into_iter()
+        .find(|v| v.constant() == residue)
+        .ok_or(AddressError::BadChecksum)?;
+    Ok((
+        hrp.to_string(),
+        data[..data.len() - BECH32_CHECKSUM_LENGTH].to_vec(),
+        variant,
+    ))
+}
+
+/// Output type an address pays to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressKind {
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    /// SegWit version reserved for future upgrades
+    FutureSegwit(u8),
+}
+
+impl AddressKind {
+    pub fn label(&self) -> String {
+        match self {
+            AddressKind::P2pkh => "P2PKH".to_string(),
+            AddressKind::P2sh => "P2SH".to_string(),
+            AddressKind::P2wpkh => "P2WPKH".to_string(),
+            AddressKind::P2wsh => "P2WSH".to_string(),
+            AddressKind::P2tr => "P2TR".to_string(),
+            AddressKind::FutureSegwit(version) => format!("SegWit v{version}"),
+        }
+    }
+}
+
+/// A checked mainnet address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    /// Canonical text: bech32 addresses lowercased
+    text: String,
+    kind: AddressKind,
+}
+
+impl Address {
+    /// Validates `input`, checksum included
+    pub fn parse(input: &str) -> Result<Self, AddressError> {
+        let input = input.trim();
+        let lower = input.to_ascii_lowercase();
+        let bech32 = std::iter::once(MAINNET_HRP)
+            .chain(TEST_HRPS)
+            .any(|hrp| lower.starts_with(&format!("{hrp}1")));
+        if bech32 {
+            Self::parse_segwit(input)
+        } else {
+            Self::parse_base58(input)
+        }
+    }
+
+    fn parse_base58(input: &str) -> Result<Self, AddressError> {
+        let payload = base58check_decode(input)?;
+        let kind = match payload.first() {
+            _ if payload.len() != 21 => return Err(AddressError::BadLength),
+            Some(&P2PKH_VERSION) => AddressKind::P2pkh,
+            Some(&P2SH_VERSION) => AddressKind::P2sh,
+            Some(version) if TEST_VERSIONS.contains(version) => {
+                return Err(AddressError::WrongNetwork)
+            }
+            _ => return Err(AddressError::Unknown),
+        };
+        Ok(Self {
+            text: input.to_string(),
+            kind,
+        })
+    }
+
+    fn parse_segwit(input: &str) -> Result<Self, AddressError> {
+        let (hrp, data, variant) = bech32_decode(input)?;
+        if hrp != MAINNET_HRP {
+            return Err(AddressError::WrongNetwork);
+        }
+        let (&version, program) = data.split_first().ok_or(AddressError::BadLength)?;
+        if version > 16 {
+            return Err(AddressError::Unknown);
+        }
+        if variant != Variant::for_version(version) {
+            return Err(AddressError::WrongVariant(
+                version,
+                Variant::for_version(version),
+            ));
+        }
+        let program = convert_bits(program, 5, 8, false).ok_or(AddressError::BadLength)?;
+        let kind = match (version, program.len()) {
+            (_, len) if !(2..=40).contains(&len) => return Err(AddressError::BadLength),
+            (0, 20) => AddressKind::P2wpkh,
+            (0, 32) => AddressKind::P2wsh,
+            (0, _) => return Err(AddressError::BadLength),
+            (1, 32) => AddressKind::P2tr,
+            (version, _) => AddressKind::FutureSegwit(version),
+        };
+        Ok(Self {
+            text: input.to_ascii_lowercase(),
+            kind,
+        })
+    }
+
+    /// Pay-to-public-key-hash address for a key hash
+    pub fn p2pkh(key_hash: &[u8; 20]) -> Self {
+        Self::base58(P2PKH_VERSION, key_hash, AddressKind::P2pkh)
+    }
+
+    /// Pay-to-script-hash address for a script hash
+    pub fn p2sh(script_hash: &[u8; 20]) -> Self {
+        Self::base58(P2SH_VERSION, script_hash, AddressKind::P2sh)
+    }
+
+    /// Native SegWit v0 address for a key hash
+    pub fn p2wpkh(key_hash: &[u8; 20]) -> Self {
+        let mut data = vec![0u8];
+        data.extend(convert_bits(key_hash, 8, 5, true).expect("bytes fit in 8 bits"));
+        Self {
+            text: bech32_encode(MAINNET_HRP, &data, Variant::Bech32),
+            kind: AddressKind::P2wpkh,
+        }
+    }
+
+    fn base58(version: u8, hash: &[u8; 20], kind: AddressKind) -> Self {
+        let mut payload = vec![version];
+        payload.extend_from_slice(hash);
+        Self {
+            text: base58check_encode(&payload),
+            kind,
+        }
+    }
+
+    pub fn kind(&self) -> AddressKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn kind(input: &str) -> Result<AddressKind, AddressError> {
+        Address::parse(input).map(|a| a.kind())
+    }
+
+    /// Encodes a witness program with a chosen checksum, right or wrong
+    fn segwit(version: u8, program: &[u8], variant: Variant) -> String {
+        let mut data = vec![version];
+        data.extend(convert_bits(program, 8, 5, true).unwrap());
+        bech32_encode(MAINNET_HRP, &data, variant)
+    }
+
+    #[test]
+    fn base58_addresses() {
+        assert_eq!(
+            kind("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"),
+            Ok(AddressKind::P2pkh)
+        );
+        assert_eq!(
+            kind("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy"),
+            Ok(AddressKind::P2sh)
+        );
+        assert_eq!(
+            kind("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb"),
+            Err(AddressError::BadChecksum)
+        );
+        assert_eq!(
+            kind("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfN0"),
+            Err(AddressError::BadCharacter('0'))
+        );
+        assert_eq!(
+            kind("mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn"),
+            Err(AddressError::WrongNetwork)
+        );
+    }
+
+    #[test]
+    fn base58check_round_trips_leading_zeros() {
+        let hash = from_hex("62e907b15cbf27d5425399ebf6f0fb50ebb88f18");
+        let address = Address::p2pkh(&hash.clone().try_into().unwrap());
+        assert_eq!(address.to_string(), "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+        let payload = base58check_decode(&address.to_string()).unwrap();
+        assert_eq!(payload[0], 0);
+        assert_eq!(payload[1..], hash[..]);
+    }
+
+    #[test]
+    fn bech32_v0_addresses() {
+        assert_eq!(
+            kind("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4"),
+            Ok(AddressKind::P2wpkh)
+        );
+        assert_eq!(
+            Address::parse("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4")
+                .unwrap()
+                .to_string(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            kind("bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3"),
+            Ok(AddressKind::P2wsh)
+        );
+        assert_eq!(
+            kind("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Err(AddressError::BadChecksum)
+        );
+        assert_eq!(
+            kind("bc1qw508d6qejxtdg4y5r3zarvAry0c5xw7kv8f3t4"),
+            Err(AddressError::MixedCase)
+        );
+        assert_eq!(
+            kind("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"),
+            Err(AddressError::WrongNetwork)
+        );
+    }
+
+    #[test]
+    fn bech32m_addresses() {
+        assert_eq!(
+            kind("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"),
+            Ok(AddressKind::P2tr)
+        );
+        assert_eq!(
+            kind(&segwit(2, &[0x75, 0x1e], Variant::Bech32m)),
+            Ok(AddressKind::FutureSegwit(2))
+        );
+    }
+
+    #[test]
+    fn checksum_variant_must_match_the_version() {
+        let program = [7u8; 32];
+        assert_eq!(
+            kind(&segwit(1, &program, Variant::Bech32)),
+            Err(AddressError::WrongVariant(1, Variant::Bech32m))
+        );
+        assert_eq!(
+            kind(&segwit(0, &program[..20], Variant::Bech32m)),
+            Err(AddressError::WrongVariant(0, Variant::Bech32))
+        );
+        assert_eq!(
+            kind(&segwit(0, &program[..25], Variant::Bech32)),
+            Err(AddressError::BadLength)
+        );
+    }
+
+    #[test]
+    fn key_hash_encodings() {
+        // hash160 of the generator point G, compressed
+        let key = from_hex("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+        let hash = hash160(&key);
+        assert_eq!(
+            Address::p2wpkh(&hash).to_string(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            Address::p2pkh(&hash).to_string(),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+    }
+}
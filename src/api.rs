@@ -1,30 +1,371 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{LazyLock, RwLock};
+use std::time::Instant;
 use tracing::{debug, error, info, warn};
 
-use crate::http_client::CLIENT;
+use crate::candles;
+use crate::country::{Country, CountryPrice};
+use crate::fx_matrix::FxMatrix;
+use crate::history::{unix_now, SeriesPoint};
+use crate::http_client::{HttpError, Transport, TRANSPORT};
+use crate::metrics::METRICS;
+use crate::store::Snapshot;
+
+/// CoinGecko API key from the environment, overriding the stored one
+pub const API_KEY_ENV: &str = "IFB_COINGECKO_API_KEY";
+/// `demo` (default) or `pro`, the plan of [`API_KEY_ENV`]
+pub const API_PLAN_ENV: &str = "IFB_COINGECKO_PLAN";
+
+/// Host used by the public and Demo plans
+const PUBLIC_BASE_URL: &str = "https://api.coingecko.com/api/v3";
+/// Host used by paid plans
+const PRO_BASE_URL: &str = "https://pro-api.coingecko.com/api/v3";
+/// Currency every snapshot must quote
+const REFERENCE_CURRENCY: &str = "usd";
+
+/// CoinGecko access plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiPlan {
+    /// Keyless public endpoint (heavily rate limited)
+    #[default]
+    Public,
+    Demo,
+    Pro,
+}
+
+impl ApiPlan {
+    pub const ALL: [ApiPlan; 3] = [ApiPlan::Public, ApiPlan::Demo, ApiPlan::Pro];
+
+    pub fn base_url(self) -> &'static str {
+        match self {
+            ApiPlan::Public | ApiPlan::Demo => PUBLIC_BASE_URL,
+            ApiPlan::Pro => PRO_BASE_URL,
+        }
+    }
+
+    /// Header carrying the key, if the plan uses one
+    pub fn key_header(self) -> Option<&'static str> {
+        match self {
+            ApiPlan::Public => None,
+            ApiPlan::Demo => Some("x-cg-demo-api-key"),
+            ApiPlan::Pro => Some("x-cg-pro-api-key"),
+        }
+    }
+}
+
+impl fmt::Display for ApiPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ApiPlan::Public => "Public (no key)",
+            ApiPlan::Demo => "Demo",
+            ApiPlan::Pro => "Pro",
+        })
+    }
+}
+
+/// A CoinGecko Demo or Pro key
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub plan: ApiPlan,
+    pub key: String,
+}
+
+// Hand-written so the key never ends up in logs
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("plan", &self.plan)
+            .field("key", &"***")
+            .finish()
+    }
+}
+
+impl ApiKey {
+    /// `None` for the public plan or a blank key
+    pub fn new(plan: ApiPlan, key: &str) -> Option<Self> {
+        let key = key.trim();
+        (plan != ApiPlan::Public && !key.is_empty()).then(|| Self {
+            plan,
+            key: key.to_string(),
+        })
+    }
+
+    /// Key from [`API_KEY_ENV`] and [`API_PLAN_ENV`], if set
+    pub fn from_env() -> Option<Self> {
+        let key = std::env::var(API_KEY_ENV).ok()?;
+        let plan = match std::env::var(API_PLAN_ENV).ok().as_deref() {
+            Some("pro") => ApiPlan::Pro,
+            Some("demo") | None => ApiPlan::Demo,
+            Some(other) => {
+                warn!("{API_PLAN_ENV}={other} is not `demo` or `pro`; assuming demo");
+                ApiPlan::Demo
+            }
+        };
+        Self::new(plan, &key)
+    }
+}
+
+/// Key used by [`fetch_btc_with`]; set at startup and from Settings
+static API_KEY: LazyLock<RwLock<Option<ApiKey>>> = LazyLock::new(|| RwLock::new(None));
+
+pub fn api_key() -> Option<ApiKey> {
+    API_KEY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn set_api_key(key: Option<ApiKey>) {
+    info!(
+        "CoinGecko plan: {}",
+        key.as_ref().map_or(ApiPlan::Public, |k| k.plan)
+    );
+    *API_KEY.write().unwrap_or_else(|e| e.into_inner()) = key;
+}
 
 /// Response structure from CoinGecko API
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CoinGeckoResponse {
     pub bitcoin: HashMap<String, f64>,
+    /// Provider-side quote time (Unix seconds), moved out of `bitcoin` after parsing
+    #[serde(skip)]
+    pub last_updated_at: Option<u64>,
+}
+
+impl CoinGeckoResponse {
+    /// Moves CoinGecko's `last_updated_at` entry out of the price map
+    fn extract_last_updated_at(mut self) -> Self {
+        self.last_updated_at = self
+            .bitcoin
+            .remove("last_updated_at")
+            .filter(|t| *t > 0.0)
+            .map(|t| t as u64);
+        self
+    }
+}
+
+/// Response of CoinGecko's `/coins/bitcoin/market_chart`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarketChart {
+    /// `[unix_millis, price]` pairs, oldest first
+    pub prices: Vec<(f64, f64)>,
+    /// `[unix_millis, volume]` pairs: trailing 24h traded volume at each time
+    #[serde(default)]
+    pub total_volumes: Vec<(f64, f64)>,
 }
 
-/// API-related errors
-#[derive(Debug, thiserror::Error)]
+impl MarketChart {
+    /// Prices as a series with Unix-second timestamps
+    pub fn series(&self) -> Vec<SeriesPoint> {
+        self.prices
+            .iter()
+            .map(|&(millis, price)| SeriesPoint {
+                timestamp: (millis / 1000.0) as u64,
+                price,
+            })
+            .collect()
+    }
+
+    /// Trailing 24h volumes as `(unix_seconds, volume)`
+    pub fn volumes(&self) -> Vec<(u64, f64)> {
+        self.total_volumes
+            .iter()
+            .map(|&(millis, volume)| ((millis / 1000.0) as u64, volume))
+            .collect()
+    }
+}
+
+/// One OHLC candle, as returned by `/coins/bitcoin/ohlc` or aggregated from
+/// recorded prices
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(from = "[f64; 5]")]
+pub struct Candle {
+    /// Unix seconds at which the candle closes
+    pub time: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Trailing 24h volume at the close, when known
+    pub volume: Option<f64>,
+}
+
+impl From<[f64; 5]> for Candle {
+    /// CoinGecko's `[unix_millis, open, high, low, close]` row
+    fn from([millis, open, high, low, close]: [f64; 5]) -> Self {
+        Self {
+            time: (millis / 1000.0) as u64,
+            open,
+            high,
+            low,
+            close,
+            volume: None,
+        }
+    }
+}
+
+/// Longest response body excerpt kept in an error
+const BODY_EXCERPT_LEN: usize = 300;
+
+/// API-related errors.
+///
+/// Variants keep structured fields (URL, status, body excerpt) rather than a
+/// pre-formatted string, so the UI can categorise the failure and offer
+/// diagnostics. The error is `Clone` so it can travel inside messages.
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum ApiError {
-    /// Network request failed
-    #[error("Network error: {0}")]
-    NetworkError(#[from] reqwest::Error),
+    /// Network request failed (offline, DNS, TLS, timeout, cassette miss)
+    #[error("Network error: {message}")]
+    NetworkError {
+        url: String,
+        message: String,
+        /// DNS failure or refused connection, i.e. the host was not reached at
+        /// all; a timeout is not one
+        unreachable: bool,
+    },
+    /// Server answered with a non-success status
+    #[error("HTTP {status} from CoinGecko")]
+    HttpStatus {
+        url: String,
+        status: u16,
+        body: String,
+    },
     /// Failed to parse JSON response
-    #[error("Failed to parse response: {0}")]
-    ParseError(String),
-    /// Invalid response format
-    #[error("Invalid response: {0}")]
-    InvalidResponse(String),
+    #[error("Failed to parse response: {message}")]
+    ParseError {
+        url: String,
+        message: String,
+        body: String,
+    },
+    /// Response parsed but lacks required data
+    #[error("Invalid response: {reason}")]
+    InvalidResponse { url: String, reason: String },
+}
+
+/// User-facing grouping of [`ApiError`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    Offline,
+    /// The request failed or timed out after reaching the network
+    Network,
+    RateLimited,
+    Server,
+    Parse,
+}
+
+impl ErrorCategory {
+    pub fn title(self) -> &'static str {
+        match self {
+            ErrorCategory::Offline => "You appear to be offline",
+            ErrorCategory::Network => "Network error",
+            ErrorCategory::RateLimited => "Rate limited by CoinGecko",
+            ErrorCategory::Server => "CoinGecko server error",
+            ErrorCategory::Parse => "Unexpected response",
+        }
+    }
+
+    pub fn hint(self) -> &'static str {
+        match self {
+            ErrorCategory::Offline => "CoinGecko could not be reached. Check your connection.",
+            ErrorCategory::Network => "The request failed or timed out. Try again shortly.",
+            ErrorCategory::RateLimited => "Too many requests. Wait a minute before retrying.",
+            ErrorCategory::Server => "The price service returned an error. Try again shortly.",
+            ErrorCategory::Parse => "The price service answered with data that could not be read.",
+        }
+    }
 }
 
-/// Fetches Bitcoin prices in the specified currencies
+impl ApiError {
+    fn network(url: &str, error: HttpError) -> Self {
+        ApiError::NetworkError {
+            url: url.to_string(),
+            unreachable: error.is_unreachable(),
+            message: error.to_string(),
+        }
+    }
+
+    /// Short, stable label for the variant (used as a metrics label)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ApiError::NetworkError { .. } => "network",
+            ApiError::HttpStatus { .. } => "http_status",
+            ApiError::ParseError { .. } => "parse",
+            ApiError::InvalidResponse { .. } => "invalid_response",
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ApiError::NetworkError { unreachable: true, .. } => ErrorCategory::Offline,
+            ApiError::NetworkError { .. } => ErrorCategory::Network,
+            ApiError::HttpStatus { status: 429, .. } => ErrorCategory::RateLimited,
+            ApiError::HttpStatus { .. } => ErrorCategory::Server,
+            ApiError::ParseError { .. } | ApiError::InvalidResponse { .. } => {
+                ErrorCategory::Parse
+            }
+        }
+    }
+
+    /// Whether the failure means the network itself is down
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, ApiError::NetworkError { unreachable: true, .. })
+    }
+
+    pub fn url(&self) -> &str {
+        match self {
+            ApiError::NetworkError { url, .. }
+            | ApiError::HttpStatus { url, .. }
+            | ApiError::ParseError { url, .. }
+            | ApiError::InvalidResponse { url, .. } => url,
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ApiError::HttpStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Start of the response body, when one was received
+    pub fn body_excerpt(&self) -> Option<&str> {
+        match self {
+            ApiError::HttpStatus { body, .. } | ApiError::ParseError { body, .. } => {
+                Some(body.as_str()).filter(|b| !b.is_empty())
+            }
+            _ => None,
+        }
+    }
+
+    /// Plain-text report suitable for pasting into a bug report
+    pub fn diagnostics(&self) -> String {
+        let mut report = format!(
+            "iced-fetch-bitcoin {}\nCategory: {}\nError: {self}\n",
+            env!("CARGO_PKG_VERSION"),
+            self.category().title()
+        );
+        if let Some(status) = self.status() {
+            report.push_str(&format!("Status: {status}\n"));
+        }
+        report.push_str(&format!("URL: {}\n", self.url()));
+        if let Some(body) = self.body_excerpt() {
+            report.push_str(&format!("Body: {body}\n"));
+        }
+        report
+    }
+}
+
+/// Truncates `text` to at most `max` characters, marking the cut
+fn excerpt(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+/// Fetches Bitcoin prices in the specified currencies over `transport`
+/// (live, or e.g. a replay cassette)
 ///
 /// # Arguments
 /// * `currencies` - List of currency codes to fetch prices for
@@ -32,65 +373,251 @@ pub enum ApiError {
 /// # Returns
 /// * `Ok((usd_price, response))` on success
 /// * `Err(ApiError)` on failure
-pub async fn fetch_btc(currencies: Vec<String>) -> Result<(f64, CoinGeckoResponse), ApiError> {
-    let currencies_string = currencies.join(",");
+pub async fn fetch_btc_with(
+    transport: &Transport,
+    currencies: Vec<String>,
+) -> Result<(f64, CoinGeckoResponse), ApiError> {
+    let started = Instant::now();
+    let result = fetch_btc_inner(transport, api_key().as_ref(), currencies).await;
+    match &result {
+        Ok((_, response)) => METRICS.record_fetch_success(started.elapsed(), &response.bitcoin),
+        Err(e) => METRICS.record_fetch_error(started.elapsed(), e.kind()),
+    }
+    result
+}
+
+/// Fetches prices for every given country, pairing each with its quote.
+///
+/// Once [`fetch_supported_vs_currencies`] has succeeded (the Bitcoin page
+/// asks at startup), currencies CoinGecko does not support are left out of
+/// the request. Unsupported currencies and quotes missing from the response
+/// have no price.
+pub async fn fetch_country_prices(countries: &[Country]) -> Result<Snapshot, ApiError> {
+    let supported = supported_vs_currencies();
+    fetch_country_prices_with(&TRANSPORT, countries, supported.as_ref()).await
+}
+
+/// Same as [`fetch_country_prices`], over an explicit transport and with the
+/// provider's currency list given
+async fn fetch_country_prices_with(
+    transport: &Transport,
+    countries: &[Country],
+    supported: Option<&HashSet<String>>,
+) -> Result<Snapshot, ApiError> {
+    let currencies = requested_currencies(countries, supported);
+    let (usd, response) = fetch_btc_with(transport, currencies).await?;
+    let prices = countries
+        .iter()
+        .map(|c| CountryPrice::new(c.clone(), response.bitcoin.get(&c.currency).copied()))
+        .collect();
+    Ok(Snapshot {
+        fetched_at: unix_now(),
+        provider_updated_at: response.last_updated_at,
+        usd,
+        prices,
+        cross_rates: FxMatrix::from_response(&response),
+    })
+}
+
+/// USD, which every fetch needs even when the catalogue hides it, then each
+/// supported catalogue currency
+fn requested_currencies(
+    countries: &[Country],
+    supported: Option<&HashSet<String>>,
+) -> Vec<String> {
+    let mut currencies = vec![REFERENCE_CURRENCY.to_string()];
+    currencies.extend(
+        countries
+            .iter()
+            .map(|c| &c.currency)
+            .filter(|c| *c != REFERENCE_CURRENCY)
+            .filter(|c| supported.is_none_or(|s| s.contains(*c)))
+            .cloned(),
+    );
+    currencies
+}
+
+/// Currencies from the last successful [`fetch_supported_vs_currencies`]
+static SUPPORTED_VS_CURRENCIES: LazyLock<RwLock<Option<HashSet<String>>>> =
+    LazyLock::new(|| RwLock::new(None));
+
+/// Cached provider currency list, if it has been fetched
+pub fn supported_vs_currencies() -> Option<HashSet<String>> {
+    SUPPORTED_VS_CURRENCIES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Fetches the currencies CoinGecko can quote BTC in and caches them for
+/// [`fetch_country_prices`]
+pub async fn fetch_supported_vs_currencies() -> Result<HashSet<String>, ApiError> {
+    let supported = fetch_supported_with(&TRANSPORT, api_key().as_ref()).await?;
+    *SUPPORTED_VS_CURRENCIES
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = Some(supported.clone());
+    Ok(supported)
+}
+
+/// Countries whose currency is missing from the provider's `supported` list
+pub fn unsupported_countries<'a>(
+    countries: &'a [Country],
+    supported: &HashSet<String>,
+) -> Vec<&'a Country> {
+    countries
+        .iter()
+        .filter(|c| !supported.contains(&c.currency))
+        .collect()
+}
+
+async fn fetch_supported_with(
+    transport: &Transport,
+    key: Option<&ApiKey>,
+) -> Result<HashSet<String>, ApiError> {
+    let plan = key.map_or(ApiPlan::Public, |k| k.plan);
+    let url = format!("{}/simple/supported_vs_currencies", plan.base_url());
+    info!("Fetching supported currencies ({plan} plan) from: {url}");
+
+    let currencies: Vec<String> = get_json(transport, key, &url).await?;
+    Ok(currencies.into_iter().map(|c| c.to_lowercase()).collect())
+}
+
+/// Fetches one daily BTC price per day for the last `days` days in `currency`
+pub async fn fetch_daily_prices(currency: String, days: u32) -> Result<Vec<SeriesPoint>, ApiError> {
+    fetch_daily_with(&TRANSPORT, api_key().as_ref(), &currency, days).await
+}
+
+async fn fetch_daily_with(
+    transport: &Transport,
+    key: Option<&ApiKey>,
+    currency: &str,
+    days: u32,
+) -> Result<Vec<SeriesPoint>, ApiError> {
+    let plan = key.map_or(ApiPlan::Public, |k| k.plan);
     let url = format!(
-        "https://api.coingecko.com/api/v3/simple/price?ids=bitcoin&vs_currencies={}",
-        currencies_string
+        "{}/coins/bitcoin/market_chart?vs_currency={currency}&days={days}&interval=daily",
+        plan.base_url()
     );
+    info!("Fetching daily BTC prices ({plan} plan) from: {url}");
 
-    info!("Fetching BTC prices from: {}", url);
+    let chart: MarketChart = get_json(transport, key, &url).await?;
+    Ok(chart.series())
+}
 
-    // Step 1: Send HTTP request (using shared client with connection pooling)
-    let http_response = CLIENT
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| {
-            error!("HTTP request failed: {e}");
-            ApiError::NetworkError(e)
-        })?;
+/// Fetches CoinGecko's BTC candles for the last `days` days in `currency`,
+/// with the trailing 24h volume at each close.
+///
+/// CoinGecko picks the candle width from `days`: 30 minutes up to 2 days,
+/// 4 hours up to 30 days and 4 days beyond.
+pub async fn fetch_ohlc(currency: String, days: u32) -> Result<Vec<Candle>, ApiError> {
+    fetch_ohlc_with(&TRANSPORT, api_key().as_ref(), &currency, days).await
+}
 
-    let status = http_response.status();
-    debug!("Response status: {status}");
+async fn fetch_ohlc_with(
+    transport: &Transport,
+    key: Option<&ApiKey>,
+    currency: &str,
+    days: u32,
+) -> Result<Vec<Candle>, ApiError> {
+    let plan = key.map_or(ApiPlan::Public, |k| k.plan);
+    let base = plan.base_url();
+    let url = format!("{base}/coins/bitcoin/ohlc?vs_currency={currency}&days={days}");
+    info!("Fetching BTC candles ({plan} plan) from: {url}");
+    let mut candles: Vec<Candle> = get_json(transport, key, &url).await?;
 
-    // Step 2: Read raw body text for debugging
-    let body_text = http_response.text().await.map_err(|e| {
-        error!("Failed to read response body: {e}");
-        ApiError::NetworkError(e)
-    })?;
+    // The OHLC endpoint carries no volume; market_chart does
+    let url = format!("{base}/coins/bitcoin/market_chart?vs_currency={currency}&days={days}");
+    let chart: MarketChart = get_json(transport, key, &url).await?;
+    candles::attach_volumes(&mut candles, &chart.volumes());
+    Ok(candles)
+}
 
-    debug!("Raw response body (first 500 chars): {}", &body_text[..body_text.len().min(500)]);
+async fn fetch_btc_inner(
+    transport: &Transport,
+    key: Option<&ApiKey>,
+    currencies: Vec<String>,
+) -> Result<(f64, CoinGeckoResponse), ApiError> {
+    let plan = key.map_or(ApiPlan::Public, |k| k.plan);
+    let currencies_string = currencies.join(",");
+    let url = format!(
+        "{}/simple/price?ids=bitcoin&vs_currencies={}&include_last_updated_at=true",
+        plan.base_url(),
+        currencies_string
+    );
 
-    if !status.is_success() {
-        warn!("API returned non-success status {status}: {body_text}");
-        return Err(ApiError::InvalidResponse(format!(
-            "HTTP {status}: {body_text}"
-        )));
-    }
+    // The key travels in a header, so the URL is safe to log
+    info!("Fetching BTC prices ({plan} plan) from: {}", url);
 
-    // Step 3: Parse JSON from raw text
-    let response: CoinGeckoResponse = serde_json::from_str(&body_text).map_err(|e| {
-        error!("JSON parse error: {e}");
-        debug!("Full body that failed to parse: {body_text}");
-        ApiError::ParseError(format!("{e} | body: {}", &body_text[..body_text.len().min(200)]))
-    })?;
+    let response = get_json::<CoinGeckoResponse>(transport, key, &url)
+        .await?
+        .extract_last_updated_at();
 
     debug!("Parsed response: {:?}", response);
 
-    let usd = response.bitcoin.get("usd").copied().unwrap_or(0.0);
+    let usd = response
+        .bitcoin
+        .get(REFERENCE_CURRENCY)
+        .copied()
+        .unwrap_or(0.0);
 
     if usd == 0.0 {
         warn!("USD price is 0 or missing in response");
-        return Err(ApiError::InvalidResponse(
-            "USD price not found in response".to_string(),
-        ));
+        return Err(ApiError::InvalidResponse {
+            url,
+            reason: "USD price not found in response".to_string(),
+        });
     }
 
     info!("BTC/USD = {usd:.2}");
     Ok((usd, response))
 }
 
+/// GETs `url` with the key header for `key`'s plan and parses the JSON body
+async fn get_json<T: DeserializeOwned>(
+    transport: &Transport,
+    key: Option<&ApiKey>,
+    url: &str,
+) -> Result<T, ApiError> {
+    let headers: Vec<(&str, &str)> = key
+        .and_then(|k| Some((k.plan.key_header()?, k.key.as_str())))
+        .into_iter()
+        .collect();
+
+    // Step 1: Send HTTP request and buffer the body (live, recorded or replayed)
+    let http_response = transport.get_with_headers(url, &headers).await.map_err(|e| {
+        error!("HTTP request failed: {e}");
+        ApiError::network(url, e)
+    })?;
+
+    let status = http_response.status;
+    debug!("Response status: {status}");
+
+    // Step 2: Read raw body text for debugging
+    let body_text = http_response.text();
+
+    debug!("Raw response body (first 500 chars): {}", excerpt(&body_text, 500));
+
+    if !status.is_success() {
+        warn!("API returned non-success status {status}: {body_text}");
+        return Err(ApiError::HttpStatus {
+            url: url.to_string(),
+            status: status.as_u16(),
+            body: excerpt(&body_text, BODY_EXCERPT_LEN),
+        });
+    }
+
+    // Step 3: Parse JSON from raw text
+    serde_json::from_str::<T>(&body_text).map_err(|e| {
+        error!("JSON parse error: {e}");
+        debug!("Full body that failed to parse: {body_text}");
+        ApiError::ParseError {
+            url: url.to_string(),
+            message: e.to_string(),
+            body: excerpt(&body_text, BODY_EXCERPT_LEN),
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,6 +638,67 @@ mod tests {
         assert!(response.bitcoin.is_empty());
     }
 
+    #[test]
+    fn last_updated_at_is_not_a_price() {
+        let json = r#"{"bitcoin":{"usd":65497.0,"last_updated_at":1711356300}}"#;
+        let response = serde_json::from_str::<CoinGeckoResponse>(json)
+            .unwrap()
+            .extract_last_updated_at();
+        assert_eq!(response.last_updated_at, Some(1711356300));
+        assert_eq!(response.bitcoin.len(), 1);
+        assert!(!response.bitcoin.contains_key("last_updated_at"));
+    }
+
+    #[test]
+    fn parse_market_chart() {
+        let json = r#"{"prices":[[1711238400000,64037.1],[1711324800000,67211.5]],"market_caps":[],"total_volumes":[]}"#;
+        let chart: MarketChart = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            chart.series(),
+            [
+                SeriesPoint {
+                    timestamp: 1711238400,
+                    price: 64037.1
+                },
+                SeriesPoint {
+                    timestamp: 1711324800,
+                    price: 67211.5
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_ohlc() {
+        let json = r#"[[1711353600000,65012.5,65480.0,64910.2,65497.0],[1711368000000,65497.0,66010.0,65300.0,65800.5]]"#;
+        let candles: Vec<Candle> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            candles[0],
+            Candle {
+                time: 1711353600,
+                open: 65012.5,
+                high: 65480.0,
+                low: 64910.2,
+                close: 65497.0,
+                volume: None,
+            }
+        );
+        assert_eq!(candles[1].close, 65800.5);
+    }
+
+    #[test]
+    fn parse_ohlc_rejects_short_rows() {
+        let result: Result<Vec<Candle>, _> = serde_json::from_str("[[1711353600000,65012.5,65480.0]]");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_market_chart_volumes() {
+        let json = r#"{"prices":[],"total_volumes":[[1711238400000,21000000000.0]]}"#;
+        let chart: MarketChart = serde_json::from_str(json).unwrap();
+        assert_eq!(chart.volumes(), [(1711238400, 21e9)]);
+    }
+
     #[test]
     fn parse_invalid_json_fails() {
         let result: Result<CoinGeckoResponse, _> = serde_json::from_str("not json");
@@ -119,10 +707,183 @@ mod tests {
 
     #[test]
     fn api_error_display() {
-        let err = ApiError::ParseError("bad json".into());
+        let err = ApiError::ParseError {
+            url: "https://x".into(),
+            message: "bad json".into(),
+            body: "<html>".into(),
+        };
         assert_eq!(err.to_string(), "Failed to parse response: bad json");
 
-        let err = ApiError::InvalidResponse("HTTP 403".into());
-        assert_eq!(err.to_string(), "Invalid response: HTTP 403");
+        let err = ApiError::HttpStatus {
+            url: "https://x".into(),
+            status: 403,
+            body: String::new(),
+        };
+        assert_eq!(err.to_string(), "HTTP 403 from CoinGecko");
+    }
+
+    #[test]
+    fn errors_are_categorised() {
+        let http = |status| ApiError::HttpStatus {
+            url: String::new(),
+            status,
+            body: String::new(),
+        };
+        assert_eq!(http(429).category(), ErrorCategory::RateLimited);
+        assert_eq!(http(503).category(), ErrorCategory::Server);
+        let offline = ApiError::NetworkError {
+            url: String::new(),
+            message: "dns error".into(),
+            unreachable: true,
+        };
+        assert_eq!(offline.category(), ErrorCategory::Offline);
+        let timed_out = ApiError::NetworkError {
+            url: String::new(),
+            message: "operation timed out".into(),
+            unreachable: false,
+        };
+        assert_eq!(timed_out.category(), ErrorCategory::Network);
+        let invalid = ApiError::InvalidResponse {
+            url: String::new(),
+            reason: "no usd".into(),
+        };
+        assert_eq!(invalid.category(), ErrorCategory::Parse);
+    }
+
+    #[test]
+    fn diagnostics_include_structured_fields() {
+        let err = ApiError::HttpStatus {
+            url: "https://api.coingecko.com/x".into(),
+            status: 429,
+            body: "slow down".into(),
+        };
+        let report = err.diagnostics();
+        assert!(report.contains("Category: Rate limited by CoinGecko\n"));
+        assert!(report.contains("Status: 429\n"));
+        assert!(report.contains("URL: https://api.coingecko.com/x\n"));
+        assert!(report.contains("Body: slow down\n"));
+    }
+
+    #[test]
+    fn excerpt_cuts_on_char_boundaries() {
+        assert_eq!(excerpt("short", 10), "short");
+        assert_eq!(excerpt("ééééé", 3), "ééé…");
+    }
+
+    fn replay(cassette: &str) -> Transport {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cassettes/");
+        let cassette = crate::cassette::Cassette::load(format!("{path}{cassette}")).unwrap();
+        Transport::Replay(std::sync::Arc::new(cassette))
+    }
+
+    #[tokio::test]
+    async fn fetch_btc_replays_recorded_response() {
+        let transport = replay("coingecko.json");
+        let currencies = vec!["usd".to_string(), "gbp".to_string(), "jpy".to_string()];
+        let (usd, response) = fetch_btc_with(&transport, currencies).await.unwrap();
+        assert_eq!(usd, 65497.0);
+        assert_eq!(response.bitcoin["gbp"], 51234.0);
+        assert_eq!(response.bitcoin["jpy"], 9876543.0);
+        assert_eq!(response.last_updated_at, Some(1711356300));
+    }
+
+    #[tokio::test]
+    async fn fetch_btc_replays_rate_limit_as_http_status() {
+        let transport = replay("coingecko.json");
+        let err = fetch_btc_with(&transport, vec!["usd".to_string()])
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(429), "{err}");
+        assert_eq!(err.category(), ErrorCategory::RateLimited);
+        assert!(err.body_excerpt().unwrap().contains("Rate Limit"));
+        assert!(err.url().contains("vs_currencies=usd&"));
+    }
+
+    #[tokio::test]
+    async fn supported_currencies_flag_unknown_catalogue_entries() {
+        let transport = replay("coingecko.json");
+        let supported = fetch_supported_with(&transport, None).await.unwrap();
+        assert!(supported.contains("usd"));
+        assert!(supported.contains("gbp"), "codes are lowercased");
+
+        let countries = crate::country::default_catalogue();
+        let unsupported: Vec<_> = unsupported_countries(&countries, &supported)
+            .iter()
+            .map(|c| c.currency.as_str())
+            .collect();
+        assert!(unsupported.contains(&"vef"), "{unsupported:?}");
+        assert!(!unsupported.contains(&"usd"));
+    }
+
+    #[tokio::test]
+    async fn usd_is_fetched_even_when_hidden() {
+        let transport = replay("coingecko.json");
+        let countries: Vec<Country> = crate::country::default_catalogue()
+            .into_iter()
+            .filter(|c| ["gbp", "jpy"].contains(&c.currency.as_str()))
+            .collect();
+        let snapshot = fetch_country_prices_with(&transport, &countries, None)
+            .await
+            .unwrap();
+        assert_eq!(snapshot.usd, 65497.0);
+        let currencies: Vec<_> = snapshot
+            .prices
+            .iter()
+            .map(|p| p.country.currency.as_str())
+            .collect();
+        assert_eq!(currencies, ["gbp", "jpy"], "usd stays hidden");
+        assert_eq!(snapshot.prices[0].price, Some(51234.0));
+    }
+
+    #[tokio::test]
+    async fn daily_prices_replay_recorded_chart() {
+        let transport = replay("coingecko.json");
+        let series = fetch_daily_with(&transport, None, "usd", 3).await.unwrap();
+        let prices: Vec<f64> = series.iter().map(|p| p.price).collect();
+        assert_eq!(prices, [64037.1, 67211.5, 65497.0]);
+        assert_eq!(series[0].timestamp, 1711152000);
+    }
+
+    #[tokio::test]
+    async fn ohlc_replays_candles_with_volumes() {
+        let transport = replay("coingecko.json");
+        let candles = fetch_ohlc_with(&transport, None, "usd", 1).await.unwrap();
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        assert_eq!(closes, [65497.0, 65800.5]);
+        let volumes: Vec<_> = candles.iter().map(|c| c.volume).collect();
+        assert_eq!(volumes, [Some(2.1e10), Some(2.3e10)]);
+    }
+
+    #[tokio::test]
+    async fn pro_key_switches_host() {
+        let transport = replay("coingecko.json");
+        let key = ApiKey::new(ApiPlan::Pro, "CG-test").unwrap();
+        let (usd, _) = fetch_btc_inner(&transport, Some(&key), vec!["usd".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(usd, 65000.0);
+    }
+
+    #[test]
+    fn api_key_is_not_debug_printed() {
+        assert!(ApiKey::new(ApiPlan::Public, "CG-test").is_none());
+        assert!(ApiKey::new(ApiPlan::Demo, "  ").is_none());
+
+        let key = ApiKey::new(ApiPlan::Demo, " CG-test ").unwrap();
+        assert_eq!(key.key, "CG-test");
+        assert!(!format!("{key:?}").contains("CG-test"));
+    }
+
+    #[tokio::test]
+    async fn fetch_btc_without_recording_is_a_network_error() {
+        let transport = replay("coingecko.json");
+        let err = fetch_btc_with(&transport, vec!["chf".to_string()])
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ApiError::NetworkError { ref message, .. } if message.starts_with("no recorded response")),
+            "{err}"
+        );
+        assert!(!err.is_unreachable(), "a cassette miss says nothing about the network");
     }
 }
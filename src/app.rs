@@ -1,11 +1,27 @@
 use std::time::Duration;
 
-use iced::{widget, Subscription, Task, Theme};
+use iced::keyboard::{key, Key, Modifiers};
+use iced::theme::Mode;
+use iced::widget::{container, mouse_area, opaque, stack};
+use iced::{widget, Color, Fill, Subscription, Task, Theme};
 
-use crate::message::{BitcoinMessage, Message};
-use crate::pages::{about_page::AboutPage, bitcoin_page::BitcoinPage, settings_page::SettingsPage};
+use crate::connectivity::{self, Connectivity};
+use crate::country::get_countries;
+use crate::history::unix_now;
+use crate::http_client::TRANSPORT;
+use crate::message::{BitcoinMessage, Message, PaletteMessage};
+use crate::pages::{
+    about_page::AboutPage, alerts_page::AlertsPage, bitcoin_page::BitcoinPage,
+    candle_page::CandlePage, chart_page::ChartPage, fee_page::FeePage, markets_page::MarketsPage,
+    network_page::NetworkPage, settings_page::SettingsPage, wallet_page::WalletPage,
+};
+use crate::palette::{CommandPalette, PaletteItem};
 use crate::route::Route;
+use crate::server;
+use crate::shortcuts::{self, Action};
+use crate::views::command_palette::{self, CommandPaletteView, PaletteEntry};
 use crate::views::navigation::Navigation;
+use crate::views::offline_banner;
 
 /// Main application state
 pub struct App {
@@ -13,10 +29,30 @@ pub struct App {
     current_route: Route,
     /// Bitcoin page state
     bitcoin_page: BitcoinPage,
+    /// Chart page state; plots the Bitcoin page's recorded prices
+    chart_page: ChartPage,
+    /// Candles page state; aggregates the same recorded prices
+    candle_page: CandlePage,
+    /// Markets page state; prices the latest recorded sample against FX rates
+    markets_page: MarketsPage,
+    /// Network page state; fee and mempool data from a mempool.space-style API
+    network_page: NetworkPage,
+    /// Fees page state; prices a transaction at the Network page's fee rates
+    fee_page: FeePage,
+    /// Wallet page state; watch-only balances valued at the recorded prices
+    wallet_page: WalletPage,
+    /// Alerts page state; where alerts are delivered
+    alerts_page: AlertsPage,
     /// Settings page state — owns config that affects the whole app
     settings_page: SettingsPage,
     /// About page state
     about_page: AboutPage,
+    /// Network reachability; scheduled fetches pause while offline
+    connectivity: Connectivity,
+    /// Open command palette, if any
+    palette: Option<CommandPalette>,
+    /// OS light/dark preference, once reported
+    system_theme: Mode,
 }
 
 impl App {
@@ -32,10 +68,23 @@ impl App {
             Self {
                 current_route: Route::default(),
                 bitcoin_page,
+                chart_page: ChartPage::new(),
+                candle_page: CandlePage::new(),
+                markets_page: MarketsPage::new(),
+                network_page: NetworkPage::new(),
+                fee_page: FeePage::new(),
+                wallet_page: WalletPage::new(),
+                alerts_page: AlertsPage::new(),
                 settings_page,
                 about_page,
+                connectivity: Connectivity::Online,
+                palette: None,
+                system_theme: Mode::None,
             },
-            bitcoin_task.map(Message::Bitcoin),
+            Task::batch([
+                bitcoin_task.map(Message::Bitcoin),
+                iced::system::theme().map(Message::SystemTheme),
+            ]),
         )
     }
 
@@ -44,19 +93,56 @@ impl App {
         format!("Bitcoin Price Monitor — {}", self.current_route.display_name())
     }
 
-    /// Theme is driven by the Settings page selection
+    /// Theme is driven by the Settings page selection, or by the OS
+    /// light/dark preference when Settings says to follow it
     pub fn theme(&self) -> Theme {
-        self.settings_page.selected_theme().clone()
+        self.settings_page.theme_for(self.system_theme).clone()
     }
 
-    /// Subscription: auto-refresh BTC prices at the configured interval
+    /// Subscription: auto-refresh BTC prices at the configured interval while
+    /// online, a connectivity monitor, keyboard shortcuts, OS theme changes
+    /// while followed, plus the local API server while it is enabled in Settings
     pub fn subscription(&self) -> Subscription<Message> {
-        if self.settings_page.auto_refresh_enabled() {
+        let online = self.connectivity == Connectivity::Online;
+        let tick = if online && self.settings_page.auto_refresh_enabled() {
             let secs = self.settings_page.auto_refresh_interval();
             iced::time::every(Duration::from_secs(secs as u64)).map(|_| Message::Tick)
         } else {
             Subscription::none()
-        }
+        };
+
+        let api_server = if self.settings_page.api_server_enabled() {
+            Subscription::run_with(self.settings_page.api_server_port(), |port| server::run(*port))
+                .map(Message::ApiServer)
+        } else {
+            Subscription::none()
+        };
+
+        // Keep "updated Ns ago" current while the Bitcoin page is showing data
+        let clock = if self.current_route == Route::Bitcoin && self.bitcoin_page.has_data() {
+            iced::time::every(Duration::from_secs(1))
+                .map(|_| Message::Bitcoin(BitcoinMessage::Clock(unix_now())))
+        } else {
+            Subscription::none()
+        };
+
+        // Replayed traffic never touches the network, so there is nothing to monitor
+        let monitor = if TRANSPORT.is_live() {
+            Subscription::run_with(self.connectivity, |assumed| connectivity::watch(*assumed))
+                .map(Message::Connectivity)
+        } else {
+            Subscription::none()
+        };
+
+        let keys = iced::event::listen_with(shortcuts::key_event);
+
+        let system_theme = if self.settings_page.follow_system_theme() {
+            iced::system::theme_changes().map(Message::SystemTheme)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([tick, clock, api_server, monitor, keys, system_theme])
     }
 
     // ── Update ──────────────────────────────────────────────────
@@ -66,21 +152,177 @@ impl App {
         match message {
             Message::Navigate(route) => {
                 self.current_route = route;
-                Task::none()
+                match route {
+                    Route::Markets => self.markets_page.load().map(Message::Markets),
+                    Route::Wallet => self.wallet_page.load().map(Message::Wallet),
+                    // Rule previews price premiums against the reference rates
+                    Route::Alerts if self.alerts_page.needs_fx_rates() => {
+                        self.markets_page.load().map(Message::Markets)
+                    }
+                    // Fee quotes need the fee rates, the halving countdown the chain tip
+                    Route::Network | Route::Fees | Route::About => {
+                        self.network_page.load().map(Message::Network)
+                    }
+                    _ => Task::none(),
+                }
             }
             Message::Bitcoin(msg) => {
-                self.bitcoin_page.update(msg).map(Message::Bitcoin)
+                // Replies to superseded fetches say nothing about the network
+                // now and add no sample, so they are left to the page to drop
+                let current = self.bitcoin_page.is_current(&msg);
+                match &msg {
+                    BitcoinMessage::Error(_, error) if current && error.is_unreachable() => {
+                        self.connectivity = Connectivity::Offline;
+                    }
+                    BitcoinMessage::CurrentPrice(..) if current => {
+                        self.connectivity = Connectivity::Online;
+                    }
+                    _ => {}
+                }
+                let fetched = current && matches!(msg, BitcoinMessage::CurrentPrice(..));
+                let task = self.bitcoin_page.update(msg).map(Message::Bitcoin);
+                if !fetched {
+                    return task;
+                }
+                // Every new sample is a chance for an alert rule to fire
+                let alerts = self
+                    .alerts_page
+                    .check(
+                        self.bitcoin_page.history(),
+                        self.markets_page.rates(),
+                        self.settings_page.notifications_enabled(),
+                    )
+                    .map(Message::Alerts);
+                let rates = if self.alerts_page.needs_fx_rates() {
+                    self.markets_page.load().map(Message::Markets)
+                } else {
+                    Task::none()
+                };
+                Task::batch([task, alerts, rates])
+            }
+            Message::Chart(msg) => self.chart_page.update(msg).map(Message::Chart),
+            Message::Candles(msg) => self.candle_page.update(msg).map(Message::Candles),
+            Message::Markets(msg) => self.markets_page.update(msg).map(Message::Markets),
+            Message::Network(msg) => self.network_page.update(msg).map(Message::Network),
+            Message::Fees(msg) => {
+                self.fee_page.update(msg);
+                Task::none()
             }
+            Message::Wallet(msg) => self.wallet_page.update(msg).map(Message::Wallet),
+            Message::Alerts(msg) => self.alerts_page.update(msg).map(Message::Alerts),
             Message::Settings(msg) => {
                 self.settings_page.update(msg);
                 Task::none()
             }
+            Message::Tick if self.connectivity == Connectivity::Offline => Task::none(),
             Message::Tick => {
-                // Auto-refresh triggers a Bitcoin price refetch
+                // Auto-refresh triggers a Bitcoin price refetch unless one is running
+                self.bitcoin_page
+                    .update(BitcoinMessage::Tick)
+                    .map(Message::Bitcoin)
+            }
+            Message::ApiServer(event) => {
+                self.settings_page.set_api_server_status(event);
+                Task::none()
+            }
+            Message::SystemTheme(mode) => {
+                self.system_theme = mode;
+                Task::none()
+            }
+            Message::KeyPressed(key, modifiers) => self.handle_key(key, modifiers),
+            Message::Palette(msg) => self.update_palette(msg),
+            Message::Connectivity(state) => {
+                let was_offline = self.connectivity == Connectivity::Offline;
+                self.connectivity = state;
+                if was_offline && state == Connectivity::Online {
+                    // Back online: refresh once instead of waiting for the next tick
+                    self.bitcoin_page
+                        .update(BitcoinMessage::Resume)
+                        .map(Message::Bitcoin)
+                } else {
+                    Task::none()
+                }
+            }
+        }
+    }
+
+    /// Palette navigation keys first, then the configured shortcuts
+    fn handle_key(&mut self, key: Key, modifiers: Modifiers) -> Task<Message> {
+        if let Some(palette) = &mut self.palette {
+            match key {
+                Key::Named(key::Named::Escape) => {
+                    self.palette = None;
+                    return Task::none();
+                }
+                Key::Named(key::Named::ArrowUp) => {
+                    palette.move_selection(-1);
+                    return Task::none();
+                }
+                Key::Named(key::Named::ArrowDown) => {
+                    palette.move_selection(1);
+                    return Task::none();
+                }
+                _ => {}
+            }
+        }
+        match self.settings_page.key_bindings().action_for(&key, modifiers) {
+            Some(action) => self.run_action(action),
+            None => Task::none(),
+        }
+    }
+
+    fn update_palette(&mut self, message: PaletteMessage) -> Task<Message> {
+        let Some(palette) = &mut self.palette else {
+            return Task::none();
+        };
+        let picked = match message {
+            PaletteMessage::QueryChanged(query) => {
+                palette.set_query(query);
+                return Task::none();
+            }
+            PaletteMessage::Select(index) => palette.pick(Some(index)).cloned(),
+            PaletteMessage::Submit => palette.pick(None).cloned(),
+            PaletteMessage::Close => None,
+        };
+        self.palette = None;
+        match picked {
+            Some(PaletteItem::Action(action)) => self.run_action(action),
+            Some(PaletteItem::Currency { currency, .. }) => {
+                self.current_route = Route::Bitcoin;
+                self.bitcoin_page
+                    .update(BitcoinMessage::SearchChanged(currency.to_uppercase()))
+                    .map(Message::Bitcoin)
+            }
+            None => Task::none(),
+        }
+    }
+
+    /// Runs an action triggered by a shortcut or the command palette
+    fn run_action(&mut self, action: Action) -> Task<Message> {
+        match action {
+            Action::Refresh => self
+                .bitcoin_page
+                .update(BitcoinMessage::Refetch)
+                .map(Message::Bitcoin),
+            Action::Search => {
+                self.palette = None;
+                self.current_route = Route::Bitcoin;
                 self.bitcoin_page
-                    .update(BitcoinMessage::Refetch)
+                    .update(BitcoinMessage::FocusSearch)
                     .map(Message::Bitcoin)
             }
+            Action::CommandPalette => {
+                if self.palette.take().is_some() {
+                    return Task::none();
+                }
+                self.palette = Some(CommandPalette::new(get_countries()));
+                widget::operation::focus(command_palette::QUERY_INPUT)
+            }
+            Action::OpenSettings => {
+                self.current_route = Route::Settings;
+                Task::none()
+            }
+            Action::Navigate(route) => self.update(Message::Navigate(route)),
         }
     }
 
@@ -89,13 +331,330 @@ impl App {
     /// Renders the application view
     pub fn view(&self) -> iced::Element<'_, Message> {
         let navigation = Navigation::new(&self.current_route).view();
+        let offline = self.connectivity == Connectivity::Offline;
 
         let content = match &self.current_route {
-            Route::Bitcoin => self.bitcoin_page.view().map(Message::Bitcoin),
+            Route::Bitcoin => self
+                .bitcoin_page
+                .view(self.settings_page.stale_after_secs(), offline)
+                .map(Message::Bitcoin),
+            Route::Chart => self
+                .chart_page
+                .view(self.bitcoin_page.history())
+                .map(Message::Chart),
+            Route::Candles => self
+                .candle_page
+                .view(self.bitcoin_page.history())
+                .map(Message::Candles),
+            Route::Markets => self
+                .markets_page
+                .view(self.bitcoin_page.history(), self.bitcoin_page.cross_rates())
+                .map(Message::Markets),
+            Route::Network => self.network_page.view().map(Message::Network),
+            Route::Fees => self
+                .fee_page
+                .view(self.network_page.status(), self.bitcoin_page.history())
+                .map(Message::Fees),
+            Route::Wallet => self
+                .wallet_page
+                .view(self.bitcoin_page.history())
+                .map(Message::Wallet),
+            Route::Alerts => self
+                .alerts_page
+                .view(self.bitcoin_page.history(), self.markets_page.rates())
+                .map(Message::Alerts),
             Route::Settings => self.settings_page.view().map(Message::Settings),
-            Route::About => self.about_page.view(),
+            Route::About => self
+                .about_page
+                .view(self.network_page.status(), unix_now()),
         };
 
-        widget::column![navigation, content].into()
+        let page = if offline {
+            widget::column![navigation, offline_banner::view(), content]
+        } else {
+            widget::column![navigation, content]
+        };
+
+        match &self.palette {
+            Some(palette) => stack![page, self.palette_overlay(palette)].into(),
+            None => page.into(),
+        }
+    }
+
+    /// Palette centred over a dimmed backdrop; clicking the backdrop closes it
+    fn palette_overlay<'a>(&self, palette: &'a CommandPalette) -> iced::Element<'a, Message> {
+        let bindings = self.settings_page.key_bindings();
+        let entries = palette
+            .results()
+            .map(|item| PaletteEntry {
+                label: item.label(),
+                hint: CommandPalette::hint(item, bindings),
+            })
+            .collect();
+        let panel = CommandPaletteView::new(palette.query(), entries, palette.selected())
+            .view()
+            .map(Message::Palette);
+
+        let backdrop = container(panel)
+            .center_x(Fill)
+            .height(Fill)
+            .padding(80)
+            .style(|_| container::Style::default().background(Color::from_rgba(0.0, 0.0, 0.0, 0.5)));
+        opaque(mouse_area(backdrop).on_press(Message::Palette(PaletteMessage::Close)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::SettingsMessage;
+    use crate::shortcuts::ShortcutsFile;
+    use crate::testing::{assert_snapshot, sample_snapshot, simulator};
+
+    fn app_with_prices() -> App {
+        let (mut app, _) = App::boot();
+        // Boot starts fetch generation 1
+        let _ = app.update(Message::Bitcoin(BitcoinMessage::CurrentPrice(1, sample_snapshot())));
+        app
+    }
+
+    #[test]
+    fn stale_prices_are_not_new_samples() {
+        let (mut app, _) = App::boot();
+        app.connectivity = Connectivity::Offline;
+        let _ = app.update(Message::Bitcoin(BitcoinMessage::CurrentPrice(0, sample_snapshot())));
+        assert_eq!(app.bitcoin_page.history().len(), 0);
+        assert_eq!(
+            app.connectivity,
+            Connectivity::Offline,
+            "a superseded fetch proves nothing"
+        );
+
+        let _ = app.update(Message::Bitcoin(BitcoinMessage::CurrentPrice(1, sample_snapshot())));
+        assert_eq!(app.bitcoin_page.history().len(), 1);
+        assert_eq!(app.connectivity, Connectivity::Online);
+    }
+
+    #[test]
+    fn clicking_navigation_switches_route() {
+        let mut app = app_with_prices();
+
+        let mut ui = simulator(app.view());
+        ui.click("Settings").expect("Settings button");
+        let messages: Vec<_> = ui.into_messages().collect();
+        assert!(matches!(messages.as_slice(), [Message::Navigate(Route::Settings)]));
+
+        for message in messages {
+            let _ = app.update(message);
+        }
+        assert_eq!(app.current_route, Route::Settings);
+        assert_eq!(app.title(), "Bitcoin Price Monitor — Settings");
+
+        let mut ui = simulator(app.view());
+        assert!(ui.find("Reset to Defaults").is_ok());
+    }
+
+    #[test]
+    fn navigation_keeps_page_state() {
+        let mut app = app_with_prices();
+        let _ = app.update(Message::Navigate(Route::About));
+        let _ = app.update(Message::Navigate(Route::Bitcoin));
+
+        let mut ui = simulator(app.view());
+        assert!(ui.find("United Kingdom").is_ok());
+        assert!(ui.find("USD: $65497.00").is_ok());
+    }
+
+    #[test]
+    fn tick_refetches_prices() {
+        let mut app = app_with_prices();
+        let _ = app.update(Message::Tick);
+
+        let mut ui = simulator(app.view());
+        assert!(ui.find("Loading...").is_ok(), "fetch button shows loading state");
+    }
+
+    const OFFLINE_BANNER: &str =
+        "Offline — prices will refresh automatically when the connection returns";
+
+    fn unreachable() -> crate::api::ApiError {
+        crate::api::ApiError::NetworkError {
+            url: connectivity::PROBE_URL.into(),
+            message: "dns error".into(),
+            unreachable: true,
+        }
+    }
+
+    #[test]
+    fn unreachable_network_pauses_ticks_and_resumes_once() {
+        let mut app = app_with_prices();
+        let _ = app.update(Message::Tick);
+        let _ = app.update(Message::Bitcoin(BitcoinMessage::Error(2, unreachable())));
+        assert_eq!(app.connectivity, Connectivity::Offline);
+
+        {
+            let mut ui = simulator(app.view());
+            assert!(ui.find(OFFLINE_BANNER).is_ok());
+            assert!(
+                ui.find("You appear to be offline").is_err(),
+                "banner replaces the error panel"
+            );
+            assert!(ui.find("United Kingdom").is_ok(), "last prices stay visible");
+        }
+        assert_eq!(app.update(Message::Tick).units(), 0);
+
+        // Recovery: one refetch plus the flag and currency list downloads
+        // (neither loaded in tests)
+        let task = app.update(Message::Connectivity(Connectivity::Online));
+        assert_eq!(task.units(), 3);
+        {
+            let mut ui = simulator(app.view());
+            assert!(ui.find("Loading...").is_ok());
+            assert!(ui.find(OFFLINE_BANNER).is_err());
+        }
+
+        // A repeated "online" report does not fetch again
+        assert_eq!(app.update(Message::Connectivity(Connectivity::Online)).units(), 0);
+    }
+
+    /// Points the settings page at its own directory, so a test that changes
+    /// the theme or shortcuts does not change what other tests boot with
+    fn private_settings(app: &mut App, name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("ifb-app-{name}-{}", std::process::id()));
+        app.settings_page =
+            SettingsPage::with(ShortcutsFile::new(dir.join("shortcuts.json")), dir.join("themes"));
+        dir
+    }
+
+    #[test]
+    fn settings_drive_theme_and_reset() {
+        let mut app = app_with_prices();
+        let dir = private_settings(&mut app, "theme");
+        assert_eq!(app.theme(), Theme::Nord);
+
+        let _ = app.update(Message::Settings(SettingsMessage::ThemeChanged(Theme::Dracula)));
+        assert_eq!(app.theme(), Theme::Dracula);
+
+        let _ = app.update(Message::Settings(SettingsMessage::ResetToDefaults));
+        assert_eq!(app.theme(), Theme::Nord);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn theme_follows_os_preference_when_enabled() {
+        let mut app = app_with_prices();
+        let dir = private_settings(&mut app, "follow");
+        let _ = app.update(Message::SystemTheme(Mode::Light));
+        assert_eq!(app.theme(), Theme::Nord, "not following yet");
+
+        let _ = app.update(Message::Settings(SettingsMessage::FollowSystemThemeToggled(true)));
+        assert_eq!(app.theme(), Theme::Light);
+        let _ = app.update(Message::SystemTheme(Mode::Dark));
+        assert_eq!(app.theme(), Theme::Nord);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn pages_match_snapshots() {
+        let mut app = app_with_prices();
+
+        for (route, name) in [
+            (Route::Bitcoin, "bitcoin_page"),
+            (Route::Chart, "chart_page_session"),
+            (Route::Candles, "candle_page_session"),
+            (Route::Markets, "markets_page_session"),
+            (Route::Network, "network_page_session"),
+            (Route::Fees, "fee_page_session"),
+            (Route::Wallet, "wallet_page_session"),
+            (Route::Alerts, "alerts_page_session"),
+            (Route::Settings, "settings_page"),
+            (Route::About, "about_page"),
+        ] {
+            let _ = app.update(Message::Navigate(route));
+            let theme = app.theme();
+            let mut ui = simulator(app.view());
+            assert_snapshot(&mut ui, &theme, name);
+        }
+    }
+
+    fn press(app: &mut App, key: &str) -> Task<Message> {
+        app.update(Message::KeyPressed(Key::Character(key.into()), Modifiers::COMMAND))
+    }
+
+    fn press_named(app: &mut App, named: key::Named) -> Task<Message> {
+        app.update(Message::KeyPressed(Key::Named(named), Modifiers::empty()))
+    }
+
+    #[test]
+    fn shortcuts_navigate_and_refresh() {
+        let mut app = app_with_prices();
+        let _ = press(&mut app, "0");
+        assert_eq!(app.current_route, Route::About);
+        let _ = press(&mut app, "2");
+        assert_eq!(app.current_route, Route::Chart);
+        let _ = press(&mut app, ",");
+        assert_eq!(app.current_route, Route::Settings);
+        let _ = press(&mut app, "1");
+        assert_eq!(app.current_route, Route::Bitcoin);
+
+        assert_eq!(press(&mut app, "r").units(), 1);
+        let mut ui = simulator(app.view());
+        assert!(ui.find("Loading...").is_ok());
+    }
+
+    #[test]
+    fn rebound_shortcut_replaces_default() {
+        let mut app = app_with_prices();
+        let dir = private_settings(&mut app, "keys");
+        let _ = app.update(Message::Settings(SettingsMessage::ShortcutChanged(
+            Action::Navigate(Route::About),
+            "Ctrl+A".into(),
+        )));
+        assert_eq!(press(&mut app, "0").units(), 0);
+        assert_eq!(app.current_route, Route::Bitcoin);
+        let _ = press(&mut app, "a");
+        assert_eq!(app.current_route, Route::About);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn command_palette_runs_actions() {
+        let mut app = app_with_prices();
+        assert_eq!(press(&mut app, "k").units(), 1, "focuses the query input");
+        {
+            let mut ui = simulator(app.view());
+            assert!(ui.find("Refresh prices").is_ok());
+            assert!(ui.find("Ctrl+R").is_ok(), "shortcut hints are shown");
+        }
+
+        let _ = press_named(&mut app, key::Named::ArrowDown);
+        let _ = app.update(Message::Palette(PaletteMessage::QueryChanged("go abo".into())));
+        let _ = app.update(Message::Palette(PaletteMessage::Submit));
+        assert_eq!(app.current_route, Route::About);
+        assert!(app.palette.is_none());
+
+        let _ = press(&mut app, "k");
+        let _ = press_named(&mut app, key::Named::Escape);
+        assert!(app.palette.is_none());
+        assert_eq!(app.current_route, Route::About);
+    }
+
+    #[test]
+    fn command_palette_jumps_to_a_currency() {
+        let mut app = app_with_prices();
+        let _ = app.update(Message::Navigate(Route::Settings));
+        let _ = press(&mut app, "k");
+        let _ = app.update(Message::Palette(PaletteMessage::QueryChanged("japan".into())));
+
+        let mut ui = simulator(app.view());
+        ui.click("BTC/JPY — Japan").expect("currency result");
+        for message in ui.into_messages().collect::<Vec<_>>() {
+            let _ = app.update(message);
+        }
+
+        assert_eq!(app.current_route, Route::Bitcoin);
+        let mut ui = simulator(app.view());
+        assert!(ui.find("Japan").is_ok());
+        assert!(ui.find("United Kingdom").is_err(), "list is filtered to JPY");
     }
 }
@@ -0,0 +1,129 @@
+//! OHLC candles built from recorded prices, and helpers shared with the
+//! candles CoinGecko returns.
+
+use crate::api::Candle;
+use crate::history::SeriesPoint;
+
+/// Groups `series` (oldest first) into candles `interval` seconds wide,
+/// aligned to multiples of `interval` since the Unix epoch.
+///
+/// Buckets without prices are skipped rather than filled in. Recorded prices
+/// carry no volume, so neither do the candles.
+pub fn aggregate(series: &[SeriesPoint], interval: u64) -> Vec<Candle> {
+    let mut candles: Vec<Candle> = Vec::new();
+    if interval == 0 {
+        return candles;
+    }
+    for point in series {
+        let close_time = (point.timestamp / interval + 1) * interval;
+        match candles.last_mut() {
+            Some(candle) if candle.time == close_time => {
+                candle.high = candle.high.max(point.price);
+                candle.low = candle.low.min(point.price);
+                candle.close = point.price;
+            }
+            _ => candles.push(Candle {
+                time: close_time,
+                open: point.price,
+                high: point.price,
+                low: point.price,
+                close: point.price,
+                volume: None,
+            }),
+        }
+    }
+    candles
+}
+
+/// Sets each candle's volume to the latest `(unix_seconds, volume)` reading
+/// at or before its close; candles before the first reading keep `None`
+pub fn attach_volumes(candles: &mut [Candle], volumes: &[(u64, f64)]) {
+    let mut readings = volumes.iter().peekable();
+    let mut latest = None;
+    for candle in candles {
+        while let Some(&&(time, volume)) = readings.peek() {
+            if time > candle.time {
+                break;
+            }
+            latest = Some(volume);
+            readings.next();
+        }
+        candle.volume = latest;
+    }
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC for a Unix timestamp
+pub fn format_utc(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let secs = timestamp % 86_400;
+
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        secs / 3_600,
+        secs % 3_600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(timestamp: u64, price: f64) -> SeriesPoint {
+        SeriesPoint { timestamp, price }
+    }
+
+    #[test]
+    fn aggregate_groups_prices_by_interval() {
+        let series = [
+            point(600, 10.0),
+            point(660, 12.0),
+            point(720, 9.0),
+            point(899, 11.0),
+            point(900, 11.5),
+            // Nothing recorded between 1200 and 1500
+            point(1500, 13.0),
+        ];
+        let candles = aggregate(&series, 300);
+        let ohlc: Vec<_> = candles
+            .iter()
+            .map(|c| (c.time, c.open, c.high, c.low, c.close))
+            .collect();
+        assert_eq!(
+            ohlc,
+            [
+                (900, 10.0, 12.0, 9.0, 11.0),
+                (1200, 11.5, 11.5, 11.5, 11.5),
+                (1800, 13.0, 13.0, 13.0, 13.0),
+            ]
+        );
+        assert!(candles.iter().all(|c| c.volume.is_none()));
+        assert!(aggregate(&series, 0).is_empty());
+        assert!(aggregate(&[], 60).is_empty());
+    }
+
+    #[test]
+    fn volumes_use_latest_reading_at_close() {
+        let mut candles = aggregate(&[point(0, 1.0), point(60, 2.0), point(120, 3.0)], 60);
+        attach_volumes(&mut candles, &[(70, 5.0), (100, 6.0), (500, 9.0)]);
+        let volumes: Vec<_> = candles.iter().map(|c| c.volume).collect();
+        assert_eq!(volumes, [None, Some(6.0), Some(6.0)]);
+    }
+
+    #[test]
+    fn format_utc_dates() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00");
+        assert_eq!(format_utc(1_711_356_300), "2024-03-25 08:45");
+    }
+}
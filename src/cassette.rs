@@ -0,0 +1,162 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// A recorded HTTP exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    /// UTF-8 response body (JSON, SVG); absent for binary bodies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Base64 response body, used when the body is not valid UTF-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_base64: Option<String>,
+}
+
+impl Interaction {
+    pub fn new(method: &str, url: &str, status: u16, body: &[u8]) -> Self {
+        let (body, body_base64) = match std::str::from_utf8(body) {
+            Ok(text) => (Some(text.to_string()), None),
+            Err(_) => (
+                None,
+                Some(base64::engine::general_purpose::STANDARD.encode(body)),
+            ),
+        };
+        Self {
+            method: method.to_string(),
+            url: url.to_string(),
+            status,
+            body,
+            body_base64,
+        }
+    }
+
+    pub fn body_bytes(&self) -> Vec<u8> {
+        match (&self.body, &self.body_base64) {
+            (Some(text), _) => text.as_bytes().to_vec(),
+            (None, Some(encoded)) => base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .unwrap_or_else(|e| {
+                    warn!("Corrupt base64 body in cassette for {}: {e}", self.url);
+                    Vec::new()
+                }),
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// A JSON file of recorded interactions.
+///
+/// In record mode every response is appended and the file is rewritten.
+/// In replay mode requests are matched by method and URL; repeated requests
+/// for the same URL are served in recorded order, repeating the last one.
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    interactions: Mutex<Vec<Interaction>>,
+    cursors: Mutex<HashMap<(String, String), usize>>,
+}
+
+impl Cassette {
+    /// Starts an empty cassette that will be written to `path`
+    pub fn create(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interactions: Mutex::new(Vec::new()),
+            cursors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Loads an existing cassette for replay
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let file: CassetteFile = serde_json::from_str(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            interactions: Mutex::new(file.interactions),
+            cursors: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends an interaction and persists the whole cassette
+    pub fn record(&self, interaction: Interaction) -> io::Result<()> {
+        let mut interactions = self.interactions.lock().unwrap_or_else(|e| e.into_inner());
+        interactions.push(interaction);
+
+        let file = CassetteFile {
+            interactions: interactions.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, json)
+    }
+
+    /// Finds the next recorded response for `method url`
+    pub fn replay(&self, method: &str, url: &str) -> Option<Interaction> {
+        let interactions = self.interactions.lock().unwrap_or_else(|e| e.into_inner());
+        let matching: Vec<&Interaction> = interactions
+            .iter()
+            .filter(|i| i.method == method && i.url == url)
+            .collect();
+        let last = matching.len().checked_sub(1)?;
+
+        let mut cursors = self.cursors.lock().unwrap_or_else(|e| e.into_inner());
+        let cursor = cursors
+            .entry((method.to_string(), url.to_string()))
+            .or_default();
+        let interaction = matching[(*cursor).min(last)].clone();
+        *cursor += 1;
+        Some(interaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_bodies_round_trip_through_base64() {
+        let interaction = Interaction::new("GET", "http://x/flag.png", 200, &[0xff, 0x00, 0x9f]);
+        assert!(interaction.body.is_none());
+        assert_eq!(interaction.body_bytes(), vec![0xff, 0x00, 0x9f]);
+
+        let interaction = Interaction::new("GET", "http://x/a.svg", 200, b"<svg/>");
+        assert_eq!(interaction.body.as_deref(), Some("<svg/>"));
+    }
+
+    #[test]
+    fn replays_in_order_then_repeats_last() {
+        let cassette = Cassette::create("unused.json");
+        {
+            let mut interactions = cassette.interactions.lock().unwrap();
+            interactions.push(Interaction::new("GET", "http://x/p", 200, b"first"));
+            interactions.push(Interaction::new("GET", "http://x/p", 429, b"second"));
+        }
+        let bodies: Vec<_> = (0..3)
+            .map(|_| cassette.replay("GET", "http://x/p").unwrap().body.unwrap())
+            .collect();
+        assert_eq!(bodies, vec!["first", "second", "second"]);
+        assert!(cassette.replay("GET", "http://x/other").is_none());
+    }
+}
@@ -0,0 +1,191 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::http_client::HttpConfig;
+use crate::server;
+
+/// Default refresh interval for headless mode, in seconds
+const DEFAULT_HEADLESS_INTERVAL: u64 = 60;
+
+/// Command-line options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliArgs {
+    /// Run without a window: fetch on a timer and serve the local API
+    pub headless: bool,
+    /// Port for the local API server in headless mode
+    pub port: u16,
+    /// Refresh interval in headless mode, in seconds
+    pub interval: u64,
+    /// Print usage and exit
+    pub help: bool,
+    /// HTTP proxy URL, overriding `IFB_PROXY`
+    pub proxy: Option<String>,
+    /// Proxy basic-auth user, overriding `IFB_PROXY_USER`
+    pub proxy_user: Option<String>,
+    /// Extra root certificates (PEM), overriding `IFB_CA_BUNDLE`
+    pub ca_bundle: Option<PathBuf>,
+    /// Connect timeout in seconds, overriding `IFB_CONNECT_TIMEOUT`
+    pub connect_timeout: Option<u64>,
+    /// Request timeout in seconds, overriding `IFB_TIMEOUT`
+    pub timeout: Option<u64>,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            port: server::DEFAULT_PORT,
+            interval: DEFAULT_HEADLESS_INTERVAL,
+            help: false,
+            proxy: None,
+            proxy_user: None,
+            ca_bundle: None,
+            connect_timeout: None,
+            timeout: None,
+        }
+    }
+}
+
+pub const USAGE: &str = "\
+Usage: iced-fetch-bitcoin [OPTIONS]
+
+Options:
+      --headless          Run without a window; fetch prices on a timer and
+                          serve the local API (including /metrics)
+      --port <PORT>       Local API port for headless mode [default: 8787]
+      --interval <SECS>   Refresh interval for headless mode [default: 60]
+      --proxy <URL>       Send all requests through this proxy
+      --proxy-user <USER> Proxy basic-auth user (password: IFB_PROXY_PASSWORD)
+      --ca-bundle <PATH>  PEM file with extra trusted root certificates
+      --connect-timeout <SECS>
+                          Connect timeout [default: 10]
+      --timeout <SECS>    Whole-request timeout [default: 30]
+  -h, --help              Print this help
+
+Environment:
+  IFB_PROXY, IFB_PROXY_USER, IFB_PROXY_PASSWORD, IFB_CA_BUNDLE,
+  IFB_CONNECT_TIMEOUT, IFB_TIMEOUT    Same as the options above
+  IFB_COINGECKO_API_KEY, IFB_COINGECKO_PLAN
+                                      CoinGecko key and plan (demo|pro)
+  IFB_CONFIG_DIR                      Where settings and secrets are stored
+  IFB_MEMPOOL_URL                     mempool.space-compatible API for the
+                                      Network page [default: https://mempool.space/api]
+  IFB_ESPLORA_URL                     Esplora-compatible API for Wallet balances
+                                      [default: https://blockstream.info/api]
+  IFB_CASSETTE, IFB_CASSETTE_MODE     Record or replay HTTP traffic";
+
+impl CliArgs {
+    /// Parses process arguments (without the program name).
+    /// Unknown or malformed options are logged and ignored.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || inline_value.clone().or_else(|| args.next());
+
+            match flag.as_str() {
+                "--headless" => parsed.headless = true,
+                "-h" | "--help" => parsed.help = true,
+                "--port" => match value().and_then(|v| v.parse().ok()) {
+                    Some(port) => parsed.port = port,
+                    None => warn!("--port expects a number between 1 and 65535"),
+                },
+                "--interval" => match value().and_then(|v| v.parse().ok()) {
+                    Some(secs) if secs > 0 => parsed.interval = secs,
+                    _ => warn!("--interval expects a positive number of seconds"),
+                },
+                "--proxy" => match value() {
+                    Some(url) => parsed.proxy = Some(url),
+                    None => warn!("--proxy expects a URL"),
+                },
+                "--proxy-user" => match value() {
+                    Some(user) => parsed.proxy_user = Some(user),
+                    None => warn!("--proxy-user expects a user name"),
+                },
+                "--ca-bundle" => match value() {
+                    Some(path) => parsed.ca_bundle = Some(PathBuf::from(path)),
+                    None => warn!("--ca-bundle expects a file path"),
+                },
+                "--connect-timeout" => match value().and_then(|v| v.parse().ok()) {
+                    Some(secs) if secs > 0 => parsed.connect_timeout = Some(secs),
+                    _ => warn!("--connect-timeout expects a positive number of seconds"),
+                },
+                "--timeout" => match value().and_then(|v| v.parse().ok()) {
+                    Some(secs) if secs > 0 => parsed.timeout = Some(secs),
+                    _ => warn!("--timeout expects a positive number of seconds"),
+                },
+                other => warn!("Ignoring unknown argument: {other}"),
+            }
+        }
+        parsed
+    }
+
+    /// Applies the HTTP options given on the command line on top of `base`
+    pub fn http_config(&self, base: HttpConfig) -> HttpConfig {
+        HttpConfig {
+            proxy: self.proxy.clone().or(base.proxy),
+            proxy_user: self.proxy_user.clone().or(base.proxy_user),
+            ca_bundle: self.ca_bundle.clone().or(base.ca_bundle),
+            connect_timeout: self
+                .connect_timeout
+                .map(Duration::from_secs)
+                .unwrap_or(base.connect_timeout),
+            timeout: self.timeout.map(Duration::from_secs).unwrap_or(base.timeout),
+            ..base
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> CliArgs {
+        CliArgs::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn defaults_without_arguments() {
+        assert_eq!(parse(&[]), CliArgs::default());
+    }
+
+    #[test]
+    fn headless_with_separate_and_inline_values() {
+        let args = parse(&["--headless", "--port", "9100", "--interval=15"]);
+        assert!(args.headless);
+        assert_eq!(args.port, 9100);
+        assert_eq!(args.interval, 15);
+    }
+
+    #[test]
+    fn malformed_values_keep_defaults() {
+        let args = parse(&["--port", "http", "--interval", "0", "--bogus"]);
+        assert_eq!(args.port, server::DEFAULT_PORT);
+        assert_eq!(args.interval, DEFAULT_HEADLESS_INTERVAL);
+    }
+
+    #[test]
+    fn http_options_override_environment() {
+        let args = parse(&["--proxy=http://cli:3128", "--connect-timeout", "3"]);
+        let base = HttpConfig {
+            proxy: Some("http://env:8080".into()),
+            proxy_user: Some("env-user".into()),
+            proxy_password: Some("secret".into()),
+            timeout: Duration::from_secs(45),
+            ..HttpConfig::default()
+        };
+
+        let config = args.http_config(base);
+        assert_eq!(config.proxy.as_deref(), Some("http://cli:3128"));
+        assert_eq!(config.proxy_user.as_deref(), Some("env-user"));
+        assert_eq!(config.proxy_password.as_deref(), Some("secret"));
+        assert_eq!(config.connect_timeout, Duration::from_secs(3));
+        assert_eq!(config.timeout, Duration::from_secs(45));
+    }
+}
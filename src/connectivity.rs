@@ -0,0 +1,94 @@
+use futures::{SinkExt, Stream};
+use std::time::Duration;
+use tracing::{debug, info};
+
+use crate::http_client;
+
+/// Lightweight CoinGecko endpoint used to check reachability
+pub const PROBE_URL: &str = "https://api.coingecko.com/api/v3/ping";
+
+/// How often reachability is re-checked while online
+const ONLINE_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+/// How often reachability is re-checked while offline
+const OFFLINE_PROBE_INTERVAL: Duration = Duration::from_secs(5);
+/// Per-probe timeout; a sleeping or captive network should not stall the check
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether the price provider can be reached at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Connectivity {
+    #[default]
+    Online,
+    Offline,
+}
+
+/// Checks reachability of `url`.
+///
+/// Any HTTP response (even 429 or 500) counts as online; only failures to
+/// resolve or connect count as offline. A timeout proves neither, so it is
+/// ignored like any other request error.
+pub async fn probe(client: &reqwest::Client, url: &str) -> Connectivity {
+    match client.get(url).timeout(PROBE_TIMEOUT).send().await {
+        Ok(_) => Connectivity::Online,
+        Err(e) if http_client::is_unreachable(&e) => {
+            debug!("Connectivity probe failed: {e}");
+            Connectivity::Offline
+        }
+        Err(e) => {
+            debug!("Connectivity probe error ignored: {e}");
+            Connectivity::Online
+        }
+    }
+}
+
+/// Probes [`PROBE_URL`] periodically and yields the state whenever it differs
+/// from `assumed`.
+///
+/// Used as an iced subscription keyed on `assumed`: once the app switches
+/// state, the subscription restarts with the other probe interval.
+pub fn watch(assumed: Connectivity) -> impl Stream<Item = Connectivity> {
+    iced::stream::channel(1, async move |mut output| {
+        let interval = match assumed {
+            Connectivity::Online => ONLINE_PROBE_INTERVAL,
+            Connectivity::Offline => OFFLINE_PROBE_INTERVAL,
+        };
+        loop {
+            tokio::time::sleep(interval).await;
+            let state = probe(&http_client::client(), PROBE_URL).await;
+            if state != assumed {
+                info!("Connectivity changed: {assumed:?} -> {state:?}");
+                let _ = output.send(state).await;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::PriceStore;
+    use std::net::Ipv4Addr;
+    use std::sync::{Arc, RwLock};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn refused_connection_is_offline() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+
+        assert_eq!(probe(&http_client::client(), &url).await, Connectivity::Offline);
+    }
+
+    #[tokio::test]
+    async fn any_http_response_is_online() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        // Unknown path: the local server answers 404, which still proves reachability
+        let url = format!("http://{}/nope", listener.local_addr().unwrap());
+        let store = Arc::new(RwLock::new(PriceStore::default()));
+        let server = tokio::spawn(crate::server::serve(listener, store));
+
+        assert_eq!(probe(&http_client::client(), &url).await, Connectivity::Online);
+        server.abort();
+    }
+}
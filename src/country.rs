@@ -1,94 +1,222 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::paths;
 
 static FLAG_API_URL: &str =
     "https://raw.githubusercontent.com/lipis/flag-icons/refs/heads/main/flags/4x3/";
 
+/// Built-in catalogue, embedded at compile time
+const DEFAULT_CATALOGUE: &str = include_str!("../resources/countries.json");
+
+/// Name of the user override file inside [`paths::config_dir`]
+const OVERRIDE_FILE: &str = "countries.json";
+
 /// Country with currency and flag information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Country {
     /// ISO 4217 currency code (lowercase, e.g. "usd")
-    pub currency: &'static str,
+    pub currency: String,
     /// Country display name
-    pub name: &'static str,
+    pub name: String,
     /// ISO 3166-1 alpha-2 country code (lowercase, e.g. "us")
-    pub country_code: &'static str,
-    /// Full URL to the SVG flag image
+    pub country_code: String,
+    /// Full URL to the SVG flag image; defaults to the flag-icons set
+    #[serde(default)]
     pub flag_url: String,
 }
 
 /// A country paired with its Bitcoin price
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CountryPrice {
     pub country: Country,
-    pub price: f64,
+    /// `None` when the provider does not quote this currency
+    pub price: Option<f64>,
 }
 
 impl CountryPrice {
-    pub fn new(country: Country, price: f64) -> Self {
+    pub fn new(country: Country, price: Option<f64>) -> Self {
         Self { country, price }
     }
 }
 
 impl Country {
-    fn new(currency: &'static str, name: &'static str, country_code: &'static str) -> Country {
-        Self {
-            currency,
-            name,
-            country_code,
-            flag_url: format!("{}{}.svg", FLAG_API_URL, country_code),
+    /// Fills in the default flag URL when the entry has none
+    fn with_default_flag(mut self) -> Self {
+        if self.flag_url.is_empty() {
+            self.flag_url = format!("{}{}.svg", FLAG_API_URL, self.country_code);
+        }
+        self
+    }
+}
+
+/// User changes applied on top of the built-in catalogue.
+///
+/// ```json
+/// {
+///   "add": [{ "currency": "xau", "name": "Gold", "country_code": "un" }],
+///   "hide": ["vef"],
+///   "rename": { "hkd": "Hong Kong" },
+///   "flag_urls": { "usd": "https://example.com/us.svg" }
+/// }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CatalogueOverride {
+    /// New entries, appended after the built-in ones
+    pub add: Vec<Country>,
+    /// Currencies to leave out of the list; USD is still fetched, since
+    /// every other price is judged against it
+    pub hide: Vec<String>,
+    /// New display names, keyed by currency
+    pub rename: HashMap<String, String>,
+    /// Replacement flag URLs, keyed by currency
+    pub flag_urls: HashMap<String, String>,
+}
+
+/// Why a catalogue or override file was rejected
+#[derive(Debug, Error)]
+pub enum CatalogueError {
+    #[error("Failed to read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid catalogue JSON: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Currency {currency:?} must be three lowercase ASCII letters")]
+    BadCurrency { currency: String },
+    #[error("Country code {code:?} for {currency} must be two lowercase ASCII letters")]
+    BadCountryCode { currency: String, code: String },
+    #[error("Flag URL for {currency} must be an http(s) URL")]
+    BadFlagUrl { currency: String },
+    #[error("Duplicate currency {0}")]
+    Duplicate(String),
+    #[error("Override refers to unknown currency {0}")]
+    UnknownCurrency(String),
+    #[error("Catalogue has no currencies")]
+    Empty,
+}
+
+/// Parses a catalogue (a JSON array of countries) and validates it
+pub fn parse_catalogue(json: &str) -> Result<Vec<Country>, CatalogueError> {
+    let countries: Vec<Country> = serde_json::from_str(json)?;
+    let countries: Vec<Country> = countries
+        .into_iter()
+        .map(Country::with_default_flag)
+        .collect();
+    validate(&countries)?;
+    Ok(countries)
+}
+
+/// Applies `changes` to `countries` and validates the result
+pub fn apply_override(
+    mut countries: Vec<Country>,
+    changes: CatalogueOverride,
+) -> Result<Vec<Country>, CatalogueError> {
+    countries.extend(changes.add.into_iter().map(Country::with_default_flag));
+
+    let known: HashSet<&str> = countries.iter().map(|c| c.currency.as_str()).collect();
+    if let Some(unknown) = changes
+        .hide
+        .iter()
+        .chain(changes.rename.keys())
+        .chain(changes.flag_urls.keys())
+        .find(|c| !known.contains(c.as_str()))
+    {
+        return Err(CatalogueError::UnknownCurrency(unknown.clone()));
+    }
+
+    countries.retain(|c| !changes.hide.contains(&c.currency));
+    for country in &mut countries {
+        if let Some(name) = changes.rename.get(&country.currency) {
+            country.name = name.clone();
+        }
+        if let Some(url) = changes.flag_urls.get(&country.currency) {
+            country.flag_url = url.clone();
         }
     }
+
+    validate(&countries)?;
+    Ok(countries)
 }
 
-/// Cached list of supported countries. Allocated once on first access.
+/// Checks code formats, flag URLs and duplicates
+pub fn validate(countries: &[Country]) -> Result<(), CatalogueError> {
+    if countries.is_empty() {
+        return Err(CatalogueError::Empty);
+    }
+    let is_code = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_lowercase());
+
+    let mut seen = HashSet::new();
+    for c in countries {
+        if !is_code(&c.currency, 3) {
+            return Err(CatalogueError::BadCurrency {
+                currency: c.currency.clone(),
+            });
+        }
+        if !is_code(&c.country_code, 2) {
+            return Err(CatalogueError::BadCountryCode {
+                currency: c.currency.clone(),
+                code: c.country_code.clone(),
+            });
+        }
+        if !(c.flag_url.starts_with("https://") || c.flag_url.starts_with("http://")) {
+            return Err(CatalogueError::BadFlagUrl {
+                currency: c.currency.clone(),
+            });
+        }
+        if !seen.insert(c.currency.as_str()) {
+            return Err(CatalogueError::Duplicate(c.currency.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// The embedded catalogue
+pub fn default_catalogue() -> Vec<Country> {
+    parse_catalogue(DEFAULT_CATALOGUE).expect("embedded countries.json is valid")
+}
+
+/// Location of the user override file
+pub fn override_path() -> PathBuf {
+    paths::config_dir().join(OVERRIDE_FILE)
+}
+
+/// Built-in catalogue with the override at `path` applied, if that file exists
+pub fn load_catalogue(path: &Path) -> Result<Vec<Country>, CatalogueError> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(default_catalogue()),
+        Err(source) => {
+            return Err(CatalogueError::Read {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+    };
+    let changes: CatalogueOverride = serde_json::from_str(&text)?;
+    let countries = apply_override(default_catalogue(), changes)?;
+    info!(
+        "Loaded {} currencies using {}",
+        countries.len(),
+        path.display()
+    );
+    Ok(countries)
+}
+
+/// Cached list of supported countries. Loaded once on first access; an invalid
+/// override file is reported and ignored.
 static COUNTRIES: LazyLock<Vec<Country>> = LazyLock::new(|| {
-    vec![
-        Country::new("aed", "United Arab Emirates", "ae"),
-        Country::new("ars", "Argentina", "ar"),
-        Country::new("aud", "Australia", "au"),
-        Country::new("bdt", "Bangladesh", "bd"),
-        Country::new("bhd", "Bahrain", "bh"),
-        Country::new("bmd", "Bermuda", "bm"),
-        Country::new("brl", "Brazil", "br"),
-        Country::new("cad", "Canada", "ca"),
-        Country::new("chf", "Switzerland", "ch"),
-        Country::new("clp", "Chile", "cl"),
-        Country::new("cny", "China", "cn"),
-        Country::new("czk", "Czech Republic", "cz"),
-        Country::new("dkk", "Denmark", "dk"),
-        Country::new("gbp", "United Kingdom", "gb"),
-        Country::new("gel", "Georgia", "ge"),
-        Country::new("hkd", "China Hong Kong", "hk"),
-        Country::new("huf", "Hungary", "hu"),
-        Country::new("idr", "Indonesia", "id"),
-        Country::new("ils", "Israel", "il"),
-        Country::new("inr", "India", "in"),
-        Country::new("jpy", "Japan", "jp"),
-        Country::new("krw", "South Korea", "kr"),
-        Country::new("kwd", "Kuwait", "kw"),
-        Country::new("lkr", "Sri Lanka", "lk"),
-        Country::new("mmk", "Myanmar", "mm"),
-        Country::new("mxn", "Mexico", "mx"),
-        Country::new("myr", "Malaysia", "my"),
-        Country::new("ngn", "Nigeria", "ng"),
-        Country::new("nok", "Norway", "no"),
-        Country::new("nzd", "New Zealand", "nz"),
-        Country::new("php", "Philippines", "ph"),
-        Country::new("pkr", "Pakistan", "pk"),
-        Country::new("pln", "Poland", "pl"),
-        Country::new("rub", "Russia", "ru"),
-        Country::new("sar", "Saudi Arabia", "sa"),
-        Country::new("sek", "Sweden", "se"),
-        Country::new("sgd", "Singapore", "sg"),
-        Country::new("thb", "Thailand", "th"),
-        Country::new("try", "Turkey", "tr"),
-        Country::new("twd", "China Taiwan", "tw"),
-        Country::new("uah", "Ukraine", "ua"),
-        Country::new("usd", "United States", "us"),
-        Country::new("vef", "Venezuela", "ve"),
-        Country::new("vnd", "Vietnam", "vn"),
-        Country::new("zar", "South Africa", "za"),
-    ]
+    let path = override_path();
+    load_catalogue(&path).unwrap_or_else(|e| {
+        warn!("Ignoring {}: {e}", path.display());
+        default_catalogue()
+    })
 });
 
 /// Returns a reference to the cached country list.
@@ -102,20 +230,24 @@ mod tests {
 
     #[test]
     fn country_count() {
-        assert_eq!(get_countries().len(), 45);
+        assert_eq!(default_catalogue().len(), 45);
     }
 
     #[test]
     fn country_code_is_two_ascii_chars() {
-        for c in get_countries() {
+        for c in default_catalogue() {
             assert_eq!(c.country_code.len(), 2, "Bad country code for {}", c.name);
-            assert!(c.country_code.is_ascii(), "Non-ASCII country code for {}", c.name);
+            assert!(
+                c.country_code.is_ascii(),
+                "Non-ASCII country code for {}",
+                c.name
+            );
         }
     }
 
     #[test]
     fn currency_is_three_ascii_chars() {
-        for c in get_countries() {
+        for c in default_catalogue() {
             assert_eq!(c.currency.len(), 3, "Bad currency for {}", c.name);
             assert!(c.currency.is_ascii(), "Non-ASCII currency for {}", c.name);
         }
@@ -123,18 +255,102 @@ mod tests {
 
     #[test]
     fn flag_url_contains_country_code() {
-        for c in get_countries() {
+        for c in default_catalogue() {
             assert!(c.flag_url.ends_with(".svg"), "Bad flag URL for {}", c.name);
-            assert!(c.flag_url.contains(c.country_code), "Flag URL missing code for {}", c.name);
+            assert!(
+                c.flag_url.contains(&c.country_code),
+                "Flag URL missing code for {}",
+                c.name
+            );
         }
     }
 
     #[test]
     fn no_duplicate_currencies() {
-        let mut currencies: Vec<_> = get_countries().iter().map(|c| c.currency).collect();
+        let mut currencies: Vec<_> = default_catalogue()
+            .into_iter()
+            .map(|c| c.currency)
+            .collect();
         currencies.sort();
         let len_before = currencies.len();
         currencies.dedup();
         assert_eq!(len_before, currencies.len(), "Duplicate currencies found");
     }
+
+    fn changes(json: &str) -> CatalogueOverride {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn override_adds_hides_renames_and_changes_flags() {
+        let countries = apply_override(
+            default_catalogue(),
+            changes(
+                r#"{
+                    "add": [{ "currency": "xau", "name": "Gold", "country_code": "un" }],
+                    "hide": ["vef"],
+                    "rename": { "hkd": "Hong Kong" },
+                    "flag_urls": { "usd": "https://example.com/us.svg" }
+                }"#,
+            ),
+        )
+        .unwrap();
+        let find = |cur: &str| countries.iter().find(|c| c.currency == cur);
+
+        assert_eq!(countries.len(), 45);
+        assert!(find("vef").is_none());
+        assert_eq!(find("hkd").unwrap().name, "Hong Kong");
+        assert_eq!(find("usd").unwrap().flag_url, "https://example.com/us.svg");
+        assert!(find("xau").unwrap().flag_url.ends_with("/un.svg"));
+    }
+
+    #[test]
+    fn invalid_overrides_are_rejected() {
+        let reject = |json: &str| apply_override(default_catalogue(), changes(json)).unwrap_err();
+
+        assert!(matches!(
+            reject(r#"{ "add": [{ "currency": "usd", "name": "Again", "country_code": "us" }] }"#),
+            CatalogueError::Duplicate(c) if c == "usd"
+        ));
+        assert!(matches!(
+            reject(r#"{ "add": [{ "currency": "gold", "name": "Gold", "country_code": "un" }] }"#),
+            CatalogueError::BadCurrency { .. }
+        ));
+        assert!(matches!(
+            reject(r#"{ "add": [{ "currency": "xau", "name": "Gold", "country_code": "UN" }] }"#),
+            CatalogueError::BadCountryCode { .. }
+        ));
+        assert!(matches!(
+            reject(r#"{ "rename": { "zzz": "Nowhere" } }"#),
+            CatalogueError::UnknownCurrency(c) if c == "zzz"
+        ));
+        assert!(matches!(
+            reject(r#"{ "flag_urls": { "usd": "file:///etc/passwd" } }"#),
+            CatalogueError::BadFlagUrl { .. }
+        ));
+        assert!(serde_json::from_str::<CatalogueOverride>(r#"{ "remove": [] }"#).is_err());
+    }
+
+    #[test]
+    fn override_file_is_optional_and_validated() {
+        let dir = std::env::temp_dir().join(format!("ifb-countries-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(OVERRIDE_FILE);
+
+        assert_eq!(load_catalogue(&path).unwrap(), default_catalogue());
+
+        std::fs::write(&path, r#"{ "hide": ["usd"] }"#).unwrap();
+        assert!(load_catalogue(&path)
+            .unwrap()
+            .iter()
+            .all(|c| c.currency != "usd"));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            load_catalogue(&path),
+            Err(CatalogueError::Parse(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
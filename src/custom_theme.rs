@@ -0,0 +1,384 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use iced::theme::Palette;
+use iced::{Color, Theme};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::paths;
+
+/// Directory inside [`paths::config_dir`] holding saved palettes
+const THEMES_DIR: &str = "themes";
+/// File in the themes directory recording which themes are in use; the
+/// underscore keeps it apart from palettes, as [`file_name`] never makes one
+const SELECTION_FILE: &str = "_selection.json";
+
+/// Editable palette colours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeColor {
+    Background,
+    Text,
+    Primary,
+    Success,
+    Danger,
+}
+
+impl ThemeColor {
+    pub const ALL: [ThemeColor; 5] = [
+        ThemeColor::Background,
+        ThemeColor::Text,
+        ThemeColor::Primary,
+        ThemeColor::Success,
+        ThemeColor::Danger,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThemeColor::Background => "Background",
+            ThemeColor::Text => "Text",
+            ThemeColor::Primary => "Primary",
+            ThemeColor::Success => "Success",
+            ThemeColor::Danger => "Danger",
+        }
+    }
+}
+
+/// Parses `#rrggbb` or `rrggbb` (case-insensitive)
+pub fn parse_hex(input: &str) -> Option<Color> {
+    let hex = input.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::from_rgb8(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Formats a colour as `#rrggbb`, dropping alpha
+pub fn to_hex(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+mod hex_color {
+    use iced::Color;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::to_hex(*color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        super::parse_hex(&text)
+            .ok_or_else(|| de::Error::custom(format!("invalid colour {text:?}, expected #rrggbb")))
+    }
+}
+
+/// A named palette that builds a custom [`Theme`]; stored as JSON with
+/// `#rrggbb` colours so it can be shared as a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemePalette {
+    pub name: String,
+    #[serde(with = "hex_color")]
+    pub background: Color,
+    #[serde(with = "hex_color")]
+    pub text: Color,
+    #[serde(with = "hex_color")]
+    pub primary: Color,
+    #[serde(with = "hex_color")]
+    pub success: Color,
+    #[serde(with = "hex_color")]
+    pub danger: Color,
+}
+
+/// Why a palette file could not be used
+#[derive(Debug, Error)]
+pub enum ThemeFileError {
+    #[error("Cannot access {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Invalid theme file: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Theme name must not be empty")]
+    EmptyName,
+}
+
+impl ThemePalette {
+    /// Starts from the palette of an existing theme
+    pub fn from_theme(name: impl Into<String>, theme: &Theme) -> Self {
+        let palette = theme.palette();
+        Self {
+            name: name.into(),
+            background: palette.background,
+            text: palette.text,
+            primary: palette.primary,
+            success: palette.success,
+            danger: palette.danger,
+        }
+    }
+
+    pub fn color(&self, which: ThemeColor) -> Color {
+        match which {
+            ThemeColor::Background => self.background,
+            ThemeColor::Text => self.text,
+            ThemeColor::Primary => self.primary,
+            ThemeColor::Success => self.success,
+            ThemeColor::Danger => self.danger,
+        }
+    }
+
+    pub fn set_color(&mut self, which: ThemeColor, color: Color) {
+        match which {
+            ThemeColor::Background => self.background = color,
+            ThemeColor::Text => self.text = color,
+            ThemeColor::Primary => self.primary = color,
+            ThemeColor::Success => self.success = color,
+            ThemeColor::Danger => self.danger = color,
+        }
+    }
+
+    /// Whether the background is dark; picks the matching warning colour
+    pub fn is_dark(&self) -> bool {
+        self.background.relative_luminance() < 0.5
+    }
+
+    pub fn to_theme(&self) -> Theme {
+        let base = if self.is_dark() {
+            Palette::DARK
+        } else {
+            Palette::LIGHT
+        };
+        Theme::custom(
+            self.name.clone(),
+            Palette {
+                background: self.background,
+                text: self.text,
+                primary: self.primary,
+                success: self.success,
+                warning: base.warning,
+                danger: self.danger,
+            },
+        )
+    }
+
+    /// Reads and validates a palette file
+    pub fn load(path: &Path) -> Result<Self, ThemeFileError> {
+        let text = std::fs::read_to_string(path).map_err(|source| ThemeFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let palette: Self = serde_json::from_str(&text)?;
+        if palette.name.trim().is_empty() {
+            return Err(ThemeFileError::EmptyName);
+        }
+        Ok(palette)
+    }
+
+    /// Writes the palette as pretty JSON, creating parent directories
+    pub fn save(&self, path: &Path) -> Result<(), ThemeFileError> {
+        if self.name.trim().is_empty() {
+            return Err(ThemeFileError::EmptyName);
+        }
+        let io_error = |source| ThemeFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json).map_err(io_error)
+    }
+}
+
+/// Which themes are in use, by name, so a custom palette is found again
+/// among the saved ones at the next launch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeSelection {
+    /// Follow the OS light/dark preference instead of `selected`
+    pub follow_system: bool,
+    pub selected: String,
+    pub light: String,
+    pub dark: String,
+}
+
+impl ThemeSelection {
+    /// The selection saved in `dir`, if any
+    pub fn load(dir: &Path) -> Result<Option<Self>, ThemeFileError> {
+        let path = dir.join(SELECTION_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Ok(Some(serde_json::from_str(&text)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(ThemeFileError::Io { path, source }),
+        }
+    }
+
+    /// Writes the selection into `dir`, creating it if needed
+    pub fn save(&self, dir: &Path) -> Result<(), ThemeFileError> {
+        let path = dir.join(SELECTION_FILE);
+        let io_error = |source| ThemeFileError::Io {
+            path: path.clone(),
+            source,
+        };
+        std::fs::create_dir_all(dir).map_err(io_error)?;
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, json).map_err(io_error)
+    }
+}
+
+/// Built-in theme or saved palette called `name`
+pub fn theme_named(name: &str, saved: &[ThemePalette]) -> Option<Theme> {
+    Theme::ALL
+        .iter()
+        .find(|theme| theme.to_string() == name)
+        .cloned()
+        .or_else(|| {
+            saved
+                .iter()
+                .find(|palette| palette.name == name)
+                .map(ThemePalette::to_theme)
+        })
+}
+
+/// Directory holding saved palettes
+pub fn themes_dir() -> PathBuf {
+    paths::config_dir().join(THEMES_DIR)
+}
+
+/// File name for a palette: its name lowercased, other characters as `-`
+pub fn file_name(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{slug}.json")
+}
+
+/// Every valid palette in `dir`, sorted by name; broken files are skipped
+pub fn load_saved(dir: &Path) -> Vec<ThemePalette> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut palettes: Vec<ThemePalette> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter(|path| !path.ends_with(SELECTION_FILE))
+        .filter_map(|path| {
+            ThemePalette::load(&path)
+                .inspect_err(|e| warn!("Skipping theme {}: {e}", path.display()))
+                .ok()
+        })
+        .collect();
+    palettes.sort_by(|a, b| a.name.cmp(&b.name));
+    palettes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ifb-themes-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn hex_colours_round_trip() {
+        assert_eq!(parse_hex("#ff8000"), Some(Color::from_rgb8(255, 128, 0)));
+        assert_eq!(parse_hex(" FF8000 "), Some(Color::from_rgb8(255, 128, 0)));
+        assert_eq!(parse_hex("#ff80"), None);
+        assert_eq!(parse_hex("#gg8000"), None);
+        assert_eq!(to_hex(Color::from_rgb8(1, 2, 255)), "#0102ff");
+    }
+
+    #[test]
+    fn palette_builds_a_custom_theme() {
+        let mut palette = ThemePalette::from_theme("Midnight", &Theme::Nord);
+        palette.set_color(ThemeColor::Primary, Color::from_rgb8(255, 128, 0));
+        let theme = palette.to_theme();
+
+        assert_eq!(theme.to_string(), "Midnight");
+        assert_eq!(theme.palette().primary, Color::from_rgb8(255, 128, 0));
+        assert_eq!(theme.palette().background, Theme::Nord.palette().background);
+        assert!(palette.is_dark());
+        assert!(!ThemePalette::from_theme("Day", &Theme::Light).is_dark());
+    }
+
+    #[test]
+    fn palettes_are_saved_shared_and_reloaded() {
+        let dir = temp_dir("save");
+        let palette = ThemePalette::from_theme("My Theme!", &Theme::Dracula);
+        let path = dir.join(file_name(&palette.name));
+        assert!(path.ends_with("my-theme-.json"));
+
+        palette.save(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("\"background\": \"#"), "{text}");
+        assert_eq!(ThemePalette::load(&path).unwrap(), palette);
+
+        std::fs::write(dir.join("broken.json"), r#"{ "name": "Broken" }"#).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+        assert_eq!(load_saved(&dir), vec![palette.clone()]);
+
+        // The selection lives next to the palettes without being one
+        assert_eq!(ThemeSelection::load(&dir).unwrap(), None);
+        let selection = ThemeSelection {
+            follow_system: true,
+            selected: "Nord".into(),
+            light: "Solarized Light".into(),
+            dark: "My Theme!".into(),
+        };
+        selection.save(&dir).unwrap();
+        assert_eq!(ThemeSelection::load(&dir).unwrap(), Some(selection));
+        assert_eq!(load_saved(&dir), vec![palette.clone()]);
+        assert_eq!(
+            theme_named("Solarized Light", &[]),
+            Some(Theme::SolarizedLight)
+        );
+        assert_eq!(
+            theme_named("My Theme!", std::slice::from_ref(&palette)),
+            Some(palette.to_theme())
+        );
+        assert_eq!(theme_named("My Theme!", &[]), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_palette_files_are_rejected() {
+        let dir = temp_dir("invalid");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bad.json");
+
+        let mut palette = ThemePalette::from_theme("", &Theme::Dark);
+        assert!(matches!(
+            palette.save(&path),
+            Err(ThemeFileError::EmptyName)
+        ));
+
+        palette.name = "Bad".into();
+        let json = serde_json::to_string(&palette)
+            .unwrap()
+            .replace(&to_hex(palette.text), "#12");
+        std::fs::write(&path, json).unwrap();
+        let error = ThemePalette::load(&path).unwrap_err();
+        assert!(
+            error.to_string().contains("invalid colour \"#12\""),
+            "{error}"
+        );
+
+        assert!(matches!(
+            ThemePalette::load(&dir.join("missing.json")),
+            Err(ThemeFileError::Io { .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,288 @@
+//! Address balances from an Esplora-compatible REST API (Blockstream's
+//! explorer, mempool.space, or a self-hosted electrs).
+//!
+//! Only addresses leave the machine: extended keys are expanded locally and
+//! each derived address is looked up on its own.
+
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::info;
+
+use crate::address::Address;
+use crate::http_client::{Transport, TRANSPORT};
+use crate::watchlist::WatchTarget;
+use crate::xpub::{ExtendedPubKey, XpubError, CHANGE_CHAIN, RECEIVE_CHAIN};
+
+/// Environment variable overriding the API base URL
+pub const ESPLORA_URL_ENV: &str = "IFB_ESPLORA_URL";
+
+pub const DEFAULT_BASE_URL: &str = "https://blockstream.info/api";
+
+/// Unused addresses in a row after which a chain is assumed to end (BIP 44)
+pub const GAP_LIMIT: u32 = 20;
+
+/// Funding and spending totals, in sats
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct TxoStats {
+    funded_txo_sum: u64,
+    spent_txo_sum: u64,
+    tx_count: u64,
+}
+
+/// `/address/:address`
+#[derive(Deserialize, Debug, Clone, Copy)]
+struct AddressInfo {
+    chain_stats: TxoStats,
+    mempool_stats: TxoStats,
+}
+
+/// Balance of an address or of everything an extended key has used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Balance {
+    /// In sats
+    pub confirmed: u64,
+    /// Net effect of mempool transactions in sats; negative while spending
+    pub unconfirmed: i64,
+    pub tx_count: u64,
+    /// Addresses with any history
+    pub used_addresses: u32,
+}
+
+impl Balance {
+    /// Confirmed plus unconfirmed, in sats
+    pub fn total(&self) -> u64 {
+        (self.confirmed as i64 + self.unconfirmed).max(0) as u64
+    }
+
+    fn add(&mut self, info: &AddressInfo) {
+        let chain = info.chain_stats;
+        let mempool = info.mempool_stats;
+        self.confirmed += chain.funded_txo_sum.saturating_sub(chain.spent_txo_sum);
+        self.unconfirmed += mempool.funded_txo_sum as i64 - mempool.spent_txo_sum as i64;
+        self.tx_count += chain.tx_count + mempool.tx_count;
+        if chain.tx_count + mempool.tx_count > 0 {
+            self.used_addresses += 1;
+        }
+    }
+}
+
+/// Why a balance could not be loaded
+#[derive(Debug, Clone, Error)]
+pub enum EsploraError {
+    #[error("Could not reach {url}: {message}")]
+    Network { url: String, message: String },
+    #[error("HTTP {status} from {url}")]
+    HttpStatus { url: String, status: u16 },
+    #[error("Invalid response from {url}: {message}")]
+    Invalid { url: String, message: String },
+    #[error("Could not derive addresses: {0}")]
+    Derivation(#[from] XpubError),
+}
+
+/// An Esplora-compatible API, addressed by its base URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EsploraApi {
+    base_url: String,
+}
+
+impl Default for EsploraApi {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+}
+
+impl EsploraApi {
+    /// The base URL in [`ESPLORA_URL_ENV`] when valid, otherwise Blockstream's
+    pub fn from_env() -> Self {
+        match std::env::var(ESPLORA_URL_ENV) {
+            Ok(url) => Self::new(&url).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring {ESPLORA_URL_ENV}: {e}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Checks that `base_url` is an http(s) URL; a trailing `/` is dropped
+    pub fn new(base_url: &str) -> Result<Self, String> {
+        let base_url = base_url.trim().trim_end_matches('/');
+        let host = base_url
+            .strip_prefix("https://")
+            .or_else(|| base_url.strip_prefix("http://"))
+            .ok_or_else(|| "Base URL must start with http:// or https://".to_string())?;
+        if host.is_empty() {
+            return Err("Base URL needs a host".to_string());
+        }
+        Ok(Self {
+            base_url: base_url.to_string(),
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn fetch_balance(&self, target: &WatchTarget) -> Result<Balance, EsploraError> {
+        self.fetch_balance_with(&TRANSPORT, target, GAP_LIMIT).await
+    }
+
+    /// Same as [`fetch_balance`](Self::fetch_balance), over an explicit
+    /// transport and with an explicit gap limit
+    async fn fetch_balance_with(
+        &self,
+        transport: &Transport,
+        target: &WatchTarget,
+        gap_limit: u32,
+    ) -> Result<Balance, EsploraError> {
+        let mut balance = Balance::default();
+        match target {
+            WatchTarget::Address(address) => {
+                balance.add(&self.address_info(transport, address).await?);
+            }
+            WatchTarget::Xpub(key) => {
+                for chain in [RECEIVE_CHAIN, CHANGE_CHAIN] {
+                    self.scan_chain(transport, key, chain, gap_limit, &mut balance)
+                        .await?;
+                }
+            }
+        }
+        Ok(balance)
+    }
+
+    /// Adds up addresses on one chain until `gap_limit` unused ones in a row
+    async fn scan_chain(
+        &self,
+        transport: &Transport,
+        key: &ExtendedPubKey,
+        chain: u32,
+        gap_limit: u32,
+        balance: &mut Balance,
+    ) -> Result<(), EsploraError> {
+        let mut unused = 0;
+        let mut index = 0;
+        while unused < gap_limit {
+            let address = key.derive(chain, index)?;
+            let info = self.address_info(transport, &address).await?;
+            let used_before = balance.used_addresses;
+            balance.add(&info);
+            unused = if balance.used_addresses > used_before {
+                0
+            } else {
+                unused + 1
+            };
+            index += 1;
+        }
+        Ok(())
+    }
+
+    async fn address_info(
+        &self,
+        transport: &Transport,
+        address: &Address,
+    ) -> Result<AddressInfo, EsploraError> {
+        let url = format!("{}/address/{address}", self.base_url);
+        info!("Fetching address balance from: {url}");
+        let response = transport
+            .get(&url)
+            .await
+            .map_err(|e| EsploraError::Network {
+                url: url.clone(),
+                message: e.to_string(),
+            })?;
+        if !response.status.is_success() {
+            return Err(EsploraError::HttpStatus {
+                url,
+                status: response.status.as_u16(),
+            });
+        }
+        serde_json::from_str(&response.text()).map_err(|e| EsploraError::Invalid {
+            url,
+            message: e.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cassette::Cassette;
+    use std::sync::Arc;
+
+    const LOCAL: &str = "http://127.0.0.1:3002/api";
+    /// BIP 84 test vector account key
+    const ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    fn replay() -> Transport {
+        let path = format!(
+            "{}/tests/cassettes/esplora.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        Transport::Replay(Arc::new(Cassette::load(path).unwrap()))
+    }
+
+    #[test]
+    fn base_url_is_validated_and_normalised() {
+        assert_eq!(
+            EsploraApi::new(" http://127.0.0.1:3002/api/ ")
+                .unwrap()
+                .base_url(),
+            LOCAL
+        );
+        assert!(EsploraApi::new("blockstream.info/api").is_err());
+        assert_eq!(EsploraApi::default().base_url(), DEFAULT_BASE_URL);
+    }
+
+    #[tokio::test]
+    async fn address_balance_includes_the_mempool() {
+        let api = EsploraApi::new(LOCAL).unwrap();
+        let target = WatchTarget::parse("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4").unwrap();
+        let balance = api
+            .fetch_balance_with(&replay(), &target, GAP_LIMIT)
+            .await
+            .unwrap();
+        assert_eq!(
+            balance,
+            Balance {
+                confirmed: 150_000,
+                unconfirmed: -50_000,
+                tx_count: 4,
+                used_addresses: 1,
+            }
+        );
+        assert_eq!(balance.total(), 100_000);
+    }
+
+    #[tokio::test]
+    async fn xpub_scan_stops_after_the_gap_limit() {
+        // With a gap limit of 3 the cassette answers receive 0–5 (0 and 2
+        // used) and change 0–3 (0 used); anything further would be a miss
+        let api = EsploraApi::new(LOCAL).unwrap();
+        let target = WatchTarget::parse(ZPUB).unwrap();
+        let balance = api.fetch_balance_with(&replay(), &target, 3).await.unwrap();
+        assert_eq!(
+            balance,
+            Balance {
+                confirmed: 250_000,
+                unconfirmed: 30_000,
+                tx_count: 5,
+                used_addresses: 3,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn failures_name_the_address() {
+        let api = EsploraApi::new("http://127.0.0.1:3002/down").unwrap();
+        let target = WatchTarget::parse("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").unwrap();
+        let err = api
+            .fetch_balance_with(&replay(), &target, GAP_LIMIT)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "HTTP 503 from http://127.0.0.1:3002/down/address/1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"
+        );
+    }
+}
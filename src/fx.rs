@@ -0,0 +1,310 @@
+//! Fiat exchange rates from a reference independent of CoinGecko, and the
+//! local BTC premiums they reveal.
+
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::info;
+
+use crate::http_client::{Transport, TRANSPORT};
+
+/// Environment variable naming a rates file to use instead of the live provider
+pub const FX_FIXTURE_ENV: &str = "IFB_FX_FIXTURE";
+
+const OPEN_ER_API_URL: &str = "https://open.er-api.com/v6/latest/USD";
+
+/// Units of each currency per US dollar
+#[derive(Debug, Clone, PartialEq)]
+pub struct FxRates {
+    /// Where the rates came from, for display
+    pub source: String,
+    /// Provider's last update (Unix seconds), if reported
+    pub updated_at: Option<u64>,
+    /// Rates keyed by lowercase currency code
+    pub per_usd: HashMap<String, f64>,
+}
+
+impl FxRates {
+    /// Units of `currency` (lowercase) one dollar buys
+    pub fn rate(&self, currency: &str) -> Option<f64> {
+        self.per_usd.get(currency).copied()
+    }
+}
+
+/// Body of open.er-api.com's `/v6/latest/USD`, also the fixture file format
+#[derive(Deserialize, Debug)]
+struct OpenErApiResponse {
+    result: String,
+    base_code: String,
+    time_last_update_unix: Option<u64>,
+    #[serde(default)]
+    rates: HashMap<String, f64>,
+}
+
+impl OpenErApiResponse {
+    fn into_rates(self, source: String) -> Result<FxRates, String> {
+        if self.result != "success" {
+            return Err(format!("provider reported {:?}", self.result));
+        }
+        if !self.base_code.eq_ignore_ascii_case("usd") {
+            return Err(format!("expected USD rates, got {}", self.base_code));
+        }
+        let per_usd = self
+            .rates
+            .into_iter()
+            .filter(|(_, rate)| rate.is_finite() && *rate > 0.0)
+            .map(|(code, rate)| (code.to_lowercase(), rate))
+            .collect();
+        Ok(FxRates {
+            source,
+            updated_at: self.time_last_update_unix,
+            per_usd,
+        })
+    }
+}
+
+/// Why exchange rates could not be loaded
+#[derive(Debug, Clone, Error)]
+pub enum FxError {
+    #[error("Could not reach the FX provider: {message}")]
+    Network { url: String, message: String },
+    #[error("HTTP {status} from the FX provider")]
+    HttpStatus { url: String, status: u16 },
+    #[error("Invalid FX rates from {source_name}: {message}")]
+    Invalid {
+        source_name: String,
+        message: String,
+    },
+    #[error("Failed to read {path}: {message}")]
+    Read { path: PathBuf, message: String },
+}
+
+/// A source of USD exchange rates. New sources implement this trait; the
+/// Markets page only sees it through `Arc<dyn FxProvider>`.
+pub trait FxProvider: Send + Sync {
+    /// Short name shown next to the rates
+    fn name(&self) -> String;
+
+    /// Fetches the current rates
+    fn rates(&self) -> BoxFuture<'_, Result<FxRates, FxError>>;
+}
+
+/// The fixture named by [`FX_FIXTURE_ENV`], otherwise the live provider
+pub fn provider_from_env() -> Arc<dyn FxProvider> {
+    match std::env::var_os(FX_FIXTURE_ENV) {
+        Some(path) => Arc::new(FixtureRates::new(path)),
+        None => Arc::new(OpenErApi),
+    }
+}
+
+/// open.er-api.com's free daily reference rates (no key needed)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenErApi;
+
+impl OpenErApi {
+    /// Same as [`FxProvider::rates`], over an explicit transport
+    async fn rates_with(&self, transport: &Transport) -> Result<FxRates, FxError> {
+        let url = OPEN_ER_API_URL;
+        info!("Fetching FX rates from: {url}");
+        let response = transport.get(url).await.map_err(|e| FxError::Network {
+            url: url.to_string(),
+            message: e.to_string(),
+        })?;
+        if !response.status.is_success() {
+            return Err(FxError::HttpStatus {
+                url: url.to_string(),
+                status: response.status.as_u16(),
+            });
+        }
+        parse_rates(&response.text(), self.name())
+    }
+}
+
+impl FxProvider for OpenErApi {
+    fn name(&self) -> String {
+        "open.er-api.com".to_string()
+    }
+
+    fn rates(&self) -> BoxFuture<'_, Result<FxRates, FxError>> {
+        Box::pin(self.rates_with(&TRANSPORT))
+    }
+}
+
+/// A rates file in open.er-api.com's format, for tests and offline use
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureRates {
+    path: PathBuf,
+}
+
+impl FixtureRates {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl FxProvider for FixtureRates {
+    fn name(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or(self.path.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn rates(&self) -> BoxFuture<'_, Result<FxRates, FxError>> {
+        Box::pin(async move {
+            let body = tokio::fs::read_to_string(&self.path)
+                .await
+                .map_err(|e| FxError::Read {
+                    path: self.path.clone(),
+                    message: e.to_string(),
+                })?;
+            parse_rates(&body, self.name())
+        })
+    }
+}
+
+/// Rates from a body in open.er-api.com's format, credited to `source`
+fn parse_rates(body: &str, source: String) -> Result<FxRates, FxError> {
+    let invalid = |message: String| FxError::Invalid {
+        source_name: source.clone(),
+        message,
+    };
+    serde_json::from_str::<OpenErApiResponse>(body)
+        .map_err(|e| invalid(e.to_string()))?
+        .into_rates(source.clone())
+        .map_err(invalid)
+}
+
+/// How a local BTC price compares with the USD price converted at the
+/// reference rate
+#[derive(Debug, Clone, PartialEq)]
+pub struct Premium {
+    /// Lowercase currency code
+    pub currency: String,
+    /// BTC price quoted in the currency
+    pub local: f64,
+    /// BTC/USD price times the reference rate
+    pub implied: f64,
+    /// `local / implied - 1`: 0.03 means 3% above the reference
+    pub premium: f64,
+}
+
+/// Premium of every currency in `prices` (lowercase code to BTC price, USD
+/// included) that has a reference rate, highest first
+pub fn premiums(prices: &HashMap<String, f64>, rates: &FxRates) -> Vec<Premium> {
+    let Some(&usd) = prices.get("usd") else {
+        return Vec::new();
+    };
+    let mut premiums: Vec<Premium> = prices
+        .iter()
+        .filter(|(currency, _)| currency.as_str() != "usd")
+        .filter_map(|(currency, &local)| {
+            let implied = usd * rates.rate(currency)?;
+            (implied > 0.0 && local.is_finite()).then(|| Premium {
+                currency: currency.clone(),
+                local,
+                implied,
+                premium: local / implied - 1.0,
+            })
+        })
+        .collect();
+    premiums.sort_by(|a, b| {
+        b.premium
+            .total_cmp(&a.premium)
+            .then_with(|| a.currency.cmp(&b.currency))
+    });
+    premiums
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> FixtureRates {
+        FixtureRates::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/fx_usd.json"
+        ))
+    }
+
+    #[tokio::test]
+    async fn fixture_rates_are_lowercased() {
+        let rates = fixture().rates().await.unwrap();
+        assert_eq!(rates.rate("krw"), Some(1340.0));
+        assert_eq!(rates.rate("usd"), Some(1.0));
+        assert_eq!(rates.rate("KRW"), None);
+        assert_eq!(rates.updated_at, Some(1711324951));
+    }
+
+    #[tokio::test]
+    async fn live_provider_replays_recorded_rates() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cassettes/fx.json");
+        let cassette = crate::cassette::Cassette::load(path).unwrap();
+        let transport = Transport::Replay(std::sync::Arc::new(cassette));
+
+        let rates = OpenErApi.rates_with(&transport).await.unwrap();
+        assert_eq!(rates.rate("eur"), Some(0.9254));
+        assert_eq!(rates.source, "open.er-api.com");
+    }
+
+    #[tokio::test]
+    async fn bad_rates_are_rejected() {
+        let dir = std::env::temp_dir().join(format!("ifb-fx-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("eur.json");
+        std::fs::write(
+            &path,
+            r#"{"result":"success","base_code":"EUR","rates":{"USD":1.08}}"#,
+        )
+        .unwrap();
+
+        let err = FixtureRates::new(&path).rates().await.unwrap_err();
+        assert!(
+            err.to_string().ends_with("expected USD rates, got EUR"),
+            "{err}"
+        );
+
+        let err = FixtureRates::new(dir.join("missing.json"))
+            .rates()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FxError::Read { .. }));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn premiums_compare_local_prices_with_converted_usd() {
+        let rates = fixture().rates().await.unwrap();
+        let prices = HashMap::from([
+            ("usd".to_string(), 65_000.0),
+            // 5% above 65,000 × 1,340
+            ("krw".to_string(), 91_455_000.0),
+            // Exactly at the reference
+            ("eur".to_string(), 65_000.0 * 0.9254),
+            ("ngn".to_string(), 65_000.0 * 1_450.0 * 0.98),
+            // No reference rate
+            ("xyz".to_string(), 1.0),
+        ]);
+
+        let list = premiums(&prices, &rates);
+        let summary: Vec<(&str, String)> = list
+            .iter()
+            .map(|p| (p.currency.as_str(), format!("{:.4}", p.premium)))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("krw", "0.0500".to_string()),
+                ("eur", "0.0000".to_string()),
+                ("ngn", "-0.0200".to_string()),
+            ]
+        );
+        assert_eq!(list[0].implied, 87_100_000.0);
+
+        assert!(premiums(&HashMap::new(), &rates).is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+//! Fiat-to-fiat exchange rates implied by BTC prices.
+//!
+//! One CoinGecko response quotes BTC in every fiat, so any two quotes give a
+//! cross rate: EUR/JPY = btc_jpy / btc_eur.
+
+use std::collections::HashMap;
+
+use crate::api::CoinGeckoResponse;
+
+/// Cross rates between every pair of currencies in one set of BTC prices
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FxMatrix {
+    /// Lowercase currency codes, sorted
+    currencies: Vec<String>,
+    /// BTC price per currency, same order as `currencies`
+    btc: Vec<f64>,
+}
+
+impl FxMatrix {
+    /// Builds the matrix from BTC prices keyed by currency code; zero,
+    /// negative and non-finite prices are left out
+    pub fn from_btc_prices(prices: &HashMap<String, f64>) -> Self {
+        let mut quotes: Vec<(String, f64)> = prices
+            .iter()
+            .filter(|(_, price)| price.is_finite() && **price > 0.0)
+            .map(|(currency, price)| (currency.to_lowercase(), *price))
+            .collect();
+        quotes.sort_by(|a, b| a.0.cmp(&b.0));
+        quotes.dedup_by(|a, b| a.0 == b.0);
+        let (currencies, btc) = quotes.into_iter().unzip();
+        Self { currencies, btc }
+    }
+
+    /// Cross rates of one CoinGecko price response
+    pub fn from_response(response: &CoinGeckoResponse) -> Self {
+        Self::from_btc_prices(&response.bitcoin)
+    }
+
+    /// Currencies with a usable BTC price, sorted
+    pub fn currencies(&self) -> &[String] {
+        &self.currencies
+    }
+
+    fn btc(&self, currency: &str) -> Option<f64> {
+        let currency = currency.to_lowercase();
+        self.currencies
+            .binary_search(&currency)
+            .ok()
+            .map(|i| self.btc[i])
+    }
+
+    /// Units of `quote` that one `base` buys: `rate("eur", "jpy")` is EUR/JPY
+    pub fn rate(&self, base: &str, quote: &str) -> Option<f64> {
+        Some(self.btc(quote)? / self.btc(base)?)
+    }
+
+    /// Converts `amount` of `from` into `to`
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        Some(amount * self.rate(from, to)?)
+    }
+
+    /// Relative move of `base`/`quote` since `previous`: 0.01 means the
+    /// rate rose 1%
+    pub fn change_since(&self, previous: &FxMatrix, base: &str, quote: &str) -> Option<f64> {
+        Some(self.rate(base, quote)? / previous.rate(base, quote)? - 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn matrix(quotes: &[(&str, f64)]) -> FxMatrix {
+        FxMatrix::from_btc_prices(&quotes.iter().map(|(c, p)| (c.to_string(), *p)).collect())
+    }
+
+    #[test]
+    fn cross_rates_from_btc_prices() {
+        let response: CoinGeckoResponse = serde_json::from_str(
+            r#"{"bitcoin":{"usd":65000.0,"eur":60000.0,"jpy":9840000.0,"xyz":0.0}}"#,
+        )
+        .unwrap();
+        let m = FxMatrix::from_response(&response);
+
+        assert_eq!(
+            m.currencies(),
+            ["eur", "jpy", "usd"],
+            "unpriced xyz dropped"
+        );
+        assert_eq!(m.rate("eur", "jpy"), Some(164.0));
+        assert_eq!(m.rate("EUR", "usd"), Some(65000.0 / 60000.0));
+        assert_eq!(m.rate("usd", "usd"), Some(1.0));
+        assert_eq!(m.rate("usd", "xyz"), None);
+        assert_eq!(m.convert(10.0, "eur", "jpy"), Some(1640.0));
+    }
+
+    #[test]
+    fn change_since_previous_fetch() {
+        let before = matrix(&[("usd", 65000.0), ("jpy", 9_750_000.0)]);
+        let after = matrix(&[("usd", 65000.0), ("jpy", 9_847_500.0)]);
+        let change = after.change_since(&before, "usd", "jpy").unwrap();
+        assert!((change - 0.01).abs() < 1e-12, "{change}");
+        assert_eq!(after.change_since(&FxMatrix::default(), "usd", "jpy"), None);
+    }
+
+    /// BTC prices across a realistic span of fiat magnitudes (CHF to VND)
+    fn btc_prices() -> impl Strategy<Value = HashMap<String, f64>> {
+        prop::collection::hash_map("[a-z]{3}", 1e3..1e10f64, 3..12)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * a.abs().max(b.abs())
+    }
+
+    proptest! {
+        #[test]
+        fn rates_are_reciprocal(prices in btc_prices()) {
+            let m = FxMatrix::from_btc_prices(&prices);
+            for a in m.currencies() {
+                for b in m.currencies() {
+                    let there = m.rate(a, b).unwrap();
+                    let back = m.rate(b, a).unwrap();
+                    prop_assert!(close(there * back, 1.0), "{a}/{b}: {there} × {back}");
+                }
+            }
+        }
+
+        #[test]
+        fn rates_are_triangularly_consistent(prices in btc_prices()) {
+            let m = FxMatrix::from_btc_prices(&prices);
+            for a in m.currencies() {
+                for b in m.currencies() {
+                    for c in m.currencies() {
+                        let direct = m.rate(a, c).unwrap();
+                        let via = m.rate(a, b).unwrap() * m.rate(b, c).unwrap();
+                        prop_assert!(close(direct, via), "{a}/{c} via {b}: {direct} vs {via}");
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn converting_there_and_back_is_lossless(
+            prices in btc_prices(),
+            amount in 0.01..1e9f64,
+        ) {
+            let m = FxMatrix::from_btc_prices(&prices);
+            let (first, last) = (&m.currencies()[0], m.currencies().last().unwrap());
+            let round_trip = m
+                .convert(m.convert(amount, first, last).unwrap(), last, first)
+                .unwrap();
+            prop_assert!(close(round_trip, amount));
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::io;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+use crate::api;
+use crate::cli::CliArgs;
+use crate::country::get_countries;
+use crate::server;
+use crate::store::{self, STORE};
+
+/// Wait before asking for CoinGecko's currency list again after a failure,
+/// so a rate-limited or unreachable API is not sent two requests per tick
+const SUPPORTED_RETRY: Duration = Duration::from_secs(600);
+
+/// Runs without a window: fetches prices every `args.interval` seconds and
+/// serves the local API (including `/metrics`) on `127.0.0.1:args.port`.
+pub fn run(args: &CliArgs) -> io::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, args.port)).await?;
+        info!(
+            "Headless mode: serving http://{} every {}s",
+            listener.local_addr()?,
+            args.interval
+        );
+
+        tokio::spawn(fetch_loop(Duration::from_secs(args.interval)));
+        server::serve(listener, STORE.clone()).await
+    })
+}
+
+async fn fetch_loop(interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    let mut next_list_fetch = Instant::now();
+    loop {
+        ticker.tick().await;
+        if api::supported_vs_currencies().is_none() && Instant::now() >= next_list_fetch {
+            if let Err(e) = api::fetch_supported_vs_currencies().await {
+                warn!("Could not fetch supported currencies, requesting all: {e}");
+                next_list_fetch = Instant::now() + SUPPORTED_RETRY;
+            }
+        }
+        match api::fetch_country_prices(get_countries()).await {
+            Ok(snapshot) => store::record_success(&snapshot),
+            Err(e) => {
+                warn!("Headless fetch failed: {e}");
+                store::record_failure(format!("Failed to fetch prices: {e}"));
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default number of samples kept in memory (one day at a 60s refresh)
+pub const DEFAULT_CAPACITY: usize = 1440;
+
+/// One successful fetch: BTC price per currency at a point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceSample {
+    /// Unix timestamp (seconds) at which the sample was recorded
+    pub timestamp: u64,
+    /// BTC price keyed by lowercase currency code
+    pub prices: HashMap<String, f64>,
+}
+
+/// A single point of a per-currency series
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SeriesPoint {
+    pub timestamp: u64,
+    pub price: f64,
+}
+
+/// Bounded, time-ordered ring buffer of price samples
+#[derive(Debug, Clone)]
+pub struct PriceHistory {
+    samples: VecDeque<PriceSample>,
+    capacity: usize,
+}
+
+impl Default for PriceHistory {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl PriceHistory {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity.min(DEFAULT_CAPACITY)),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Appends a sample, evicting the oldest one when full
+    pub fn push(&mut self, sample: PriceSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = &PriceSample> + ExactSizeIterator {
+        self.samples.iter()
+    }
+
+    /// Returns the series for one currency, oldest first, limited to the last `limit` points
+    pub fn series(&self, currency: &str, limit: usize) -> Vec<SeriesPoint> {
+        let mut points: Vec<SeriesPoint> = self
+            .samples
+            .iter()
+            .rev()
+            .filter_map(|s| {
+                s.prices.get(currency).map(|&price| SeriesPoint {
+                    timestamp: s.timestamp,
+                    price,
+                })
+            })
+            .take(limit)
+            .collect();
+        points.reverse();
+        points
+    }
+}
+
+/// Current wall-clock time as Unix seconds
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: u64, usd: f64) -> PriceSample {
+        PriceSample {
+            timestamp,
+            prices: HashMap::from([("usd".to_string(), usd)]),
+        }
+    }
+
+    #[test]
+    fn evicts_oldest_when_full() {
+        let mut history = PriceHistory::with_capacity(2);
+        history.push(sample(1, 10.0));
+        history.push(sample(2, 20.0));
+        history.push(sample(3, 30.0));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.samples().next().unwrap().timestamp, 2);
+        assert_eq!(history.samples().last().unwrap().timestamp, 3);
+    }
+
+    #[test]
+    fn series_is_oldest_first_and_limited() {
+        let mut history = PriceHistory::default();
+        for t in 1..=5 {
+            history.push(sample(t, t as f64));
+        }
+        let series = history.series("usd", 3);
+        let timestamps: Vec<_> = series.iter().map(|p| p.timestamp).collect();
+        assert_eq!(timestamps, vec![3, 4, 5]);
+        assert!(history.series("eur", 10).is_empty());
+    }
+}
@@ -1,15 +1,606 @@
-use std::sync::LazyLock;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, RwLock};
 use std::time::Duration;
+use tracing::{info, warn};
 
-/// Shared HTTP client with connection pooling and proper User-Agent.
-/// Created once on first access and reused across all requests.
-pub static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
-    reqwest::Client::builder()
-        .user_agent(format!(
-            "iced-fetch-bitcoin/{}",
-            env!("CARGO_PKG_VERSION")
-        ))
-        .timeout(Duration::from_secs(30))
-        .build()
-        .expect("Failed to build HTTP client")
+use crate::cassette::{Cassette, Interaction};
+
+/// Path of the cassette file used by record/replay mode
+pub const CASSETTE_ENV: &str = "IFB_CASSETTE";
+/// `record` or `replay`; anything else (or unset) means live traffic
+pub const CASSETTE_MODE_ENV: &str = "IFB_CASSETTE_MODE";
+
+/// Explicit proxy for all requests, e.g. `http://proxy.corp:3128`
+pub const PROXY_ENV: &str = "IFB_PROXY";
+/// Basic-auth user name for [`PROXY_ENV`]
+pub const PROXY_USER_ENV: &str = "IFB_PROXY_USER";
+/// Basic-auth password for [`PROXY_ENV`]
+pub const PROXY_PASSWORD_ENV: &str = "IFB_PROXY_PASSWORD";
+/// PEM file with extra root certificates
+pub const CA_BUNDLE_ENV: &str = "IFB_CA_BUNDLE";
+/// Connect timeout in seconds
+pub const CONNECT_TIMEOUT_ENV: &str = "IFB_CONNECT_TIMEOUT";
+/// Whole-request timeout in seconds
+pub const TIMEOUT_ENV: &str = "IFB_TIMEOUT";
+
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Settings the shared HTTP client is built from
+#[derive(Clone, PartialEq, Eq)]
+pub struct HttpConfig {
+    /// Explicit proxy for all traffic; `None` falls back to the system proxy variables
+    pub proxy: Option<String>,
+    /// Basic-auth user name for the proxy
+    pub proxy_user: Option<String>,
+    /// Basic-auth password for the proxy
+    pub proxy_password: Option<String>,
+    /// PEM bundle of root certificates trusted in addition to the built-in ones
+    pub ca_bundle: Option<PathBuf>,
+    pub connect_timeout: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            proxy_user: None,
+            proxy_password: None,
+            ca_bundle: None,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+// Hand-written so the proxy password never ends up in logs
+impl fmt::Debug for HttpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpConfig")
+            .field("proxy", &self.proxy)
+            .field("proxy_user", &self.proxy_user)
+            .field("proxy_password", &self.proxy_password.as_ref().map(|_| "***"))
+            .field("ca_bundle", &self.ca_bundle)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// Reasons a client cannot be built from an [`HttpConfig`]
+#[derive(Debug, thiserror::Error)]
+pub enum HttpConfigError {
+    #[error("invalid proxy URL: {0}")]
+    Proxy(reqwest::Error),
+    #[error("cannot read CA bundle {}: {source}", path.display())]
+    CaBundleRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid CA bundle {}: {source}", path.display())]
+    CaBundle {
+        path: PathBuf,
+        source: reqwest::Error,
+    },
+    #[error("no certificates in CA bundle {}", .0.display())]
+    EmptyCaBundle(PathBuf),
+    #[error("cannot build HTTP client: {0}")]
+    Build(reqwest::Error),
+}
+
+impl HttpConfig {
+    /// Defaults overridden by the `IFB_*` variables above
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let non_empty = |name| var(name).filter(|v| !v.trim().is_empty());
+        let secs = |name| {
+            let value = non_empty(name)?;
+            match value.trim().parse::<u64>() {
+                Ok(secs) if secs > 0 => Some(Duration::from_secs(secs)),
+                _ => {
+                    warn!("{name}={value} is not a positive number of seconds; ignoring");
+                    None
+                }
+            }
+        };
+
+        let defaults = Self::default();
+        Self {
+            proxy: non_empty(PROXY_ENV),
+            proxy_user: non_empty(PROXY_USER_ENV),
+            proxy_password: non_empty(PROXY_PASSWORD_ENV),
+            ca_bundle: non_empty(CA_BUNDLE_ENV).map(PathBuf::from),
+            connect_timeout: secs(CONNECT_TIMEOUT_ENV).unwrap_or(defaults.connect_timeout),
+            timeout: secs(TIMEOUT_ENV).unwrap_or(defaults.timeout),
+        }
+    }
+
+    /// Builds a client with connection pooling and the app's User-Agent
+    pub fn build_client(&self) -> Result<reqwest::Client, HttpConfigError> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(format!(
+                "iced-fetch-bitcoin/{}",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.timeout);
+
+        if let Some(url) = &self.proxy {
+            let mut proxy = reqwest::Proxy::all(url.as_str()).map_err(HttpConfigError::Proxy)?;
+            if let Some(user) = &self.proxy_user {
+                proxy = proxy.basic_auth(user, self.proxy_password.as_deref().unwrap_or(""));
+            }
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(path) = &self.ca_bundle {
+            let pem = std::fs::read(path).map_err(|source| HttpConfigError::CaBundleRead {
+                path: path.clone(),
+                source,
+            })?;
+            let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|source| {
+                HttpConfigError::CaBundle {
+                    path: path.clone(),
+                    source,
+                }
+            })?;
+            if certs.is_empty() {
+                return Err(HttpConfigError::EmptyCaBundle(path.clone()));
+            }
+            builder = builder.tls_certs_merge(certs);
+        }
+
+        builder.build().map_err(HttpConfigError::Build)
+    }
+}
+
+/// The shared client together with the config it was built from
+struct Configured {
+    config: HttpConfig,
+    client: reqwest::Client,
+}
+
+static CURRENT: LazyLock<RwLock<Configured>> = LazyLock::new(|| {
+    let config = HttpConfig::default();
+    let client = config
+        .build_client()
+        .expect("Failed to build HTTP client");
+    RwLock::new(Configured { config, client })
 });
+
+/// Shared HTTP client with connection pooling and proper User-Agent.
+/// Cloning is cheap; the client is rebuilt whenever [`configure`] succeeds.
+pub fn client() -> reqwest::Client {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).client.clone()
+}
+
+/// Config the current shared client was built from
+pub fn config() -> HttpConfig {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).config.clone()
+}
+
+/// Rebuilds the shared client. On error the previous client stays in use.
+pub fn configure(config: HttpConfig) -> Result<(), HttpConfigError> {
+    let client = config.build_client()?;
+    info!("HTTP client configured: {config:?}");
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Configured { config, client };
+    Ok(())
+}
+
+/// Transport used by `api` and `http_utils`, chosen from the environment on
+/// first access (see [`CASSETTE_ENV`] and [`CASSETTE_MODE_ENV`]).
+pub static TRANSPORT: LazyLock<Transport> = LazyLock::new(Transport::from_env);
+
+/// HTTP-level errors
+#[derive(Debug, thiserror::Error)]
+pub enum HttpError {
+    /// The request failed on the wire
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    /// Replay mode has no recorded response for this request
+    #[error("no recorded response for {0}")]
+    CassetteMiss(String),
+}
+
+impl HttpError {
+    /// Whether the host could not be resolved or connected to (see [`is_unreachable`])
+    pub fn is_unreachable(&self) -> bool {
+        match self {
+            HttpError::Request(e) => is_unreachable(e),
+            HttpError::CassetteMiss(_) => false,
+        }
+    }
+}
+
+/// Whether a request failed on DNS resolution or a refused connection.
+///
+/// Timeouts, connect timeouts included, do not count: a slow server is an
+/// ordinary fetch error, not a sign that the network is down.
+pub fn is_unreachable(error: &reqwest::Error) -> bool {
+    error.is_connect() && !error.is_timeout()
+}
+
+/// Fully-buffered response
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: reqwest::StatusCode,
+    pub body: Vec<u8>,
+}
+
+impl RawResponse {
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// Where HTTP GETs go: the network, the network plus a cassette, or only a cassette.
+/// Network requests always use the current shared [`client`].
+#[derive(Debug, Clone)]
+pub enum Transport {
+    Live,
+    Record(Arc<Cassette>),
+    Replay(Arc<Cassette>),
+}
+
+impl Transport {
+    /// Whether requests reach the network (false when replaying a cassette)
+    pub fn is_live(&self) -> bool {
+        !matches!(self, Transport::Replay(_))
+    }
+
+    fn from_env() -> Self {
+        let path = std::env::var(CASSETTE_ENV).ok();
+        let mode = std::env::var(CASSETTE_MODE_ENV).ok();
+
+        match (mode.as_deref(), path) {
+            (Some("record"), Some(path)) => {
+                info!("Recording HTTP traffic to {path}");
+                Transport::Record(Arc::new(Cassette::create(path)))
+            }
+            (Some("replay"), Some(path)) => match Cassette::load(&path) {
+                Ok(cassette) => {
+                    info!("Replaying HTTP traffic from {path}");
+                    Transport::Replay(Arc::new(cassette))
+                }
+                Err(e) => {
+                    warn!("Cannot load cassette {path}: {e}; replaying nothing");
+                    Transport::Replay(Arc::new(Cassette::create(path)))
+                }
+            },
+            (Some(mode), None) => {
+                warn!("{CASSETTE_MODE_ENV}={mode} needs {CASSETTE_ENV}; using live traffic");
+                Transport::Live
+            }
+            _ => Transport::Live,
+        }
+    }
+
+    /// Performs a GET and buffers the whole body
+    pub async fn get(&self, url: &str) -> Result<RawResponse, HttpError> {
+        self.get_with_headers(url, &[]).await
+    }
+
+    /// Same as [`get`](Self::get) with extra request headers. Headers are never
+    /// written to or matched against cassettes, so API keys stay out of them.
+    pub async fn get_with_headers(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<RawResponse, HttpError> {
+        self.request(reqwest::Method::GET, url, headers, None).await
+    }
+
+    /// Performs a POST of `body` and buffers the whole response. As with
+    /// [`get_with_headers`](Self::get_with_headers), headers stay out of
+    /// cassettes; so do the request body and the URL's path and query, which
+    /// for webhooks and ntfy topics are the secret.
+    pub async fn post(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<RawResponse, HttpError> {
+        self.request(reqwest::Method::POST, url, headers, Some(body)).await
+    }
+
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<Vec<u8>>,
+    ) -> Result<RawResponse, HttpError> {
+        // Cassettes are committed as fixtures: key POSTs by scheme and host only
+        let cassette_url = if method == reqwest::Method::GET {
+            url.to_string()
+        } else {
+            without_path(url)
+        };
+        match self {
+            Transport::Live => send(&client(), method, url, headers, body).await,
+            Transport::Record(cassette) => {
+                let name = method.to_string();
+                let response = send(&client(), method, url, headers, body).await?;
+                let interaction = Interaction::new(
+                    &name,
+                    &cassette_url,
+                    response.status.as_u16(),
+                    &response.body,
+                );
+                if let Err(e) = cassette.record(interaction) {
+                    warn!("Failed to write cassette {}: {e}", cassette.path().display());
+                }
+                Ok(response)
+            }
+            Transport::Replay(cassette) => {
+                let interaction = cassette
+                    .replay(method.as_str(), &cassette_url)
+                    .ok_or(HttpError::CassetteMiss(cassette_url))?;
+                Ok(RawResponse {
+                    status: reqwest::StatusCode::from_u16(interaction.status)
+                        .unwrap_or(reqwest::StatusCode::INTERNAL_SERVER_ERROR),
+                    body: interaction.body_bytes(),
+                })
+            }
+        }
+    }
+}
+
+/// Host and port of `url`; webhook paths are credentials and stay out of
+/// logs, error messages and cassettes
+pub fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split(['/', '?']).next().unwrap_or(rest)
+}
+
+/// `url` without its path and query
+fn without_path(url: &str) -> String {
+    match url.split_once("://") {
+        Some((scheme, _)) => format!("{scheme}://{}", host_of(url)),
+        None => host_of(url).to_string(),
+    }
+}
+
+async fn send(
+    client: &reqwest::Client,
+    method: reqwest::Method,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<Vec<u8>>,
+) -> Result<RawResponse, HttpError> {
+    let mut request = client.request(method, url);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    if let Some(body) = body {
+        request = request.body(body);
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    let body = response.bytes().await?.to_vec();
+    Ok(RawResponse { status, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::PriceStore;
+    use std::net::Ipv4Addr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures")).join(name)
+    }
+
+    #[test]
+    fn config_from_env_vars() {
+        let vars = |name: &str| match name {
+            PROXY_ENV => Some("http://proxy.corp:3128".to_string()),
+            PROXY_USER_ENV => Some("alice".to_string()),
+            PROXY_PASSWORD_ENV => Some("s3cret".to_string()),
+            CONNECT_TIMEOUT_ENV => Some("4".to_string()),
+            TIMEOUT_ENV => Some("soon".to_string()),
+            CA_BUNDLE_ENV => Some("  ".to_string()),
+            _ => None,
+        };
+        let config = HttpConfig::from_vars(vars);
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(config.proxy_user.as_deref(), Some("alice"));
+        assert_eq!(config.connect_timeout, Duration::from_secs(4));
+        assert_eq!(config.timeout, DEFAULT_TIMEOUT, "malformed value keeps the default");
+        assert_eq!(config.ca_bundle, None);
+
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("s3cret"), "{debug}");
+    }
+
+    #[test]
+    fn ca_bundle_is_validated() {
+        let config = HttpConfig {
+            ca_bundle: Some(fixture("test-ca.pem")),
+            ..HttpConfig::default()
+        };
+        assert!(config.build_client().is_ok());
+
+        let config = HttpConfig {
+            ca_bundle: Some(fixture("missing.pem")),
+            ..HttpConfig::default()
+        };
+        assert!(matches!(
+            config.build_client(),
+            Err(HttpConfigError::CaBundleRead { .. })
+        ));
+
+        // A file without any PEM certificate block
+        let config = HttpConfig {
+            ca_bundle: Some(PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))),
+            ..HttpConfig::default()
+        };
+        assert!(matches!(
+            config.build_client(),
+            Err(HttpConfigError::EmptyCaBundle(_))
+        ));
+    }
+
+    /// Accepts one connection and returns it with the raw request head
+    async fn accept_request(listener: TcpListener) -> (TcpStream, String) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut head = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).await.unwrap();
+            head.extend_from_slice(&buf[..n]);
+        }
+        (stream, String::from_utf8_lossy(&head).into_owned())
+    }
+
+    async fn respond(mut stream: TcpStream, body: &str) {
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+    }
+
+    /// Accepts one connection and answers with the raw request head as the body
+    async fn echo_request_head(listener: TcpListener) {
+        let (stream, head) = accept_request(listener).await;
+        respond(stream, &head).await;
+    }
+
+    /// Accepts one connection and answers `body`
+    async fn answer(listener: TcpListener, body: &str) {
+        let (stream, _) = accept_request(listener).await;
+        respond(stream, body).await;
+    }
+
+    #[tokio::test]
+    async fn headers_are_sent_but_not_recorded() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let url = format!("http://{}/simple/price", listener.local_addr().unwrap());
+        let server = tokio::spawn(echo_request_head(listener));
+
+        let nanos = std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let path = std::env::temp_dir().join(format!(
+            "ifb-cassette-headers-{}-{nanos}.json",
+            std::process::id()
+        ));
+        let recorder = Transport::Record(Arc::new(Cassette::create(&path)));
+        let response = recorder
+            .get_with_headers(&url, &[("x-cg-pro-api-key", "CG-secret")])
+            .await
+            .unwrap();
+        server.await.unwrap();
+        let cassette = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(response.text().contains("x-cg-pro-api-key: CG-secret"));
+        // Cassettes store no request headers at all
+        let recorded: serde_json::Value = serde_json::from_str(&cassette).unwrap();
+        let interaction = &recorded["interactions"][0];
+        assert_eq!(
+            interaction.as_object().unwrap().keys().collect::<Vec<_>>(),
+            ["body", "method", "status", "url"]
+        );
+        assert!(!interaction["url"].as_str().unwrap().contains("CG-secret"));
+    }
+
+    #[tokio::test]
+    async fn recorded_posts_keep_no_path() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let host = listener.local_addr().unwrap().to_string();
+        let url = format!("http://{host}/services/T000/B000/s3cret?topic=alerts");
+        let server = tokio::spawn(answer(listener, "ok"));
+
+        let nanos = std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let path = std::env::temp_dir().join(format!(
+            "ifb-cassette-post-{}-{nanos}.json",
+            std::process::id()
+        ));
+        let recorder = Transport::Record(Arc::new(Cassette::create(&path)));
+        let response = recorder.post(&url, &[], b"{}".to_vec()).await.unwrap();
+        server.await.unwrap();
+        assert_eq!(response.text(), "ok");
+
+        let cassette = std::fs::read_to_string(&path).unwrap();
+        assert!(!cassette.contains("s3cret") && !cassette.contains("alerts"));
+        let recorded: serde_json::Value = serde_json::from_str(&cassette).unwrap();
+        assert_eq!(recorded["interactions"][0]["url"], format!("http://{host}"));
+
+        // Replay finds it again from the full URL
+        let replayer = Transport::Replay(Arc::new(Cassette::load(&path).unwrap()));
+        let _ = std::fs::remove_file(&path);
+        let replayed = replayer.post(&url, &[], Vec::new()).await.unwrap();
+        assert_eq!(replayed.text(), "ok");
+    }
+
+    #[tokio::test]
+    async fn requests_go_through_authenticated_proxy() {
+        // Minimal stand-in proxy: answers the request itself and echoes the head
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+        let proxy = tokio::spawn(echo_request_head(listener));
+
+        let client = HttpConfig {
+            proxy: Some(proxy_url),
+            proxy_user: Some("alice".into()),
+            proxy_password: Some("s3cret".into()),
+            ..HttpConfig::default()
+        }
+        .build_client()
+        .unwrap();
+        let response = send(&client, reqwest::Method::GET, "http://prices.example/api", &[], None)
+            .await
+            .unwrap();
+        proxy.await.unwrap();
+
+        let head = response.text();
+        assert!(head.starts_with("GET http://prices.example/api HTTP/1.1"), "{head}");
+        // base64("alice:s3cret")
+        assert!(
+            head.to_ascii_lowercase()
+                .contains("proxy-authorization: basic ywxpy2u6cznjcmv0"),
+            "{head}"
+        );
+    }
+
+    #[test]
+    fn invalid_proxy_url_is_rejected() {
+        let config = HttpConfig {
+            proxy: Some("not a url".into()),
+            ..HttpConfig::default()
+        };
+        assert!(matches!(config.build_client(), Err(HttpConfigError::Proxy(_))));
+    }
+
+    #[tokio::test]
+    async fn recorded_traffic_replays_without_network() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let url = format!("http://{}/api/v1/health", listener.local_addr().unwrap());
+        let store = Arc::new(std::sync::RwLock::new(PriceStore::default()));
+        let server = tokio::spawn(crate::server::serve(listener, store));
+
+        let nanos = std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let path = std::env::temp_dir().join(format!(
+            "ifb-cassette-{}-{nanos}.json",
+            std::process::id()
+        ));
+        let recorder = Transport::Record(Arc::new(Cassette::create(&path)));
+        let live = recorder.get(&url).await.unwrap();
+        server.abort();
+
+        let replayer = Transport::Replay(Arc::new(Cassette::load(&path).unwrap()));
+        let replayed = replayer.get(&url).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(replayed.status, live.status);
+        assert_eq!(replayed.body, live.body);
+        assert!(replayed.text().contains("\"status\":\"starting\""));
+    }
+}
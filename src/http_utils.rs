@@ -1,7 +1,8 @@
 use futures::stream::{FuturesUnordered, StreamExt};
 use std::collections::HashMap;
 
-use crate::http_client::CLIENT;
+use crate::http_client::{HttpError, Transport, TRANSPORT};
+use crate::metrics::METRICS;
 
 /// Downloads multiple SVG flag images concurrently and returns them as in-memory byte vectors.
 ///
@@ -12,13 +13,21 @@ pub async fn download_svgs_to_memory(
     codes: Vec<String>,
     flags: Vec<String>,
 ) -> HashMap<String, Vec<u8>> {
-    let client = CLIENT.clone();
+    download_svgs_with(&TRANSPORT, codes, flags).await
+}
+
+/// Same as [`download_svgs_to_memory`], over an explicit transport (e.g. a replay cassette)
+pub async fn download_svgs_with(
+    transport: &Transport,
+    codes: Vec<String>,
+    flags: Vec<String>,
+) -> HashMap<String, Vec<u8>> {
     let mut tasks = FuturesUnordered::new();
 
-    for (code, flag) in codes.into_iter().zip(flags.into_iter()) {
-        let client = client.clone();
+    for (code, flag) in codes.into_iter().zip(flags) {
+        let transport = transport.clone();
         tasks.push(tokio::spawn(async move {
-            let data = download_svg(&client, &flag).await;
+            let data = download_svg(&transport, &flag).await;
             (code, data)
         }));
     }
@@ -27,12 +36,15 @@ pub async fn download_svgs_to_memory(
     while let Some(task_result) = tasks.next().await {
         match task_result {
             Ok((code, Ok(svg_data))) => {
+                METRICS.record_flag_download(true);
                 results.insert(code, svg_data);
             }
             Ok((_code, Err(e))) => {
+                METRICS.record_flag_download(false);
                 tracing::warn!("Failed to download SVG: {e}");
             }
             Err(e) => {
+                METRICS.record_flag_download(false);
                 tracing::warn!("SVG download task panicked: {e}");
             }
         }
@@ -40,7 +52,48 @@ pub async fn download_svgs_to_memory(
     results
 }
 
-async fn download_svg(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, reqwest::Error> {
-    let bytes = client.get(url).send().await?.bytes().await?;
-    Ok(bytes.to_vec())
+/// Failure to fetch a single flag
+#[derive(Debug, thiserror::Error)]
+enum DownloadError {
+    #[error(transparent)]
+    Http(#[from] HttpError),
+    #[error("HTTP {status} for {url}")]
+    Status {
+        status: reqwest::StatusCode,
+        url: String,
+    },
+}
+
+async fn download_svg(transport: &Transport, url: &str) -> Result<Vec<u8>, DownloadError> {
+    let response = transport.get(url).await?;
+    if !response.status.is_success() {
+        return Err(DownloadError::Status {
+            status: response.status,
+            url: url.to_string(),
+        });
+    }
+    Ok(response.body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cassette::Cassette;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn downloads_replayed_flags_and_skips_failures() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cassettes/flags.json");
+        let transport = Transport::Replay(Arc::new(Cassette::load(path).unwrap()));
+        let base = "https://raw.githubusercontent.com/lipis/flag-icons/refs/heads/main/flags/4x3";
+
+        let codes = vec!["us", "gb", "zz", "jp"];
+        let flags = codes.iter().map(|c| format!("{base}/{c}.svg")).collect();
+        let codes = codes.into_iter().map(String::from).collect();
+
+        let svgs = download_svgs_with(&transport, codes, flags).await;
+        assert_eq!(svgs.len(), 2, "404 and unrecorded flags must be skipped");
+        assert!(String::from_utf8_lossy(&svgs["us"]).starts_with("<svg"));
+        assert!(String::from_utf8_lossy(&svgs["gb"]).starts_with("<svg"));
+    }
 }
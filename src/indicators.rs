@@ -0,0 +1,290 @@
+//! Technical indicators over a price series.
+//!
+//! Every function takes prices oldest first and returns one entry per input
+//! price, `None` until enough history exists, so results line up with the
+//! series they were computed from.
+
+/// Simple moving average over `period` prices
+pub fn sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    if period == 0 {
+        return vec![None; values.len()];
+    }
+    let mut sum = 0.0;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            sum += v;
+            if i >= period {
+                sum -= values[i - period];
+            }
+            (i + 1 >= period).then(|| sum / period as f64)
+        })
+        .collect()
+}
+
+/// Exponential moving average with smoothing `2 / (period + 1)`, seeded with
+/// the simple average of the first `period` prices
+pub fn ema(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if period == 0 || values.len() < period {
+        return out;
+    }
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut current = values[..period].iter().sum::<f64>() / period as f64;
+    out[period - 1] = Some(current);
+    for (i, &v) in values.iter().enumerate().skip(period) {
+        current += alpha * (v - current);
+        out[i] = Some(current);
+    }
+    out
+}
+
+/// Relative strength index (0–100) with Wilder's smoothing
+pub fn rsi(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if period == 0 || values.len() <= period {
+        return out;
+    }
+    let change = |i: usize| values[i] - values[i - 1];
+    let mut gain = (1..=period).map(|i| change(i).max(0.0)).sum::<f64>() / period as f64;
+    let mut loss = (1..=period).map(|i| (-change(i)).max(0.0)).sum::<f64>() / period as f64;
+    out[period] = Some(rsi_from(gain, loss));
+
+    let n = period as f64;
+    for (i, slot) in out.iter_mut().enumerate().skip(period + 1) {
+        gain = (gain * (n - 1.0) + change(i).max(0.0)) / n;
+        loss = (loss * (n - 1.0) + (-change(i)).max(0.0)) / n;
+        *slot = Some(rsi_from(gain, loss));
+    }
+    out
+}
+
+fn rsi_from(gain: f64, loss: f64) -> f64 {
+    if loss == 0.0 {
+        if gain == 0.0 {
+            50.0
+        } else {
+            100.0
+        }
+    } else {
+        100.0 - 100.0 / (1.0 + gain / loss)
+    }
+}
+
+/// One MACD reading
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Macd {
+    /// Fast EMA minus slow EMA
+    pub macd: f64,
+    /// EMA of the MACD line
+    pub signal: f64,
+    /// MACD minus signal
+    pub histogram: f64,
+}
+
+/// Moving average convergence/divergence, e.g. `macd(values, 12, 26, 9)`
+pub fn macd(values: &[f64], fast: usize, slow: usize, signal: usize) -> Vec<Option<Macd>> {
+    let line: Vec<Option<f64>> = ema(values, fast)
+        .into_iter()
+        .zip(ema(values, slow))
+        .map(|(f, s)| Some(f? - s?))
+        .collect();
+
+    // The signal line only starts once the MACD line exists
+    let start = line.iter().position(Option::is_some).unwrap_or(line.len());
+    let defined: Vec<f64> = line[start..].iter().flatten().copied().collect();
+    let mut out = vec![None; values.len()];
+    for (offset, signal) in ema(&defined, signal).into_iter().enumerate() {
+        if let Some(signal) = signal {
+            let macd = defined[offset];
+            out[start + offset] = Some(Macd {
+                macd,
+                signal,
+                histogram: macd - signal,
+            });
+        }
+    }
+    out
+}
+
+/// One Bollinger band reading
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Band {
+    pub lower: f64,
+    pub middle: f64,
+    pub upper: f64,
+}
+
+/// Bollinger bands: the `period` simple average ± `width` population
+/// standard deviations
+pub fn bollinger(values: &[f64], period: usize, width: f64) -> Vec<Option<Band>> {
+    sma(values, period)
+        .into_iter()
+        .enumerate()
+        .map(|(i, middle)| {
+            let middle = middle?;
+            let window = &values[i + 1 - period..=i];
+            let variance = window.iter().map(|v| (v - middle).powi(2)).sum::<f64>() / period as f64;
+            let spread = width * variance.sqrt();
+            Some(Band {
+                lower: middle - spread,
+                middle,
+                upper: middle + spread,
+            })
+        })
+        .collect()
+}
+
+/// Annualised realised volatility: sample standard deviation of the last
+/// `window` log returns, scaled by `sqrt(periods_per_year)`. A window
+/// containing a non-positive price has no reading.
+pub fn realised_volatility(
+    values: &[f64],
+    window: usize,
+    periods_per_year: f64,
+) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if window < 2 {
+        return out;
+    }
+    let returns: Vec<f64> = values.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+    for end in window..=returns.len() {
+        let slice = &returns[end - window..end];
+        if slice.iter().any(|r| !r.is_finite()) {
+            continue;
+        }
+        let mean = slice.iter().sum::<f64>() / window as f64;
+        let variance = slice.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (window - 1) as f64;
+        // returns[k] ends at values[k + 1]
+        out[end] = Some(variance.sqrt() * periods_per_year.sqrt());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10-day moving average example from StockCharts' ChartSchool
+    const CLOSES_10D: [f64; 30] = [
+        22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29, 22.15, 22.39, 22.38,
+        22.61, 23.36, 24.05, 23.75, 23.83, 23.95, 23.63, 23.82, 23.87, 23.65, 23.19, 23.10, 23.33,
+        22.68, 23.10, 22.40, 22.17,
+    ];
+
+    /// 14-day RSI example from Wilder / StockCharts' ChartSchool
+    const CLOSES_RSI: [f64; 33] = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61,
+        46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64, 46.21, 46.25, 45.71, 46.45, 45.78, 45.35,
+        44.03, 44.18, 44.22, 44.57, 43.42, 42.66, 43.13,
+    ];
+
+    /// Published tables are rounded to cents
+    fn assert_close(actual: &[Option<f64>], expected: &[f64]) {
+        let actual: Vec<f64> = actual.iter().flatten().copied().collect();
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() <= 0.005 + 1e-9, "{a} != {e} in {actual:?}");
+        }
+    }
+
+    #[test]
+    fn sma_matches_a_naive_mean() {
+        assert_eq!(
+            sma(&[1.0, 2.0, 3.0, 4.0, 5.0], 3),
+            [None, None, Some(2.0), Some(3.0), Some(4.0)]
+        );
+
+        let sma = sma(&CLOSES_10D, 10);
+        assert!(sma[..9].iter().all(Option::is_none));
+        for (i, value) in sma.iter().enumerate().skip(9) {
+            let mean = CLOSES_10D[i - 9..=i].iter().sum::<f64>() / 10.0;
+            assert!((value.unwrap() - mean).abs() < 1e-9);
+        }
+        assert_close(&sma[9..10], &[22.22]);
+    }
+
+    #[test]
+    fn ema_matches_reference_values() {
+        let ema = ema(&CLOSES_10D, 10);
+        assert!(ema[..9].iter().all(Option::is_none));
+        assert_close(
+            &ema,
+            &[
+                22.22, 22.21, 22.24, 22.27, 22.33, 22.52, 22.80, 22.97, 23.13, 23.28, 23.34, 23.43,
+                23.51, 23.53, 23.47, 23.40, 23.39, 23.26, 23.23, 23.08, 22.92,
+            ],
+        );
+    }
+
+    #[test]
+    fn rsi_matches_reference_values() {
+        let rsi = rsi(&CLOSES_RSI, 14);
+        assert!(rsi[..14].iter().all(Option::is_none));
+        assert_close(
+            &rsi,
+            &[
+                70.46, 66.25, 66.48, 69.35, 66.29, 57.92, 62.88, 63.21, 56.01, 62.34, 54.67, 50.39,
+                40.02, 41.49, 41.90, 45.50, 37.32, 33.09, 37.79,
+            ],
+        );
+    }
+
+    #[test]
+    fn rsi_saturates_on_one_way_moves() {
+        let rising: Vec<f64> = (0..20).map(f64::from).collect();
+        assert_eq!(rsi(&rising, 14).last(), Some(&Some(100.0)));
+        assert_eq!(rsi(&[5.0; 20], 14).last(), Some(&Some(50.0)));
+    }
+
+    #[test]
+    fn macd_of_a_linear_trend_is_the_ema_lag_gap() {
+        // On a straight line an n-period EMA trails by (n - 1) / 2 steps,
+        // so MACD(12, 26) is exactly 7 and the signal line agrees
+        let line: Vec<f64> = (0..60).map(f64::from).collect();
+        let macd = macd(&line, 12, 26, 9);
+        assert!(macd[..33].iter().all(Option::is_none));
+        for reading in macd[33..].iter().flatten() {
+            assert!((reading.macd - 7.0).abs() < 1e-9, "{reading:?}");
+            assert!((reading.signal - 7.0).abs() < 1e-9, "{reading:?}");
+            assert!(reading.histogram.abs() < 1e-9, "{reading:?}");
+        }
+        assert_eq!(macd.iter().flatten().count(), 27);
+    }
+
+    #[test]
+    fn bollinger_uses_population_deviation() {
+        let bands = bollinger(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 5, 2.0);
+        assert_eq!(bands[3], None);
+        let band = bands[4].unwrap();
+        assert_eq!(band.middle, 3.0);
+        assert!((band.upper - (3.0 + 2.0 * 2f64.sqrt())).abs() < 1e-12);
+        assert!((band.lower - (3.0 - 2.0 * 2f64.sqrt())).abs() < 1e-12);
+        assert_eq!(bands[5].unwrap().middle, 4.0);
+    }
+
+    #[test]
+    fn realised_volatility_annualises_log_returns() {
+        let zigzag = [100.0, 110.0, 100.0, 110.0, 100.0, 110.0];
+        let vol = realised_volatility(&zigzag, 4, 365.0);
+        assert!(vol[..4].iter().all(Option::is_none));
+        let expected = 1.1f64.ln() * (4.0f64 / 3.0).sqrt() * 365f64.sqrt();
+        assert!((vol[4].unwrap() - expected).abs() < 1e-12);
+        assert_eq!(vol[4], vol[5]);
+
+        assert_eq!(realised_volatility(&[50.0; 6], 4, 365.0)[5], Some(0.0));
+        assert_eq!(
+            realised_volatility(&[1.0, 0.0, 1.0, 2.0], 2, 365.0)[2],
+            None
+        );
+    }
+
+    #[test]
+    fn zero_periods_yield_nothing() {
+        assert!(sma(&CLOSES_10D, 0).iter().all(Option::is_none));
+        assert!(ema(&CLOSES_10D, 0).iter().all(Option::is_none));
+        assert!(rsi(&CLOSES_10D, 0).iter().all(Option::is_none));
+        assert!(ema(&CLOSES_10D[..5], 10).iter().all(Option::is_none));
+    }
+}
@@ -1,4 +1,5 @@
-// Hide the console window on Windows in release builds.
+// Hide the console window on Windows in release builds; command-line runs
+// reattach to the parent console in `main`.
 // On macOS/Linux this attribute is harmless (ignored).
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
@@ -7,24 +8,58 @@ use iced::Size;
 use tracing_subscriber::EnvFilter;
 
 // Core modules
+mod address;
 mod api;
 mod app;
+mod candles;
+mod cassette;
+mod cli;
+mod connectivity;
 mod country;
+mod custom_theme;
+mod esplora;
+mod fx;
+mod fx_matrix;
+mod headless;
+mod history;
 mod http_client;
 mod http_utils;
+mod indicators;
 mod message;
+mod mempool;
+mod metrics;
+mod notify;
+mod palette;
+mod paths;
 mod route;
+mod rules;
+mod secrets;
+mod server;
+mod shortcuts;
+mod store;
+mod supply;
+#[cfg(test)]
+mod testing;
+mod vsize;
+mod watchlist;
+mod xpub;
 
 // UI modules
 mod pages;
 mod views;
 
 use app::App;
+use cli::CliArgs;
 
 const WINDOW_ICON: &[u8] = include_bytes!("../resources/Bitcoin.png");
 
 /// Main application entry point
 fn main() -> iced::Result {
+    // Any flag may print (--help) or run without a window (--headless)
+    if std::env::args_os().len() > 1 {
+        attach_parent_console();
+    }
+
     // Initialize logging: respect RUST_LOG env var if set,
     // otherwise debug builds default to DEBUG, release builds to INFO.
     let default_filter = if cfg!(debug_assertions) {
@@ -40,6 +75,32 @@ fn main() -> iced::Result {
         )
         .init();
 
+    let args = CliArgs::parse(std::env::args().skip(1));
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
+    // Env first, then command-line overrides; Settings can rebuild it later
+    let http_config = args.http_config(http_client::HttpConfig::from_env());
+    if let Err(e) = http_client::configure(http_config) {
+        tracing::error!("Invalid HTTP settings, using defaults: {e}");
+        if args.headless {
+            std::process::exit(1);
+        }
+    }
+
+    // An explicit environment key wins over the stored one
+    api::set_api_key(api::ApiKey::from_env().or_else(secrets::load_api_key));
+
+    if args.headless {
+        if let Err(e) = headless::run(&args) {
+            tracing::error!("Headless mode failed: {e}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     iced::application(App::boot, App::update, App::view)
         .title(App::title)
         .theme(App::theme)
@@ -52,3 +113,21 @@ fn main() -> iced::Result {
         })
         .run()
 }
+
+/// Send stdout/stderr to the console the app was started from.
+///
+/// Release builds on Windows use the GUI subsystem, so without this the
+/// output of `--help` and `--headless` goes nowhere. Fails quietly when
+/// there is no parent console (e.g. started from Explorer).
+#[cfg(windows)]
+fn attach_parent_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // SAFETY: takes no pointers; failure only means there is no console
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
@@ -0,0 +1,251 @@
+//! Chain and fee data from a mempool.space-compatible REST API.
+//!
+//! The base URL is configurable so a self-hosted instance (or a local stub)
+//! can stand in for mempool.space.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::info;
+
+use crate::http_client::{Transport, TRANSPORT};
+
+/// Environment variable overriding the API base URL
+pub const MEMPOOL_URL_ENV: &str = "IFB_MEMPOOL_URL";
+
+pub const DEFAULT_BASE_URL: &str = "https://mempool.space/api";
+
+/// `/v1/fees/recommended`, in sat/vB
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecommendedFees {
+    /// Next block
+    pub fastest_fee: f64,
+    /// Within about half an hour
+    pub half_hour_fee: f64,
+    /// Within about an hour
+    pub hour_fee: f64,
+    pub economy_fee: f64,
+    pub minimum_fee: f64,
+}
+
+/// `/mempool`: unconfirmed transactions waiting for a block
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MempoolStats {
+    /// Transaction count
+    pub count: u64,
+    /// Total virtual size in vbytes
+    pub vsize: u64,
+    /// Sum of fees in sats
+    pub total_fee: f64,
+}
+
+/// `/v1/difficulty-adjustment`: progress through the 2016-block epoch
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DifficultyAdjustment {
+    /// Share of the epoch mined, 0–100
+    pub progress_percent: f64,
+    /// Estimated difficulty change at the retarget, in percent
+    pub difficulty_change: f64,
+    pub remaining_blocks: u64,
+    /// Estimated retarget time, Unix milliseconds
+    pub estimated_retarget_date: u64,
+    pub next_retarget_height: u64,
+    /// Average block interval this epoch, milliseconds
+    pub time_avg: u64,
+}
+
+/// Everything the Network page shows, from one round of requests
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkStatus {
+    pub tip_height: u64,
+    pub fees: RecommendedFees,
+    pub mempool: MempoolStats,
+    pub difficulty: DifficultyAdjustment,
+}
+
+/// Why network data could not be loaded
+#[derive(Debug, Clone, Error)]
+pub enum MempoolError {
+    #[error("Could not reach {url}: {message}")]
+    Network { url: String, message: String },
+    #[error("HTTP {status} from {url}")]
+    HttpStatus { url: String, status: u16 },
+    #[error("Invalid response from {url}: {message}")]
+    Invalid { url: String, message: String },
+}
+
+/// A mempool.space-compatible API, addressed by its base URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MempoolApi {
+    base_url: String,
+}
+
+impl Default for MempoolApi {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+}
+
+impl MempoolApi {
+    /// The base URL in [`MEMPOOL_URL_ENV`] when valid, otherwise mempool.space
+    pub fn from_env() -> Self {
+        match std::env::var(MEMPOOL_URL_ENV) {
+            Ok(url) => Self::new(&url).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring {MEMPOOL_URL_ENV}: {e}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Checks that `base_url` is an http(s) URL; a trailing `/` is dropped
+    pub fn new(base_url: &str) -> Result<Self, String> {
+        let base_url = base_url.trim().trim_end_matches('/');
+        let host = base_url
+            .strip_prefix("https://")
+            .or_else(|| base_url.strip_prefix("http://"))
+            .ok_or_else(|| "Base URL must start with http:// or https://".to_string())?;
+        if host.is_empty() {
+            return Err("Base URL needs a host".to_string());
+        }
+        Ok(Self {
+            base_url: base_url.to_string(),
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn fetch_status(&self) -> Result<NetworkStatus, MempoolError> {
+        self.fetch_status_with(&TRANSPORT).await
+    }
+
+    /// Same as [`fetch_status`](Self::fetch_status), over an explicit transport
+    async fn fetch_status_with(
+        &self,
+        transport: &Transport,
+    ) -> Result<NetworkStatus, MempoolError> {
+        let height = self.get(transport, "/blocks/tip/height").await?;
+        let tip_height = height
+            .1
+            .trim()
+            .parse()
+            .map_err(|e: std::num::ParseIntError| MempoolError::Invalid {
+                url: height.0,
+                message: e.to_string(),
+            })?;
+        Ok(NetworkStatus {
+            tip_height,
+            fees: self.get_json(transport, "/v1/fees/recommended").await?,
+            mempool: self.get_json(transport, "/mempool").await?,
+            difficulty: self
+                .get_json(transport, "/v1/difficulty-adjustment")
+                .await?,
+        })
+    }
+
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        transport: &Transport,
+        path: &str,
+    ) -> Result<T, MempoolError> {
+        let (url, body) = self.get(transport, path).await?;
+        serde_json::from_str(&body).map_err(|e| MempoolError::Invalid {
+            url,
+            message: e.to_string(),
+        })
+    }
+
+    /// Requested URL and body of a successful GET
+    async fn get(
+        &self,
+        transport: &Transport,
+        path: &str,
+    ) -> Result<(String, String), MempoolError> {
+        let url = format!("{}{path}", self.base_url);
+        info!("Fetching network data from: {url}");
+        let response = transport
+            .get(&url)
+            .await
+            .map_err(|e| MempoolError::Network {
+                url: url.clone(),
+                message: e.to_string(),
+            })?;
+        if !response.status.is_success() {
+            return Err(MempoolError::HttpStatus {
+                url,
+                status: response.status.as_u16(),
+            });
+        }
+        Ok((url, response.text()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cassette::Cassette;
+    use std::sync::Arc;
+
+    fn replay(name: &str) -> Transport {
+        let path = format!("{}/tests/cassettes/{name}", env!("CARGO_MANIFEST_DIR"));
+        Transport::Replay(Arc::new(Cassette::load(path).unwrap()))
+    }
+
+    #[test]
+    fn base_url_is_validated_and_normalised() {
+        assert_eq!(
+            MempoolApi::new(" http://127.0.0.1:8999/api/ ")
+                .unwrap()
+                .base_url(),
+            "http://127.0.0.1:8999/api"
+        );
+        assert!(MempoolApi::new("mempool.space/api").is_err());
+        assert!(MempoolApi::new("https://").is_err());
+        assert_eq!(MempoolApi::default().base_url(), DEFAULT_BASE_URL);
+    }
+
+    #[tokio::test]
+    async fn status_replays_from_a_self_hosted_instance() {
+        let api = MempoolApi::new("http://127.0.0.1:8999/api").unwrap();
+        let status = api
+            .fetch_status_with(&replay("mempool.json"))
+            .await
+            .unwrap();
+
+        assert_eq!(status.tip_height, 840_000);
+        assert_eq!(status.fees.fastest_fee, 32.0);
+        assert_eq!(status.fees.hour_fee, 18.0);
+        assert_eq!(status.mempool.count, 48_213);
+        assert_eq!(status.difficulty.remaining_blocks, 672);
+        assert_eq!(status.difficulty.next_retarget_height, 840_672);
+    }
+
+    #[tokio::test]
+    async fn failures_name_the_endpoint() {
+        let api = MempoolApi::new("http://127.0.0.1:8999/broken").unwrap();
+        let err = api
+            .fetch_status_with(&replay("mempool.json"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, MempoolError::Invalid { url, .. } if url.ends_with("/blocks/tip/height")),
+            "{err}"
+        );
+
+        let api = MempoolApi::new("http://127.0.0.1:8999/down").unwrap();
+        let err = api
+            .fetch_status_with(&replay("mempool.json"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "HTTP 503 from http://127.0.0.1:8999/down/blocks/tip/height"
+        );
+    }
+}
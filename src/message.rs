@@ -1,7 +1,24 @@
-use crate::country::CountryPrice;
+use crate::api::{ApiError, ApiPlan, Candle};
+use crate::connectivity::Connectivity;
+use crate::custom_theme::ThemeColor;
+use crate::esplora::{Balance, EsploraError};
+use crate::fx::{FxError, FxRates};
+use crate::history::SeriesPoint;
+use crate::mempool::{MempoolError, NetworkStatus};
+use crate::notify::{Channel, NotifyError, SmtpSecurity, WebhookPreset};
+use crate::pages::alerts_page::{AlertsTab, Field};
+use crate::pages::candle_page::CandleInterval;
+use crate::pages::chart_page::{ChartRange, Overlay, Pane};
+use crate::pages::fee_page::ScriptChoice;
+use crate::pages::markets_page::MarketsTab;
 use crate::route::Route;
+use crate::server::ServerEvent;
+use crate::shortcuts::Action;
+use crate::store::Snapshot;
+use iced::keyboard::{Key, Modifiers};
+use iced::theme::Mode;
 use iced::Theme;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Application-level messages
 #[derive(Debug, Clone)]
@@ -10,23 +27,204 @@ pub enum Message {
     Navigate(Route),
     /// Messages related to Bitcoin page functionality
     Bitcoin(BitcoinMessage),
+    /// Messages related to Chart page functionality
+    Chart(ChartMessage),
+    /// Messages related to Candles page functionality
+    Candles(CandleMessage),
+    /// Messages related to Markets page functionality
+    Markets(MarketsMessage),
+    /// Messages related to Network page functionality
+    Network(NetworkMessage),
+    /// Messages related to Fees page functionality
+    Fees(FeeMessage),
+    /// Messages related to Wallet page functionality
+    Wallet(WalletMessage),
+    /// Messages related to Alerts page functionality
+    Alerts(AlertsMessage),
     /// Messages related to Settings page functionality
     Settings(SettingsMessage),
     /// Auto-refresh tick from subscription
     Tick,
+    /// Status update from the local API server subscription
+    ApiServer(ServerEvent),
+    /// Network reachability changed
+    Connectivity(Connectivity),
+    /// OS light/dark preference reported or changed
+    SystemTheme(Mode),
+    /// A key press that may be a shortcut or drive the command palette
+    KeyPressed(Key, Modifiers),
+    /// Messages from the command palette overlay
+    Palette(PaletteMessage),
+}
+
+/// Messages specific to the command palette
+#[derive(Debug, Clone)]
+pub enum PaletteMessage {
+    /// Query edited
+    QueryChanged(String),
+    /// Result at this position clicked
+    Select(usize),
+    /// Run the highlighted result
+    Submit,
+    /// Dismiss the palette
+    Close,
 }
 
 /// Messages specific to Bitcoin price fetching and display
 #[derive(Debug, Clone)]
 pub enum BitcoinMessage {
-    /// Trigger a refetch of Bitcoin prices
+    /// Trigger a refetch of Bitcoin prices, aborting any fetch in flight
     Refetch,
-    /// Received current Bitcoin prices
-    CurrentPrice((f64, Vec<CountryPrice>)),
+    /// Scheduled refresh; skipped while a fetch is in flight
+    Tick,
+    /// Connectivity returned: refetch prices and download any missing flags
+    Resume,
+    /// Received current Bitcoin prices for the given fetch generation
+    CurrentPrice(u64, Snapshot),
+    /// Wall-clock tick (Unix seconds) to refresh relative ages
+    Clock(u64),
     /// SVG flag images have been loaded
     SvgLoaded(HashMap<String, Vec<u8>>),
-    /// Error occurred during operation
-    Error(String),
+    /// Fetch with the given generation failed
+    Error(u64, ApiError),
+    /// The provider's list of quote currencies arrived, or could not be fetched
+    SupportedCurrencies(Result<HashSet<String>, ApiError>),
+    /// Expand or collapse the technical section of the error panel
+    ToggleErrorDetails,
+    /// Copy the current error's diagnostics to the clipboard
+    CopyDiagnostics,
+    /// Currency filter edited
+    SearchChanged(String),
+    /// Move keyboard focus to the currency filter
+    FocusSearch,
+}
+
+/// Messages specific to the Chart page
+#[derive(Debug, Clone)]
+pub enum ChartMessage {
+    /// Switch between recorded samples and daily history
+    RangeSelected(ChartRange),
+    /// Chart another currency (uppercase code)
+    CurrencySelected(String),
+    /// Show or hide an overlay on the price pane
+    OverlayToggled(Overlay, bool),
+    /// Show or hide an indicator sub-pane
+    PaneToggled(Pane, bool),
+    /// Daily prices for a currency arrived
+    DailyLoaded(String, Vec<SeriesPoint>),
+    /// Daily prices could not be fetched
+    DailyFailed(ApiError),
+}
+
+/// Messages specific to the Candles page
+#[derive(Debug, Clone)]
+pub enum CandleMessage {
+    /// Candle width (and source) picked
+    IntervalSelected(CandleInterval),
+    /// Chart another currency (uppercase code)
+    CurrencySelected(String),
+    /// CoinGecko candles for an interval and currency arrived
+    Loaded(CandleInterval, String, Vec<Candle>),
+    /// CoinGecko candles could not be fetched
+    Failed(ApiError),
+}
+
+/// Messages specific to the Markets page
+#[derive(Debug, Clone)]
+pub enum MarketsMessage {
+    /// Switch between premiums and the cross-rate matrix
+    TabSelected(MarketsTab),
+    /// Matrix currency list edited
+    MatrixCurrenciesChanged(String),
+    /// Fetch the reference FX rates again
+    RefreshRates,
+    /// Reference FX rates arrived
+    RatesLoaded(FxRates),
+    /// Reference FX rates could not be loaded
+    RatesFailed(FxError),
+}
+
+/// Messages specific to the Network page
+#[derive(Debug, Clone)]
+pub enum NetworkMessage {
+    /// Base URL input edited
+    BaseUrlChanged(String),
+    /// Use the typed base URL and fetch from it
+    ApplyBaseUrl,
+    /// Fetch network data again
+    Refresh,
+    /// Network data arrived from the API at this base URL
+    Loaded(String, NetworkStatus),
+    /// Fetching from the API at this base URL failed
+    Failed(String, MempoolError),
+}
+
+/// Messages specific to the Fees page
+#[derive(Debug, Clone)]
+pub enum FeeMessage {
+    /// Script type of every input and output picked
+    ScriptSelected(ScriptChoice),
+    /// Input count edited
+    InputsChanged(String),
+    /// Output count edited
+    OutputsChanged(String),
+    /// Multisig signature count edited
+    RequiredChanged(String),
+    /// Multisig key count edited
+    KeysChanged(String),
+    /// Value fees in another currency (uppercase code)
+    CurrencySelected(String),
+}
+
+/// Messages specific to the Wallet page
+#[derive(Debug, Clone)]
+pub enum WalletMessage {
+    /// Address or extended key input edited
+    InputChanged(String),
+    /// Label input edited
+    LabelChanged(String),
+    /// Validate the input and start watching it
+    Add,
+    /// Stop watching the entry with this address or key
+    Remove(String),
+    /// Fetch every balance again
+    Refresh,
+    /// Value balances in another currency (uppercase code)
+    CurrencySelected(String),
+    /// Balance arrived for this address or key
+    Loaded(String, Balance),
+    /// Fetching the balance for this address or key failed
+    Failed(String, EsploraError),
+}
+
+/// Messages specific to the Alerts page
+#[derive(Debug, Clone)]
+pub enum AlertsMessage {
+    /// Show the rules or another channel's settings
+    TabSelected(AlertsTab),
+    /// Shown channel switched on or off
+    EnabledToggled(bool),
+    /// A text setting of the shown channel edited
+    FieldChanged(Field, String),
+    /// Replace the webhook template with a ready-made one
+    PresetSelected(WebhookPreset),
+    SecuritySelected(SmtpSecurity),
+    /// Write every channel's settings to disk
+    Save,
+    /// Send a test alert through the shown channel as currently edited
+    SendTest,
+    /// A test alert was delivered or failed
+    TestSent(Channel, Result<(), NotifyError>),
+    /// A rule's alert was delivered or failed
+    Delivered(Channel, Result<(), NotifyError>),
+    /// A saved rule edited
+    RuleChanged(usize, String),
+    NewRuleChanged(String),
+    /// Append the new rule once it checks
+    AddRule,
+    RemoveRule(usize),
+    /// Write the rules to disk
+    SaveRules,
 }
 
 /// Messages specific to Settings page
@@ -36,10 +234,61 @@ pub enum SettingsMessage {
     AutoRefreshIntervalChanged(u32),
     /// Theme selection changed — uses iced's built-in Theme
     ThemeChanged(Theme),
+    /// Follow the OS light/dark preference toggle changed
+    FollowSystemThemeToggled(bool),
+    /// Theme used for a light OS preference
+    LightThemeChanged(Theme),
+    /// Theme used for a dark OS preference
+    DarkThemeChanged(Theme),
+    /// Theme editor: palette name edited
+    ThemeNameChanged(String),
+    /// Theme editor: a colour's hex text edited
+    ThemeColorChanged(ThemeColor, String),
+    /// Theme editor: import/export file path edited
+    ThemeFileChanged(String),
+    /// Load the selected theme's palette into the editor
+    EditCurrentTheme,
+    /// Use the edited palette as the current theme
+    ApplyCustomTheme,
+    /// Save the edited palette to the themes directory and use it
+    SaveCustomTheme,
+    /// Load a palette file and use it
+    ImportTheme,
+    /// Write the edited palette to the file path
+    ExportTheme,
     /// Notifications toggle changed
     NotificationsToggled(bool),
     /// Auto-refresh toggle changed
     AutoRefreshToggled(bool),
+    /// Data is flagged stale after this many refresh intervals
+    StaleMultipleChanged(u32),
+    /// Local API server toggle changed
+    ApiServerToggled(bool),
+    /// Local API server port input edited
+    ApiServerPortChanged(String),
+    /// A network (HTTP client) field edited; applied with `ApplyHttpConfig`
+    HttpFieldChanged(HttpField, String),
+    /// Rebuild the shared HTTP client from the network fields
+    ApplyHttpConfig,
+    /// CoinGecko plan selected
+    ApiPlanChanged(ApiPlan),
+    /// CoinGecko API key input edited
+    ApiKeyChanged(String),
+    /// Store the API key and start using it
+    SaveApiKey,
+    /// Shortcut input for an action edited; applied when valid
+    ShortcutChanged(Action, String),
     /// Reset all settings to defaults
     ResetToDefaults,
 }
+
+/// Editable HTTP client settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpField {
+    Proxy,
+    ProxyUser,
+    ProxyPassword,
+    CaBundle,
+    ConnectTimeout,
+    Timeout,
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use crate::history::unix_now;
+
+/// Process-wide metrics registry, rendered in Prometheus text format at `/metrics`
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::default);
+
+/// Upper bounds (seconds) of the `fetch_btc` latency histogram buckets
+const LATENCY_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Cumulative latency histogram with fixed buckets
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Per-bucket counts (non-cumulative); last slot is `+Inf`
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        let slot = LATENCY_BUCKETS
+            .iter()
+            .position(|&le| seconds <= le)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[slot] += 1;
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+/// Counters, gauges and histograms for price fetching and flag downloads
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Latest BTC price per currency
+    prices: Mutex<BTreeMap<String, f64>>,
+    /// Unix time of the last successful `fetch_btc`
+    last_success: AtomicU64,
+    /// `fetch_btc` latency, successful or not
+    fetch_latency: Mutex<Histogram>,
+    fetch_success: AtomicU64,
+    /// Failed fetches keyed by `ApiError` variant
+    fetch_errors: Mutex<BTreeMap<&'static str, u64>>,
+    flag_download_success: AtomicU64,
+    flag_download_failure: AtomicU64,
+}
+
+impl Metrics {
+    /// Records a successful fetch and updates the per-currency price gauges
+    pub fn record_fetch_success<'a>(
+        &self,
+        elapsed: Duration,
+        prices: impl IntoIterator<Item = (&'a String, &'a f64)>,
+    ) {
+        self.observe_latency(elapsed);
+        self.fetch_success.fetch_add(1, Ordering::Relaxed);
+        self.last_success.store(unix_now(), Ordering::Relaxed);
+        if let Ok(mut gauges) = self.prices.lock() {
+            for (currency, price) in prices {
+                gauges.insert(currency.clone(), *price);
+            }
+        }
+    }
+
+    /// Records a failed fetch under the given error kind (an `ApiError` variant name)
+    pub fn record_fetch_error(&self, elapsed: Duration, kind: &'static str) {
+        self.observe_latency(elapsed);
+        if let Ok(mut errors) = self.fetch_errors.lock() {
+            *errors.entry(kind).or_default() += 1;
+        }
+    }
+
+    pub fn record_flag_download(&self, success: bool) {
+        let counter = if success {
+            &self.flag_download_success
+        } else {
+            &self.flag_download_failure
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn observe_latency(&self, elapsed: Duration) {
+        if let Ok(mut histogram) = self.fetch_latency.lock() {
+            histogram.observe(elapsed.as_secs_f64());
+        }
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format (v0.0.4)
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP btc_price Latest BTC price in the given currency.\n");
+        out.push_str("# TYPE btc_price gauge\n");
+        if let Ok(prices) = self.prices.lock() {
+            for (currency, price) in prices.iter() {
+                let _ = writeln!(out, "btc_price{{currency=\"{currency}\"}} {price}");
+            }
+        }
+
+        let last_success = self.last_success.load(Ordering::Relaxed);
+        out.push_str("# HELP btc_last_success_timestamp_seconds Unix time of the last successful price fetch (0 if none).\n");
+        out.push_str("# TYPE btc_last_success_timestamp_seconds gauge\n");
+        let _ = writeln!(out, "btc_last_success_timestamp_seconds {last_success}");
+
+        if last_success > 0 {
+            out.push_str(
+                "# HELP btc_data_age_seconds Seconds since the last successful price fetch.\n",
+            );
+            out.push_str("# TYPE btc_data_age_seconds gauge\n");
+            let _ = writeln!(
+                out,
+                "btc_data_age_seconds {}",
+                unix_now().saturating_sub(last_success)
+            );
+        }
+
+        out.push_str("# HELP btc_fetch_success_total Successful price fetches.\n");
+        out.push_str("# TYPE btc_fetch_success_total counter\n");
+        let _ = writeln!(
+            out,
+            "btc_fetch_success_total {}",
+            self.fetch_success.load(Ordering::Relaxed)
+        );
+
+        out.push_str("# HELP btc_fetch_errors_total Failed price fetches by error kind.\n");
+        out.push_str("# TYPE btc_fetch_errors_total counter\n");
+        if let Ok(errors) = self.fetch_errors.lock() {
+            for (kind, count) in errors.iter() {
+                let _ = writeln!(out, "btc_fetch_errors_total{{kind=\"{kind}\"}} {count}");
+            }
+        }
+
+        out.push_str("# HELP btc_fetch_duration_seconds Latency of price fetches.\n");
+        out.push_str("# TYPE btc_fetch_duration_seconds histogram\n");
+        if let Ok(histogram) = self.fetch_latency.lock() {
+            let mut cumulative = 0;
+            for (le, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "btc_fetch_duration_seconds_bucket{{le=\"{le}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "btc_fetch_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(out, "btc_fetch_duration_seconds_sum {}", histogram.sum);
+            let _ = writeln!(out, "btc_fetch_duration_seconds_count {}", histogram.count);
+        }
+
+        out.push_str("# HELP flag_downloads_total Flag SVG downloads by result.\n");
+        out.push_str("# TYPE flag_downloads_total counter\n");
+        let _ = writeln!(
+            out,
+            "flag_downloads_total{{result=\"success\"}} {}",
+            self.flag_download_success.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "flag_downloads_total{{result=\"failure\"}} {}",
+            self.flag_download_failure.load(Ordering::Relaxed)
+        );
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn renders_gauges_counters_and_histogram() {
+        let metrics = Metrics::default();
+        let prices = HashMap::from([("usd".to_string(), 65497.0), ("gbp".to_string(), 51234.5)]);
+        metrics.record_fetch_success(Duration::from_millis(300), &prices);
+        metrics.record_fetch_error(Duration::from_secs(40), "network");
+        metrics.record_fetch_error(Duration::from_millis(50), "network");
+        metrics.record_flag_download(true);
+        metrics.record_flag_download(false);
+
+        let text = metrics.render();
+        assert!(text.contains("btc_price{currency=\"usd\"} 65497\n"));
+        assert!(text.contains("btc_price{currency=\"gbp\"} 51234.5\n"));
+        assert!(text.contains("btc_fetch_success_total 1\n"));
+        assert!(text.contains("btc_fetch_errors_total{kind=\"network\"} 2\n"));
+        assert!(text.contains("btc_fetch_duration_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(text.contains("btc_fetch_duration_seconds_bucket{le=\"0.5\"} 2\n"));
+        assert!(text.contains("btc_fetch_duration_seconds_bucket{le=\"30\"} 2\n"));
+        assert!(text.contains("btc_fetch_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("btc_fetch_duration_seconds_count 3\n"));
+        assert!(text.contains("flag_downloads_total{result=\"success\"} 1\n"));
+        assert!(text.contains("flag_downloads_total{result=\"failure\"} 1\n"));
+        assert!(text.contains("btc_data_age_seconds "));
+    }
+}
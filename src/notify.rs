@@ -0,0 +1,854 @@
+//! Alert delivery to people who are not watching the window: chat webhooks
+//! (Slack, Discord, Teams or any JSON endpoint), SMTP email and ntfy or
+//! Gotify push notifications.
+//!
+//! Channel settings include credentials, so they live in their own
+//! owner-only file next to the secrets file.
+
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::info;
+
+use crate::http_client::{self, host_of, Transport, TRANSPORT};
+use crate::paths;
+
+/// Name of the notifier settings file inside [`paths::config_dir`]
+const NOTIFIERS_FILE: &str = "notifiers.json";
+
+/// Placeholders a webhook template may use; values are inserted JSON-escaped
+pub const TITLE_PLACEHOLDER: &str = "{{title}}";
+pub const MESSAGE_PLACEHOLDER: &str = "{{message}}";
+
+pub const DEFAULT_NTFY_SERVER: &str = "https://ntfy.sh";
+/// Gotify's default priority, which shows a notification on Android
+pub const DEFAULT_GOTIFY_PRIORITY: u8 = 5;
+
+/// Something worth telling the user about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alert {
+    pub title: String,
+    pub message: String,
+}
+
+impl Alert {
+    /// Sent by the "Send test" buttons
+    pub fn test() -> Self {
+        Self {
+            title: "Test alert".to_string(),
+            message: "If you can read this, iced-fetch-bitcoin alerts reach you here.".to_string(),
+        }
+    }
+}
+
+/// Where an alert can be sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Channel {
+    #[default]
+    Webhook,
+    Email,
+    Ntfy,
+    Gotify,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 4] = [
+        Channel::Webhook,
+        Channel::Email,
+        Channel::Ntfy,
+        Channel::Gotify,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Channel::Webhook => "Webhook",
+            Channel::Email => "Email",
+            Channel::Ntfy => "ntfy",
+            Channel::Gotify => "Gotify",
+        }
+    }
+}
+
+/// Credential that is saved and sent as is but never printed
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.is_empty() { "\"\"" } else { "***" })
+    }
+}
+
+/// Ready-made webhook bodies for common chat services
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookPreset {
+    Slack,
+    Discord,
+    Teams,
+}
+
+impl WebhookPreset {
+    pub const ALL: [WebhookPreset; 3] = [
+        WebhookPreset::Slack,
+        WebhookPreset::Discord,
+        WebhookPreset::Teams,
+    ];
+
+    pub fn template(self) -> &'static str {
+        match self {
+            WebhookPreset::Slack => r#"{"text": "*{{title}}*\n{{message}}"}"#,
+            WebhookPreset::Discord => r#"{"content": "**{{title}}**\n{{message}}"}"#,
+            WebhookPreset::Teams => {
+                r#"{"@type": "MessageCard", "@context": "https://schema.org/extensions", "summary": "{{title}}", "title": "{{title}}", "text": "{{message}}"}"#
+            }
+        }
+    }
+
+    /// The preset whose template is exactly `template`
+    pub fn matching(template: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.template() == template)
+    }
+}
+
+impl fmt::Display for WebhookPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WebhookPreset::Slack => "Slack",
+            WebhookPreset::Discord => "Discord",
+            WebhookPreset::Teams => "Microsoft Teams",
+        })
+    }
+}
+
+/// JSON POSTed to an incoming-webhook URL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    pub url: String,
+    /// JSON body with [`TITLE_PLACEHOLDER`] and [`MESSAGE_PLACEHOLDER`]
+    pub template: String,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            template: WebhookPreset::Slack.template().to_string(),
+        }
+    }
+}
+
+/// How the SMTP connection is protected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain text; only for relays on the local machine or network
+    None,
+    /// Upgrades a plain connection, usually on port 587
+    #[default]
+    StartTls,
+    /// TLS from the first byte, usually on port 465
+    Tls,
+}
+
+impl SmtpSecurity {
+    pub const ALL: [SmtpSecurity; 3] = [
+        SmtpSecurity::StartTls,
+        SmtpSecurity::Tls,
+        SmtpSecurity::None,
+    ];
+
+    /// Port the security mode is normally served on
+    pub fn default_port(self) -> u16 {
+        match self {
+            SmtpSecurity::None => 25,
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::Tls => 465,
+        }
+    }
+}
+
+impl fmt::Display for SmtpSecurity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SmtpSecurity::None => "None (plain text)",
+            SmtpSecurity::StartTls => "STARTTLS",
+            SmtpSecurity::Tls => "TLS",
+        })
+    }
+}
+
+/// Plain-text email through an SMTP server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    /// Empty to send without logging in
+    pub username: String,
+    pub password: Secret,
+    pub from: String,
+    /// Comma-separated recipients
+    pub to: String,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: SmtpSecurity::default().default_port(),
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            password: Secret::default(),
+            from: String::new(),
+            to: String::new(),
+        }
+    }
+}
+
+/// Push notification through an ntfy server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NtfyConfig {
+    pub enabled: bool,
+    pub server: String,
+    pub topic: String,
+    /// Access token for protected topics; empty for public ones
+    pub token: Secret,
+}
+
+impl Default for NtfyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server: DEFAULT_NTFY_SERVER.to_string(),
+            topic: String::new(),
+            token: Secret::default(),
+        }
+    }
+}
+
+/// Push notification through a Gotify server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GotifyConfig {
+    pub enabled: bool,
+    pub server: String,
+    /// Application token
+    pub token: Secret,
+    pub priority: u8,
+}
+
+impl Default for GotifyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server: String::new(),
+            token: Secret::default(),
+            priority: DEFAULT_GOTIFY_PRIORITY,
+        }
+    }
+}
+
+/// Settings of every channel; disabled ones keep theirs for later
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Notifiers {
+    pub webhook: WebhookConfig,
+    pub email: EmailConfig,
+    pub ntfy: NtfyConfig,
+    pub gotify: GotifyConfig,
+}
+
+/// Why an alert was not delivered
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum NotifyError {
+    #[error("{0}")]
+    Config(String),
+    #[error("Template does not produce valid JSON: {0}")]
+    Template(String),
+    #[error("Could not reach {host}: {message}")]
+    Network { host: String, message: String },
+    #[error("HTTP {status} from {host}")]
+    HttpStatus { host: String, status: u16 },
+    #[error("Email was not sent: {0}")]
+    Smtp(String),
+}
+
+impl Notifiers {
+    pub fn get_enabled(&self, channel: Channel) -> bool {
+        match channel {
+            Channel::Webhook => self.webhook.enabled,
+            Channel::Email => self.email.enabled,
+            Channel::Ntfy => self.ntfy.enabled,
+            Channel::Gotify => self.gotify.enabled,
+        }
+    }
+
+    pub fn set_enabled(&mut self, channel: Channel, enabled: bool) {
+        match channel {
+            Channel::Webhook => self.webhook.enabled = enabled,
+            Channel::Email => self.email.enabled = enabled,
+            Channel::Ntfy => self.ntfy.enabled = enabled,
+            Channel::Gotify => self.gotify.enabled = enabled,
+        }
+    }
+
+    /// Delivers `alert` through `channel`, enabled or not
+    pub async fn send(&self, channel: Channel, alert: &Alert) -> Result<(), NotifyError> {
+        self.send_with(&TRANSPORT, channel, alert).await
+    }
+
+    /// Same as [`send`](Self::send); HTTP channels go over `transport`
+    async fn send_with(
+        &self,
+        transport: &Transport,
+        channel: Channel,
+        alert: &Alert,
+    ) -> Result<(), NotifyError> {
+        match channel {
+            Channel::Webhook => {
+                let url = http_url(&self.webhook.url, "Webhook URL")?;
+                let body = render_template(&self.webhook.template, alert)?;
+                post(
+                    transport,
+                    &url,
+                    &[("content-type", "application/json")],
+                    body,
+                )
+                .await
+            }
+            Channel::Email => send_email(&self.email, alert).await,
+            Channel::Ntfy => {
+                let server = http_url(&self.ntfy.server, "ntfy server")?;
+                let topic = self.ntfy.topic.trim();
+                if topic.is_empty() || topic.contains('/') {
+                    return Err(NotifyError::Config(
+                        "ntfy topic must be a single name, such as btc-alerts".to_string(),
+                    ));
+                }
+                let auth = format!("Bearer {}", self.ntfy.token.expose().trim());
+                let mut headers = vec![("title", alert.title.as_str())];
+                if !self.ntfy.token.is_empty() {
+                    headers.push(("authorization", auth.as_str()));
+                }
+                let url = format!("{server}/{topic}");
+                post(
+                    transport,
+                    &url,
+                    &headers,
+                    alert.message.clone().into_bytes(),
+                )
+                .await
+            }
+            Channel::Gotify => {
+                let server = http_url(&self.gotify.server, "Gotify server")?;
+                if self.gotify.token.is_empty() {
+                    return Err(NotifyError::Config(
+                        "Gotify needs an application token".to_string(),
+                    ));
+                }
+                let body = serde_json::json!({
+                    "title": alert.title,
+                    "message": alert.message,
+                    "priority": self.gotify.priority,
+                });
+                let headers = [
+                    ("content-type", "application/json"),
+                    ("x-gotify-key", self.gotify.token.expose().trim()),
+                ];
+                let url = format!("{server}/message");
+                post(transport, &url, &headers, body.to_string().into_bytes()).await
+            }
+        }
+    }
+}
+
+/// `template` with the alert filled in, checked to be JSON
+pub fn render_template(template: &str, alert: &Alert) -> Result<Vec<u8>, NotifyError> {
+    // serde_json quotes the string; the template supplies its own quotes
+    let escape = |value: &str| {
+        let quoted = serde_json::Value::from(value).to_string();
+        quoted[1..quoted.len() - 1].to_string()
+    };
+    let body = template
+        .replace(TITLE_PLACEHOLDER, &escape(&alert.title))
+        .replace(MESSAGE_PLACEHOLDER, &escape(&alert.message));
+    serde_json::from_str::<serde_json::Value>(&body)
+        .map_err(|e| NotifyError::Template(e.to_string()))?;
+    Ok(body.into_bytes())
+}
+
+/// `url` trimmed and without a trailing `/`, when it is http(s) with a host
+fn http_url(url: &str, what: &str) -> Result<String, NotifyError> {
+    let url = url.trim().trim_end_matches('/');
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(|| {
+            NotifyError::Config(format!("{what} must start with http:// or https://"))
+        })?;
+    if rest.is_empty() {
+        return Err(NotifyError::Config(format!("{what} needs a host")));
+    }
+    Ok(url.to_string())
+}
+
+async fn post(
+    transport: &Transport,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Vec<u8>,
+) -> Result<(), NotifyError> {
+    let host = host_of(url).to_string();
+    info!("Sending alert to {host}");
+    let response = transport
+        .post(url, headers, body)
+        .await
+        .map_err(|e| NotifyError::Network {
+            host: host.clone(),
+            message: e.to_string().replace(url, &host),
+        })?;
+    if !response.status.is_success() {
+        return Err(NotifyError::HttpStatus {
+            host,
+            status: response.status.as_u16(),
+        });
+    }
+    Ok(())
+}
+
+async fn send_email(config: &EmailConfig, alert: &Alert) -> Result<(), NotifyError> {
+    let host = config.host.trim();
+    if host.is_empty() {
+        return Err(NotifyError::Config("SMTP server is required".to_string()));
+    }
+    let from: Mailbox = config
+        .from
+        .trim()
+        .parse()
+        .map_err(|e| NotifyError::Config(format!("From address is invalid: {e}")))?;
+    let mut builder = lettre::Message::builder()
+        .from(from)
+        .subject(alert.title.as_str())
+        .header(ContentType::TEXT_PLAIN);
+    let recipients: Vec<&str> = config
+        .to
+        .split(',')
+        .map(str::trim)
+        .filter(|to| !to.is_empty())
+        .collect();
+    if recipients.is_empty() {
+        return Err(NotifyError::Config(
+            "Add at least one recipient".to_string(),
+        ));
+    }
+    for to in recipients {
+        let to: Mailbox = to
+            .parse()
+            .map_err(|e| NotifyError::Config(format!("Recipient {to} is invalid: {e}")))?;
+        builder = builder.to(to);
+    }
+    let email = builder
+        .body(alert.message.clone())
+        .map_err(|e| NotifyError::Config(e.to_string()))?;
+
+    let smtp_error = |e: lettre::transport::smtp::Error| NotifyError::Smtp(e.to_string());
+    let mut transport = match config.security {
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        SmtpSecurity::StartTls => {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host).map_err(smtp_error)?
+        }
+        SmtpSecurity::Tls => {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(host).map_err(smtp_error)?
+        }
+    }
+    .port(config.port)
+    .timeout(Some(http_client::config().timeout));
+    if !config.username.trim().is_empty() {
+        transport = transport.credentials(Credentials::new(
+            config.username.trim().to_string(),
+            config.password.expose().to_string(),
+        ));
+    }
+
+    info!("Sending alert email through {host}:{}", config.port);
+    transport
+        .build()
+        .send(email)
+        .await
+        .map(|_| ())
+        .map_err(smtp_error)
+}
+
+/// JSON file with the channel settings, readable by the owner only (mode
+/// 0600 on Unix)
+#[derive(Debug, Clone)]
+pub struct NotifiersFile {
+    path: PathBuf,
+}
+
+impl NotifiersFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The notifier settings in the per-user config directory
+    pub fn default_location() -> Self {
+        Self::new(paths::config_dir().join(NOTIFIERS_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Saved settings; a missing file means every channel is off
+    pub fn load(&self) -> io::Result<Notifiers> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Notifiers::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, notifiers: &Notifiers) -> io::Result<()> {
+        if let Some(parent) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(notifiers)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            if self.path.exists() {
+                std::fs::set_permissions(&self.path, std::fs::Permissions::from_mode(0o600))?;
+            }
+        }
+        options.open(&self.path)?.write_all(json.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// A request as seen by [`http_stand_in`]
+    struct Captured {
+        head: String,
+        body: String,
+    }
+
+    /// Stand-in HTTP server: accepts one request, answers `status` and hands
+    /// back what it received
+    async fn http_stand_in(status: u16) -> (SocketAddr, tokio::task::JoinHandle<Captured>) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buf = [0u8; 1024];
+            let (head, length) = loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                received.extend_from_slice(&buf[..n]);
+                if let Some(end) = received.windows(4).position(|w| w == b"\r\n\r\n") {
+                    let head = String::from_utf8_lossy(&received[..end]).into_owned();
+                    let length = head
+                        .lines()
+                        .find_map(|l| {
+                            l.to_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    received.drain(..end + 4);
+                    break (head, length);
+                }
+            };
+            while received.len() < length {
+                let n = stream.read(&mut buf).await.unwrap();
+                received.extend_from_slice(&buf[..n]);
+            }
+            let response = format!(
+                "HTTP/1.1 {status} Stand-in\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            Captured {
+                head,
+                body: String::from_utf8(received).unwrap(),
+            }
+        });
+        (addr, server)
+    }
+
+    /// Stand-in SMTP server that accepts one message and returns the session
+    /// transcript, client lines only
+    async fn smtp_stand_in() -> (SocketAddr, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            let mut transcript = String::new();
+            write.write_all(b"220 stand-in ESMTP\r\n").await.unwrap();
+            let mut in_data = false;
+            while let Some(line) = lines.next_line().await.unwrap() {
+                transcript.push_str(&line);
+                transcript.push('\n');
+                let reply: &[u8] = if in_data {
+                    if line != "." {
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else {
+                    match line.split(' ').next().unwrap().to_uppercase().as_str() {
+                        "EHLO" => b"250-stand-in\r\n250 AUTH PLAIN LOGIN\r\n",
+                        "AUTH" => b"235 authenticated\r\n",
+                        "DATA" => {
+                            in_data = true;
+                            b"354 go ahead\r\n"
+                        }
+                        "QUIT" => {
+                            write.write_all(b"221 bye\r\n").await.unwrap();
+                            break;
+                        }
+                        _ => b"250 ok\r\n",
+                    }
+                };
+                write.write_all(reply).await.unwrap();
+            }
+            transcript
+        });
+        (addr, server)
+    }
+
+    fn alert() -> Alert {
+        Alert {
+            title: "BTC above 70,000 USD".to_string(),
+            message: "Last price \"70,120\"\nsince 12:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn presets_render_escaped_json() {
+        for preset in WebhookPreset::ALL {
+            let body = render_template(preset.template(), &alert()).unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert!(json
+                .to_string()
+                .contains(r#"Last price \"70,120\"\nsince 12:00"#));
+            assert_eq!(WebhookPreset::matching(preset.template()), Some(preset));
+        }
+        let slack = render_template(WebhookPreset::Slack.template(), &alert()).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&slack).unwrap();
+        assert_eq!(
+            json["text"],
+            "*BTC above 70,000 USD*\nLast price \"70,120\"\nsince 12:00"
+        );
+        assert_eq!(
+            render_template(r#"{"text": {{message}}}"#, &alert()).map(|_| ()),
+            Err(NotifyError::Template(
+                "expected value at line 1 column 10".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn secrets_stay_out_of_debug_output() {
+        let mut notifiers = Notifiers::default();
+        notifiers.email.password = Secret::new("hunter2");
+        notifiers.gotify.token = Secret::new("AbCdEf");
+        let debug = format!("{notifiers:?}");
+        assert!(!debug.contains("hunter2") && !debug.contains("AbCdEf"));
+        assert_eq!(
+            host_of("https://hooks.slack.com/services/T0/B0/xyz"),
+            "hooks.slack.com"
+        );
+        assert_eq!(host_of("http://127.0.0.1:8080?token=x"), "127.0.0.1:8080");
+    }
+
+    #[tokio::test]
+    async fn webhook_posts_the_rendered_template() {
+        let (addr, server) = http_stand_in(200).await;
+        let mut notifiers = Notifiers::default();
+        notifiers.webhook.url = format!("http://{addr}/hooks/secret-path/");
+        notifiers.webhook.template = WebhookPreset::Discord.template().to_string();
+        notifiers
+            .send_with(&Transport::Live, Channel::Webhook, &alert())
+            .await
+            .unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.head.starts_with("POST /hooks/secret-path HTTP/1.1"));
+        assert!(request.head.contains("content-type: application/json"));
+        let json: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(
+            json["content"],
+            "**BTC above 70,000 USD**\nLast price \"70,120\"\nsince 12:00"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_webhooks_do_not_reveal_the_url() {
+        let (addr, server) = http_stand_in(404).await;
+        let mut notifiers = Notifiers::default();
+        notifiers.webhook.url = format!("http://{addr}/hooks/secret-path");
+        let err = notifiers
+            .send_with(&Transport::Live, Channel::Webhook, &alert())
+            .await
+            .unwrap_err();
+        server.await.unwrap();
+        assert_eq!(err.to_string(), format!("HTTP 404 from {addr}"));
+
+        notifiers.webhook.url = "hooks.slack.com/services/x".to_string();
+        assert_eq!(
+            notifiers
+                .send_with(&Transport::Live, Channel::Webhook, &alert())
+                .await,
+            Err(NotifyError::Config(
+                "Webhook URL must start with http:// or https://".to_string()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn ntfy_posts_to_the_topic_with_a_token() {
+        let (addr, server) = http_stand_in(200).await;
+        let mut notifiers = Notifiers::default();
+        notifiers.ntfy.server = format!("http://{addr}");
+        notifiers.ntfy.topic = "btc-alerts".to_string();
+        notifiers.ntfy.token = Secret::new("tk_abc");
+        notifiers
+            .send_with(&Transport::Live, Channel::Ntfy, &alert())
+            .await
+            .unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.head.starts_with("POST /btc-alerts HTTP/1.1"));
+        assert!(request.head.contains("title: BTC above 70,000 USD"));
+        assert!(request.head.contains("authorization: Bearer tk_abc"));
+        assert_eq!(request.body, alert().message);
+    }
+
+    #[tokio::test]
+    async fn gotify_posts_a_message_with_the_app_token() {
+        let (addr, server) = http_stand_in(200).await;
+        let mut notifiers = Notifiers::default();
+        notifiers.gotify.server = format!("http://{addr}/gotify/");
+        notifiers.gotify.token = Secret::new("AbCdEf");
+        notifiers
+            .send_with(&Transport::Live, Channel::Gotify, &alert())
+            .await
+            .unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.head.starts_with("POST /gotify/message HTTP/1.1"));
+        assert!(request.head.contains("x-gotify-key: AbCdEf"));
+        let json: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(json["title"], "BTC above 70,000 USD");
+        assert_eq!(json["priority"], 5);
+    }
+
+    #[tokio::test]
+    async fn email_is_delivered_over_smtp() {
+        let (addr, server) = smtp_stand_in().await;
+        let config = EmailConfig {
+            enabled: true,
+            host: addr.ip().to_string(),
+            port: addr.port(),
+            security: SmtpSecurity::None,
+            username: "alerts".to_string(),
+            password: Secret::new("hunter2"),
+            from: "Bitcoin alerts <alerts@example.com>".to_string(),
+            to: "me@example.com, you@example.com".to_string(),
+        };
+        let notifiers = Notifiers {
+            email: config,
+            ..Notifiers::default()
+        };
+        notifiers
+            .send_with(&Transport::Live, Channel::Email, &alert())
+            .await
+            .unwrap();
+
+        let transcript = server.await.unwrap();
+        assert!(transcript.contains("AUTH PLAIN"));
+        assert!(transcript.contains("MAIL FROM:<alerts@example.com>"));
+        assert!(transcript.contains("RCPT TO:<me@example.com>"));
+        assert!(transcript.contains("RCPT TO:<you@example.com>"));
+        assert!(transcript.contains("Subject: BTC above 70,000 USD"));
+        assert!(transcript.contains("since 12:00"));
+    }
+
+    #[tokio::test]
+    async fn incomplete_email_settings_are_reported() {
+        let mut notifiers = Notifiers::default();
+        notifiers.email.host = "smtp.example.com".to_string();
+        notifiers.email.from = "alerts@example.com".to_string();
+        assert_eq!(
+            notifiers.send(Channel::Email, &alert()).await,
+            Err(NotifyError::Config(
+                "Add at least one recipient".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn settings_round_trip_in_a_private_file() {
+        let nanos = std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("ifb-notifiers-{}-{nanos}", std::process::id()));
+        let file = NotifiersFile::new(dir.join("notifiers.json"));
+        assert_eq!(file.load().unwrap(), Notifiers::default());
+
+        let mut notifiers = Notifiers::default();
+        notifiers.set_enabled(Channel::Gotify, true);
+        notifiers.gotify.server = "https://push.example.com".to_string();
+        notifiers.gotify.token = Secret::new("AbCdEf");
+        file.save(&notifiers).unwrap();
+        assert_eq!(file.load().unwrap(), notifiers);
+        assert!(file.load().unwrap().get_enabled(Channel::Gotify));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(file.path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
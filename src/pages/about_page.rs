@@ -1,5 +1,12 @@
+use crate::candles::format_utc;
+use crate::mempool::NetworkStatus;
 use crate::message::Message;
-use crate::views::about_view::AboutView;
+use crate::supply::{self, COIN};
+use crate::views::about_view::{AboutView, CountdownProps, EraRow, HalvingProps};
+use crate::views::price_chart::SupplyChart;
+
+/// Blocks between points of the supply curve
+const CURVE_STEP: u64 = 10_000;
 
 /// About page — purely informational, no interactive state
 #[derive(Default)]
@@ -10,7 +17,124 @@ impl AboutPage {
         Self
     }
 
-    pub fn view(&self) -> iced::Element<'_, Message> {
-        AboutView::new().view()
+    /// `network` supplies the chain tip and recent block interval for the
+    /// halving countdown; `now` is the current Unix time
+    pub fn view(&self, network: Option<&NetworkStatus>, now: u64) -> iced::Element<'_, Message> {
+        AboutView::new(halving(network, now)).view()
+    }
+}
+
+fn halving(network: Option<&NetworkStatus>, now: u64) -> HalvingProps {
+    let tip = network.map(|n| n.tip_height);
+    let countdown = network.map(|n| {
+        let block_secs = n.difficulty.time_avg as f64 / 1000.0;
+        let estimate = supply::estimate_halving(n.tip_height, block_secs);
+        CountdownProps {
+            height: estimate.height,
+            blocks_left: estimate.blocks_left,
+            secs_left: estimate.secs_left,
+            date: format_utc(now + estimate.secs_left),
+            block_secs: block_secs.round() as u64,
+            subsidy_before: estimate.subsidy_before,
+            subsidy_after: estimate.subsidy_after,
+            supply: supply::supply_after(n.tip_height),
+        }
+    });
+
+    let eras = supply::schedule();
+    let last_height = eras.last().map_or(0, |e| e.last_height);
+    let curve = (0..=last_height / CURVE_STEP)
+        .map(|i| supply::supply_after(i * CURVE_STEP) as f64 / COIN as f64)
+        .collect();
+
+    HalvingProps {
+        status: countdown
+            .is_none()
+            .then(|| "Waiting for the block height from the Network page's server".to_string()),
+        countdown,
+        eras: eras
+            .into_iter()
+            .map(|era| EraRow {
+                index: era.index,
+                first_height: era.first_height,
+                subsidy: era.subsidy,
+                supply_at_end: era.supply_at_end,
+                current: tip.is_some_and(|t| era.contains(t)),
+            })
+            .collect(),
+        chart: SupplyChart {
+            supply: curve,
+            step: CURVE_STEP,
+            tip,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mempool::{DifficultyAdjustment, MempoolStats, RecommendedFees};
+    use crate::testing::{assert_snapshot, simulator_with_size};
+    use iced::Size;
+
+    fn network(tip_height: u64) -> NetworkStatus {
+        NetworkStatus {
+            tip_height,
+            fees: RecommendedFees {
+                fastest_fee: 32.0,
+                half_hour_fee: 24.0,
+                hour_fee: 18.0,
+                economy_fee: 9.0,
+                minimum_fee: 4.0,
+            },
+            mempool: MempoolStats {
+                count: 0,
+                vsize: 0,
+                total_fee: 0.0,
+            },
+            difficulty: DifficultyAdjustment {
+                progress_percent: 50.0,
+                difficulty_change: 0.0,
+                remaining_blocks: 1_008,
+                estimated_retarget_date: 0,
+                next_retarget_height: 0,
+                time_avg: 587_500,
+            },
+        }
+    }
+
+    #[test]
+    fn countdown_uses_the_recent_block_interval() {
+        let props = halving(Some(&network(840_000)), 1_713_571_200);
+        let countdown = props.countdown.unwrap();
+        assert_eq!(countdown.height, 1_050_000);
+        assert_eq!(countdown.secs_left, 210_000 * 5875 / 10);
+        assert_eq!(countdown.date, "2028-03-17 22:50");
+        assert_eq!(countdown.block_secs, 588);
+        assert!(props.status.is_none());
+        assert!(props.eras[4].current && !props.eras[3].current);
+        assert_eq!(props.chart.supply.len(), 693);
+        assert_eq!(props.chart.supply[0], 50.0);
+    }
+
+    #[test]
+    fn schedule_shows_without_a_tip() {
+        let props = halving(None, 0);
+        assert!(props.countdown.is_none());
+        assert!(props.status.is_some());
+        assert_eq!(props.eras.len(), 33);
+        assert!(props.eras.iter().all(|e| !e.current));
+    }
+
+    #[test]
+    fn halving_section_matches_snapshot() {
+        let page = AboutPage::new();
+        let mut ui = simulator_with_size(
+            Size::new(700.0, 900.0),
+            page.view(Some(&network(840_000)), 1_713_571_200),
+        );
+        assert!(ui.find("≈ 1,427 days 22 hours").is_ok());
+        assert!(ui.find("Subsidy 3.125 → 1.5625 BTC per block").is_ok());
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "about_halving");
     }
 }
@@ -0,0 +1,695 @@
+use std::collections::{HashMap, HashSet};
+
+use iced::Task;
+use tracing::warn;
+
+use crate::candles::format_utc;
+use crate::fx::FxRates;
+use crate::history::PriceHistory;
+use crate::message::AlertsMessage;
+use crate::notify::{
+    Alert, Channel, Notifiers, NotifiersFile, NotifyError, Secret, WebhookPreset,
+    MESSAGE_PLACEHOLDER, TITLE_PLACEHOLDER,
+};
+use crate::rules::{Rule, RulesFile};
+use crate::views::alerts_view::{
+    AlertsContent, AlertsProps, AlertsView, ChannelProps, FieldRow, RuleRow, RulesProps,
+};
+
+/// Most recent firings listed under each rule
+const PREVIEW_FIRINGS: usize = 3;
+
+/// Tabs of the Alerts page
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AlertsTab {
+    /// When to alert
+    #[default]
+    Rules,
+    /// Where one channel delivers alerts
+    Channel(Channel),
+}
+
+/// Text settings edited on the Alerts page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    WebhookUrl,
+    WebhookTemplate,
+    SmtpHost,
+    SmtpPort,
+    SmtpUsername,
+    SmtpPassword,
+    SmtpFrom,
+    SmtpTo,
+    NtfyServer,
+    NtfyTopic,
+    NtfyToken,
+    GotifyServer,
+    GotifyToken,
+    GotifyPriority,
+}
+
+/// Alerts page state: the alert rules and every channel's settings as
+/// edited, and test and delivery results
+pub struct AlertsPage {
+    file: NotifiersFile,
+    /// Settings as edited
+    notifiers: Notifiers,
+    /// Settings as last saved; rule alerts only go through these, so
+    /// half-typed edits never send
+    saved: Notifiers,
+    /// Numbers as typed; checked on save and test
+    smtp_port: String,
+    gotify_priority: String,
+    tab: AlertsTab,
+    /// Channel of the last channel tab shown
+    channel: Channel,
+    sending: HashSet<Channel>,
+    /// Outcome of each channel's last test
+    results: HashMap<Channel, Result<(), NotifyError>>,
+    /// Outcome of each channel's last rule alert
+    deliveries: HashMap<Channel, Result<(), NotifyError>>,
+    /// Where the last save went, or why it failed
+    save_status: Option<String>,
+    rules_file: RulesFile,
+    /// Rules as typed, including ones that do not check
+    rules: Vec<String>,
+    /// Rule being written in the "Add" box
+    new_rule: String,
+    rules_status: Option<String>,
+    /// Last firing alerted for each rule, so one firing alerts once
+    notified: HashMap<String, u64>,
+}
+
+impl Default for AlertsPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlertsPage {
+    /// Channel settings saved in the per-user config directory
+    pub fn new() -> Self {
+        Self::with(
+            NotifiersFile::default_location(),
+            RulesFile::default_location(),
+        )
+    }
+
+    pub fn with(file: NotifiersFile, rules_file: RulesFile) -> Self {
+        let notifiers = file.load().unwrap_or_else(|e| {
+            warn!("Cannot read {}: {e}", file.path().display());
+            Notifiers::default()
+        });
+        let rules = rules_file.load().unwrap_or_else(|e| {
+            warn!("Cannot read {}: {e}", rules_file.path().display());
+            Vec::new()
+        });
+        Self {
+            file,
+            smtp_port: notifiers.email.port.to_string(),
+            gotify_priority: notifiers.gotify.priority.to_string(),
+            saved: notifiers.clone(),
+            notifiers,
+            tab: AlertsTab::default(),
+            channel: Channel::default(),
+            sending: HashSet::new(),
+            results: HashMap::new(),
+            deliveries: HashMap::new(),
+            save_status: None,
+            rules_file,
+            rules,
+            new_rule: String::new(),
+            rules_status: None,
+            notified: HashMap::new(),
+        }
+    }
+
+    /// Whether any rule reads premiums, which need reference FX rates
+    pub fn needs_fx_rates(&self) -> bool {
+        self.rules
+            .iter()
+            .filter_map(|source| Rule::parse(source).ok())
+            .any(|rule| rule.uses_premium())
+    }
+
+    /// Alerts every channel enabled in the saved settings about rules that
+    /// fired on the latest sample of `history`; `enabled` is the "Enable
+    /// price alerts" setting
+    pub fn check(
+        &mut self,
+        history: &PriceHistory,
+        rates: Option<&FxRates>,
+        enabled: bool,
+    ) -> Task<AlertsMessage> {
+        let channels: Vec<Channel> = Channel::ALL
+            .into_iter()
+            .filter(|c| self.saved.get_enabled(*c))
+            .collect();
+        let Some(latest) = history.samples().next_back().map(|s| s.timestamp) else {
+            return Task::none();
+        };
+        if !enabled || channels.is_empty() {
+            return Task::none();
+        }
+
+        let mut tasks = Vec::new();
+        for rule in self
+            .rules
+            .iter()
+            .filter_map(|source| Rule::parse(source).ok())
+        {
+            if rule.firings(history, rates).last() != Some(&latest)
+                || self.notified.insert(rule.source().to_string(), latest) == Some(latest)
+            {
+                continue;
+            }
+            let alert = Alert {
+                title: "Bitcoin price alert".to_string(),
+                message: format!("{} (at {} UTC)", rule.source(), format_utc(latest)),
+            };
+            for &channel in &channels {
+                let notifiers = self.saved.clone();
+                let alert = alert.clone();
+                tasks.push(Task::perform(
+                    async move { notifiers.send(channel, &alert).await },
+                    move |result| AlertsMessage::Delivered(channel, result),
+                ));
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    /// Settings as edited, with the typed numbers checked
+    fn edited(&self) -> Result<Notifiers, String> {
+        let mut notifiers = self.notifiers.clone();
+        notifiers.email.port = match self.smtp_port.trim().parse::<u16>() {
+            Ok(port) if port > 0 => port,
+            _ => return Err("SMTP port must be a number from 1 to 65535".to_string()),
+        };
+        notifiers.gotify.priority = match self.gotify_priority.trim().parse::<u8>() {
+            Ok(priority) if priority <= 10 => priority,
+            _ => return Err("Gotify priority must be a number from 0 to 10".to_string()),
+        };
+        Ok(notifiers)
+    }
+
+    pub fn update(&mut self, message: AlertsMessage) -> Task<AlertsMessage> {
+        match message {
+            AlertsMessage::TabSelected(tab) => {
+                self.tab = tab;
+                if let AlertsTab::Channel(channel) = tab {
+                    self.channel = channel;
+                }
+            }
+            AlertsMessage::EnabledToggled(enabled) => {
+                self.notifiers.set_enabled(self.channel, enabled)
+            }
+            AlertsMessage::FieldChanged(field, value) => self.set_field(field, value),
+            AlertsMessage::PresetSelected(preset) => {
+                self.notifiers.webhook.template = preset.template().to_string();
+            }
+            AlertsMessage::SecuritySelected(security) => {
+                let email = &mut self.notifiers.email;
+                // Follow the new mode's usual port unless a custom one was typed
+                if self.smtp_port.trim() == email.security.default_port().to_string() {
+                    self.smtp_port = security.default_port().to_string();
+                }
+                email.security = security;
+            }
+            AlertsMessage::Save => {
+                self.save_status = Some(match self.edited() {
+                    Ok(notifiers) => match self.file.save(&notifiers) {
+                        Ok(()) => {
+                            self.notifiers = notifiers.clone();
+                            self.saved = notifiers;
+                            format!("Saved to {}", self.file.path().display())
+                        }
+                        Err(e) => format!("Could not save {}: {e}", self.file.path().display()),
+                    },
+                    Err(e) => e,
+                });
+            }
+            AlertsMessage::SendTest => {
+                let channel = self.channel;
+                let notifiers = match self.edited() {
+                    Ok(notifiers) => notifiers,
+                    Err(e) => {
+                        self.results.insert(channel, Err(NotifyError::Config(e)));
+                        return Task::none();
+                    }
+                };
+                if !self.sending.insert(channel) {
+                    return Task::none();
+                }
+                self.results.remove(&channel);
+                return Task::perform(
+                    async move { notifiers.send(channel, &Alert::test()).await },
+                    move |result| AlertsMessage::TestSent(channel, result),
+                );
+            }
+            AlertsMessage::TestSent(channel, result) => {
+                self.sending.remove(&channel);
+                self.results.insert(channel, result);
+            }
+            AlertsMessage::Delivered(channel, result) => {
+                if let Err(e) = &result {
+                    warn!("Could not deliver alert by {}: {e}", channel.label());
+                }
+                self.deliveries.insert(channel, result);
+            }
+            AlertsMessage::RuleChanged(index, source) => {
+                if let Some(rule) = self.rules.get_mut(index) {
+                    *rule = source;
+                }
+            }
+            AlertsMessage::NewRuleChanged(source) => self.new_rule = source,
+            AlertsMessage::AddRule => {
+                if Rule::parse(&self.new_rule).is_ok() {
+                    self.rules
+                        .push(std::mem::take(&mut self.new_rule).trim().to_string());
+                }
+            }
+            AlertsMessage::RemoveRule(index) => {
+                if index < self.rules.len() {
+                    self.rules.remove(index);
+                }
+            }
+            AlertsMessage::SaveRules => {
+                self.rules_status = Some(match self.rules_file.save(&self.rules) {
+                    Ok(()) => format!("Saved to {}", self.rules_file.path().display()),
+                    Err(e) => format!("Could not save {}: {e}", self.rules_file.path().display()),
+                });
+            }
+        }
+        Task::none()
+    }
+
+    fn set_field(&mut self, field: Field, value: String) {
+        let n = &mut self.notifiers;
+        match field {
+            Field::WebhookUrl => n.webhook.url = value,
+            Field::WebhookTemplate => n.webhook.template = value,
+            Field::SmtpHost => n.email.host = value,
+            Field::SmtpPort => self.smtp_port = value,
+            Field::SmtpUsername => n.email.username = value,
+            Field::SmtpPassword => n.email.password = Secret::new(value),
+            Field::SmtpFrom => n.email.from = value,
+            Field::SmtpTo => n.email.to = value,
+            Field::NtfyServer => n.ntfy.server = value,
+            Field::NtfyTopic => n.ntfy.topic = value,
+            Field::NtfyToken => n.ntfy.token = Secret::new(value),
+            Field::GotifyServer => n.gotify.server = value,
+            Field::GotifyToken => n.gotify.token = Secret::new(value),
+            Field::GotifyPriority => self.gotify_priority = value,
+        }
+    }
+
+    /// Text fields of the shown channel, in display order
+    fn fields(&self) -> Vec<FieldRow> {
+        let n = &self.notifiers;
+        let row = |label, field, value: &str, placeholder| FieldRow {
+            label,
+            field,
+            value: value.to_string(),
+            placeholder,
+            secure: false,
+        };
+        let secret = |label, field, value: &Secret, placeholder| FieldRow {
+            secure: true,
+            ..row(label, field, value.expose(), placeholder)
+        };
+        match self.channel {
+            Channel::Webhook => vec![
+                row(
+                    "URL",
+                    Field::WebhookUrl,
+                    &n.webhook.url,
+                    "https://hooks.slack.com/services/…",
+                ),
+                row(
+                    "Body",
+                    Field::WebhookTemplate,
+                    &n.webhook.template,
+                    "{\"text\": \"{{title}}: {{message}}\"}",
+                ),
+            ],
+            Channel::Email => vec![
+                row("Server", Field::SmtpHost, &n.email.host, "smtp.example.com"),
+                row("Port", Field::SmtpPort, &self.smtp_port, "587"),
+                row(
+                    "Username",
+                    Field::SmtpUsername,
+                    &n.email.username,
+                    "optional",
+                ),
+                secret(
+                    "Password",
+                    Field::SmtpPassword,
+                    &n.email.password,
+                    "optional",
+                ),
+                row(
+                    "From",
+                    Field::SmtpFrom,
+                    &n.email.from,
+                    "Alerts <alerts@example.com>",
+                ),
+                row("To", Field::SmtpTo, &n.email.to, "me@example.com, …"),
+            ],
+            Channel::Ntfy => vec![
+                row(
+                    "Server",
+                    Field::NtfyServer,
+                    &n.ntfy.server,
+                    "https://ntfy.sh",
+                ),
+                row("Topic", Field::NtfyTopic, &n.ntfy.topic, "btc-alerts"),
+                secret("Token", Field::NtfyToken, &n.ntfy.token, "optional"),
+            ],
+            Channel::Gotify => vec![
+                row(
+                    "Server",
+                    Field::GotifyServer,
+                    &n.gotify.server,
+                    "https://push.example.com",
+                ),
+                secret("App token", Field::GotifyToken, &n.gotify.token, "A1b2C3…"),
+                row(
+                    "Priority",
+                    Field::GotifyPriority,
+                    &self.gotify_priority,
+                    "5",
+                ),
+            ],
+        }
+    }
+
+    /// Rule editor rows: each rule checked as typed, with the times it
+    /// would have fired over `history`
+    fn rule_rows(&self, history: &PriceHistory, rates: Option<&FxRates>) -> Vec<RuleRow> {
+        let since = history.samples().next().map(|s| format_utc(s.timestamp));
+        self.rules
+            .iter()
+            .map(|source| {
+                let rule = match Rule::parse(source) {
+                    Ok(rule) => rule,
+                    Err(e) => {
+                        return RuleRow {
+                            source: source.clone(),
+                            error: Some(e.render(source)),
+                            preview: None,
+                        }
+                    }
+                };
+                let preview = match &since {
+                    _ if rule.uses_premium() && rates.is_none() => {
+                        "Waiting for FX rates to price the premium…".to_string()
+                    }
+                    None => "No prices recorded yet".to_string(),
+                    Some(since) => {
+                        let firings = rule.firings(history, rates);
+                        if firings.is_empty() {
+                            format!("Would not have fired since {since} UTC")
+                        } else {
+                            let recent: Vec<String> = firings
+                                .iter()
+                                .rev()
+                                .take(PREVIEW_FIRINGS)
+                                .map(|&t| format_utc(t))
+                                .collect();
+                            format!(
+                                "Would have fired {} time{} since {since} UTC, last at {}",
+                                firings.len(),
+                                if firings.len() == 1 { "" } else { "s" },
+                                recent.join(", ")
+                            )
+                        }
+                    }
+                };
+                RuleRow {
+                    source: source.clone(),
+                    error: None,
+                    preview: Some(preview),
+                }
+            })
+            .collect()
+    }
+
+    fn rules_props(&self, history: &PriceHistory, rates: Option<&FxRates>) -> RulesProps {
+        let new_rule = self.new_rule.trim();
+        let new_rule_error = if new_rule.is_empty() {
+            None
+        } else {
+            Rule::parse(new_rule).err().map(|e| e.render(new_rule))
+        };
+        RulesProps {
+            can_add: !new_rule.is_empty() && new_rule_error.is_none(),
+            rules: self.rule_rows(history, rates),
+            new_rule: self.new_rule.clone(),
+            new_rule_error,
+            status: self.rules_status.clone(),
+        }
+    }
+
+    fn channel_props(&self) -> ChannelProps {
+        let hint = match self.channel {
+            Channel::Webhook => format!(
+                "POSTs the body as JSON; {TITLE_PLACEHOLDER} and {MESSAGE_PLACEHOLDER} are filled in"
+            ),
+            Channel::Email => "Sends a plain-text email through your SMTP server".to_string(),
+            Channel::Ntfy => "Publishes to an ntfy topic; subscribe to it in the ntfy app".to_string(),
+            Channel::Gotify => "Posts a message to your Gotify server".to_string(),
+        };
+        let status = if self.sending.contains(&self.channel) {
+            Some("Sending test alert…".to_string())
+        } else {
+            self.results.get(&self.channel).map(|result| match result {
+                Ok(()) => "Test alert sent".to_string(),
+                Err(e) => format!("Test failed: {e}"),
+            })
+        };
+
+        ChannelProps {
+            channel: self.channel,
+            enabled: self.notifiers.get_enabled(self.channel),
+            hint,
+            preset: (self.channel == Channel::Webhook)
+                .then(|| WebhookPreset::matching(&self.notifiers.webhook.template)),
+            security: (self.channel == Channel::Email).then_some(self.notifiers.email.security),
+            fields: self.fields(),
+            status,
+            sending: self.sending.contains(&self.channel),
+            delivery: self
+                .deliveries
+                .get(&self.channel)
+                .map(|result| match result {
+                    Ok(()) => "Last rule alert delivered".to_string(),
+                    Err(e) => format!("Last rule alert failed: {e}"),
+                }),
+            save_status: self.save_status.clone(),
+        }
+    }
+
+    pub fn view(
+        &self,
+        history: &PriceHistory,
+        rates: Option<&FxRates>,
+    ) -> iced::Element<'_, AlertsMessage> {
+        let content = match self.tab {
+            AlertsTab::Rules => AlertsContent::Rules(self.rules_props(history, rates)),
+            AlertsTab::Channel(_) => AlertsContent::Channel(self.channel_props()),
+        };
+        AlertsView::new(AlertsProps {
+            tab: self.tab,
+            enabled_channels: Channel::ALL
+                .into_iter()
+                .filter(|c| self.notifiers.get_enabled(*c))
+                .collect(),
+            content,
+        })
+        .view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::PriceSample;
+    use crate::notify::SmtpSecurity;
+    use crate::testing::{assert_snapshot, simulator};
+
+    fn temp_page() -> AlertsPage {
+        let nanos = std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("ifb-alerts-{}-{nanos}", std::process::id()));
+        AlertsPage::with(
+            NotifiersFile::new(dir.join("notifiers.json")),
+            RulesFile::new(dir.join("rules.json")),
+        )
+    }
+
+    /// USD prices one minute apart
+    fn history(prices: &[f64]) -> PriceHistory {
+        let mut history = PriceHistory::default();
+        for (i, &usd) in prices.iter().enumerate() {
+            history.push(PriceSample {
+                timestamp: 1_700_000_000 + i as u64 * 60,
+                prices: HashMap::from([("usd".to_string(), usd)]),
+            });
+        }
+        history
+    }
+
+    #[test]
+    fn channels_are_configured_on_their_own_tabs() {
+        let mut page = temp_page();
+        let history = PriceHistory::default();
+        let _ = page.update(AlertsMessage::TabSelected(AlertsTab::Channel(
+            Channel::Webhook,
+        )));
+        {
+            let mut ui = simulator(page.view(&history, None));
+            assert!(ui.find("Send alerts by Webhook").is_ok());
+            assert!(ui.find("Send test").is_ok());
+            assert_snapshot(&mut ui, &iced::Theme::Nord, "alerts_page");
+        }
+
+        let _ = page.update(AlertsMessage::TabSelected(AlertsTab::Channel(
+            Channel::Email,
+        )));
+        let _ = page.update(AlertsMessage::SecuritySelected(SmtpSecurity::Tls));
+        assert_eq!(
+            page.smtp_port, "465",
+            "default port follows the security mode"
+        );
+        let _ = page.update(AlertsMessage::FieldChanged(Field::SmtpPort, "2525".into()));
+        let _ = page.update(AlertsMessage::SecuritySelected(SmtpSecurity::StartTls));
+        assert_eq!(page.smtp_port, "2525", "a typed port is kept");
+
+        let _ = page.update(AlertsMessage::FieldChanged(Field::SmtpPort, "smtp".into()));
+        let _ = page.update(AlertsMessage::SendTest);
+        let mut ui = simulator(page.view(&history, None));
+        assert!(ui
+            .find("Test failed: SMTP port must be a number from 1 to 65535")
+            .is_ok());
+    }
+
+    #[test]
+    fn saved_settings_are_loaded_again() {
+        let mut page = temp_page();
+        let _ = page.update(AlertsMessage::TabSelected(AlertsTab::Channel(
+            Channel::Ntfy,
+        )));
+        let _ = page.update(AlertsMessage::EnabledToggled(true));
+        let _ = page.update(AlertsMessage::FieldChanged(
+            Field::NtfyTopic,
+            "btc-alerts".into(),
+        ));
+        let _ = page.update(AlertsMessage::PresetSelected(WebhookPreset::Teams));
+        let _ = page.update(AlertsMessage::Save);
+        assert!(page.save_status.as_deref().unwrap().starts_with("Saved to"));
+
+        let reloaded = AlertsPage::with(page.file.clone(), page.rules_file.clone());
+        assert!(reloaded.notifiers.ntfy.enabled);
+        assert_eq!(reloaded.notifiers.ntfy.topic, "btc-alerts");
+        assert_eq!(
+            WebhookPreset::matching(&reloaded.notifiers.webhook.template),
+            Some(WebhookPreset::Teams)
+        );
+        let _ = std::fs::remove_dir_all(page.file.path().parent().unwrap());
+    }
+
+    #[test]
+    fn rules_are_checked_as_typed_and_previewed() {
+        let mut page = temp_page();
+        let history = history(&[99_000.0, 101_000.0, 98_000.0, 102_000.0]);
+
+        let _ = page.update(AlertsMessage::NewRuleChanged("usd >".into()));
+        {
+            let mut ui = simulator(page.view(&history, None));
+            assert!(ui.find("usd >\n     ^ expected a value after `>`").is_ok());
+        }
+        let _ = page.update(AlertsMessage::AddRule);
+        assert!(
+            page.rules.is_empty(),
+            "a rule that does not check is not added"
+        );
+
+        let _ = page.update(AlertsMessage::NewRuleChanged("usd > 100000".into()));
+        let _ = page.update(AlertsMessage::AddRule);
+        assert_eq!(page.rules, ["usd > 100000"]);
+        assert!(page.new_rule.is_empty());
+        {
+            let mut ui = simulator(page.view(&history, None));
+            assert!(ui
+                .find(
+                    "Would have fired 2 times since 2023-11-14 22:13 UTC, \
+                     last at 2023-11-14 22:16, 2023-11-14 22:14"
+                )
+                .is_ok());
+            assert_snapshot(&mut ui, &iced::Theme::Nord, "alerts_rules");
+        }
+
+        let _ = page.update(AlertsMessage::RuleChanged(0, "usd > 100000 for".into()));
+        {
+            let mut ui = simulator(page.view(&history, None));
+            assert!(ui
+                .find("usd > 100000 for\n             ^^^ `for` needs a duration after it, such as 10m")
+                .is_ok());
+        }
+        let _ = page.update(AlertsMessage::SaveRules);
+        let reloaded = AlertsPage::with(page.file.clone(), page.rules_file.clone());
+        assert_eq!(
+            reloaded.rules,
+            ["usd > 100000 for"],
+            "kept to be fixed later"
+        );
+        let _ = std::fs::remove_dir_all(page.file.path().parent().unwrap());
+    }
+
+    #[test]
+    fn a_firing_alerts_enabled_channels_once() {
+        let mut page = temp_page();
+        let _ = page.update(AlertsMessage::NewRuleChanged("usd > 100000".into()));
+        let _ = page.update(AlertsMessage::AddRule);
+        let fired = history(&[99_000.0, 101_000.0]);
+
+        assert_eq!(
+            page.check(&fired, None, true).units(),
+            0,
+            "no channel enabled"
+        );
+        let _ = page.update(AlertsMessage::TabSelected(AlertsTab::Channel(
+            Channel::Ntfy,
+        )));
+        let _ = page.update(AlertsMessage::EnabledToggled(true));
+        let _ = page.update(AlertsMessage::TabSelected(AlertsTab::Channel(
+            Channel::Gotify,
+        )));
+        let _ = page.update(AlertsMessage::EnabledToggled(true));
+        assert_eq!(
+            page.check(&fired, None, true).units(),
+            0,
+            "enabled but not saved yet"
+        );
+        let _ = page.update(AlertsMessage::Save);
+        assert_eq!(
+            page.check(&fired, None, false).units(),
+            0,
+            "alerts off in Settings"
+        );
+
+        assert_eq!(page.check(&fired, None, true).units(), 2, "one per channel");
+        assert_eq!(page.check(&fired, None, true).units(), 0, "already alerted");
+        let still_above = history(&[99_000.0, 101_000.0, 102_000.0]);
+        assert_eq!(
+            page.check(&still_above, None, true).units(),
+            0,
+            "not a new firing"
+        );
+
+        // Unsaved edits do not reach live alerts
+        let _ = page.update(AlertsMessage::EnabledToggled(false));
+        let fired_again = history(&[99_000.0, 101_000.0, 99_000.0, 101_000.0]);
+        assert_eq!(page.check(&fired_again, None, true).units(), 2);
+    }
+}
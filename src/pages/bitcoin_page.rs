@@ -1,13 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use iced::task;
 use iced::widget::svg;
 use iced::Task;
+use tracing::{debug, warn};
 
-use crate::api;
+use crate::api::{self, ApiError};
 use crate::country::{get_countries, Country, CountryPrice};
+use crate::fx_matrix::FxMatrix;
+use crate::history::PriceHistory;
 use crate::http_utils::download_svgs_to_memory;
 use crate::message::BitcoinMessage;
-use crate::views::bitcoin_view::BitcoinView;
+use crate::store;
+use crate::views::bitcoin_view::{self, BitcoinView, Freshness};
+use crate::views::error_panel::ErrorPanel;
 
 /// State for the Bitcoin price page
 pub struct BitcoinPage {
@@ -19,114 +25,557 @@ pub struct BitcoinPage {
     svg_map: HashMap<String, svg::Handle>,
     /// Loading state indicator
     is_loading: bool,
-    /// Error message if any operation fails
-    error_message: Option<String>,
+    /// Error of the latest failed fetch, if any
+    error: Option<ApiError>,
+    /// Whether the error panel's technical details are expanded
+    error_expanded: bool,
+    /// Currencies CoinGecko supports, once known; explains missing prices
+    supported_currencies: Option<HashSet<String>>,
+    /// When the displayed prices were fetched (Unix seconds)
+    fetched_at: Option<u64>,
+    /// Provider's own quote time for the displayed prices (Unix seconds)
+    provider_updated_at: Option<u64>,
+    /// Wall clock used for relative ages, advanced by `Clock` messages
+    now: u64,
+    /// Currency filter as typed
+    search: String,
+    /// Samples recorded this session; feeds the sparklines and the chart
+    history: PriceHistory,
+    /// Cross rates of the latest fetch and the one before it
+    cross_rates: Option<FxMatrix>,
+    previous_cross_rates: Option<FxMatrix>,
+    /// Generation of the latest fetch; responses tagged with an older one are dropped
+    fetch_generation: u64,
+    /// Abort handle of the in-flight fetch, if any
+    in_flight: Option<task::Handle>,
 }
 
 impl BitcoinPage {
     /// Creates a new Bitcoin page and kicks off both SVG download AND initial price fetch
     pub fn new() -> (Self, Task<BitcoinMessage>) {
-        let countries = get_countries();
+        let mut page = Self {
+            price_usd: 0.0,
+            vs_currencies: Vec::new(),
+            svg_map: HashMap::new(),
+            is_loading: true,
+            error: None,
+            error_expanded: false,
+            supported_currencies: None,
+            fetched_at: None,
+            provider_updated_at: None,
+            now: 0,
+            search: String::new(),
+            history: PriceHistory::default(),
+            cross_rates: None,
+            previous_cross_rates: None,
+            fetch_generation: 0,
+            in_flight: None,
+        };
+        let svg_task = page.load_missing_flags();
+        let supported_task = page.load_supported_currencies();
+        let price_task = page.start_fetch();
 
-        let codes: Vec<String> = countries.iter().map(|c| c.country_code.to_string()).collect();
-        let flags: Vec<String> = countries.iter().map(|c| c.flag_url.clone()).collect();
-        let currencies: Vec<String> = countries.iter().map(|c| c.currency.to_string()).collect();
-
-        let svg_task = Task::perform(
-            download_svgs_to_memory(codes, flags),
-            BitcoinMessage::SvgLoaded,
-        );
-
-        let price_task = Task::perform(
-            Self::fetch_prices(countries, currencies),
-            |result| result,
-        );
-
-        (
-            Self {
-                price_usd: 0.0,
-                vs_currencies: Vec::new(),
-                svg_map: HashMap::new(),
-                is_loading: true,
-                error_message: None,
-            },
-            Task::batch([svg_task, price_task]),
-        )
+        (page, Task::batch([svg_task, supported_task, price_task]))
     }
 
     /// Updates the page state based on received messages
     pub fn update(&mut self, message: BitcoinMessage) -> Task<BitcoinMessage> {
         match message {
-            BitcoinMessage::Refetch => {
-                self.is_loading = true;
-                self.error_message = None;
-
-                let countries = get_countries();
-                let currencies: Vec<String> =
-                    countries.iter().map(|c| c.currency.to_string()).collect();
-
-                Task::perform(Self::fetch_prices(countries, currencies), |r| r)
+            BitcoinMessage::Refetch => self.start_fetch(),
+            BitcoinMessage::Resume => {
+                let flags = self.load_missing_flags();
+                let supported = self.load_supported_currencies();
+                Task::batch([self.start_fetch(), flags, supported])
+            }
+            BitcoinMessage::Tick => {
+                if self.in_flight.is_some() {
+                    debug!(
+                        "Skipping scheduled refresh: fetch {} still in flight",
+                        self.fetch_generation
+                    );
+                    return Task::none();
+                }
+                self.start_fetch()
             }
-            BitcoinMessage::CurrentPrice((usd, prices)) => {
-                self.price_usd = usd;
-                self.vs_currencies = prices;
+            BitcoinMessage::CurrentPrice(generation, _) | BitcoinMessage::Error(generation, _)
+                if generation != self.fetch_generation =>
+            {
+                debug!(
+                    "Dropping stale response from fetch {generation} (latest is {})",
+                    self.fetch_generation
+                );
+                Task::none()
+            }
+            BitcoinMessage::CurrentPrice(_, snapshot) => {
+                self.in_flight = None;
+                store::record_success(&snapshot);
+                self.history.push(snapshot.to_sample());
+                self.previous_cross_rates =
+                    self.cross_rates.replace(snapshot.cross_rates.clone());
+                self.now = self.now.max(snapshot.fetched_at);
+                self.fetched_at = Some(snapshot.fetched_at);
+                self.provider_updated_at = snapshot.provider_updated_at;
+                self.price_usd = snapshot.usd;
+                self.vs_currencies = snapshot.prices;
                 self.is_loading = false;
-                self.error_message = None;
+                self.error = None;
+                Task::none()
+            }
+            BitcoinMessage::Clock(now) => {
+                self.now = now;
                 Task::none()
             }
             BitcoinMessage::SvgLoaded(raw_svg_map) => {
-                self.svg_map = raw_svg_map
-                    .into_iter()
-                    .map(|(code, data)| (code, svg::Handle::from_memory(data)))
-                    .collect();
+                self.svg_map.extend(
+                    raw_svg_map
+                        .into_iter()
+                        .map(|(code, data)| (code, svg::Handle::from_memory(data))),
+                );
                 // Keep loading true if prices haven't arrived yet
                 if self.price_usd > 0.0 {
                     self.is_loading = false;
                 }
                 Task::none()
             }
-            BitcoinMessage::Error(error) => {
-                self.error_message = Some(error);
+            BitcoinMessage::Error(_, error) => {
+                self.in_flight = None;
+                store::record_failure(format!("Failed to fetch prices: {error}"));
+                self.error = Some(error);
                 self.is_loading = false;
                 Task::none()
             }
+            BitcoinMessage::SupportedCurrencies(Ok(supported)) => {
+                for country in api::unsupported_countries(get_countries(), &supported) {
+                    warn!(
+                        "CoinGecko does not support {} ({}); its price will show as N/A",
+                        country.currency, country.name
+                    );
+                }
+                self.supported_currencies = Some(supported);
+                Task::none()
+            }
+            BitcoinMessage::SupportedCurrencies(Err(e)) => {
+                // Not retried per tick: while rate limited or offline that
+                // would double the requests. Resume asks again.
+                warn!("Could not fetch supported currencies, requesting all: {e}");
+                Task::none()
+            }
+            BitcoinMessage::ToggleErrorDetails => {
+                self.error_expanded = !self.error_expanded;
+                Task::none()
+            }
+            BitcoinMessage::CopyDiagnostics => match &self.error {
+                Some(error) => iced::clipboard::write(error.diagnostics()),
+                None => Task::none(),
+            },
+            BitcoinMessage::SearchChanged(search) => {
+                self.search = search;
+                Task::none()
+            }
+            BitcoinMessage::FocusSearch => {
+                iced::widget::operation::focus(bitcoin_view::SEARCH_INPUT)
+            }
+        }
+    }
+
+    /// Whether `message` answers the latest fetch; replies to older ones are
+    /// dropped by [`update`](Self::update)
+    pub fn is_current(&self, message: &BitcoinMessage) -> bool {
+        match message {
+            BitcoinMessage::CurrentPrice(generation, _) | BitcoinMessage::Error(generation, _) => {
+                *generation == self.fetch_generation
+            }
+            _ => true,
         }
     }
 
-    /// Renders the page view
-    pub fn view(&self) -> iced::Element<'_, BitcoinMessage> {
+    /// Cross rates of the latest fetch, and of the one before for changes
+    pub fn cross_rates(&self) -> (Option<&FxMatrix>, Option<&FxMatrix>) {
+        (self.cross_rates.as_ref(), self.previous_cross_rates.as_ref())
+    }
+
+    /// Prices recorded since startup, oldest first
+    pub fn history(&self) -> &PriceHistory {
+        &self.history
+    }
+
+    /// Whether there are prices whose age is worth ticking a clock for
+    pub fn has_data(&self) -> bool {
+        self.fetched_at.is_some()
+    }
+
+    /// Renders the page view; data older than `stale_after` seconds is flagged.
+    /// While `offline` the app shows a banner instead of the error panel.
+    pub fn view(&self, stale_after: u64, offline: bool) -> iced::Element<'_, BitcoinMessage> {
         BitcoinView::new(
             self.price_usd,
             &self.vs_currencies,
             &self.svg_map,
             self.is_loading,
-            self.error_message.as_deref(),
+            self.error
+                .as_ref()
+                .filter(|_| !offline)
+                .map(|e| ErrorPanel::new(e, self.error_expanded, !self.is_loading)),
+            self.supported_currencies.as_ref(),
+            Freshness {
+                fetched_at: self.fetched_at,
+                provider_updated_at: self.provider_updated_at,
+                now: self.now,
+                stale_after,
+            },
         )
+        .search(&self.search)
+        .history(&self.history)
         .view()
     }
 
     // ── Private helpers ─────────────────────────────────────────
 
-    async fn fetch_prices(
-        countries: &'static [Country],
-        currencies: Vec<String>,
-    ) -> BitcoinMessage {
-        match api::fetch_btc(currencies).await {
-            Ok((usd, response)) => {
-                let prices = countries
-                    .iter()
-                    .map(|c| {
-                        let price = response
-                            .bitcoin
-                            .get(c.currency)
-                            .copied()
-                            .unwrap_or(0.0);
-                        CountryPrice::new(c.clone(), price)
-                    })
-                    .collect();
-                BitcoinMessage::CurrentPrice((usd, prices))
+    /// Aborts any in-flight fetch and starts a new one under the next generation
+    fn start_fetch(&mut self) -> Task<BitcoinMessage> {
+        if let Some(handle) = self.in_flight.take() {
+            debug!("Aborting fetch {}", self.fetch_generation);
+            handle.abort();
+        }
+
+        self.fetch_generation += 1;
+        self.is_loading = true;
+        self.error = None;
+
+        let (task, handle) =
+            Task::perform(Self::fetch_prices(get_countries(), self.fetch_generation), |r| r)
+                .abortable();
+        self.in_flight = Some(handle);
+        task
+    }
+
+    /// Fetches the provider's currency list unless it is already known
+    fn load_supported_currencies(&self) -> Task<BitcoinMessage> {
+        if self.supported_currencies.is_some() {
+            return Task::none();
+        }
+        Task::perform(
+            api::fetch_supported_vs_currencies(),
+            BitcoinMessage::SupportedCurrencies,
+        )
+    }
+
+    /// Downloads the flags not loaded yet (all of them on first call)
+    fn load_missing_flags(&self) -> Task<BitcoinMessage> {
+        let (codes, flags): (Vec<String>, Vec<String>) = get_countries()
+            .iter()
+            .filter(|c| !self.svg_map.contains_key(&c.country_code))
+            .map(|c| (c.country_code.clone(), c.flag_url.clone()))
+            .unzip();
+        if codes.is_empty() {
+            return Task::none();
+        }
+
+        Task::perform(
+            download_svgs_to_memory(codes, flags),
+            BitcoinMessage::SvgLoaded,
+        )
+    }
+
+    async fn fetch_prices(countries: &'static [Country], generation: u64) -> BitcoinMessage {
+        match api::fetch_country_prices(countries).await {
+            Ok(snapshot) => BitcoinMessage::CurrentPrice(generation, snapshot),
+            Err(e) => BitcoinMessage::Error(generation, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Snapshot;
+    use crate::testing::{assert_snapshot, sample_snapshot, simulator};
+
+    const DEFAULT_STALE_AFTER: u64 = 90;
+
+    fn offline() -> ApiError {
+        ApiError::NetworkError {
+            url: "https://api.coingecko.com/api/v3/simple/price".into(),
+            message: "error sending request".into(),
+            unreachable: true,
+        }
+    }
+
+    fn rate_limited() -> ApiError {
+        ApiError::HttpStatus {
+            url: "https://api.coingecko.com/api/v3/simple/price".into(),
+            status: 429,
+            body: "You've exceeded the Rate Limit.".into(),
+        }
+    }
+
+    #[test]
+    fn refetch_sets_loading_and_clears_error() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::Error(page.fetch_generation, offline()));
+        assert!(!page.is_loading);
+
+        let _ = page.update(BitcoinMessage::Refetch);
+        assert!(page.is_loading);
+        assert!(page.error.is_none());
+    }
+
+    #[test]
+    fn current_price_renders_table() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::CurrentPrice(page.fetch_generation, sample_snapshot()));
+        assert!(!page.is_loading);
+        assert_eq!(page.price_usd, 65497.0);
+
+        let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+        assert!(ui.find("United Kingdom").is_ok());
+        assert!(ui.find("GBP").is_ok());
+        assert!(ui.find("51234.00").is_ok());
+        assert!(ui.find("9876543.21").is_ok());
+    }
+
+    #[test]
+    fn error_is_rendered_with_category() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::Error(page.fetch_generation, offline()));
+
+        let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+        assert!(ui.find("You appear to be offline").is_ok());
+        // Technical details stay collapsed until asked for
+        assert!(ui.find("Network error: error sending request").is_err());
+    }
+
+    #[test]
+    fn error_panel_retries_and_expands_details() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::Error(page.fetch_generation, rate_limited()));
+
+        {
+            let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+            assert!(ui.find("Rate limited by CoinGecko").is_ok());
+            ui.click("Retry").expect("retry button");
+            ui.click("Show details").expect("details toggle");
+            let messages: Vec<_> = ui.into_messages().collect();
+            assert!(matches!(
+                messages.as_slice(),
+                [BitcoinMessage::Refetch, BitcoinMessage::ToggleErrorDetails]
+            ));
+        }
+
+        let _ = page.update(BitcoinMessage::ToggleErrorDetails);
+        let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+        assert!(ui.find("Hide details").is_ok());
+        assert!(ui.find("Status: 429").is_ok());
+        assert!(ui
+            .find("URL: https://api.coingecko.com/api/v3/simple/price")
+            .is_ok());
+        assert!(ui.find("Response: You've exceeded the Rate Limit.").is_ok());
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "bitcoin_page_error");
+    }
+
+    #[test]
+    fn missing_prices_render_as_not_available() {
+        let (mut page, _) = BitcoinPage::new();
+        let mut snapshot = sample_snapshot();
+        snapshot.prices[1].price = None;
+        let _ = page.update(BitcoinMessage::CurrentPrice(1, snapshot));
+
+        let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+        assert!(ui.find("N/A").is_ok());
+        assert!(ui.find("0.00").is_err(), "a missing quote must not look like a price");
+    }
+
+    #[test]
+    fn sparklines_follow_recent_fetches() {
+        let (mut page, _) = BitcoinPage::new();
+        for (i, factor) in [1.0, 1.02, 0.99, 1.05].into_iter().enumerate() {
+            if i > 0 {
+                let _ = page.update(BitcoinMessage::Refetch);
             }
-            Err(e) => BitcoinMessage::Error(format!("Failed to fetch prices: {e}")),
+            let mut snapshot = sample_snapshot();
+            snapshot.fetched_at += i as u64 * 60;
+            snapshot.usd *= factor;
+            for p in &mut snapshot.prices {
+                // GBP falls while the others rise
+                let f = if p.country.currency == "gbp" { 2.0 - factor } else { factor };
+                p.price = p.price.map(|price| price * f);
+            }
+            let _ = page.update(BitcoinMessage::CurrentPrice(page.fetch_generation, snapshot));
+        }
+
+        assert_eq!(page.history.series("gbp", 100).len(), 4);
+        let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+        assert!(ui.find("Trend").is_ok());
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "bitcoin_page_trends");
+    }
+
+    #[test]
+    fn copy_diagnostics_writes_clipboard_only_with_an_error() {
+        let (mut page, _) = BitcoinPage::new();
+        assert_eq!(page.update(BitcoinMessage::CopyDiagnostics).units(), 0);
+
+        let _ = page.update(BitcoinMessage::Error(page.fetch_generation, rate_limited()));
+        assert_eq!(page.update(BitcoinMessage::CopyDiagnostics).units(), 1);
+    }
+
+    #[test]
+    fn error_keeps_last_good_prices_visible() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::CurrentPrice(1, sample_snapshot()));
+        let _ = page.update(BitcoinMessage::Refetch);
+        let _ = page.update(BitcoinMessage::Error(2, offline()));
+
+        let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+        assert!(ui.find("You appear to be offline").is_ok());
+        assert!(ui.find("United Kingdom").is_ok());
+        assert!(ui.find("51234.00").is_ok());
+    }
+
+    #[test]
+    fn status_bar_shows_age_and_flags_stale_data() {
+        let (mut page, _) = BitcoinPage::new();
+        let snapshot = sample_snapshot();
+        let fetched_at = snapshot.fetched_at;
+        let _ = page.update(BitcoinMessage::CurrentPrice(1, snapshot));
+
+        let _ = page.update(BitcoinMessage::Clock(fetched_at + 42));
+        {
+            let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+            assert!(ui.find("Updated 42s ago").is_ok());
+            assert!(ui.find("Stale").is_err());
         }
+
+        let _ = page.update(BitcoinMessage::Clock(fetched_at + 600));
+        let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+        assert!(ui.find("Updated 10m ago").is_ok());
+        assert!(ui.find("Stale").is_ok());
+    }
+
+    #[test]
+    fn fetch_button_emits_refetch() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::CurrentPrice(page.fetch_generation, sample_snapshot()));
+
+        let mut ui = simulator(page.view(DEFAULT_STALE_AFTER, false));
+        ui.click("Fetch Current Price").expect("fetch button");
+        let messages: Vec<_> = ui.into_messages().collect();
+        assert!(matches!(messages.as_slice(), [BitcoinMessage::Refetch]));
+    }
+
+    #[test]
+    fn resume_downloads_only_missing_flags() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::SupportedCurrencies(Ok(HashSet::from([
+            "usd".to_string(),
+        ]))));
+        let _ = page.update(BitcoinMessage::CurrentPrice(1, sample_snapshot()));
+        assert_eq!(page.update(BitcoinMessage::Resume).units(), 2);
+
+        let _ = page.update(BitcoinMessage::CurrentPrice(2, sample_snapshot()));
+        let all_flags = get_countries()
+            .iter()
+            .map(|c| (c.country_code.to_string(), b"<svg/>".to_vec()))
+            .collect();
+        let _ = page.update(BitcoinMessage::SvgLoaded(all_flags));
+        assert_eq!(page.update(BitcoinMessage::Resume).units(), 1);
+        assert_eq!(page.fetch_generation, 3);
+    }
+
+    #[test]
+    fn currency_list_is_retried_on_resume_not_per_tick() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::SupportedCurrencies(Err(rate_limited())));
+        let _ = page.update(BitcoinMessage::CurrentPrice(1, sample_snapshot()));
+        assert_eq!(page.update(BitcoinMessage::Tick).units(), 1, "prices only");
+        assert_eq!(
+            page.update(BitcoinMessage::Resume).units(),
+            3,
+            "prices, flags and the currency list"
+        );
+    }
+
+    fn older_prices() -> Snapshot {
+        let mut snapshot = sample_snapshot();
+        snapshot.usd /= 2.0;
+        for p in &mut snapshot.prices {
+            p.price = p.price.map(|price| price / 2.0);
+        }
+        snapshot
+    }
+
+    #[test]
+    fn manual_refetch_aborts_previous_fetch() {
+        let (mut page, _) = BitcoinPage::new();
+        let first = page.in_flight.as_ref().unwrap().clone();
+        assert!(!first.is_aborted());
+
+        let task = page.update(BitcoinMessage::Refetch);
+        assert_eq!(task.units(), 1);
+        assert!(first.is_aborted());
+        assert_eq!(page.fetch_generation, 2);
+        assert!(!page.in_flight.as_ref().unwrap().is_aborted());
+    }
+
+    #[test]
+    fn tick_is_skipped_while_fetch_is_slow() {
+        let (mut page, _) = BitcoinPage::new();
+        let first = page.in_flight.as_ref().unwrap().clone();
+
+        // The initial fetch has not answered yet: ticks must not pile up requests
+        for _ in 0..3 {
+            let task = page.update(BitcoinMessage::Tick);
+            assert_eq!(task.units(), 0);
+        }
+        assert_eq!(page.fetch_generation, 1);
+        assert!(!first.is_aborted());
+
+        // Once it answers, the next tick fetches again
+        let _ = page.update(BitcoinMessage::CurrentPrice(1, sample_snapshot()));
+        assert!(page.in_flight.is_none());
+        let task = page.update(BitcoinMessage::Tick);
+        assert_eq!(task.units(), 1);
+        assert_eq!(page.fetch_generation, 2);
+    }
+
+    #[test]
+    fn reordered_responses_keep_newest_data() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::Refetch);
+        assert_eq!(page.fetch_generation, 2);
+
+        // Newest answer arrives first, then the slow, superseded one
+        let _ = page.update(BitcoinMessage::CurrentPrice(2, sample_snapshot()));
+        let _ = page.update(BitcoinMessage::CurrentPrice(1, older_prices()));
+        let _ = page.update(BitcoinMessage::Error(1, offline()));
+
+        assert_eq!(page.price_usd, 65497.0);
+        assert!(page.error.is_none());
+        assert!(!page.is_loading);
+    }
+
+    #[test]
+    fn cross_rates_keep_the_previous_fetch() {
+        let (mut page, _) = BitcoinPage::new();
+        assert_eq!(page.cross_rates(), (None, None));
+
+        let _ = page.update(BitcoinMessage::CurrentPrice(1, sample_snapshot()));
+        let (current, previous) = page.cross_rates();
+        assert_eq!(current.unwrap().rate("usd", "gbp"), Some(51234.0 / 65497.0));
+        assert!(previous.is_none());
+
+        let _ = page.update(BitcoinMessage::Refetch);
+        let _ = page.update(BitcoinMessage::CurrentPrice(2, sample_snapshot()));
+        assert!(page.cross_rates().1.is_some());
+    }
+
+    #[test]
+    fn stale_response_does_not_end_newer_fetch() {
+        let (mut page, _) = BitcoinPage::new();
+        let _ = page.update(BitcoinMessage::Refetch);
+
+        let _ = page.update(BitcoinMessage::CurrentPrice(1, older_prices()));
+        assert!(page.is_loading);
+        assert!(page.in_flight.is_some());
+        assert_eq!(page.price_usd, 0.0);
     }
 }
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+
+use iced::Task;
+
+use crate::api::{self, ApiError, Candle};
+use crate::candles;
+use crate::history::PriceHistory;
+use crate::message::CandleMessage;
+use crate::pages::chart_page::chart_currencies;
+use crate::views::candle_view::{CandleProps, CandleView};
+use crate::views::price_chart::CandleChart;
+
+/// Candle width, and whether candles come from recorded prices or CoinGecko
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CandleInterval {
+    /// One-minute candles from prices recorded this session
+    Minute,
+    #[default]
+    FiveMinutes,
+    FifteenMinutes,
+    Hour,
+    /// CoinGecko 30-minute candles over the last day
+    Day,
+    /// CoinGecko 4-hour candles over the last 30 days
+    Month,
+    /// CoinGecko 4-day candles over the last year
+    Year,
+}
+
+impl CandleInterval {
+    /// Intervals built from recorded prices
+    pub const RECORDED: [CandleInterval; 4] = [
+        CandleInterval::Minute,
+        CandleInterval::FiveMinutes,
+        CandleInterval::FifteenMinutes,
+        CandleInterval::Hour,
+    ];
+
+    /// Intervals fetched from CoinGecko
+    pub const COINGECKO: [CandleInterval; 3] = [
+        CandleInterval::Day,
+        CandleInterval::Month,
+        CandleInterval::Year,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CandleInterval::Minute => "1m",
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::FifteenMinutes => "15m",
+            CandleInterval::Hour => "1h",
+            CandleInterval::Day => "30m · 1 day",
+            CandleInterval::Month => "4h · 30 days",
+            CandleInterval::Year => "4d · 1 year",
+        }
+    }
+
+    /// Candle width in seconds when aggregated from recorded prices
+    pub fn recorded_seconds(&self) -> Option<u64> {
+        match self {
+            CandleInterval::Minute => Some(60),
+            CandleInterval::FiveMinutes => Some(300),
+            CandleInterval::FifteenMinutes => Some(900),
+            CandleInterval::Hour => Some(3_600),
+            _ => None,
+        }
+    }
+
+    /// `days` requested from CoinGecko, which picks the matching candle width
+    pub fn coingecko_days(&self) -> Option<u32> {
+        match self {
+            CandleInterval::Day => Some(1),
+            CandleInterval::Month => Some(30),
+            CandleInterval::Year => Some(365),
+            _ => None,
+        }
+    }
+}
+
+/// Candles page state: the selected interval and CoinGecko candles fetched so far
+pub struct CandlePage {
+    interval: CandleInterval,
+    /// Lowercase currency code
+    currency: String,
+    /// CoinGecko candles per interval and currency, fetched on first use
+    fetched: HashMap<(CandleInterval, String), Vec<Candle>>,
+    /// Interval and currency whose candles are being fetched
+    loading: Option<(CandleInterval, String)>,
+    error: Option<ApiError>,
+}
+
+impl Default for CandlePage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CandlePage {
+    pub fn new() -> Self {
+        Self {
+            interval: CandleInterval::default(),
+            currency: "usd".to_string(),
+            fetched: HashMap::new(),
+            loading: None,
+            error: None,
+        }
+    }
+
+    pub fn update(&mut self, message: CandleMessage) -> Task<CandleMessage> {
+        match message {
+            CandleMessage::IntervalSelected(interval) => {
+                self.interval = interval;
+                self.load()
+            }
+            CandleMessage::CurrencySelected(currency) => {
+                self.currency = currency.to_lowercase();
+                self.load()
+            }
+            CandleMessage::Loaded(interval, currency, candles) => {
+                let key = (interval, currency);
+                if self.loading.as_ref() == Some(&key) {
+                    self.loading = None;
+                }
+                self.error = None;
+                self.fetched.insert(key, candles);
+                self.load()
+            }
+            CandleMessage::Failed(error) => {
+                self.loading = None;
+                self.error = Some(error);
+                Task::none()
+            }
+        }
+    }
+
+    /// Fetches CoinGecko candles for the selection unless they are cached,
+    /// already on the way or built from recorded prices
+    fn load(&mut self) -> Task<CandleMessage> {
+        let Some(days) = self.interval.coingecko_days() else {
+            return Task::none();
+        };
+        let key = (self.interval, self.currency.clone());
+        if self.fetched.contains_key(&key) || self.loading.is_some() {
+            return Task::none();
+        }
+        self.loading = Some(key);
+        self.error = None;
+        Task::perform(
+            Self::fetch(self.interval, self.currency.clone(), days),
+            |m| m,
+        )
+    }
+
+    async fn fetch(interval: CandleInterval, currency: String, days: u32) -> CandleMessage {
+        match api::fetch_ohlc(currency.clone(), days).await {
+            Ok(candles) => CandleMessage::Loaded(interval, currency, candles),
+            Err(e) => CandleMessage::Failed(e),
+        }
+    }
+
+    /// Renders candles aggregated from `session` (prices recorded since
+    /// startup) or fetched from CoinGecko
+    pub fn view<'a>(&'a self, session: &PriceHistory) -> iced::Element<'a, CandleMessage> {
+        let candles = match self.interval.recorded_seconds() {
+            Some(seconds) => {
+                candles::aggregate(&session.series(&self.currency, usize::MAX), seconds)
+            }
+            None => self
+                .fetched
+                .get(&(self.interval, self.currency.clone()))
+                .cloned()
+                .unwrap_or_default(),
+        };
+
+        let recorded = self.interval.recorded_seconds().is_some();
+        let status = match (&self.error, &self.loading) {
+            (Some(e), _) if !recorded => Some(format!("Could not load candles: {e}")),
+            (_, Some(_)) if !recorded => Some("Loading candles…".to_string()),
+            _ if candles.is_empty() => Some(if recorded {
+                "No prices recorded yet; candles fill in with every fetch".to_string()
+            } else {
+                "No candles".to_string()
+            }),
+            _ => None,
+        };
+
+        CandleView::new(CandleProps {
+            interval: self.interval,
+            currency: self.currency.to_uppercase(),
+            currencies: chart_currencies(),
+            status,
+            count: candles.len(),
+            chart: CandleChart { candles },
+        })
+        .view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::PriceSample;
+    use crate::testing::{assert_snapshot, simulator, simulator_with_size};
+
+    /// Thirty made-up 4-hour candles drifting up, with growing volume
+    fn coingecko_candles() -> Vec<Candle> {
+        let mut close = 64_000.0;
+        (0..30u64)
+            .map(|i| {
+                let open = close;
+                close = open + (i as f64 * 1.3).sin() * 600.0 + 80.0;
+                Candle {
+                    time: 1_711_000_000 + i * 14_400,
+                    open,
+                    high: open.max(close) + 250.0,
+                    low: open.min(close) - 200.0,
+                    close,
+                    volume: Some(2.0e10 + i as f64 * 1.5e8),
+                }
+            })
+            .collect()
+    }
+
+    fn session(prices: &[f64]) -> PriceHistory {
+        let mut history = PriceHistory::default();
+        for (i, price) in prices.iter().enumerate() {
+            history.push(PriceSample {
+                timestamp: 1_700_000_000 + i as u64 * 60,
+                prices: HashMap::from([("usd".to_string(), *price)]),
+            });
+        }
+        history
+    }
+
+    #[test]
+    fn recorded_prices_become_candles() {
+        let mut page = CandlePage::new();
+        {
+            let mut ui = simulator(page.view(&PriceHistory::default()));
+            assert!(ui
+                .find("No prices recorded yet; candles fill in with every fetch")
+                .is_ok());
+        }
+
+        // Ten one-minute samples starting 200s into a five-minute bucket
+        let history = session(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
+        {
+            let mut ui = simulator(page.view(&history));
+            assert!(ui.find("3 candles").is_ok());
+        }
+
+        assert_eq!(
+            page.update(CandleMessage::IntervalSelected(CandleInterval::Minute))
+                .units(),
+            0,
+            "recorded candles need no fetch"
+        );
+        let mut ui = simulator(page.view(&history));
+        assert!(ui.find("10 candles").is_ok());
+
+        ui.click("4h · 30 days").expect("interval button");
+        let messages: Vec<_> = ui.into_messages().collect();
+        assert!(matches!(
+            messages.as_slice(),
+            [CandleMessage::IntervalSelected(CandleInterval::Month)]
+        ));
+    }
+
+    #[test]
+    fn coingecko_candles_are_fetched_once_per_interval_and_currency() {
+        let mut page = CandlePage::new();
+        assert_eq!(
+            page.update(CandleMessage::IntervalSelected(CandleInterval::Month))
+                .units(),
+            1
+        );
+        {
+            let mut ui = simulator(page.view(&PriceHistory::default()));
+            assert!(ui.find("Loading candles…").is_ok());
+        }
+
+        let _ = page.update(CandleMessage::Loaded(
+            CandleInterval::Month,
+            "usd".into(),
+            coingecko_candles(),
+        ));
+        assert!(page.loading.is_none());
+        let _ = page.update(CandleMessage::IntervalSelected(CandleInterval::Hour));
+        assert_eq!(
+            page.update(CandleMessage::IntervalSelected(CandleInterval::Month))
+                .units(),
+            0,
+            "cached"
+        );
+        assert_eq!(
+            page.update(CandleMessage::IntervalSelected(CandleInterval::Year))
+                .units(),
+            1,
+            "another interval is another request"
+        );
+
+        let _ = page.update(CandleMessage::Failed(ApiError::HttpStatus {
+            url: String::new(),
+            status: 429,
+            body: String::new(),
+        }));
+        let mut ui = simulator(page.view(&PriceHistory::default()));
+        assert!(ui
+            .find("Could not load candles: HTTP 429 from CoinGecko")
+            .is_ok());
+    }
+
+    #[test]
+    fn candles_with_hover_readout_match_snapshot() {
+        let mut page = CandlePage::new();
+        let _ = page.update(CandleMessage::IntervalSelected(CandleInterval::Month));
+        let _ = page.update(CandleMessage::Loaded(
+            CandleInterval::Month,
+            "usd".into(),
+            coingecko_candles(),
+        ));
+
+        let mut ui = simulator_with_size(
+            iced::Size::new(900.0, 600.0),
+            page.view(&PriceHistory::default()),
+        );
+        assert!(ui.find("30 candles").is_ok());
+        ui.point_at(iced::Point::new(420.0, 300.0));
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "candle_page");
+    }
+}
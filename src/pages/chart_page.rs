@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+
+use iced::Task;
+
+use crate::api::{self, ApiError};
+use crate::country::get_countries;
+use crate::history::{PriceHistory, SeriesPoint};
+use crate::indicators;
+use crate::message::ChartMessage;
+use crate::views::chart_view::{ChartProps, ChartView};
+use crate::views::price_chart::{PaneChart, PriceChart};
+
+/// Days of daily prices requested from CoinGecko; enough for a 200-day average
+pub const DAILY_DAYS: u32 = 365;
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 86_400.0;
+
+/// Which prices the chart shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChartRange {
+    /// Prices recorded since startup, one per fetch
+    #[default]
+    Session,
+    /// One price per day over the last year, from CoinGecko
+    Daily,
+}
+
+impl ChartRange {
+    pub const ALL: [ChartRange; 2] = [ChartRange::Session, ChartRange::Daily];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChartRange::Session => "This session",
+            ChartRange::Daily => "1 year (daily)",
+        }
+    }
+}
+
+/// Indicator drawn over the price line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overlay {
+    Sma50,
+    Sma200,
+    Ema20,
+    Bollinger,
+}
+
+impl Overlay {
+    pub const ALL: [Overlay; 4] = [
+        Overlay::Sma50,
+        Overlay::Sma200,
+        Overlay::Ema20,
+        Overlay::Bollinger,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Overlay::Sma50 => "SMA 50",
+            Overlay::Sma200 => "SMA 200",
+            Overlay::Ema20 => "EMA 20",
+            Overlay::Bollinger => "Bollinger 20, 2σ",
+        }
+    }
+}
+
+/// Indicator drawn in its own pane under the price chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pane {
+    Rsi,
+    Macd,
+    Volatility,
+}
+
+impl Pane {
+    pub const ALL: [Pane; 3] = [Pane::Rsi, Pane::Macd, Pane::Volatility];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Pane::Rsi => "RSI 14",
+            Pane::Macd => "MACD 12/26/9",
+            Pane::Volatility => "Volatility 30 (annualised)",
+        }
+    }
+}
+
+/// Chart page state: what to plot and the daily prices fetched so far
+pub struct ChartPage {
+    range: ChartRange,
+    /// Lowercase currency code
+    currency: String,
+    overlays: Vec<Overlay>,
+    panes: Vec<Pane>,
+    /// Daily prices per currency, fetched on first use
+    daily: HashMap<String, Vec<SeriesPoint>>,
+    /// Currency whose daily prices are being fetched
+    loading: Option<String>,
+    error: Option<ApiError>,
+}
+
+impl Default for ChartPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChartPage {
+    pub fn new() -> Self {
+        Self {
+            range: ChartRange::default(),
+            currency: "usd".to_string(),
+            overlays: vec![Overlay::Sma50, Overlay::Sma200],
+            panes: vec![Pane::Rsi],
+            daily: HashMap::new(),
+            loading: None,
+            error: None,
+        }
+    }
+
+    pub fn update(&mut self, message: ChartMessage) -> Task<ChartMessage> {
+        match message {
+            ChartMessage::RangeSelected(range) => {
+                self.range = range;
+                self.load_daily()
+            }
+            ChartMessage::CurrencySelected(currency) => {
+                self.currency = currency.to_lowercase();
+                self.load_daily()
+            }
+            ChartMessage::OverlayToggled(overlay, on) => {
+                toggle(&mut self.overlays, overlay, on, &Overlay::ALL);
+                Task::none()
+            }
+            ChartMessage::PaneToggled(pane, on) => {
+                toggle(&mut self.panes, pane, on, &Pane::ALL);
+                Task::none()
+            }
+            ChartMessage::DailyLoaded(currency, series) => {
+                if self.loading.as_ref() == Some(&currency) {
+                    self.loading = None;
+                }
+                self.error = None;
+                self.daily.insert(currency, series);
+                self.load_daily()
+            }
+            ChartMessage::DailyFailed(error) => {
+                self.loading = None;
+                self.error = Some(error);
+                Task::none()
+            }
+        }
+    }
+
+    /// Fetches the selected currency's daily prices unless they are cached
+    /// or already on the way
+    fn load_daily(&mut self) -> Task<ChartMessage> {
+        if self.range != ChartRange::Daily
+            || self.daily.contains_key(&self.currency)
+            || self.loading.is_some()
+        {
+            return Task::none();
+        }
+        self.loading = Some(self.currency.clone());
+        self.error = None;
+        Task::perform(Self::fetch_daily(self.currency.clone()), |m| m)
+    }
+
+    async fn fetch_daily(currency: String) -> ChartMessage {
+        match api::fetch_daily_prices(currency.clone(), DAILY_DAYS).await {
+            Ok(series) => ChartMessage::DailyLoaded(currency, series),
+            Err(e) => ChartMessage::DailyFailed(e),
+        }
+    }
+
+    /// Renders the chart of `session` (prices recorded since startup) or of
+    /// the daily prices, with the enabled indicators
+    pub fn view<'a>(&'a self, session: &PriceHistory) -> iced::Element<'a, ChartMessage> {
+        let series = match self.range {
+            ChartRange::Session => session.series(&self.currency, usize::MAX),
+            ChartRange::Daily => self.daily.get(&self.currency).cloned().unwrap_or_default(),
+        };
+        let prices: Vec<f64> = series.iter().map(|p| p.price).collect();
+
+        let status = match (&self.error, &self.loading, self.range) {
+            (Some(e), _, ChartRange::Daily) => Some(format!("Could not load daily prices: {e}")),
+            (_, Some(_), ChartRange::Daily) => Some("Loading daily prices…".to_string()),
+            _ if prices.is_empty() => Some(match self.range {
+                ChartRange::Session => {
+                    "No prices recorded yet; the chart fills in with every fetch".to_string()
+                }
+                ChartRange::Daily => "No daily prices".to_string(),
+            }),
+            _ => None,
+        };
+
+        let panes = self
+            .panes
+            .iter()
+            .map(|pane| pane_chart(*pane, &prices, periods_per_year(&series)))
+            .collect();
+
+        ChartView::new(ChartProps {
+            range: self.range,
+            currency: self.currency.to_uppercase(),
+            currencies: chart_currencies(),
+            overlays: &self.overlays,
+            panes: &self.panes,
+            status,
+            points: prices.len(),
+            chart: price_chart(&self.overlays, prices),
+            pane_charts: panes,
+        })
+        .view()
+    }
+}
+
+/// Uppercase codes of every catalogue currency, sorted and deduplicated
+pub fn chart_currencies() -> Vec<String> {
+    let mut currencies: Vec<String> = get_countries()
+        .iter()
+        .map(|c| c.currency.to_uppercase())
+        .collect();
+    currencies.sort();
+    currencies.dedup();
+    currencies
+}
+
+/// Adds or removes `item`, keeping `all`'s order
+fn toggle<T: Copy + PartialEq>(items: &mut Vec<T>, item: T, on: bool, all: &[T]) {
+    items.retain(|i| *i != item);
+    if on {
+        items.push(item);
+    }
+    items.sort_by_key(|i| all.iter().position(|a| a == i));
+}
+
+fn price_chart(overlays: &[Overlay], prices: Vec<f64>) -> PriceChart {
+    let lines = overlays
+        .iter()
+        .filter_map(|overlay| {
+            let values = match overlay {
+                Overlay::Sma50 => indicators::sma(&prices, 50),
+                Overlay::Sma200 => indicators::sma(&prices, 200),
+                Overlay::Ema20 => indicators::ema(&prices, 20),
+                Overlay::Bollinger => return None,
+            };
+            Some((*overlay, values))
+        })
+        .collect();
+    let band = overlays
+        .contains(&Overlay::Bollinger)
+        .then(|| indicators::bollinger(&prices, 20, 2.0));
+    PriceChart {
+        prices,
+        lines,
+        band,
+    }
+}
+
+fn pane_chart(pane: Pane, prices: &[f64], periods_per_year: f64) -> PaneChart {
+    match pane {
+        Pane::Rsi => PaneChart::Rsi(indicators::rsi(prices, 14)),
+        Pane::Macd => PaneChart::Macd(indicators::macd(prices, 12, 26, 9)),
+        Pane::Volatility => PaneChart::Volatility(indicators::realised_volatility(
+            prices,
+            30,
+            periods_per_year,
+        )),
+    }
+}
+
+/// Samples per year implied by the median spacing of `series`, used to
+/// annualise volatility whatever the refresh interval
+pub fn periods_per_year(series: &[SeriesPoint]) -> f64 {
+    let mut gaps: Vec<u64> = series
+        .windows(2)
+        .map(|w| w[1].timestamp.saturating_sub(w[0].timestamp))
+        .filter(|gap| *gap > 0)
+        .collect();
+    if gaps.is_empty() {
+        return 0.0;
+    }
+    gaps.sort_unstable();
+    SECONDS_PER_YEAR / gaps[gaps.len() / 2] as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::PriceSample;
+    use crate::testing::{assert_snapshot, simulator, simulator_with_size};
+
+    /// A year of made-up daily closes: an uptrend with a slow swing
+    fn daily_series() -> Vec<SeriesPoint> {
+        (0..DAILY_DAYS as u64)
+            .map(|day| SeriesPoint {
+                timestamp: 1_700_000_000 + day * 86_400,
+                price: 40_000.0 + day as f64 * 60.0 + (day as f64 / 9.0).sin() * 3_000.0,
+            })
+            .collect()
+    }
+
+    fn session(prices: &[f64]) -> PriceHistory {
+        let mut history = PriceHistory::default();
+        for (i, price) in prices.iter().enumerate() {
+            history.push(PriceSample {
+                timestamp: 1_700_000_000 + i as u64 * 60,
+                prices: HashMap::from([("usd".to_string(), *price)]),
+            });
+        }
+        history
+    }
+
+    #[test]
+    fn volatility_is_annualised_by_sample_spacing() {
+        let daily = daily_series();
+        assert_eq!(periods_per_year(&daily), 365.0);
+        assert_eq!(
+            periods_per_year(&session(&[1.0, 2.0, 3.0]).series("usd", 10)),
+            365.0 * 24.0 * 60.0
+        );
+        assert_eq!(periods_per_year(&daily[..1]), 0.0);
+    }
+
+    #[test]
+    fn toggles_keep_a_stable_order() {
+        let mut page = ChartPage::new();
+        let _ = page.update(ChartMessage::OverlayToggled(Overlay::Bollinger, true));
+        let _ = page.update(ChartMessage::OverlayToggled(Overlay::Sma50, false));
+        let _ = page.update(ChartMessage::OverlayToggled(Overlay::Sma50, true));
+        assert_eq!(
+            page.overlays,
+            [Overlay::Sma50, Overlay::Sma200, Overlay::Bollinger]
+        );
+
+        let _ = page.update(ChartMessage::PaneToggled(Pane::Volatility, true));
+        let _ = page.update(ChartMessage::PaneToggled(Pane::Rsi, false));
+        assert_eq!(page.panes, [Pane::Volatility]);
+    }
+
+    #[test]
+    fn session_chart_uses_recorded_prices() {
+        let page = ChartPage::new();
+        let mut ui = simulator(page.view(&PriceHistory::default()));
+        assert!(ui
+            .find("No prices recorded yet; the chart fills in with every fetch")
+            .is_ok());
+
+        let history = session(&[65_000.0, 65_500.0, 64_900.0]);
+        let mut ui = simulator(page.view(&history));
+        assert!(ui.find("3 prices").is_ok());
+
+        ui.click("MACD 12/26/9").expect("pane toggle");
+        let messages: Vec<_> = ui.into_messages().collect();
+        assert!(matches!(
+            messages.as_slice(),
+            [ChartMessage::PaneToggled(Pane::Macd, true)]
+        ));
+    }
+
+    #[test]
+    fn daily_prices_are_fetched_once_per_currency() {
+        let mut page = ChartPage::new();
+        assert_eq!(
+            page.update(ChartMessage::CurrencySelected("EUR".into()))
+                .units(),
+            0,
+            "session range needs no fetch"
+        );
+        assert_eq!(
+            page.update(ChartMessage::RangeSelected(ChartRange::Daily))
+                .units(),
+            1
+        );
+        assert_eq!(page.loading.as_deref(), Some("eur"));
+        {
+            let mut ui = simulator(page.view(&PriceHistory::default()));
+            assert!(ui.find("Loading daily prices…").is_ok());
+        }
+
+        let _ = page.update(ChartMessage::DailyLoaded("eur".into(), daily_series()));
+        assert!(page.loading.is_none());
+        let _ = page.update(ChartMessage::RangeSelected(ChartRange::Session));
+        assert_eq!(
+            page.update(ChartMessage::RangeSelected(ChartRange::Daily))
+                .units(),
+            0,
+            "cached"
+        );
+
+        assert_eq!(
+            page.update(ChartMessage::CurrencySelected("JPY".into()))
+                .units(),
+            1
+        );
+        let _ = page.update(ChartMessage::DailyFailed(ApiError::HttpStatus {
+            url: String::new(),
+            status: 429,
+            body: String::new(),
+        }));
+        let mut ui = simulator(page.view(&PriceHistory::default()));
+        assert!(ui
+            .find("Could not load daily prices: HTTP 429 from CoinGecko")
+            .is_ok());
+    }
+
+    #[test]
+    fn daily_chart_with_every_indicator_matches_snapshot() {
+        let mut page = ChartPage::new();
+        let _ = page.update(ChartMessage::RangeSelected(ChartRange::Daily));
+        let _ = page.update(ChartMessage::DailyLoaded("usd".into(), daily_series()));
+        for overlay in Overlay::ALL {
+            let _ = page.update(ChartMessage::OverlayToggled(overlay, true));
+        }
+        for pane in Pane::ALL {
+            let _ = page.update(ChartMessage::PaneToggled(pane, true));
+        }
+
+        let mut ui = simulator_with_size(
+            iced::Size::new(900.0, 1000.0),
+            page.view(&PriceHistory::default()),
+        );
+        assert!(ui.find("365 prices").is_ok());
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "chart_page");
+    }
+}
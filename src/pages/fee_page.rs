@@ -0,0 +1,301 @@
+use std::fmt;
+
+use crate::history::PriceHistory;
+use crate::mempool::NetworkStatus;
+use crate::message::FeeMessage;
+use crate::supply::COIN;
+use crate::views::fee_view::{FeeProps, FeeQuote, FeeView};
+use crate::views::network_view::format_count;
+use crate::vsize::{self, ScriptType};
+
+/// Script type as picked in the calculator; multisig thresholds are typed separately
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScriptChoice {
+    P2pkh,
+    #[default]
+    P2wpkh,
+    P2tr,
+    P2shMultisig,
+    P2wshMultisig,
+}
+
+impl ScriptChoice {
+    pub const ALL: [ScriptChoice; 5] = [
+        ScriptChoice::P2pkh,
+        ScriptChoice::P2wpkh,
+        ScriptChoice::P2tr,
+        ScriptChoice::P2shMultisig,
+        ScriptChoice::P2wshMultisig,
+    ];
+
+    pub fn is_multisig(self) -> bool {
+        matches!(
+            self,
+            ScriptChoice::P2shMultisig | ScriptChoice::P2wshMultisig
+        )
+    }
+
+    fn script(self, m: u8, n: u8) -> ScriptType {
+        match self {
+            ScriptChoice::P2pkh => ScriptType::P2pkh,
+            ScriptChoice::P2wpkh => ScriptType::P2wpkh,
+            ScriptChoice::P2tr => ScriptType::P2tr,
+            ScriptChoice::P2shMultisig => ScriptType::P2shMultisig { m, n },
+            ScriptChoice::P2wshMultisig => ScriptType::P2wshMultisig { m, n },
+        }
+    }
+}
+
+impl fmt::Display for ScriptChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScriptChoice::P2pkh => "P2PKH (legacy)",
+            ScriptChoice::P2wpkh => "P2WPKH (native SegWit)",
+            ScriptChoice::P2tr => "P2TR (Taproot)",
+            ScriptChoice::P2shMultisig => "P2SH multisig",
+            ScriptChoice::P2wshMultisig => "P2WSH multisig",
+        })
+    }
+}
+
+/// Fee calculator state: the transaction shape as typed
+pub struct FeePage {
+    script: ScriptChoice,
+    inputs: String,
+    outputs: String,
+    /// Multisig signatures required
+    required: String,
+    /// Multisig keys
+    keys: String,
+    /// Lowercase code fees are valued in
+    currency: String,
+}
+
+impl Default for FeePage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeePage {
+    /// One input paying a recipient plus change
+    pub fn new() -> Self {
+        Self {
+            script: ScriptChoice::default(),
+            inputs: "1".to_string(),
+            outputs: "2".to_string(),
+            required: "2".to_string(),
+            keys: "3".to_string(),
+            currency: "usd".to_string(),
+        }
+    }
+
+    pub fn update(&mut self, message: FeeMessage) {
+        match message {
+            FeeMessage::ScriptSelected(script) => self.script = script,
+            FeeMessage::InputsChanged(input) => self.inputs = input,
+            FeeMessage::OutputsChanged(input) => self.outputs = input,
+            FeeMessage::RequiredChanged(input) => self.required = input,
+            FeeMessage::KeysChanged(input) => self.keys = input,
+            FeeMessage::CurrencySelected(currency) => self.currency = currency.to_lowercase(),
+        }
+    }
+
+    /// Script type and counts, or why they cannot be used
+    fn shape(&self) -> Result<(ScriptType, u64, u64), String> {
+        let count = |input: &str, what: &str| {
+            input
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("{what} must be a whole number"))
+        };
+        let inputs = count(&self.inputs, "Inputs")?;
+        let outputs = count(&self.outputs, "Outputs")?;
+        let (m, n) = if self.script.is_multisig() {
+            let keys = |input: &str, what: &str| {
+                input
+                    .trim()
+                    .parse::<u8>()
+                    .map_err(|_| format!("{what} must be a number from 1 to 20"))
+            };
+            (
+                keys(&self.required, "Signatures")?,
+                keys(&self.keys, "Keys")?,
+            )
+        } else {
+            (0, 0)
+        };
+        Ok((self.script.script(m, n), inputs, outputs))
+    }
+
+    /// Sizes the typed transaction and prices it at `network`'s fee rates and
+    /// the latest BTC price in `history`
+    pub fn view(
+        &self,
+        network: Option<&NetworkStatus>,
+        history: &PriceHistory,
+    ) -> iced::Element<'_, FeeMessage> {
+        let prices = history.samples().next_back().map(|s| &s.prices);
+        let mut currencies: Vec<String> = prices
+            .map(|p| p.keys().map(|c| c.to_uppercase()).collect())
+            .unwrap_or_default();
+        currencies.sort();
+        let price = prices.and_then(|p| p.get(&self.currency)).copied();
+        let code = self.currency.to_uppercase();
+
+        let size = self.shape().and_then(|(script, inputs, outputs)| {
+            vsize::estimate(script, inputs, outputs).map_err(|e| e.to_string())
+        });
+        let (size, error) = match size {
+            Ok(size) => (Some(size), None),
+            Err(e) => (None, Some(e)),
+        };
+
+        let quotes = match (size, network) {
+            (Some(size), Some(network)) => {
+                let fees = &network.fees;
+                [
+                    ("Fast", "next block", fees.fastest_fee),
+                    ("Medium", "~30 minutes", fees.half_hour_fee),
+                    ("Slow", "~1 hour", fees.hour_fee),
+                    ("Economy", "hours or more", fees.economy_fee),
+                ]
+                .into_iter()
+                .map(|(label, target, rate)| {
+                    let sats = vsize::fee(size.vsize, rate);
+                    FeeQuote {
+                        label,
+                        target,
+                        rate,
+                        sats: format!("{} sats", format_count(sats)),
+                        fiat: price.map(|p| format!("{:.2} {code}", sats as f64 / COIN as f64 * p)),
+                    }
+                })
+                .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let notice = match (network, price) {
+            (None, _) => Some("Waiting for fee rates from the Network page's server".to_string()),
+            (_, None) => Some(format!("Waiting for a {code} price to value fees")),
+            _ => None,
+        };
+
+        FeeView::new(FeeProps {
+            script: self.script,
+            inputs: self.inputs.clone(),
+            outputs: self.outputs.clone(),
+            multisig: self
+                .script
+                .is_multisig()
+                .then(|| (self.required.clone(), self.keys.clone())),
+            error,
+            size: size.map(|s| {
+                format!(
+                    "{} vB · {} weight units",
+                    format_count(s.vsize),
+                    format_count(s.weight)
+                )
+            }),
+            notice,
+            currencies,
+            currency: price.map(|_| code.clone()),
+            quotes,
+        })
+        .view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::PriceSample;
+    use crate::mempool::{DifficultyAdjustment, MempoolStats, RecommendedFees};
+    use crate::testing::{assert_snapshot, simulator};
+    use std::collections::HashMap;
+
+    fn network() -> NetworkStatus {
+        NetworkStatus {
+            tip_height: 840_000,
+            fees: RecommendedFees {
+                fastest_fee: 32.0,
+                half_hour_fee: 24.0,
+                hour_fee: 18.0,
+                economy_fee: 9.0,
+                minimum_fee: 4.0,
+            },
+            mempool: MempoolStats {
+                count: 0,
+                vsize: 0,
+                total_fee: 0.0,
+            },
+            difficulty: DifficultyAdjustment {
+                progress_percent: 0.0,
+                difficulty_change: 0.0,
+                remaining_blocks: 0,
+                estimated_retarget_date: 0,
+                next_retarget_height: 0,
+                time_avg: 600_000,
+            },
+        }
+    }
+
+    fn history() -> PriceHistory {
+        let mut history = PriceHistory::with_capacity(1);
+        history.push(PriceSample {
+            timestamp: 1_700_000_000,
+            prices: HashMap::from([("usd".to_string(), 65_000.0), ("eur".to_string(), 60_000.0)]),
+        });
+        history
+    }
+
+    #[test]
+    fn default_payment_is_priced_at_each_rate() {
+        let page = FeePage::new();
+        let history = history();
+        let mut ui = simulator(page.view(Some(&network()), &history));
+        assert!(ui.find("141 vB · 562 weight units").is_ok());
+        // 141 vB × 32 sat/vB
+        assert!(ui.find("4,512 sats").is_ok());
+        assert!(ui.find("2.93 USD").is_ok());
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "fee_page");
+    }
+
+    #[test]
+    fn multisig_shape_is_validated() {
+        let mut page = FeePage::new();
+        page.update(FeeMessage::ScriptSelected(ScriptChoice::P2wshMultisig));
+        page.update(FeeMessage::CurrencySelected("EUR".into()));
+        let history = history();
+        {
+            let mut ui = simulator(page.view(Some(&network()), &history));
+            assert!(ui.find("201 vB · 804 weight units").is_ok());
+            // 201 vB × 9 sat/vB at 60,000 EUR
+            assert!(ui.find("1.09 EUR").is_ok());
+        }
+
+        page.update(FeeMessage::RequiredChanged("4".into()));
+        {
+            let mut ui = simulator(page.view(Some(&network()), &history));
+            assert!(ui
+                .find("Multisig needs 1 ≤ m ≤ n signatures, not 4 of 3")
+                .is_ok());
+            assert!(ui.find("Fast").is_err(), "no quotes without a size");
+        }
+
+        page.update(FeeMessage::ScriptSelected(ScriptChoice::P2pkh));
+        page.update(FeeMessage::InputsChanged("100000000000000000".into()));
+        {
+            let mut ui = simulator(page.view(Some(&network()), &history));
+            assert!(ui.find("Too many inputs or outputs to estimate").is_ok());
+        }
+
+        page.update(FeeMessage::InputsChanged("two".into()));
+        let mut ui = simulator(page.view(None, &history));
+        assert!(ui.find("Inputs must be a whole number").is_ok());
+        assert!(ui
+            .find("Waiting for fee rates from the Network page's server")
+            .is_ok());
+    }
+}
@@ -0,0 +1,384 @@
+use std::sync::Arc;
+
+use iced::Task;
+
+use crate::candles::format_utc;
+use crate::country::get_countries;
+use crate::fx::{self, FxError, FxProvider, FxRates};
+use crate::fx_matrix::FxMatrix;
+use crate::history::{PriceHistory, PriceSample};
+use crate::message::MarketsMessage;
+use crate::views::markets_view::{
+    MarketsContent, MarketsView, MatrixCell, MatrixProps, PremiumProps, PremiumRow,
+};
+
+/// Currencies in the cross-rate matrix until the user picks others
+const DEFAULT_MATRIX_CURRENCIES: &str = "usd, gbp, jpy, chf, cny, krw";
+
+/// Which comparison the Markets page shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MarketsTab {
+    /// Local prices against reference FX rates
+    #[default]
+    Premiums,
+    /// Cross rates implied by BTC prices
+    Matrix,
+}
+
+impl MarketsTab {
+    pub const ALL: [MarketsTab; 2] = [MarketsTab::Premiums, MarketsTab::Matrix];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MarketsTab::Premiums => "Premiums",
+            MarketsTab::Matrix => "FX matrix",
+        }
+    }
+}
+
+/// Markets page state: reference FX rates used to price local premiums, and
+/// the currencies picked for the cross-rate matrix
+pub struct MarketsPage {
+    tab: MarketsTab,
+    provider: Arc<dyn FxProvider>,
+    rates: Option<FxRates>,
+    loading: bool,
+    error: Option<FxError>,
+    /// Matrix currencies as typed, comma or space separated
+    matrix_input: String,
+}
+
+impl Default for MarketsPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarketsPage {
+    /// Uses the provider chosen by the environment (see [`fx::provider_from_env`])
+    pub fn new() -> Self {
+        Self::with_provider(fx::provider_from_env())
+    }
+
+    pub fn with_provider(provider: Arc<dyn FxProvider>) -> Self {
+        Self {
+            tab: MarketsTab::default(),
+            provider,
+            rates: None,
+            loading: false,
+            error: None,
+            matrix_input: DEFAULT_MATRIX_CURRENCIES.to_string(),
+        }
+    }
+
+    /// Fetches reference rates the first time the page is shown
+    pub fn load(&mut self) -> Task<MarketsMessage> {
+        if self.rates.is_some() || self.loading {
+            return Task::none();
+        }
+        self.fetch()
+    }
+
+    /// Reference FX rates, once loaded
+    pub fn rates(&self) -> Option<&FxRates> {
+        self.rates.as_ref()
+    }
+
+    fn fetch(&mut self) -> Task<MarketsMessage> {
+        self.loading = true;
+        self.error = None;
+        let provider = self.provider.clone();
+        Task::perform(
+            async move { provider.rates().await },
+            |result| match result {
+                Ok(rates) => MarketsMessage::RatesLoaded(rates),
+                Err(e) => MarketsMessage::RatesFailed(e),
+            },
+        )
+    }
+
+    pub fn update(&mut self, message: MarketsMessage) -> Task<MarketsMessage> {
+        match message {
+            MarketsMessage::TabSelected(tab) => {
+                self.tab = tab;
+                Task::none()
+            }
+            MarketsMessage::MatrixCurrenciesChanged(input) => {
+                self.matrix_input = input;
+                Task::none()
+            }
+            MarketsMessage::RefreshRates => {
+                if self.loading {
+                    return Task::none();
+                }
+                self.fetch()
+            }
+            MarketsMessage::RatesLoaded(rates) => {
+                self.loading = false;
+                self.rates = Some(rates);
+                Task::none()
+            }
+            MarketsMessage::RatesFailed(error) => {
+                self.loading = false;
+                self.error = Some(error);
+                Task::none()
+            }
+        }
+    }
+
+    /// Renders the selected tab: premiums from the latest sample of
+    /// `history`, the matrix from the latest and previous fetches'
+    /// `cross_rates`
+    pub fn view(
+        &self,
+        history: &PriceHistory,
+        (current, previous): (Option<&FxMatrix>, Option<&FxMatrix>),
+    ) -> iced::Element<'_, MarketsMessage> {
+        let content = match self.tab {
+            MarketsTab::Premiums => {
+                MarketsContent::Premiums(self.premiums(history.samples().next_back()))
+            }
+            MarketsTab::Matrix => MarketsContent::Matrix(self.matrix(current, previous)),
+        };
+        MarketsView::new(self.tab, content).view()
+    }
+
+    fn premiums(&self, latest: Option<&PriceSample>) -> PremiumProps {
+        let rows = match (latest, &self.rates) {
+            (Some(sample), Some(rates)) => fx::premiums(&sample.prices, rates)
+                .into_iter()
+                .map(|p| PremiumRow {
+                    country: get_countries()
+                        .iter()
+                        .find(|c| c.currency == p.currency)
+                        .map_or_else(|| "—".to_string(), |c| c.name.clone()),
+                    currency: p.currency.to_uppercase(),
+                    local: p.local,
+                    implied: p.implied,
+                    premium: p.premium,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let status = match (&self.error, self.loading, latest) {
+            (Some(e), ..) => Some(format!("Could not load FX rates: {e}")),
+            (_, true, _) => Some("Loading FX rates…".to_string()),
+            (_, _, None) => Some("Waiting for the first price fetch".to_string()),
+            _ => None,
+        };
+
+        let source = self.rates.as_ref().map(|rates| match rates.updated_at {
+            Some(at) => format!(
+                "FX reference: {}, updated {} UTC",
+                rates.source,
+                format_utc(at)
+            ),
+            None => format!("FX reference: {}", rates.source),
+        });
+
+        PremiumProps {
+            source,
+            status,
+            loading: self.loading,
+            rows,
+        }
+    }
+
+    fn matrix(&self, latest: Option<&FxMatrix>, previous: Option<&FxMatrix>) -> MatrixProps {
+        let empty = FxMatrix::default();
+        let current = latest.unwrap_or(&empty);
+
+        let (currencies, unknown): (Vec<String>, Vec<String>) =
+            parse_currencies(&self.matrix_input)
+                .into_iter()
+                .partition(|c| current.currencies().contains(c));
+
+        let cells = currencies
+            .iter()
+            .map(|base| {
+                currencies
+                    .iter()
+                    .map(|quote| MatrixCell {
+                        rate: current.convert(1.0, base, quote),
+                        change: previous.and_then(|p| current.change_since(p, base, quote)),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let status = match (latest, previous) {
+            (None, _) => Some("Waiting for the first price fetch".to_string()),
+            (Some(_), None) => Some("Changes appear after the next fetch".to_string()),
+            _ => None,
+        };
+
+        MatrixProps {
+            input: self.matrix_input.clone(),
+            currencies: currencies.iter().map(|c| c.to_uppercase()).collect(),
+            unknown: if latest.is_some() {
+                unknown.iter().map(|c| c.to_uppercase()).collect()
+            } else {
+                Vec::new()
+            },
+            cells,
+            status,
+        }
+    }
+}
+
+/// Lowercase codes from a comma or space separated list, first mention kept
+fn parse_currencies(input: &str) -> Vec<String> {
+    let mut codes: Vec<String> = Vec::new();
+    for code in input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|c| !c.is_empty())
+        .map(str::to_lowercase)
+    {
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    codes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::CoinGeckoResponse;
+    use crate::fx::FixtureRates;
+    use crate::history::PriceSample;
+    use crate::testing::{assert_snapshot, simulator};
+    use std::collections::HashMap;
+
+    fn fixture_page() -> MarketsPage {
+        MarketsPage::with_provider(Arc::new(FixtureRates::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/fx_usd.json"
+        ))))
+    }
+
+    fn history() -> PriceHistory {
+        let mut history = PriceHistory::default();
+        history.push(PriceSample {
+            timestamp: 1_711_356_300,
+            prices: HashMap::from([
+                ("usd".to_string(), 65_000.0),
+                ("krw".to_string(), 65_000.0 * 1_340.0 * 1.042),
+                ("eur".to_string(), 65_000.0 * 0.9254 * 0.998),
+                ("gbp".to_string(), 65_000.0 * 0.7921 * 1.001),
+                ("ars".to_string(), 65_000.0 * 857.5 * 1.12),
+                ("ngn".to_string(), 65_000.0 * 1_450.0 * 0.93),
+            ]),
+        });
+        history
+    }
+
+    #[tokio::test]
+    async fn rates_load_once_and_refresh_on_demand() {
+        let mut page = fixture_page();
+        assert_eq!(page.load().units(), 1);
+        assert_eq!(page.load().units(), 0, "already loading");
+
+        let rates = page.provider.rates().await.unwrap();
+        let _ = page.update(MarketsMessage::RatesLoaded(rates));
+        assert_eq!(page.load().units(), 0, "already loaded");
+        assert_eq!(page.update(MarketsMessage::RefreshRates).units(), 1);
+
+        let _ = page.update(MarketsMessage::RatesFailed(FxError::HttpStatus {
+            url: String::new(),
+            status: 503,
+        }));
+        let mut ui = simulator(page.view(&history(), (None, None)));
+        assert!(ui
+            .find("Could not load FX rates: HTTP 503 from the FX provider")
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn premiums_match_snapshot() {
+        let mut page = fixture_page();
+        {
+            let mut ui = simulator(page.view(&PriceHistory::default(), (None, None)));
+            assert!(ui.find("Waiting for the first price fetch").is_ok());
+        }
+
+        let rates = page.provider.rates().await.unwrap();
+        let _ = page.update(MarketsMessage::RatesLoaded(rates));
+        let mut ui = simulator(page.view(&history(), (None, None)));
+        assert!(ui.find("South Korea").is_ok());
+        assert!(ui.find("+4.20%").is_ok());
+        assert!(ui.find("-7.00%").is_ok());
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "markets_page");
+    }
+
+    /// Cross rates of a price response with `quotes`
+    fn cross_rates(quotes: &[(&str, f64)]) -> FxMatrix {
+        FxMatrix::from_response(&CoinGeckoResponse {
+            bitcoin: quotes.iter().map(|(c, p)| (c.to_string(), *p)).collect(),
+            last_updated_at: None,
+        })
+    }
+
+    fn latest_quotes() -> Vec<(&'static str, f64)> {
+        let sample = history().samples().next_back().unwrap().clone();
+        let mut quotes: Vec<_> = ["usd", "eur", "gbp"]
+            .into_iter()
+            .map(|c| (c, sample.prices[c]))
+            .collect();
+        // JPY firms 0.4% against USD, GBP slips 0.1%
+        quotes.push(("jpy", 65_000.0 * 151.35 * 0.996));
+        quotes
+    }
+
+    #[test]
+    fn matrix_heatmap_matches_snapshot() {
+        let latest = latest_quotes();
+        let previous: Vec<_> = latest
+            .iter()
+            .map(|&(c, p)| match c {
+                "jpy" => (c, 65_000.0 * 151.35),
+                "gbp" => (c, p * 0.999),
+                _ => (c, p),
+            })
+            .collect();
+        let (current, previous) = (cross_rates(&latest), cross_rates(&previous));
+
+        let mut page = fixture_page();
+        let _ = page.update(MarketsMessage::TabSelected(MarketsTab::Matrix));
+        let _ = page.update(MarketsMessage::MatrixCurrenciesChanged(
+            "usd eur, GBP jpy".to_string(),
+        ));
+        let mut ui = simulator(page.view(&history(), (Some(&current), Some(&previous))));
+        assert!(ui.find("Cross rates").is_ok());
+        assert!(ui.find("150.7").is_ok());
+        assert!(ui.find("-0.40%").is_ok());
+        assert!(ui.find("+0.10%").is_ok());
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "markets_matrix");
+    }
+
+    #[test]
+    fn matrix_reports_unknown_currencies_and_first_fetch() {
+        let mut page = fixture_page();
+        let _ = page.update(MarketsMessage::TabSelected(MarketsTab::Matrix));
+        let _ = page.update(MarketsMessage::MatrixCurrenciesChanged(
+            "usd, xyz, gbp, usd".to_string(),
+        ));
+        {
+            let mut ui = simulator(page.view(&PriceHistory::default(), (None, None)));
+            assert!(ui.find("Waiting for the first price fetch").is_ok());
+            assert!(ui.find("No BTC price for XYZ").is_err());
+        }
+
+        let current = cross_rates(&latest_quotes());
+        let props = page.matrix(Some(&current), None);
+        assert_eq!(props.currencies, ["USD", "GBP"]);
+        assert_eq!(props.unknown, ["XYZ"]);
+        assert_eq!(props.cells[0][0].rate, Some(1.0));
+        assert!(props.cells.iter().flatten().all(|c| c.change.is_none()));
+
+        let mut ui = simulator(page.view(&history(), (Some(&current), None)));
+        assert!(ui.find("No BTC price for XYZ").is_ok());
+        assert!(ui.find("Changes appear after the next fetch").is_ok());
+    }
+}
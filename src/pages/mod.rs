@@ -1,5 +1,19 @@
 /// Bitcoin price display page
 pub mod bitcoin_page;
+/// Price chart and technical indicators page
+pub mod chart_page;
+/// OHLC candlestick page
+pub mod candle_page;
+/// Local premium page
+pub mod markets_page;
+/// Chain tip, fee and mempool page
+pub mod network_page;
+/// Transaction fee calculator page
+pub mod fee_page;
+/// Watch-only balances page
+pub mod wallet_page;
+/// Alert channel settings page
+pub mod alerts_page;
 /// Settings configuration page
 pub mod settings_page;
 /// About information page
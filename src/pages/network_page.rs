@@ -0,0 +1,276 @@
+use iced::Task;
+
+use crate::candles::format_utc;
+use crate::mempool::{MempoolApi, MempoolError, NetworkStatus};
+use crate::message::NetworkMessage;
+use crate::views::network_view::{
+    format_count, format_duration, DifficultyProps, FeeRow, MempoolProps, NetworkProps, NetworkView,
+};
+
+/// Network page state: chain tip, fees and mempool from the configured API
+pub struct NetworkPage {
+    api: MempoolApi,
+    /// Base URL as typed; applied to `api` when valid
+    url_input: String,
+    /// Why the typed base URL was not applied
+    url_error: Option<String>,
+    status: Option<NetworkStatus>,
+    loading: bool,
+    error: Option<MempoolError>,
+}
+
+impl Default for NetworkPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetworkPage {
+    /// Uses the base URL chosen by the environment (see [`MempoolApi::from_env`])
+    pub fn new() -> Self {
+        Self::with_api(MempoolApi::from_env())
+    }
+
+    pub fn with_api(api: MempoolApi) -> Self {
+        Self {
+            url_input: api.base_url().to_string(),
+            api,
+            url_error: None,
+            status: None,
+            loading: false,
+            error: None,
+        }
+    }
+
+    /// Fetches network data the first time the page is shown
+    pub fn load(&mut self) -> Task<NetworkMessage> {
+        if self.status.is_some() || self.loading {
+            return Task::none();
+        }
+        self.fetch()
+    }
+
+    fn fetch(&mut self) -> Task<NetworkMessage> {
+        self.loading = true;
+        self.error = None;
+        let api = self.api.clone();
+        Task::perform(
+            async move {
+                let result = api.fetch_status().await;
+                (api.base_url().to_string(), result)
+            },
+            |(base_url, result)| match result {
+                Ok(status) => NetworkMessage::Loaded(base_url, status),
+                Err(e) => NetworkMessage::Failed(base_url, e),
+            },
+        )
+    }
+
+    pub fn update(&mut self, message: NetworkMessage) -> Task<NetworkMessage> {
+        match message {
+            NetworkMessage::BaseUrlChanged(input) => {
+                self.url_input = input;
+                self.url_error = None;
+                Task::none()
+            }
+            NetworkMessage::ApplyBaseUrl => match MempoolApi::new(&self.url_input) {
+                Ok(api) => {
+                    self.url_input = api.base_url().to_string();
+                    if api == self.api && self.status.is_some() {
+                        return Task::none();
+                    }
+                    self.api = api;
+                    self.status = None;
+                    self.fetch()
+                }
+                Err(e) => {
+                    self.url_error = Some(e);
+                    Task::none()
+                }
+            },
+            NetworkMessage::Refresh => {
+                if self.loading {
+                    return Task::none();
+                }
+                self.fetch()
+            }
+            // Answers from a server that was replaced meanwhile are dropped
+            NetworkMessage::Loaded(base_url, _) | NetworkMessage::Failed(base_url, _)
+                if base_url != self.api.base_url() =>
+            {
+                Task::none()
+            }
+            NetworkMessage::Loaded(_, status) => {
+                self.loading = false;
+                self.status = Some(status);
+                Task::none()
+            }
+            NetworkMessage::Failed(_, error) => {
+                self.loading = false;
+                self.error = Some(error);
+                Task::none()
+            }
+        }
+    }
+
+    /// Latest data, once loaded
+    pub fn status(&self) -> Option<&NetworkStatus> {
+        self.status.as_ref()
+    }
+
+    pub fn view(&self) -> iced::Element<'_, NetworkMessage> {
+        let notice = match (&self.error, self.loading) {
+            (Some(e), _) => Some(format!("Could not load network data: {e}")),
+            (None, true) => Some("Loading network data…".to_string()),
+            _ => None,
+        };
+
+        let status = self.status.as_ref();
+        NetworkView::new(NetworkProps {
+            url_input: self.url_input.clone(),
+            url_error: self.url_error.clone(),
+            notice,
+            loading: self.loading,
+            tip_height: status.map(|s| format_count(s.tip_height)),
+            fees: status.map_or_else(Vec::new, |s| {
+                vec![
+                    FeeRow {
+                        label: "Fast",
+                        target: "next block",
+                        rate: s.fees.fastest_fee,
+                    },
+                    FeeRow {
+                        label: "Medium",
+                        target: "~30 minutes",
+                        rate: s.fees.half_hour_fee,
+                    },
+                    FeeRow {
+                        label: "Slow",
+                        target: "~1 hour",
+                        rate: s.fees.hour_fee,
+                    },
+                ]
+            }),
+            mempool: status.map(|s| MempoolProps {
+                transactions: format!("{} transactions", format_count(s.mempool.count)),
+                size: format!("{:.1} MvB", s.mempool.vsize as f64 / 1e6),
+                fees: format!("{:.3} BTC in fees", s.mempool.total_fee / 1e8),
+            }),
+            difficulty: status.map(|s| {
+                let d = &s.difficulty;
+                DifficultyProps {
+                    progress: d.progress_percent.clamp(0.0, 100.0) as f32,
+                    remaining: format!(
+                        "{:.1}% of the epoch mined, {} blocks to go",
+                        d.progress_percent,
+                        format_count(d.remaining_blocks)
+                    ),
+                    change: format!("{:+.2}%", d.difficulty_change),
+                    retarget: format!(
+                        "Block {} around {} UTC",
+                        format_count(d.next_retarget_height),
+                        format_utc(d.estimated_retarget_date / 1000)
+                    ),
+                    block_time: format_duration(d.time_avg / 1000),
+                }
+            }),
+        })
+        .view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mempool::{DifficultyAdjustment, MempoolStats, RecommendedFees};
+    use crate::testing::{assert_snapshot, simulator};
+
+    const LOCAL: &str = "http://127.0.0.1:8999/api";
+
+    fn status() -> NetworkStatus {
+        NetworkStatus {
+            tip_height: 840_000,
+            fees: RecommendedFees {
+                fastest_fee: 32.0,
+                half_hour_fee: 24.0,
+                hour_fee: 18.0,
+                economy_fee: 9.0,
+                minimum_fee: 4.0,
+            },
+            mempool: MempoolStats {
+                count: 48_213,
+                vsize: 61_234_567,
+                total_fee: 152_345_678.0,
+            },
+            difficulty: DifficultyAdjustment {
+                progress_percent: 66.67,
+                difficulty_change: 2.31,
+                remaining_blocks: 672,
+                estimated_retarget_date: 1_713_740_400_000,
+                next_retarget_height: 840_672,
+                time_avg: 587_500,
+            },
+        }
+    }
+
+    #[test]
+    fn base_url_applies_only_when_valid() {
+        let mut page = NetworkPage::with_api(MempoolApi::default());
+        assert_eq!(page.load().units(), 1);
+        assert_eq!(page.load().units(), 0, "already loading");
+
+        let _ = page.update(NetworkMessage::BaseUrlChanged("localhost:8999".into()));
+        assert_eq!(page.update(NetworkMessage::ApplyBaseUrl).units(), 0);
+        {
+            let mut ui = simulator(page.view());
+            assert!(ui
+                .find("Base URL must start with http:// or https://")
+                .is_ok());
+        }
+
+        let _ = page.update(NetworkMessage::BaseUrlChanged(format!("{LOCAL}/")));
+        assert_eq!(page.update(NetworkMessage::ApplyBaseUrl).units(), 1);
+        assert_eq!(page.api.base_url(), LOCAL);
+
+        // The answer from mempool.space arrives late and is ignored
+        let _ = page.update(NetworkMessage::Loaded(
+            crate::mempool::DEFAULT_BASE_URL.into(),
+            status(),
+        ));
+        assert!(page.status.is_none());
+        let _ = page.update(NetworkMessage::Loaded(LOCAL.into(), status()));
+        assert_eq!(page.status.as_ref().map(|s| s.tip_height), Some(840_000));
+        assert_eq!(page.load().units(), 0, "already loaded");
+    }
+
+    #[test]
+    fn failures_are_reported() {
+        let mut page = NetworkPage::with_api(MempoolApi::new(LOCAL).unwrap());
+        let _ = page.load();
+        let _ = page.update(NetworkMessage::Failed(
+            LOCAL.into(),
+            MempoolError::HttpStatus {
+                url: format!("{LOCAL}/blocks/tip/height"),
+                status: 503,
+            },
+        ));
+        let mut ui = simulator(page.view());
+        assert!(ui
+            .find("Could not load network data: HTTP 503 from http://127.0.0.1:8999/api/blocks/tip/height")
+            .is_ok());
+    }
+
+    #[test]
+    fn dashboard_matches_snapshot() {
+        let mut page = NetworkPage::with_api(MempoolApi::new(LOCAL).unwrap());
+        let _ = page.load();
+        let _ = page.update(NetworkMessage::Loaded(LOCAL.into(), status()));
+
+        let mut ui = simulator(page.view());
+        assert!(ui.find("840,000").is_ok());
+        assert!(ui.find("32 sat/vB").is_ok());
+        assert!(ui.find("48,213 transactions").is_ok());
+        assert!(ui.find("+2.31%").is_ok());
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "network_page");
+    }
+}
@@ -1,7 +1,125 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use iced::theme::Mode;
 use iced::Theme;
+use tracing::warn;
+
+use crate::api::{self, ApiKey, ApiPlan};
+use crate::custom_theme::{self, ThemeColor, ThemePalette, ThemeSelection};
+use crate::http_client::{self, HttpConfig};
+use crate::message::{HttpField, SettingsMessage};
+use crate::secrets;
+use crate::server::{self, ServerEvent};
+use crate::shortcuts::{Action, KeyBindings, Shortcut, ShortcutsFile};
+use crate::views::settings_view::{
+    ApiKeyProps, ApiServerProps, GeneralProps, NetworkProps, SettingsView, ShortcutProps,
+    ThemeProps,
+};
+
+/// Custom palette as typed in the theme editor
+#[derive(Debug, Clone)]
+struct ThemeEditor {
+    name: String,
+    /// Hex text per colour, in [`ThemeColor::ALL`] order
+    colors: Vec<(ThemeColor, String)>,
+    /// File path used by Import / Export
+    file: String,
+    /// Outcome of the last editor action, if any
+    status: Option<Result<String, String>>,
+}
+
+impl ThemeEditor {
+    fn from_palette(palette: &ThemePalette) -> Self {
+        Self {
+            name: palette.name.clone(),
+            colors: ThemeColor::ALL
+                .into_iter()
+                .map(|c| (c, custom_theme::to_hex(palette.color(c))))
+                .collect(),
+            file: String::new(),
+            status: None,
+        }
+    }
 
-use crate::message::SettingsMessage;
-use crate::views::settings_view::SettingsView;
+    fn palette(&self) -> Result<ThemePalette, String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Enter a theme name".to_string());
+        }
+        let mut palette = ThemePalette::from_theme(name, &Theme::Dark);
+        for (which, hex) in &self.colors {
+            let color = custom_theme::parse_hex(hex)
+                .ok_or_else(|| format!("{} must be a #rrggbb colour", which.label()))?;
+            palette.set_color(*which, color);
+        }
+        Ok(palette)
+    }
+
+    fn file_path(&self) -> Result<PathBuf, String> {
+        Some(self.file.trim())
+            .filter(|f| !f.is_empty())
+            .map(PathBuf::from)
+            .ok_or_else(|| "Enter a file path".to_string())
+    }
+}
+
+/// HTTP client settings as typed in the Network section
+#[derive(Debug, Clone)]
+struct HttpForm {
+    proxy: String,
+    proxy_user: String,
+    proxy_password: String,
+    ca_bundle: String,
+    connect_timeout: String,
+    timeout: String,
+}
+
+impl HttpForm {
+    fn from_config(config: &HttpConfig) -> Self {
+        Self {
+            proxy: config.proxy.clone().unwrap_or_default(),
+            proxy_user: config.proxy_user.clone().unwrap_or_default(),
+            proxy_password: config.proxy_password.clone().unwrap_or_default(),
+            ca_bundle: config
+                .ca_bundle
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            connect_timeout: config.connect_timeout.as_secs().to_string(),
+            timeout: config.timeout.as_secs().to_string(),
+        }
+    }
+
+    fn to_config(&self) -> Result<HttpConfig, String> {
+        let optional = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        let secs = |value: &str, label: &str| match value.trim().parse::<u64>() {
+            Ok(secs) if secs > 0 => Ok(Duration::from_secs(secs)),
+            _ => Err(format!("{label} must be a positive number of seconds")),
+        };
+
+        Ok(HttpConfig {
+            proxy: optional(&self.proxy),
+            proxy_user: optional(&self.proxy_user),
+            // Passwords may legitimately contain spaces
+            proxy_password: Some(self.proxy_password.clone()).filter(|p| !p.is_empty()),
+            ca_bundle: optional(&self.ca_bundle).map(PathBuf::from),
+            connect_timeout: secs(&self.connect_timeout, "Connect timeout")?,
+            timeout: secs(&self.timeout, "Request timeout")?,
+        })
+    }
+
+    fn field_mut(&mut self, field: HttpField) -> &mut String {
+        match field {
+            HttpField::Proxy => &mut self.proxy,
+            HttpField::ProxyUser => &mut self.proxy_user,
+            HttpField::ProxyPassword => &mut self.proxy_password,
+            HttpField::CaBundle => &mut self.ca_bundle,
+            HttpField::ConnectTimeout => &mut self.connect_timeout,
+            HttpField::Timeout => &mut self.timeout,
+        }
+    }
+}
 
 /// Settings page — owns configuration that the App layer reads
 #[derive(Debug, Clone)]
@@ -10,10 +128,52 @@ pub struct SettingsPage {
     auto_refresh_interval: u32,
     /// Whether auto-refresh is enabled
     auto_refresh_enabled: bool,
+    /// Prices older than this many refresh intervals are flagged stale
+    stale_multiple: u32,
     /// Theme selection — uses iced's built-in Theme directly
     selected_theme: Theme,
+    /// Follow the OS light/dark preference instead of `selected_theme`
+    follow_system_theme: bool,
+    /// Theme used while following a light OS preference
+    light_theme: Theme,
+    /// Theme used while following a dark OS preference
+    dark_theme: Theme,
+    /// Palette being edited in the theme editor
+    theme_editor: ThemeEditor,
+    /// Custom palettes offered next to the built-in themes
+    custom_themes: Vec<ThemePalette>,
+    /// Where "Save" writes palettes
+    themes_dir: PathBuf,
     /// Enable notifications
     notifications_enabled: bool,
+    /// Whether the localhost API server is running
+    api_server_enabled: bool,
+    /// Port the API server binds on 127.0.0.1
+    api_server_port: u16,
+    /// Raw port text as typed; only applied to `api_server_port` when valid
+    api_server_port_input: String,
+    /// Last status reported by the API server subscription
+    api_server_status: Option<ServerEvent>,
+    /// Network section as typed; applied to the shared HTTP client on demand
+    http_form: HttpForm,
+    /// HTTP config restored by "Reset to Defaults" (env and CLI at startup)
+    http_defaults: HttpConfig,
+    /// Outcome of the last "Apply", if any
+    http_status: Option<Result<(), String>>,
+    /// CoinGecko plan selected in the API key section
+    api_plan: ApiPlan,
+    /// API key as typed (rendered masked)
+    api_key_input: String,
+    /// Outcome of the last "Save key", if any
+    api_key_status: Option<Result<String, String>>,
+    /// Keyboard shortcuts read by the App
+    key_bindings: KeyBindings,
+    /// Where `key_bindings` are saved whenever they change
+    shortcuts_file: ShortcutsFile,
+    /// Shortcut text per action as typed; applied to `key_bindings` when valid
+    shortcut_inputs: Vec<(Action, String)>,
+    /// Why the last edited shortcut was not applied
+    shortcut_error: Option<(Action, String)>,
 }
 
 impl Default for SettingsPage {
@@ -21,21 +181,102 @@ impl Default for SettingsPage {
         Self {
             auto_refresh_interval: 30,
             auto_refresh_enabled: false,
+            stale_multiple: 3,
             selected_theme: Theme::Nord,
+            follow_system_theme: false,
+            light_theme: Theme::Light,
+            dark_theme: Theme::Nord,
+            theme_editor: ThemeEditor::from_palette(&ThemePalette::from_theme(
+                "My Theme",
+                &Theme::Nord,
+            )),
+            custom_themes: Vec::new(),
+            themes_dir: PathBuf::new(),
             notifications_enabled: false,
+            api_server_enabled: false,
+            api_server_port: server::DEFAULT_PORT,
+            api_server_port_input: server::DEFAULT_PORT.to_string(),
+            api_server_status: None,
+            http_form: HttpForm::from_config(&HttpConfig::default()),
+            http_defaults: HttpConfig::default(),
+            http_status: None,
+            api_plan: ApiPlan::Public,
+            api_key_input: String::new(),
+            api_key_status: None,
+            shortcut_inputs: shortcut_inputs(&KeyBindings::default()),
+            key_bindings: KeyBindings::default(),
+            shortcuts_file: ShortcutsFile::default(),
+            shortcut_error: None,
         }
     }
 }
 
+/// Current shortcut text for every action, in display order
+fn shortcut_inputs(bindings: &KeyBindings) -> Vec<(Action, String)> {
+    Action::all()
+        .into_iter()
+        .map(|action| {
+            let text = bindings.get(action).map(|s| s.to_string()).unwrap_or_default();
+            (action, text)
+        })
+        .collect()
+}
+
 impl SettingsPage {
+    /// Starts from defaults, with the network section showing the HTTP
+    /// config the app was launched with, and the saved custom palettes,
+    /// theme choice and keyboard shortcuts
     pub fn new() -> Self {
-        Self::default()
+        Self::with(
+            ShortcutsFile::default_location(),
+            custom_theme::themes_dir(),
+        )
+    }
+
+    /// Like [`SettingsPage::new`], keeping shortcuts in `file` and palettes
+    /// with the theme choice in `themes_dir`
+    pub fn with(file: ShortcutsFile, themes_dir: PathBuf) -> Self {
+        let key_bindings = file.load().unwrap_or_else(|e| {
+            warn!("Cannot read {}: {e}", file.path().display());
+            KeyBindings::default()
+        });
+        let selection = ThemeSelection::load(&themes_dir).unwrap_or_else(|e| {
+            warn!("Cannot read the theme choice: {e}");
+            None
+        });
+        let http_defaults = http_client::config();
+        let key = api::api_key();
+        let mut page = Self {
+            custom_themes: custom_theme::load_saved(&themes_dir),
+            themes_dir,
+            http_form: HttpForm::from_config(&http_defaults),
+            http_defaults,
+            api_plan: key.as_ref().map_or(ApiPlan::Public, |k| k.plan),
+            api_key_input: key.map(|k| k.key).unwrap_or_default(),
+            shortcut_inputs: shortcut_inputs(&key_bindings),
+            key_bindings,
+            shortcuts_file: file,
+            ..Self::default()
+        };
+        if let Some(selection) = selection {
+            page.restore_theme(&selection);
+        }
+        page
     }
 
     // ── Public getters (read by App) ────────────────────────────
 
-    pub fn selected_theme(&self) -> &Theme {
-        &self.selected_theme
+    /// Theme to use given the OS light/dark `mode`
+    pub fn theme_for(&self, mode: Mode) -> &Theme {
+        match (self.follow_system_theme, mode) {
+            (true, Mode::Light) => &self.light_theme,
+            (true, Mode::Dark) => &self.dark_theme,
+            _ => &self.selected_theme,
+        }
+    }
+
+    pub fn follow_system_theme(&self) -> bool {
+        self.follow_system_theme
     }
 
     pub fn auto_refresh_enabled(&self) -> bool {
@@ -46,6 +287,33 @@ impl SettingsPage {
         self.auto_refresh_interval
     }
 
+    /// Age in seconds after which displayed prices count as stale
+    pub fn stale_after_secs(&self) -> u64 {
+        u64::from(self.auto_refresh_interval) * u64::from(self.stale_multiple)
+    }
+
+    /// Whether alert rules may send through the configured channels
+    pub fn notifications_enabled(&self) -> bool {
+        self.notifications_enabled
+    }
+
+    pub fn api_server_enabled(&self) -> bool {
+        self.api_server_enabled
+    }
+
+    pub fn key_bindings(&self) -> &KeyBindings {
+        &self.key_bindings
+    }
+
+    pub fn api_server_port(&self) -> u16 {
+        self.api_server_port
+    }
+
+    /// Called by App when the server subscription reports a status change
+    pub fn set_api_server_status(&mut self, status: ServerEvent) {
+        self.api_server_status = Some(status);
+    }
+
     // ── Update ──────────────────────────────────────────────────
 
     pub fn update(&mut self, message: SettingsMessage) {
@@ -55,6 +323,79 @@ impl SettingsPage {
             }
             SettingsMessage::ThemeChanged(theme) => {
                 self.selected_theme = theme;
+                self.save_theme_selection();
+            }
+            SettingsMessage::FollowSystemThemeToggled(enabled) => {
+                self.follow_system_theme = enabled;
+                self.save_theme_selection();
+            }
+            SettingsMessage::LightThemeChanged(theme) => {
+                self.light_theme = theme;
+                self.save_theme_selection();
+            }
+            SettingsMessage::DarkThemeChanged(theme) => {
+                self.dark_theme = theme;
+                self.save_theme_selection();
+            }
+            SettingsMessage::ThemeNameChanged(name) => {
+                self.theme_editor.name = name;
+                self.theme_editor.status = None;
+            }
+            SettingsMessage::ThemeColorChanged(which, hex) => {
+                if let Some((_, text)) = self.theme_editor.colors.iter_mut().find(|(c, _)| *c == which) {
+                    *text = hex;
+                }
+                self.theme_editor.status = None;
+            }
+            SettingsMessage::ThemeFileChanged(file) => {
+                self.theme_editor.file = file;
+                self.theme_editor.status = None;
+            }
+            SettingsMessage::EditCurrentTheme => {
+                let name = self.selected_theme.to_string();
+                let file = std::mem::take(&mut self.theme_editor.file);
+                self.theme_editor = ThemeEditor {
+                    file,
+                    ..ThemeEditor::from_palette(&ThemePalette::from_theme(name, &self.selected_theme))
+                };
+            }
+            SettingsMessage::ApplyCustomTheme => {
+                let result = self.theme_editor.palette().map(|palette| {
+                    let message = format!("Applied {}", palette.name);
+                    self.use_custom_theme(palette);
+                    message
+                });
+                self.theme_editor.status = Some(result);
+            }
+            SettingsMessage::SaveCustomTheme => {
+                let result = self.theme_editor.palette().and_then(|palette| {
+                    let path = self.themes_dir.join(custom_theme::file_name(&palette.name));
+                    palette.save(&path).map_err(|e| e.to_string())?;
+                    self.use_custom_theme(palette);
+                    Ok(format!("Saved to {}", path.display()))
+                });
+                self.theme_editor.status = Some(result);
+            }
+            SettingsMessage::ExportTheme => {
+                let result = self.theme_editor.palette().and_then(|palette| {
+                    let path = self.theme_editor.file_path()?;
+                    palette.save(&path).map_err(|e| e.to_string())?;
+                    Ok(format!("Exported to {}", path.display()))
+                });
+                self.theme_editor.status = Some(result);
+            }
+            SettingsMessage::ImportTheme => {
+                let result = self.theme_editor.file_path().and_then(|path| {
+                    let palette = ThemePalette::load(&path).map_err(|e| e.to_string())?;
+                    let message = format!("Imported {}", palette.name);
+                    self.theme_editor = ThemeEditor {
+                        file: self.theme_editor.file.clone(),
+                        ..ThemeEditor::from_palette(&palette)
+                    };
+                    self.use_custom_theme(palette);
+                    Ok(message)
+                });
+                self.theme_editor.status = Some(result);
             }
             SettingsMessage::NotificationsToggled(enabled) => {
                 self.notifications_enabled = enabled;
@@ -62,21 +403,449 @@ impl SettingsPage {
             SettingsMessage::AutoRefreshToggled(enabled) => {
                 self.auto_refresh_enabled = enabled;
             }
+            SettingsMessage::StaleMultipleChanged(multiple) => {
+                self.stale_multiple = multiple;
+            }
+            SettingsMessage::ApiServerToggled(enabled) => {
+                self.api_server_enabled = enabled;
+                self.api_server_status = None;
+            }
+            SettingsMessage::ApiServerPortChanged(input) => {
+                if let Ok(port) = input.trim().parse::<u16>() {
+                    if port != 0 && port != self.api_server_port {
+                        self.api_server_port = port;
+                        self.api_server_status = None;
+                    }
+                }
+                self.api_server_port_input = input;
+            }
+            SettingsMessage::HttpFieldChanged(field, value) => {
+                *self.http_form.field_mut(field) = value;
+                self.http_status = None;
+            }
+            SettingsMessage::ApplyHttpConfig => {
+                self.http_status = Some(self.apply_http_config());
+            }
+            SettingsMessage::ApiPlanChanged(plan) => {
+                self.api_plan = plan;
+                self.api_key_status = None;
+            }
+            SettingsMessage::ApiKeyChanged(key) => {
+                self.api_key_input = key;
+                self.api_key_status = None;
+            }
+            SettingsMessage::SaveApiKey => {
+                self.api_key_status = Some(self.save_api_key());
+            }
+            SettingsMessage::ShortcutChanged(action, input) => {
+                let result = input
+                    .parse::<Shortcut>()
+                    .and_then(|shortcut| self.key_bindings.set(action, shortcut));
+                self.shortcut_error = match result {
+                    Ok(()) => self.save_shortcuts().err().map(|e| (action, e)),
+                    Err(e) => Some((action, e.to_string())),
+                };
+                if let Some((_, text)) = self.shortcut_inputs.iter_mut().find(|(a, _)| *a == action) {
+                    *text = input;
+                }
+            }
             SettingsMessage::ResetToDefaults => {
-                *self = Self::default();
+                // The stored API key is a credential, not a preference: keep it
+                // Saved palettes are files, not preferences: keep offering them
+                let http_defaults = std::mem::take(&mut self.http_defaults);
+                let shortcuts_file = std::mem::take(&mut self.shortcuts_file);
+                *self = Self {
+                    http_form: HttpForm::from_config(&http_defaults),
+                    http_defaults,
+                    custom_themes: std::mem::take(&mut self.custom_themes),
+                    themes_dir: std::mem::take(&mut self.themes_dir),
+                    api_plan: self.api_plan,
+                    api_key_input: std::mem::take(&mut self.api_key_input),
+                    shortcuts_file,
+                    ..Self::default()
+                };
+                if let Err(e) = self.apply_http_config() {
+                    self.http_status = Some(Err(e));
+                }
+                if let Err(e) = self.save_shortcuts() {
+                    warn!("{e}");
+                }
+                self.save_theme_selection();
             }
         }
     }
 
+    /// Offers `palette` in the theme lists (replacing one with the same name)
+    /// and switches to it; while following the OS it takes the matching slot
+    fn use_custom_theme(&mut self, palette: ThemePalette) {
+        let theme = palette.to_theme();
+        if self.follow_system_theme {
+            if palette.is_dark() {
+                self.dark_theme = theme.clone();
+            } else {
+                self.light_theme = theme.clone();
+            }
+        }
+        self.selected_theme = theme;
+        self.custom_themes.retain(|p| p.name != palette.name);
+        self.custom_themes.push(palette);
+        self.custom_themes.sort_by(|a, b| a.name.cmp(&b.name));
+        self.save_theme_selection();
+    }
+
+    /// Switches to the saved choice; names that no longer match a built-in
+    /// theme or saved palette keep the default
+    fn restore_theme(&mut self, selection: &ThemeSelection) {
+        let named = |name: &str| custom_theme::theme_named(name, &self.custom_themes);
+        self.follow_system_theme = selection.follow_system;
+        if let Some(theme) = named(&selection.selected) {
+            self.selected_theme = theme;
+        }
+        if let Some(theme) = named(&selection.light) {
+            self.light_theme = theme;
+        }
+        if let Some(theme) = named(&selection.dark) {
+            self.dark_theme = theme;
+        }
+    }
+
+    /// Records the theme choice next to the palettes so the next launch
+    /// starts with it; an applied palette that was never saved is not found
+    /// again and falls back to the default
+    fn save_theme_selection(&self) {
+        let selection = ThemeSelection {
+            follow_system: self.follow_system_theme,
+            selected: self.selected_theme.to_string(),
+            light: self.light_theme.to_string(),
+            dark: self.dark_theme.to_string(),
+        };
+        if let Err(e) = selection.save(&self.themes_dir) {
+            warn!("Cannot save the theme choice: {e}");
+        }
+    }
+
+    /// Stores the key (or removes it for the public plan) and switches to it
+    fn save_api_key(&self) -> Result<String, String> {
+        let key = ApiKey::new(self.api_plan, &self.api_key_input);
+        if self.api_plan != ApiPlan::Public && key.is_none() {
+            return Err(format!("Enter the key for the {} plan", self.api_plan));
+        }
+
+        let location =
+            secrets::save_api_key(key.as_ref()).map_err(|e| format!("Cannot store key: {e}"))?;
+        let saved = match &key {
+            Some(_) => format!("Saved to {location}"),
+            None => "Key removed; using the public API".to_string(),
+        };
+        api::set_api_key(key);
+        Ok(saved)
+    }
+
+    /// Rebuilds the shared HTTP client from the form if it changed
+    fn apply_http_config(&self) -> Result<(), String> {
+        let config = self.http_form.to_config()?;
+        if config == http_client::config() {
+            return Ok(());
+        }
+        http_client::configure(config).map_err(|e| e.to_string())
+    }
+
+    /// Writes the current shortcuts so the next launch starts with them
+    fn save_shortcuts(&self) -> Result<(), String> {
+        let path = self.shortcuts_file.path();
+        self.shortcuts_file
+            .save(&self.key_bindings)
+            .map_err(|e| format!("Could not save {}: {e}", path.display()))
+    }
+
     // ── View ────────────────────────────────────────────────────
 
     pub fn view(&self) -> iced::Element<'_, SettingsMessage> {
         SettingsView::new(
-            self.auto_refresh_interval,
-            self.auto_refresh_enabled,
-            &self.selected_theme,
-            self.notifications_enabled,
+            GeneralProps {
+                auto_refresh_interval: self.auto_refresh_interval,
+                auto_refresh_enabled: self.auto_refresh_enabled,
+                stale_multiple: self.stale_multiple,
+                notifications_enabled: self.notifications_enabled,
+            },
+            ThemeProps {
+                selected: &self.selected_theme,
+                custom: self.custom_themes.iter().map(ThemePalette::to_theme).collect(),
+                follow_system: self.follow_system_theme,
+                light: &self.light_theme,
+                dark: &self.dark_theme,
+                editor_name: &self.theme_editor.name,
+                editor_colors: &self.theme_editor.colors,
+                preview: self.theme_editor.palette().map(|p| p.to_theme()),
+                file: &self.theme_editor.file,
+                status: self.theme_editor.status.as_ref(),
+            },
+            ApiServerProps {
+                enabled: self.api_server_enabled,
+                port_input: &self.api_server_port_input,
+                status: self.api_server_status.as_ref(),
+            },
+            NetworkProps {
+                proxy: &self.http_form.proxy,
+                proxy_user: &self.http_form.proxy_user,
+                proxy_password: &self.http_form.proxy_password,
+                ca_bundle: &self.http_form.ca_bundle,
+                connect_timeout: &self.http_form.connect_timeout,
+                timeout: &self.http_form.timeout,
+                status: self.http_status.as_ref(),
+            },
+            ApiKeyProps {
+                plan: self.api_plan,
+                key: &self.api_key_input,
+                status: self.api_key_status.as_ref(),
+            },
+            ShortcutProps {
+                inputs: &self.shortcut_inputs,
+                error: self.shortcut_error.as_ref(),
+            },
         )
         .view()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::simulator_with_size;
+
+    #[test]
+    fn updates_and_resets_settings() {
+        let (mut page, _) = page_in_dir("reset");
+        page.update(SettingsMessage::ThemeChanged(Theme::GruvboxDark));
+        page.update(SettingsMessage::AutoRefreshToggled(true));
+        page.update(SettingsMessage::AutoRefreshIntervalChanged(60));
+        assert_eq!(page.theme_for(Mode::None), &Theme::GruvboxDark);
+        assert!(page.auto_refresh_enabled());
+        assert_eq!(page.auto_refresh_interval(), 60);
+
+        page.update(SettingsMessage::ResetToDefaults);
+        assert_eq!(page.theme_for(Mode::None), &Theme::Nord);
+        assert!(!page.auto_refresh_enabled());
+        assert_eq!(page.auto_refresh_interval(), 30);
+    }
+
+    #[test]
+    fn stale_threshold_scales_with_interval() {
+        let mut page = SettingsPage::new();
+        assert_eq!(page.stale_after_secs(), 90);
+        page.update(SettingsMessage::AutoRefreshIntervalChanged(60));
+        page.update(SettingsMessage::StaleMultipleChanged(5));
+        assert_eq!(page.stale_after_secs(), 300);
+    }
+
+    #[test]
+    fn reset_button_emits_reset() {
+        let page = SettingsPage::new();
+        let mut ui = simulator_with_size(iced::Size::new(700.0, 2400.0), page.view());
+        ui.click("Reset to Defaults").expect("reset button");
+
+        let messages: Vec<_> = ui.into_messages().collect();
+        assert!(matches!(messages.as_slice(), [SettingsMessage::ResetToDefaults]));
+    }
+
+    #[test]
+    fn invalid_port_input_is_kept_but_not_applied() {
+        let mut page = SettingsPage::new();
+        page.update(SettingsMessage::ApiServerPortChanged("91x".into()));
+        assert_eq!(page.api_server_port(), server::DEFAULT_PORT);
+
+        page.update(SettingsMessage::ApiServerPortChanged("9100".into()));
+        assert_eq!(page.api_server_port(), 9100);
+    }
+
+    #[test]
+    fn invalid_http_settings_are_reported_and_not_applied() {
+        let mut page = SettingsPage::new();
+        let before = http_client::config();
+
+        page.update(SettingsMessage::HttpFieldChanged(HttpField::ConnectTimeout, "soon".into()));
+        page.update(SettingsMessage::ApplyHttpConfig);
+        assert_eq!(
+            page.http_status,
+            Some(Err("Connect timeout must be a positive number of seconds".into()))
+        );
+
+        page.update(SettingsMessage::HttpFieldChanged(HttpField::ConnectTimeout, "5".into()));
+        page.update(SettingsMessage::HttpFieldChanged(
+            HttpField::CaBundle,
+            "/nonexistent/corp-ca.pem".into(),
+        ));
+        assert!(page.http_status.is_none(), "editing clears the last outcome");
+        page.update(SettingsMessage::ApplyHttpConfig);
+        let Some(Err(message)) = &page.http_status else {
+            panic!("expected an error, got {:?}", page.http_status);
+        };
+        assert!(message.starts_with("cannot read CA bundle /nonexistent/corp-ca.pem"));
+        assert_eq!(http_client::config(), before);
+
+        let mut ui = simulator_with_size(iced::Size::new(700.0, 1600.0), page.view());
+        assert!(ui.find(format!("Failed: {message}")).is_ok());
+    }
+
+    #[test]
+    fn api_key_is_required_for_paid_plans_and_kept_on_reset() {
+        let mut page = SettingsPage::new();
+        page.update(SettingsMessage::ApiPlanChanged(ApiPlan::Pro));
+        page.update(SettingsMessage::SaveApiKey);
+        assert_eq!(
+            page.api_key_status,
+            Some(Err("Enter the key for the Pro plan".into()))
+        );
+        assert_eq!(api::api_key(), None);
+
+        page.update(SettingsMessage::ApiKeyChanged("CG-typed".into()));
+        page.update(SettingsMessage::ResetToDefaults);
+        assert_eq!(page.api_plan, ApiPlan::Pro);
+        assert_eq!(page.api_key_input, "CG-typed");
+
+        let mut ui = simulator_with_size(iced::Size::new(700.0, 1600.0), page.view());
+        assert!(ui.find("CG-typed").is_err(), "key input is masked");
+    }
+
+    #[test]
+    fn invalid_shortcuts_are_reported_and_not_applied() {
+        let (mut page, dir) = page_in_dir("keys");
+        let file = page.shortcuts_file.clone();
+        let defaults = KeyBindings::default();
+
+        page.update(SettingsMessage::ShortcutChanged(Action::Search, "Ctrl+R".into()));
+        assert_eq!(page.key_bindings(), &defaults);
+        assert_eq!(
+            page.shortcut_error,
+            Some((Action::Search, "Already used by \"Refresh prices\"".into()))
+        );
+
+        page.update(SettingsMessage::ShortcutChanged(Action::Search, "Ctrl+/".into()));
+        assert!(page.shortcut_error.is_none());
+        assert_eq!(page.key_bindings().get(Action::Search), Some(Shortcut::ctrl('/')));
+
+        // The next launch starts with the saved shortcut
+        let reopened = SettingsPage::with(file.clone(), dir.join("themes"));
+        assert_eq!(reopened.key_bindings(), page.key_bindings());
+        assert!(reopened
+            .shortcut_inputs
+            .contains(&(Action::Search, "Ctrl+/".to_string())));
+
+        page.update(SettingsMessage::ResetToDefaults);
+        assert_eq!(page.key_bindings(), &defaults);
+        assert!(page
+            .shortcut_inputs
+            .contains(&(Action::Search, "Ctrl+F".to_string())));
+        assert_eq!(file.load().unwrap(), defaults);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn following_the_os_uses_light_and_dark_choices() {
+        let (mut page, dir) = page_in_dir("follow");
+        page.update(SettingsMessage::ThemeChanged(Theme::Dracula));
+        assert_eq!(page.theme_for(Mode::Dark), &Theme::Dracula);
+
+        page.update(SettingsMessage::FollowSystemThemeToggled(true));
+        page.update(SettingsMessage::LightThemeChanged(Theme::SolarizedLight));
+        assert_eq!(page.theme_for(Mode::Light), &Theme::SolarizedLight);
+        assert_eq!(page.theme_for(Mode::Dark), &Theme::Nord);
+        // Until the OS reports a preference, the plain selection applies
+        assert_eq!(page.theme_for(Mode::None), &Theme::Dracula);
+
+        // The next launch starts with the same choice
+        let reopened = SettingsPage::with(page.shortcuts_file.clone(), dir.join("themes"));
+        assert!(reopened.follow_system_theme());
+        for mode in [Mode::Light, Mode::Dark, Mode::None] {
+            assert_eq!(reopened.theme_for(mode), page.theme_for(mode));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Page saving into its own directory, so tests that change the theme or
+    /// shortcuts leave the shared test config dir alone
+    fn page_in_dir(name: &str) -> (SettingsPage, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("ifb-settings-{name}-{}", std::process::id()));
+        let page = SettingsPage::with(
+            ShortcutsFile::new(dir.join("shortcuts.json")),
+            dir.join("themes"),
+        );
+        (page, dir)
+    }
+
+    #[test]
+    fn theme_editor_applies_saves_and_shares_palettes() {
+        let (mut page, dir) = page_in_dir("editor");
+        page.update(SettingsMessage::ThemeNameChanged("Sunset".into()));
+        page.update(SettingsMessage::ThemeColorChanged(ThemeColor::Primary, "#ff8000".into()));
+
+        page.update(SettingsMessage::SaveCustomTheme);
+        let saved = page.themes_dir.join("sunset.json");
+        assert_eq!(
+            page.theme_editor.status,
+            Some(Ok(format!("Saved to {}", saved.display())))
+        );
+        let theme = page.theme_for(Mode::None).clone();
+        assert_eq!(theme.to_string(), "Sunset");
+        assert_eq!(theme.palette().primary, iced::Color::from_rgb8(255, 128, 0));
+        assert_eq!(custom_theme::load_saved(&page.themes_dir).len(), 1);
+
+        // Share: export to a file, then import it into a fresh page
+        let shared = dir.join("shared.json");
+        page.update(SettingsMessage::ThemeFileChanged(shared.display().to_string()));
+        page.update(SettingsMessage::ExportTheme);
+        assert!(matches!(&page.theme_editor.status, Some(Ok(m)) if m.starts_with("Exported")));
+
+        let (mut other, _) = page_in_dir("editor-import");
+        other.update(SettingsMessage::ThemeFileChanged(shared.display().to_string()));
+        other.update(SettingsMessage::ImportTheme);
+        assert_eq!(other.theme_editor.status, Some(Ok("Imported Sunset".into())));
+        assert_eq!(other.theme_for(Mode::None), &theme);
+        assert_eq!(other.theme_editor.name, "Sunset");
+
+        // A saved palette is selected again at the next launch
+        let reopened = SettingsPage::with(page.shortcuts_file.clone(), page.themes_dir.clone());
+        assert_eq!(reopened.theme_for(Mode::None), &theme);
+
+        // Reset keeps saved palettes on offer
+        page.update(SettingsMessage::ResetToDefaults);
+        assert_eq!(page.theme_for(Mode::None), &Theme::Nord);
+        assert_eq!(page.custom_themes.len(), 1);
+        let reopened = SettingsPage::with(page.shortcuts_file.clone(), page.themes_dir.clone());
+        assert_eq!(reopened.theme_for(Mode::None), &Theme::Nord);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_palette_is_reported_and_not_applied() {
+        let (mut page, _) = page_in_dir("invalid");
+        page.update(SettingsMessage::ThemeColorChanged(ThemeColor::Danger, "red".into()));
+        page.update(SettingsMessage::ApplyCustomTheme);
+        assert_eq!(
+            page.theme_editor.status,
+            Some(Err("Danger must be a #rrggbb colour".into()))
+        );
+        assert_eq!(page.theme_for(Mode::None), &Theme::Nord);
+
+        page.update(SettingsMessage::ImportTheme);
+        assert_eq!(page.theme_editor.status, Some(Err("Enter a file path".into())));
+        assert!(!page.themes_dir.exists());
+
+        let mut ui = simulator_with_size(iced::Size::new(700.0, 2400.0), page.view());
+        assert!(ui.find("Danger must be a #rrggbb colour").is_ok());
+    }
+
+    #[test]
+    fn http_form_round_trips_config() {
+        let config = HttpConfig {
+            proxy: Some("http://proxy.corp:3128".into()),
+            proxy_user: Some("alice".into()),
+            proxy_password: Some(" s3cret ".into()),
+            ca_bundle: Some(PathBuf::from("/etc/corp/ca.pem")),
+            connect_timeout: Duration::from_secs(3),
+            timeout: Duration::from_secs(20),
+        };
+        assert_eq!(HttpForm::from_config(&config).to_config(), Ok(config));
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::{HashMap, HashSet};
+
+use iced::Task;
+use tracing::warn;
+
+use crate::esplora::{Balance, EsploraApi, EsploraError};
+use crate::history::PriceHistory;
+use crate::message::WalletMessage;
+use crate::supply::COIN;
+use crate::views::about_view::format_btc;
+use crate::views::network_view::format_count;
+use crate::views::wallet_view::{WalletProps, WalletRow, WalletView};
+use crate::watchlist::{WatchEntry, WatchTarget, WatchlistFile};
+
+/// Characters kept at each end of a shortened extended key
+const KEY_ENDS: usize = 12;
+
+/// Wallet page state: watch-only addresses and keys with their balances
+pub struct WalletPage {
+    api: EsploraApi,
+    file: WatchlistFile,
+    entries: Vec<WatchEntry>,
+    /// By address or key text
+    balances: HashMap<String, Balance>,
+    errors: HashMap<String, EsploraError>,
+    pending: HashSet<String>,
+    loaded: bool,
+    input: String,
+    label_input: String,
+    /// Why the input was not added
+    input_error: Option<String>,
+    /// Why the watchlist could not be saved
+    save_error: Option<String>,
+    /// Lowercase code balances are valued in
+    currency: String,
+}
+
+impl Default for WalletPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WalletPage {
+    /// Saved watchlist and the API chosen by the environment (see
+    /// [`EsploraApi::from_env`])
+    pub fn new() -> Self {
+        Self::with(EsploraApi::from_env(), WatchlistFile::default_location())
+    }
+
+    pub fn with(api: EsploraApi, file: WatchlistFile) -> Self {
+        let entries = file.load().unwrap_or_else(|e| {
+            warn!("Cannot read {}: {e}", file.path().display());
+            Vec::new()
+        });
+        Self {
+            api,
+            file,
+            entries,
+            balances: HashMap::new(),
+            errors: HashMap::new(),
+            pending: HashSet::new(),
+            loaded: false,
+            input: String::new(),
+            label_input: String::new(),
+            input_error: None,
+            save_error: None,
+            currency: "usd".to_string(),
+        }
+    }
+
+    /// Fetches every balance the first time the page is shown
+    pub fn load(&mut self) -> Task<WalletMessage> {
+        if self.loaded {
+            return Task::none();
+        }
+        self.loaded = true;
+        self.fetch_all()
+    }
+
+    fn fetch_all(&mut self) -> Task<WalletMessage> {
+        let targets: Vec<WatchTarget> = self.entries.iter().map(|e| e.target.clone()).collect();
+        Task::batch(targets.into_iter().map(|target| self.fetch(target)))
+    }
+
+    fn fetch(&mut self, target: WatchTarget) -> Task<WalletMessage> {
+        let key = target.to_string();
+        if !self.pending.insert(key.clone()) {
+            return Task::none();
+        }
+        self.errors.remove(&key);
+        let api = self.api.clone();
+        Task::perform(
+            async move { api.fetch_balance(&target).await },
+            move |result| match result {
+                Ok(balance) => WalletMessage::Loaded(key.clone(), balance),
+                Err(e) => WalletMessage::Failed(key.clone(), e),
+            },
+        )
+    }
+
+    pub fn update(&mut self, message: WalletMessage) -> Task<WalletMessage> {
+        match message {
+            WalletMessage::InputChanged(input) => {
+                self.input = input;
+                self.input_error = None;
+                Task::none()
+            }
+            WalletMessage::LabelChanged(label) => {
+                self.label_input = label;
+                Task::none()
+            }
+            WalletMessage::Add => {
+                let target = match WatchTarget::parse(&self.input) {
+                    Ok(target) => target,
+                    Err(e) => {
+                        self.input_error = Some(e);
+                        return Task::none();
+                    }
+                };
+                if self.entries.iter().any(|e| e.target == target) {
+                    self.input_error = Some("Already on the watchlist".to_string());
+                    return Task::none();
+                }
+                self.entries.push(WatchEntry {
+                    label: self.label_input.trim().to_string(),
+                    target: target.clone(),
+                });
+                self.input.clear();
+                self.label_input.clear();
+                self.save();
+                self.fetch(target)
+            }
+            WalletMessage::Remove(key) => {
+                self.entries.retain(|e| e.target.to_string() != key);
+                self.balances.remove(&key);
+                self.errors.remove(&key);
+                self.save();
+                Task::none()
+            }
+            WalletMessage::Refresh => self.fetch_all(),
+            WalletMessage::CurrencySelected(currency) => {
+                self.currency = currency.to_lowercase();
+                Task::none()
+            }
+            WalletMessage::Loaded(key, balance) => {
+                self.pending.remove(&key);
+                self.balances.insert(key, balance);
+                Task::none()
+            }
+            WalletMessage::Failed(key, error) => {
+                self.pending.remove(&key);
+                self.errors.insert(key, error);
+                Task::none()
+            }
+        }
+    }
+
+    fn save(&mut self) {
+        self.save_error = self
+            .file
+            .save(&self.entries)
+            .err()
+            .map(|e| format!("Could not save {}: {e}", self.file.path().display()));
+    }
+
+    /// Balances valued at the latest price in `history`
+    pub fn view(&self, history: &PriceHistory) -> iced::Element<'_, WalletMessage> {
+        let prices = history.samples().next_back().map(|s| &s.prices);
+        let mut currencies: Vec<String> = prices
+            .map(|p| p.keys().map(|c| c.to_uppercase()).collect())
+            .unwrap_or_default();
+        currencies.sort();
+        let price = prices.and_then(|p| p.get(&self.currency)).copied();
+        let code = self.currency.to_uppercase();
+        let fiat =
+            |sats: u64| price.map(|p| format!("{:.2} {code}", sats as f64 / COIN as f64 * p));
+
+        let rows = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let key = entry.target.to_string();
+                let balance = self.balances.get(&key);
+                let detail = match (self.errors.get(&key), balance) {
+                    (Some(e), _) => e.to_string(),
+                    _ if self.pending.contains(&key) => "Loading…".to_string(),
+                    (None, Some(b)) => describe(&entry.target, b),
+                    (None, None) => String::new(),
+                };
+                WalletRow {
+                    label: entry.label.clone(),
+                    target: shorten(&key),
+                    kind: entry.target.kind_label(),
+                    btc: balance.map(|b| format!("{} BTC", format_btc(b.total()))),
+                    fiat: balance.and_then(|b| fiat(b.total())),
+                    detail,
+                    key,
+                }
+            })
+            .collect();
+
+        let total: u64 = self.balances.values().map(Balance::total).sum();
+        let notice = if self.entries.is_empty() {
+            Some("Add an address or an xpub, ypub or zpub to watch its balance".to_string())
+        } else if price.is_none() {
+            Some(format!("Waiting for a {code} price to value balances"))
+        } else {
+            None
+        };
+
+        WalletView::new(WalletProps {
+            input: self.input.clone(),
+            label_input: self.label_input.clone(),
+            input_error: self.input_error.clone().or_else(|| self.save_error.clone()),
+            notice,
+            source: format!("Balances from {}", self.api.base_url()),
+            loading: !self.pending.is_empty(),
+            currencies,
+            currency: price.map(|_| code.clone()),
+            rows,
+            total: (!self.balances.is_empty()).then(|| match fiat(total) {
+                Some(value) => format!("Total {} BTC ≈ {value}", format_btc(total)),
+                None => format!("Total {} BTC", format_btc(total)),
+            }),
+        })
+        .view()
+    }
+}
+
+/// Transaction count, pending change and, for keys, addresses in use
+fn describe(target: &WatchTarget, balance: &Balance) -> String {
+    let mut parts = vec![match balance.tx_count {
+        1 => "1 transaction".to_string(),
+        n => format!("{} transactions", format_count(n)),
+    }];
+    if let WatchTarget::Xpub(_) = target {
+        parts.push(format!("{} addresses used", balance.used_addresses));
+    }
+    if balance.unconfirmed != 0 {
+        let sign = if balance.unconfirmed > 0 { '+' } else { '−' };
+        parts.push(format!(
+            "{sign}{} BTC unconfirmed",
+            format_btc(balance.unconfirmed.unsigned_abs())
+        ));
+    }
+    parts.join(" · ")
+}
+
+/// Extended keys are too long for a row; keep both ends
+fn shorten(text: &str) -> String {
+    if text.len() <= 2 * KEY_ENDS + 1 || !crate::xpub::is_extended_key(text) {
+        return text.to_string();
+    }
+    format!("{}…{}", &text[..KEY_ENDS], &text[text.len() - KEY_ENDS..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::PriceSample;
+    use crate::testing::{assert_snapshot, simulator};
+
+    const ADDRESS: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+    const ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    fn page(name: &str) -> WalletPage {
+        let nanos = std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("ifb-wallet-{name}-{}-{nanos}", std::process::id()));
+        WalletPage::with(
+            EsploraApi::new("http://127.0.0.1:3002/api").unwrap(),
+            WatchlistFile::new(dir.join("watchlist.json")),
+        )
+    }
+
+    fn history() -> PriceHistory {
+        let mut history = PriceHistory::with_capacity(4);
+        history.push(PriceSample {
+            timestamp: 1_700_000_000,
+            prices: HashMap::from([("usd".to_string(), 60_000.0), ("eur".to_string(), 55_000.0)]),
+        });
+        history
+    }
+
+    fn add(page: &mut WalletPage, input: &str, label: &str) -> usize {
+        let _ = page.update(WalletMessage::InputChanged(input.into()));
+        let _ = page.update(WalletMessage::LabelChanged(label.into()));
+        page.update(WalletMessage::Add).units()
+    }
+
+    #[test]
+    fn entries_are_validated_saved_and_removed() {
+        let mut page = page("edit");
+        assert_eq!(
+            add(&mut page, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5", ""),
+            0
+        );
+        assert_eq!(
+            page.input_error.as_deref(),
+            Some("Checksum does not match; check for typos")
+        );
+
+        assert_eq!(add(&mut page, ADDRESS, "Donations"), 1);
+        assert_eq!(add(&mut page, &ADDRESS.to_uppercase(), ""), 0);
+        assert_eq!(
+            page.input_error.as_deref(),
+            Some("Already on the watchlist")
+        );
+        assert_eq!(add(&mut page, ZPUB, " Savings "), 1);
+        assert_eq!(page.file.load().unwrap(), page.entries);
+        assert_eq!(page.entries[1].label, "Savings");
+
+        let _ = page.update(WalletMessage::Remove(ADDRESS.into()));
+        assert_eq!(page.file.load().unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(page.file.path().parent().unwrap());
+    }
+
+    #[test]
+    fn balances_are_valued_in_the_selected_currency() {
+        let mut page = page("value");
+        let _ = add(&mut page, ADDRESS, "Donations");
+        let _ = add(&mut page, ZPUB, "Savings");
+        assert_eq!(page.load().units(), 0, "both already fetching");
+        let _ = page.update(WalletMessage::Loaded(
+            ADDRESS.into(),
+            Balance {
+                confirmed: 150_000,
+                unconfirmed: -50_000,
+                tx_count: 4,
+                used_addresses: 1,
+            },
+        ));
+        let _ = page.update(WalletMessage::Loaded(
+            ZPUB.into(),
+            Balance {
+                confirmed: 250_000,
+                unconfirmed: 30_000,
+                tx_count: 5,
+                used_addresses: 3,
+            },
+        ));
+        let _ = page.update(WalletMessage::CurrencySelected("EUR".into()));
+
+        let history = history();
+        let mut ui = simulator(page.view(&history));
+        assert!(ui.find("0.001 BTC").is_ok());
+        assert!(ui.find("55.00 EUR").is_ok());
+        assert!(ui.find("4 transactions · −0.0005 BTC unconfirmed").is_ok());
+        assert!(ui.find("zpub6rFR7y4Q…z2oz2AGutZYs").is_ok());
+        assert!(ui.find("Total 0.0038 BTC ≈ 209.00 EUR").is_ok());
+        assert_snapshot(&mut ui, &iced::Theme::Nord, "wallet_page");
+        let _ = std::fs::remove_dir_all(page.file.path().parent().unwrap());
+    }
+
+    #[test]
+    fn failures_are_shown_per_entry() {
+        let mut page = page("fail");
+        let _ = add(&mut page, ADDRESS, "");
+        let _ = page.update(WalletMessage::Failed(
+            ADDRESS.into(),
+            EsploraError::HttpStatus {
+                url: format!("http://127.0.0.1:3002/api/address/{ADDRESS}"),
+                status: 429,
+            },
+        ));
+        let history = PriceHistory::with_capacity(1);
+        let mut ui = simulator(page.view(&history));
+        assert!(ui
+            .find(format!(
+                "HTTP 429 from http://127.0.0.1:3002/api/address/{ADDRESS}"
+            ))
+            .is_ok());
+        assert!(ui.find("Waiting for a USD price to value balances").is_ok());
+        let _ = std::fs::remove_dir_all(page.file.path().parent().unwrap());
+    }
+}
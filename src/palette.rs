@@ -0,0 +1,208 @@
+use crate::country::Country;
+use crate::shortcuts::{Action, KeyBindings};
+
+/// Maximum number of results shown at once
+const MAX_RESULTS: usize = 8;
+
+/// Something the command palette can run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteItem {
+    Action(Action),
+    /// Show the Bitcoin page filtered to this currency code
+    Currency {
+        currency: String,
+        name: String,
+    },
+}
+
+impl PaletteItem {
+    /// Text the query is matched against
+    pub fn label(&self) -> String {
+        match self {
+            PaletteItem::Action(action) => action.label(),
+            PaletteItem::Currency { currency, name } => {
+                format!("BTC/{} — {name}", currency.to_uppercase())
+            }
+        }
+    }
+}
+
+/// Scores how well `query` fuzzy-matches `text`; `None` when it does not.
+///
+/// Every query character must appear in order (case-insensitive). Matches
+/// at word starts and runs of consecutive characters score higher, gaps
+/// and late first matches lower.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for q in query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+    {
+        let found = next + text[next..].iter().position(|&c| c == q)?;
+        let word_start = found == 0 || !text[found - 1].is_alphanumeric();
+        score += match previous {
+            Some(p) if p + 1 == found => 8,
+            _ if word_start => 6,
+            Some(p) => 1 - (found - p) as i32 / 4,
+            None => 1 - found as i32 / 4,
+        };
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// Command palette state: the query and the highlighted result
+#[derive(Debug, Clone)]
+pub struct CommandPalette {
+    items: Vec<PaletteItem>,
+    query: String,
+    /// Indices into `items`, best match first
+    results: Vec<usize>,
+    selected: usize,
+}
+
+impl CommandPalette {
+    /// Palette over every action and every catalogue currency
+    pub fn new(countries: &[Country]) -> Self {
+        let mut items: Vec<PaletteItem> =
+            Action::all().into_iter().map(PaletteItem::Action).collect();
+        items.extend(countries.iter().map(|c| PaletteItem::Currency {
+            currency: c.currency.clone(),
+            name: c.name.clone(),
+        }));
+        let mut palette = Self {
+            items,
+            query: String::new(),
+            results: Vec::new(),
+            selected: 0,
+        };
+        palette.refresh();
+        palette
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.refresh();
+    }
+
+    /// Matching items, best first
+    pub fn results(&self) -> impl Iterator<Item = &PaletteItem> {
+        self.results.iter().map(|&i| &self.items[i])
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Moves the highlight by `delta`, wrapping around
+    pub fn move_selection(&mut self, delta: isize) {
+        if !self.results.is_empty() {
+            let len = self.results.len() as isize;
+            self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// The `index`-th result, or the highlighted one
+    pub fn pick(&self, index: Option<usize>) -> Option<&PaletteItem> {
+        let i = *self.results.get(index.unwrap_or(self.selected))?;
+        Some(&self.items[i])
+    }
+
+    /// Shortcut hint shown next to an item
+    pub fn hint(item: &PaletteItem, bindings: &KeyBindings) -> Option<String> {
+        match item {
+            PaletteItem::Action(action) => bindings.get(*action).map(|s| s.to_string()),
+            PaletteItem::Currency { .. } => None,
+        }
+    }
+
+    fn refresh(&mut self) {
+        let mut scored: Vec<(i32, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| Some((fuzzy_score(&self.query, &item.label())?, i)))
+            .collect();
+        // Stable sort keeps catalogue order among equal scores
+        scored.sort_by_key(|(score, _)| -score);
+        self.results = scored
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, i)| i)
+            .collect();
+        self.selected = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::country::default_catalogue;
+    use crate::route::Route;
+
+    #[test]
+    fn fuzzy_matching_prefers_word_starts_and_runs() {
+        assert!(fuzzy_score("xyz", "Refresh prices").is_none());
+        assert!(fuzzy_score("", "anything").is_some());
+
+        let refresh = fuzzy_score("rp", "Refresh prices").unwrap();
+        let scattered = fuzzy_score("rp", "Search currencies ... p").unwrap();
+        assert!(refresh > scattered);
+
+        let run = fuzzy_score("set", "Open settings").unwrap();
+        let gaps = fuzzy_score("set", "Search currencies ... t").unwrap();
+        assert!(run > gaps);
+    }
+
+    #[test]
+    fn palette_finds_actions_and_currencies() {
+        let mut palette = CommandPalette::new(&default_catalogue());
+        assert_eq!(palette.results().count(), MAX_RESULTS);
+
+        palette.set_query("go ab".into());
+        assert_eq!(
+            palette.pick(None),
+            Some(&PaletteItem::Action(Action::Navigate(Route::About)))
+        );
+
+        palette.set_query("gbp".into());
+        assert!(matches!(
+            palette.pick(None),
+            Some(PaletteItem::Currency { currency, .. }) if currency == "gbp"
+        ));
+
+        palette.set_query("japan".into());
+        assert_eq!(palette.pick(None).unwrap().label(), "BTC/JPY — Japan");
+    }
+
+    #[test]
+    fn selection_wraps_and_resets_on_query() {
+        let mut palette = CommandPalette::new(&default_catalogue());
+        palette.move_selection(-1);
+        assert_eq!(palette.selected(), MAX_RESULTS - 1);
+        palette.move_selection(1);
+        assert_eq!(palette.selected(), 0);
+
+        palette.move_selection(2);
+        palette.set_query("refresh".into());
+        assert_eq!(palette.selected(), 0);
+        assert_eq!(
+            palette.pick(None),
+            Some(&PaletteItem::Action(Action::Refresh))
+        );
+
+        palette.set_query("qqqq".into());
+        palette.move_selection(1);
+        assert_eq!(palette.pick(None), None);
+    }
+}
@@ -0,0 +1,40 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Overrides the per-user configuration directory (portable installs)
+#[cfg_attr(test, allow(dead_code))]
+pub const CONFIG_DIR_ENV: &str = "IFB_CONFIG_DIR";
+
+/// Per-user configuration directory, e.g. `~/.config/iced-fetch-bitcoin`.
+/// Falls back to the working directory when the platform has none.
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = config_dir_override().filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join("iced-fetch-bitcoin"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[cfg(not(test))]
+fn config_dir_override() -> Option<OsString> {
+    std::env::var_os(CONFIG_DIR_ENV)
+}
+
+/// Tests share a scratch directory instead of the developer's settings
+#[cfg(test)]
+fn config_dir_override() -> Option<OsString> {
+    Some(crate::testing::config_dir().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_never_read_the_users_config_dir() {
+        let dir = config_dir();
+        assert!(dir.starts_with(std::env::temp_dir()), "{}", dir.display());
+        assert_eq!(dir, config_dir(), "one directory for the whole run");
+    }
+}
@@ -1,32 +1,62 @@
+use serde::{Deserialize, Serialize};
+
 /// Application routes for navigation between pages
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Route {
     /// Bitcoin price monitoring page
+    #[default]
     Bitcoin,
+    /// Price chart with technical indicators
+    Chart,
+    /// OHLC candlesticks with volume
+    Candles,
+    /// Local premiums against reference FX rates
+    Markets,
+    /// Block height, fee rates and mempool
+    Network,
+    /// Transaction size and fee calculator
+    Fees,
+    /// Watch-only address and xpub balances
+    Wallet,
+    /// Where alerts are delivered
+    Alerts,
     /// Application settings page
     Settings,
     /// About page
     About,
 }
 
-impl Default for Route {
-    fn default() -> Self {
-        Route::Bitcoin
-    }
-}
-
 impl Route {
     /// Get the display name for the route
     pub fn display_name(&self) -> &'static str {
         match self {
             Route::Bitcoin => "Bitcoin Prices",
+            Route::Chart => "Chart",
+            Route::Candles => "Candles",
+            Route::Markets => "Markets",
+            Route::Network => "Network",
+            Route::Fees => "Fees",
+            Route::Wallet => "Wallet",
+            Route::Alerts => "Alerts",
             Route::Settings => "Settings", 
             Route::About => "About",
         }
     }
 
     /// Get all available routes
-    pub fn all() -> [Route; 3] {
-        [Route::Bitcoin, Route::Settings, Route::About]
+    pub fn all() -> [Route; 10] {
+        [
+            Route::Bitcoin,
+            Route::Chart,
+            Route::Candles,
+            Route::Markets,
+            Route::Network,
+            Route::Fees,
+            Route::Wallet,
+            Route::Alerts,
+            Route::Settings,
+            Route::About,
+        ]
     }
 }
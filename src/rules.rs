@@ -0,0 +1,1145 @@
+//! Alert rules: a small expression language over the recorded prices.
+//!
+//! ```text
+//! usd > 100000 and change_24h > 5%
+//! krw premium > 3% for 10m
+//! (eur change_1h < -2% or gbp < 50000) and not jpy > 15000000
+//! ```
+//!
+//! A currency code stands for its BTC price. `<code> change_<window>` is the
+//! percentage move over the window (of USD when the code is left out) and
+//! `<code> premium` the local premium against the reference FX rates. A
+//! trailing `for <duration>` makes the condition hold that long before the
+//! rule fires.
+//!
+//! Rules are parsed, then type-checked (numbers, percentages and conditions
+//! never mix), then evaluated sample by sample over a [`PriceHistory`].
+//! Values that cannot be known yet, such as a 24h change with less than a
+//! day of history, are unknown rather than zero and never fire a rule.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::country::get_countries;
+use crate::fx::FxRates;
+use crate::history::{PriceHistory, PriceSample};
+use crate::paths;
+
+/// Name of the rules file inside [`paths::config_dir`]
+const RULES_FILE: &str = "rules.json";
+
+/// Currency of `change_<window>` without a code in front
+const DEFAULT_CURRENCY: &str = "usd";
+
+/// Byte range of the rule text an error points at
+pub type Span = Range<usize>;
+
+/// Why a rule does not parse or type-check
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message}")]
+pub struct RuleError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl RuleError {
+    fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// `source` with carets under the offending part, followed by the message
+    pub fn render(&self, source: &str) -> String {
+        let start = source[..self.span.start].chars().count();
+        let width = source[self.span.clone()].chars().count().max(1);
+        format!(
+            "{source}\n{}{} {}",
+            " ".repeat(start),
+            "^".repeat(width),
+            self.message
+        )
+    }
+}
+
+// ── Lexer ───────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    /// As a fraction: `5%` is 0.05
+    Percent(f64),
+    /// In seconds
+    Duration(u64),
+    /// Lowercased
+    Ident(String),
+    Op(BinOp),
+    Minus,
+    LParen,
+    RParen,
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(_) | Token::Percent(_) => f.write_str("a number"),
+            Token::Duration(_) => f.write_str("a duration"),
+            Token::Ident(name) => write!(f, "`{name}`"),
+            Token::Op(op) => write!(f, "`{op}`"),
+            Token::Minus => f.write_str("`-`"),
+            Token::LParen => f.write_str("`(`"),
+            Token::RParen => f.write_str("`)`"),
+            Token::End => f.write_str("the end of the rule"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Lexed {
+    token: Token,
+    span: Span,
+}
+
+/// Seconds in one `s`, `m`, `h` or `d`
+fn unit_seconds(unit: &str) -> Option<u64> {
+    match unit {
+        "s" => Some(1),
+        "m" => Some(60),
+        "h" => Some(3_600),
+        "d" => Some(86_400),
+        _ => None,
+    }
+}
+
+/// `10m`, `24h` and the like, in seconds
+fn parse_duration(text: &str) -> Option<u64> {
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = text.split_at(split);
+    count.parse::<u64>().ok()?.checked_mul(unit_seconds(unit)?)
+}
+
+fn lex(source: &str) -> Result<Vec<Lexed>, RuleError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let token = if c.is_ascii_digit()
+            || (c == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+        {
+            while i < bytes.len()
+                && (bytes[i].is_ascii_digit() || bytes[i] == b'_' || bytes[i] == b'.')
+            {
+                i += 1;
+            }
+            let digits = source[start..i].replace('_', "");
+            let value: f64 = digits.parse().map_err(|_| {
+                RuleError::new(start..i, format!("`{}` is not a number", &source[start..i]))
+            })?;
+            if bytes.get(i) == Some(&b'%') {
+                i += 1;
+                Token::Percent(value / 100.0)
+            } else if bytes.get(i).is_some_and(u8::is_ascii_alphabetic) {
+                while i < bytes.len() && bytes[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                let text = &source[start..i];
+                Token::Duration(parse_duration(text).ok_or_else(|| {
+                    RuleError::new(
+                        start..i,
+                        format!(
+                            "`{text}` is not a number or a duration; durations end in s, m, h or d"
+                        ),
+                    )
+                })?)
+            } else {
+                Token::Number(value)
+            }
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            match source[start..i].to_lowercase() {
+                word if word == "and" => Token::Op(BinOp::And),
+                word if word == "or" => Token::Op(BinOp::Or),
+                word => Token::Ident(word),
+            }
+        } else {
+            let two = source.get(i..i + 2).unwrap_or("");
+            let (token, len) = match (two, c) {
+                (">=", _) => (Token::Op(BinOp::Cmp(Cmp::Ge)), 2),
+                ("<=", _) => (Token::Op(BinOp::Cmp(Cmp::Le)), 2),
+                ("==", _) => (Token::Op(BinOp::Cmp(Cmp::Eq)), 2),
+                ("!=", _) => (Token::Op(BinOp::Cmp(Cmp::Ne)), 2),
+                ("&&", _) => return Err(RuleError::new(i..i + 2, "use `and` instead of `&&`")),
+                ("||", _) => return Err(RuleError::new(i..i + 2, "use `or` instead of `||`")),
+                (_, b'>') => (Token::Op(BinOp::Cmp(Cmp::Gt)), 1),
+                (_, b'<') => (Token::Op(BinOp::Cmp(Cmp::Lt)), 1),
+                (_, b'+') => (Token::Op(BinOp::Add), 1),
+                (_, b'-') => (Token::Minus, 1),
+                (_, b'*') => (Token::Op(BinOp::Mul), 1),
+                (_, b'/') => (Token::Op(BinOp::Div), 1),
+                (_, b'(') => (Token::LParen, 1),
+                (_, b')') => (Token::RParen, 1),
+                (_, b'=') => return Err(RuleError::new(i..i + 1, "use `==` to compare")),
+                (_, b'!') => return Err(RuleError::new(i..i + 1, "use `not` instead of `!`")),
+                _ => {
+                    let ch = source[i..].chars().next().unwrap_or_default();
+                    return Err(RuleError::new(
+                        i..i + ch.len_utf8(),
+                        format!("unexpected character `{ch}`"),
+                    ));
+                }
+            };
+            i += len;
+            token
+        };
+        tokens.push(Lexed {
+            token,
+            span: start..i,
+        });
+    }
+    tokens.push(Lexed {
+        token: Token::End,
+        span: source.len()..source.len(),
+    });
+    Ok(tokens)
+}
+
+// ── Syntax tree and parser ──────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinOp {
+    And,
+    Or,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Cmp(Cmp),
+}
+
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BinOp::And => "and",
+            BinOp::Or => "or",
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Cmp(Cmp::Lt) => "<",
+            BinOp::Cmp(Cmp::Le) => "<=",
+            BinOp::Cmp(Cmp::Gt) => ">",
+            BinOp::Cmp(Cmp::Ge) => ">=",
+            BinOp::Cmp(Cmp::Eq) => "==",
+            BinOp::Cmp(Cmp::Ne) => "!=",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Percent(f64),
+    /// BTC price in a currency
+    Price(String),
+    /// Move of the price over `window` seconds
+    Change {
+        currency: String,
+        window: u64,
+    },
+    /// Local premium against the reference FX rate
+    Premium(String),
+    Neg(Box<Node>),
+    Not(Box<Node>),
+    Binary(BinOp, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    expr: Expr,
+    span: Span,
+}
+
+impl Node {
+    fn binary(op: BinOp, lhs: Node, rhs: Node) -> Self {
+        Self {
+            span: lhs.span.start..rhs.span.end,
+            expr: Expr::Binary(op, Box::new(lhs), Box::new(rhs)),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Lexed>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Lexed {
+        &self.tokens[self.pos]
+    }
+
+    fn advance(&mut self) -> Lexed {
+        let lexed = self.tokens[self.pos].clone();
+        if lexed.token != Token::End {
+            self.pos += 1;
+        }
+        lexed
+    }
+
+    fn at_word(&self, word: &str) -> bool {
+        matches!(&self.peek().token, Token::Ident(w) if w == word)
+    }
+
+    /// `condition [for duration]`
+    fn rule(&mut self) -> Result<(Node, Option<u64>), RuleError> {
+        if self.peek().token == Token::End {
+            return Err(RuleError::new(
+                self.peek().span.clone(),
+                "the rule is empty",
+            ));
+        }
+        let condition = self.or()?;
+        let mut hold = None;
+        if self.at_word("for") {
+            let keyword = self.advance();
+            let duration = self.advance();
+            hold = Some(match duration.token {
+                Token::Duration(0) => {
+                    return Err(RuleError::new(
+                        duration.span,
+                        "the duration must be longer than zero",
+                    ))
+                }
+                Token::Duration(secs) => secs,
+                Token::Number(_) => {
+                    return Err(RuleError::new(
+                        duration.span,
+                        "the duration needs a unit, such as 10m or 2h",
+                    ))
+                }
+                Token::End => {
+                    return Err(RuleError::new(
+                        keyword.span,
+                        "`for` needs a duration after it, such as 10m",
+                    ))
+                }
+                other => {
+                    return Err(RuleError::new(
+                        duration.span,
+                        format!("expected a duration such as 10m, found {other}"),
+                    ))
+                }
+            });
+        }
+        let next = self.peek().clone();
+        match next.token {
+            Token::End => Ok((condition, hold)),
+            Token::RParen => Err(RuleError::new(next.span, "this `)` has no matching `(`")),
+            _ if hold.is_some() => Err(RuleError::new(
+                next.span,
+                format!(
+                    "expected the end of the rule after the duration, found {}",
+                    next.token
+                ),
+            )),
+            other => Err(RuleError::new(
+                next.span,
+                format!("expected `and`, `or`, `for` or the end of the rule, found {other}"),
+            )),
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, RuleError> {
+        let mut lhs = self.and()?;
+        while self.peek().token == Token::Op(BinOp::Or) {
+            self.advance();
+            lhs = Node::binary(BinOp::Or, lhs, self.and()?);
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Node, RuleError> {
+        let mut lhs = self.not()?;
+        while self.peek().token == Token::Op(BinOp::And) {
+            self.advance();
+            lhs = Node::binary(BinOp::And, lhs, self.not()?);
+        }
+        Ok(lhs)
+    }
+
+    fn not(&mut self) -> Result<Node, RuleError> {
+        if self.at_word("not") {
+            let keyword = self.advance();
+            let inner = self.not()?;
+            return Ok(Node {
+                span: keyword.span.start..inner.span.end,
+                expr: Expr::Not(Box::new(inner)),
+            });
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Node, RuleError> {
+        let lhs = self.sum()?;
+        let Token::Op(op @ BinOp::Cmp(_)) = self.peek().token else {
+            return Ok(lhs);
+        };
+        self.advance();
+        let node = Node::binary(op, lhs, self.sum()?);
+        if let Token::Op(BinOp::Cmp(_)) = self.peek().token {
+            return Err(RuleError::new(
+                self.peek().span.clone(),
+                "comparisons cannot be chained; join them with `and`",
+            ));
+        }
+        Ok(node)
+    }
+
+    fn sum(&mut self) -> Result<Node, RuleError> {
+        let mut lhs = self.product()?;
+        loop {
+            let op = match self.peek().token {
+                Token::Op(BinOp::Add) => BinOp::Add,
+                Token::Minus => BinOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.advance();
+            lhs = Node::binary(op, lhs, self.product()?);
+        }
+    }
+
+    fn product(&mut self) -> Result<Node, RuleError> {
+        let mut lhs = self.unary()?;
+        while let Token::Op(op @ (BinOp::Mul | BinOp::Div)) = self.peek().token {
+            self.advance();
+            lhs = Node::binary(op, lhs, self.unary()?);
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Node, RuleError> {
+        if self.peek().token == Token::Minus {
+            let minus = self.advance();
+            let inner = self.unary()?;
+            return Ok(Node {
+                span: minus.span.start..inner.span.end,
+                expr: Expr::Neg(Box::new(inner)),
+            });
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Node, RuleError> {
+        let lexed = self.advance();
+        let expr = match lexed.token {
+            Token::Number(n) => Expr::Number(n),
+            Token::Percent(p) => Expr::Percent(p),
+            Token::Duration(_) => {
+                return Err(RuleError::new(
+                    lexed.span,
+                    "a duration can only follow `for`, as in `for 10m`",
+                ))
+            }
+            Token::LParen => {
+                let inner = self.or()?;
+                if self.peek().token != Token::RParen {
+                    return Err(RuleError::new(lexed.span, "this `(` is never closed"));
+                }
+                let close = self.advance();
+                // A bare value keeps its own span, so errors about a currency
+                // point at its code rather than at the `(`
+                if !matches!(inner.expr, Expr::Neg(_) | Expr::Not(_) | Expr::Binary(..)) {
+                    return Ok(inner);
+                }
+                return Ok(Node {
+                    span: lexed.span.start..close.span.end,
+                    expr: inner.expr,
+                });
+            }
+            Token::Ident(word) => return self.named(word, lexed.span),
+            Token::End => {
+                let message = match self.pos.checked_sub(1).map(|i| &self.tokens[i].token) {
+                    Some(previous) => format!("expected a value after {previous}"),
+                    None => "expected a value".to_string(),
+                };
+                return Err(RuleError::new(lexed.span, message));
+            }
+            other => {
+                return Err(RuleError::new(
+                    lexed.span,
+                    format!("expected a value, found {other}"),
+                ))
+            }
+        };
+        Ok(Node {
+            expr,
+            span: lexed.span,
+        })
+    }
+
+    /// A currency with an optional `premium` or `change_<window>` after it,
+    /// or a bare `change_<window>`
+    fn named(&mut self, word: String, span: Span) -> Result<Node, RuleError> {
+        match word.as_str() {
+            "not" | "for" => {
+                return Err(RuleError::new(
+                    span,
+                    format!("expected a value, found `{word}`"),
+                ))
+            }
+            "premium" => {
+                return Err(RuleError::new(
+                    span,
+                    "`premium` needs a currency in front, as in `krw premium`",
+                ))
+            }
+            _ => {}
+        }
+        if word.starts_with("change_") {
+            let window = change_window(&word, &span)?;
+            return Ok(Node {
+                expr: Expr::Change {
+                    currency: DEFAULT_CURRENCY.to_string(),
+                    window,
+                },
+                span,
+            });
+        }
+
+        let metric = match &self.peek().token {
+            Token::Ident(next) if next == "premium" || next.starts_with("change_") => next.clone(),
+            _ => {
+                return Ok(Node {
+                    expr: Expr::Price(word),
+                    span,
+                })
+            }
+        };
+        let metric_span = self.advance().span;
+        let expr = if metric == "premium" {
+            Expr::Premium(word)
+        } else {
+            Expr::Change {
+                currency: word,
+                window: change_window(&metric, &metric_span)?,
+            }
+        };
+        Ok(Node {
+            expr,
+            span: span.start..metric_span.end,
+        })
+    }
+}
+
+/// Window of `change_1h`, `change_24h`, …
+fn change_window(word: &str, span: &Span) -> Result<u64, RuleError> {
+    word.strip_prefix("change_")
+        .and_then(parse_duration)
+        .filter(|&secs| secs > 0)
+        .ok_or_else(|| {
+            RuleError::new(
+                span.clone(),
+                format!("`{word}` needs a window such as change_1h or change_24h"),
+            )
+        })
+}
+
+// ── Type checker ────────────────────────────────────────────────
+
+/// Kind of value an expression produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Number,
+    Percent,
+    Condition,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Type::Number => "a number",
+            Type::Percent => "a percentage",
+            Type::Condition => "a condition",
+        })
+    }
+}
+
+fn check(node: &Node, known: &dyn Fn(&str) -> bool) -> Result<Type, RuleError> {
+    let currency = |code: &str| {
+        if known(code) {
+            Ok(())
+        } else {
+            Err(RuleError::new(
+                node.span.start..node.span.start + code.len(),
+                format!("unknown currency `{code}`"),
+            ))
+        }
+    };
+    match &node.expr {
+        Expr::Number(_) => Ok(Type::Number),
+        Expr::Percent(_) => Ok(Type::Percent),
+        Expr::Price(code) => currency(code).map(|_| Type::Number),
+        Expr::Change { currency: code, .. } => currency(code).map(|_| Type::Percent),
+        Expr::Premium(code) => {
+            currency(code)?;
+            if code == "usd" {
+                return Err(RuleError::new(
+                    node.span.clone(),
+                    "USD is the reference currency, so its premium is always 0%",
+                ));
+            }
+            Ok(Type::Percent)
+        }
+        Expr::Neg(inner) => match check(inner, known)? {
+            Type::Condition => Err(RuleError::new(
+                node.span.clone(),
+                "cannot negate a condition; use `not`",
+            )),
+            ty => Ok(ty),
+        },
+        Expr::Not(inner) => match check(inner, known)? {
+            Type::Condition => Ok(Type::Condition),
+            ty => Err(RuleError::new(
+                inner.span.clone(),
+                format!("`not` needs a condition, but this is {ty}"),
+            )),
+        },
+        Expr::Binary(op, lhs, rhs) => {
+            let (lt, rt) = (check(lhs, known)?, check(rhs, known)?);
+            check_binary(*op, (lhs, lt), (rhs, rt), &node.span)
+        }
+    }
+}
+
+fn check_binary(
+    op: BinOp,
+    (lhs, lt): (&Node, Type),
+    (rhs, rt): (&Node, Type),
+    span: &Span,
+) -> Result<Type, RuleError> {
+    use Type::*;
+    match op {
+        BinOp::And | BinOp::Or => {
+            for (side, ty) in [(lhs, lt), (rhs, rt)] {
+                if ty != Condition {
+                    return Err(RuleError::new(
+                        side.span.clone(),
+                        format!("`{op}` needs a condition on each side, but this is {ty}"),
+                    ));
+                }
+            }
+            Ok(Condition)
+        }
+        BinOp::Cmp(cmp) => {
+            let equality = matches!(cmp, Cmp::Eq | Cmp::Ne);
+            if lt == rt && (lt != Condition || equality) {
+                return Ok(Condition);
+            }
+            if lt == Condition || rt == Condition {
+                return Err(RuleError::new(
+                    span.clone(),
+                    format!("`{op}` compares numbers or percentages, not conditions"),
+                ));
+            }
+            let literal = [lhs, rhs].into_iter().find_map(|side| match side.expr {
+                Expr::Number(n) => Some(n),
+                _ => None,
+            });
+            let mut message = format!("cannot compare {lt} with {rt}");
+            if let Some(n) = literal {
+                message.push_str(&format!("; write {n}% for a percentage"));
+            }
+            Err(RuleError::new(span.clone(), message))
+        }
+        BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
+            if lt == Condition || rt == Condition {
+                return Err(RuleError::new(
+                    span.clone(),
+                    format!("`{op}` works on numbers and percentages, not conditions"),
+                ));
+            }
+            let result = match (op, lt, rt) {
+                (BinOp::Add | BinOp::Sub, a, b) if a == b => Some(a),
+                (BinOp::Mul, Number, ty) | (BinOp::Mul, ty, Number) => Some(ty),
+                (BinOp::Div, ty, Number) => Some(ty),
+                (BinOp::Div, Percent, Percent) => Some(Number),
+                _ => None,
+            };
+            result.ok_or_else(|| {
+                RuleError::new(
+                    span.clone(),
+                    format!("cannot combine {lt} and {rt} with `{op}`"),
+                )
+            })
+        }
+    }
+}
+
+// ── Evaluation ──────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Num(f64),
+    Bool(bool),
+}
+
+/// The samples a rule is evaluated over, oldest first
+struct Context<'a> {
+    samples: Vec<&'a PriceSample>,
+    rates: Option<&'a FxRates>,
+}
+
+impl Context<'_> {
+    fn price(&self, i: usize, currency: &str) -> Option<f64> {
+        self.samples[i].prices.get(currency).copied()
+    }
+
+    /// Price `window` seconds before sample `i`: the latest sample at or
+    /// before that time, if history reaches back that far
+    fn price_before(&self, i: usize, currency: &str, window: u64) -> Option<f64> {
+        let target = self.samples[i].timestamp.checked_sub(window)?;
+        let count = self.samples[..=i].partition_point(|s| s.timestamp <= target);
+        self.price(count.checked_sub(1)?, currency)
+    }
+
+    fn eval(&self, node: &Node, i: usize) -> Option<Value> {
+        let num = |node| match self.eval(node, i)? {
+            Value::Num(n) => Some(n),
+            Value::Bool(_) => None,
+        };
+        let cond = |node| match self.eval(node, i)? {
+            Value::Bool(b) => Some(b),
+            Value::Num(_) => None,
+        };
+        let value = match &node.expr {
+            Expr::Number(n) | Expr::Percent(n) => Value::Num(*n),
+            Expr::Price(currency) => Value::Num(self.price(i, currency)?),
+            Expr::Change { currency, window } => {
+                let then = self.price_before(i, currency, *window)?;
+                Value::Num(self.price(i, currency)? / then - 1.0)
+            }
+            Expr::Premium(currency) => {
+                let implied = self.price(i, "usd")? * self.rates?.rate(currency)?;
+                Value::Num(self.price(i, currency)? / implied - 1.0)
+            }
+            Expr::Neg(inner) => Value::Num(-num(inner)?),
+            Expr::Not(inner) => Value::Bool(!cond(inner)?),
+            // Unknown and false is false; unknown or true is true
+            Expr::Binary(BinOp::And, lhs, rhs) => match (cond(lhs), cond(rhs)) {
+                (Some(false), _) | (_, Some(false)) => Value::Bool(false),
+                (Some(true), Some(true)) => Value::Bool(true),
+                _ => return None,
+            },
+            Expr::Binary(BinOp::Or, lhs, rhs) => match (cond(lhs), cond(rhs)) {
+                (Some(true), _) | (_, Some(true)) => Value::Bool(true),
+                (Some(false), Some(false)) => Value::Bool(false),
+                _ => return None,
+            },
+            Expr::Binary(BinOp::Cmp(cmp), lhs, rhs) => {
+                let (a, b) = (self.eval(lhs, i)?, self.eval(rhs, i)?);
+                let result = match (a, b) {
+                    (Value::Num(a), Value::Num(b)) => match cmp {
+                        Cmp::Lt => a < b,
+                        Cmp::Le => a <= b,
+                        Cmp::Gt => a > b,
+                        Cmp::Ge => a >= b,
+                        Cmp::Eq => a == b,
+                        Cmp::Ne => a != b,
+                    },
+                    (Value::Bool(a), Value::Bool(b)) => match cmp {
+                        Cmp::Eq => a == b,
+                        Cmp::Ne => a != b,
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                Value::Bool(result)
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let (a, b) = (num(lhs)?, num(rhs)?);
+                Value::Num(match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    _ if b == 0.0 => return None,
+                    _ => a / b,
+                })
+            }
+        };
+        match value {
+            Value::Num(n) if !n.is_finite() => None,
+            value => Some(value),
+        }
+    }
+}
+
+// ── Rules ───────────────────────────────────────────────────────
+
+/// A parsed and type-checked rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    source: String,
+    condition: Node,
+    /// Seconds the condition must hold before the rule fires
+    hold: Option<u64>,
+}
+
+impl Rule {
+    /// Parses and checks `source` against the currency catalogue
+    pub fn parse(source: &str) -> Result<Self, RuleError> {
+        Self::parse_with(source, &|code| {
+            code == "usd" || get_countries().iter().any(|c| c.currency == code)
+        })
+    }
+
+    /// Same as [`parse`](Self::parse) with `known` deciding which currency
+    /// codes exist
+    fn parse_with(source: &str, known: &dyn Fn(&str) -> bool) -> Result<Self, RuleError> {
+        let mut parser = Parser {
+            tokens: lex(source)?,
+            pos: 0,
+        };
+        let (condition, hold) = parser.rule()?;
+        let ty = check(&condition, known)?;
+        if ty != Type::Condition {
+            return Err(RuleError::new(
+                condition.span,
+                format!("a rule must be a condition such as `usd > 100000`, but this is {ty}"),
+            ));
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            condition,
+            hold,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the rule reads premiums, which need reference FX rates
+    pub fn uses_premium(&self) -> bool {
+        fn walk(node: &Node) -> bool {
+            match &node.expr {
+                Expr::Premium(_) => true,
+                Expr::Neg(inner) | Expr::Not(inner) => walk(inner),
+                Expr::Binary(_, lhs, rhs) => walk(lhs) || walk(rhs),
+                _ => false,
+            }
+        }
+        walk(&self.condition)
+    }
+
+    /// Times (Unix seconds) at which the rule fired over `history`: when the
+    /// condition became true, or once it had held for the `for` duration.
+    /// It fires again only after the condition has stopped holding.
+    pub fn firings(&self, history: &PriceHistory, rates: Option<&FxRates>) -> Vec<u64> {
+        let context = Context {
+            samples: history.samples().collect(),
+            rates,
+        };
+        let hold = self.hold.unwrap_or(0);
+        let mut fired = Vec::new();
+        let mut since = None;
+        let mut armed = true;
+        for (i, sample) in context.samples.iter().enumerate() {
+            if context.eval(&self.condition, i) == Some(Value::Bool(true)) {
+                let start = *since.get_or_insert(sample.timestamp);
+                // Timestamps are wall-clock times, which can step backwards
+                if armed && sample.timestamp.saturating_sub(start) >= hold {
+                    fired.push(sample.timestamp);
+                    armed = false;
+                }
+            } else {
+                since = None;
+                armed = true;
+            }
+        }
+        fired
+    }
+}
+
+// ── Storage ─────────────────────────────────────────────────────
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RulesContents {
+    #[serde(default)]
+    rules: Vec<String>,
+}
+
+/// JSON file listing the rules as typed; rules that no longer check are
+/// kept so they can be fixed in the editor
+#[derive(Debug, Clone)]
+pub struct RulesFile {
+    path: PathBuf,
+}
+
+impl RulesFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The rules file in the per-user config directory
+    pub fn default_location() -> Self {
+        Self::new(paths::config_dir().join(RULES_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Saved rules; a missing file means none
+    pub fn load(&self) -> io::Result<Vec<String>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str::<RulesContents>(&text)
+                .map(|contents| contents.rules)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, rules: &[String]) -> io::Result<()> {
+        if let Some(parent) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents = RulesContents {
+            rules: rules.to_vec(),
+        };
+        let json = serde_json::to_string_pretty(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(&self.path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    /// Samples every 10 minutes; `prices` gives usd and krw at each step
+    fn history(prices: &[(f64, f64)]) -> PriceHistory {
+        let mut history = PriceHistory::default();
+        for (i, &(usd, krw)) in prices.iter().enumerate() {
+            history.push(PriceSample {
+                timestamp: 1_700_000_000 + i as u64 * 600,
+                prices: HashMap::from([("usd".to_string(), usd), ("krw".to_string(), krw)]),
+            });
+        }
+        history
+    }
+
+    fn rates() -> FxRates {
+        FxRates {
+            source: "test".to_string(),
+            updated_at: None,
+            per_usd: HashMap::from([("usd".to_string(), 1.0), ("krw".to_string(), 1_000.0)]),
+        }
+    }
+
+    fn fired_steps(source: &str, history: &PriceHistory) -> Vec<u64> {
+        Rule::parse(source)
+            .unwrap()
+            .firings(history, Some(&rates()))
+            .into_iter()
+            .map(|t| (t - 1_700_000_000) / 600)
+            .collect()
+    }
+
+    #[test]
+    fn thresholds_fire_when_crossed_and_rearm() {
+        let history = history(&[
+            (99_000.0, 0.0),
+            (101_000.0, 0.0),
+            (102_000.0, 0.0),
+            (98_000.0, 0.0),
+            (100_500.0, 0.0),
+        ]);
+        assert_eq!(fired_steps("usd > 100000", &history), [1, 4]);
+        assert_eq!(
+            fired_steps("USD >= 100_000 and not usd > 101_000", &history),
+            [1, 4]
+        );
+        assert_eq!(fired_steps("(usd - 98000) / 1000 == 0", &history), [3]);
+    }
+
+    #[test]
+    fn changes_need_enough_history() {
+        // 24h of samples at 10-minute steps, then a 6% jump
+        let mut prices = vec![(100_000.0, 0.0); 145];
+        prices.push((106_000.0, 0.0));
+        let history = history(&prices);
+        assert_eq!(
+            fired_steps("usd > 100000 and change_24h > 5%", &history),
+            [145]
+        );
+        assert_eq!(fired_steps("usd change_1h >= 6%", &history), [145]);
+        assert_eq!(
+            fired_steps("change_2d > 5% or usd > 200000", &history),
+            Vec::<u64>::new(),
+            "unknown for lack of history"
+        );
+    }
+
+    #[test]
+    fn premiums_must_hold_for_the_duration() {
+        // USD 100,000 at 1,000 KRW/USD implies 100M KRW
+        let history = history(&[
+            (100_000.0, 104_000_000.0),
+            (100_000.0, 104_000_000.0),
+            (100_000.0, 101_000_000.0),
+            (100_000.0, 104_000_000.0),
+            (100_000.0, 104_000_000.0),
+            (100_000.0, 104_000_000.0),
+        ]);
+        assert_eq!(fired_steps("krw premium > 3%", &history), [0, 3]);
+        assert_eq!(fired_steps("krw premium > 3% for 10m", &history), [1, 4]);
+        assert_eq!(fired_steps("krw premium > 3% for 20m", &history), [5]);
+
+        let rule = Rule::parse("krw premium > 3%").unwrap();
+        assert!(rule.uses_premium());
+        assert!(
+            rule.firings(&history, None).is_empty(),
+            "no rates, no premium"
+        );
+        assert!(!Rule::parse("krw > 1").unwrap().uses_premium());
+    }
+
+    #[test]
+    fn a_clock_stepping_back_does_not_cut_the_hold_short() {
+        let mut history = PriceHistory::default();
+        for timestamp in [1_700_000_600, 1_700_000_000, 1_700_000_300, 1_700_001_200] {
+            history.push(PriceSample {
+                timestamp,
+                prices: HashMap::from([("usd".to_string(), 101_000.0)]),
+            });
+        }
+        let rule = Rule::parse("usd > 100000 for 10m").unwrap();
+        assert_eq!(rule.firings(&history, None), [1_700_001_200]);
+    }
+
+    #[test]
+    fn types_do_not_mix() {
+        let error = |source| Rule::parse(source).unwrap_err().message;
+        assert_eq!(
+            error("change_24h > 5"),
+            "cannot compare a percentage with a number; write 5% for a percentage"
+        );
+        assert_eq!(
+            error("usd"),
+            "a rule must be a condition such as `usd > 100000`, but this is a number"
+        );
+        assert_eq!(
+            error("usd > 1 and 5%"),
+            "`and` needs a condition on each side, but this is a percentage"
+        );
+        assert!(Rule::parse("usd * 2% > 1000").is_err());
+        assert!(Rule::parse("usd * 2 > 1000 and krw / usd < 1500").is_ok());
+        assert!(Rule::parse("(usd > 1) == (krw > 1)").is_ok());
+    }
+
+    /// Broken rules and the errors they produce
+    const ERROR_CASES: &[&str] = &[
+        "",
+        "usd >",
+        "usd > > 5",
+        "usd > 100000 and",
+        "(usd > 100000",
+        "usd > 100000)",
+        "usd > 100000 usd",
+        "1 < usd < 2",
+        "usd > 1 && krw > 1",
+        "usd = 100000",
+        "usd > $100000",
+        "usd > 10x",
+        "krw premium > 3% for",
+        "krw premium > 3% for 10",
+        "krw premium > 3% for 0m",
+        "krw premium > 3% for 10m and usd > 1",
+        "premium > 3%",
+        "usd change_24x > 5%",
+        "usd > 5m",
+        "xyz > 5",
+        "(xyz) > 5",
+        "(xyz premium) > 1%",
+        "usd premium > 1%",
+        "change_24h > 5",
+        "usd > 1 or 2",
+        "not usd",
+        "-(usd > 1)",
+        "usd + 5% > 1",
+        "usd > 1 + (krw > 1)",
+        "usd",
+    ];
+
+    #[test]
+    fn parse_errors_match_golden_file() {
+        let rendered: String = ERROR_CASES
+            .iter()
+            .map(|source| {
+                let error = Rule::parse(source).expect_err(source);
+                format!("> {source}\n{}\n\n", error.render(source))
+            })
+            .collect();
+
+        // Written on first run like the UI snapshots; delete to regenerate
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/rule_errors.txt");
+        match std::fs::read_to_string(path) {
+            Ok(golden) => assert_eq!(rendered, golden, "rule errors differ from {path}"),
+            Err(_) => {
+                std::fs::create_dir_all(Path::new(path).parent().unwrap()).unwrap();
+                std::fs::write(path, rendered).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn rules_round_trip_as_typed() {
+        let nanos = std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("ifb-rules-{}-{nanos}", std::process::id()));
+        let file = RulesFile::new(dir.join("rules.json"));
+        assert_eq!(file.load().unwrap(), Vec::<String>::new());
+        let rules = vec!["usd > 100000".to_string(), "usd >".to_string()];
+        file.save(&rules).unwrap();
+        assert_eq!(file.load().unwrap(), rules);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    proptest! {
+        #[test]
+        fn any_input_parses_or_points_inside_it(source in "[ -~€]{0,40}") {
+            if let Err(error) = Rule::parse(&source) {
+                prop_assert!(error.span.end <= source.len());
+                prop_assert!(source.is_char_boundary(error.span.start));
+                prop_assert!(source.is_char_boundary(error.span.end));
+                prop_assert!(error.render(&source).starts_with(&source));
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::api::ApiKey;
+use crate::paths;
+
+/// Name of the secrets file inside [`paths::config_dir`]
+const SECRETS_FILE: &str = "secrets.json";
+
+/// Keyring service and entry names
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "iced-fetch-bitcoin";
+#[cfg(feature = "keyring")]
+const KEYRING_ENTRY: &str = "coingecko-api-key";
+
+/// Where a secret ended up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretLocation {
+    #[cfg_attr(not(feature = "keyring"), allow(dead_code))]
+    Keyring,
+    File(PathBuf),
+}
+
+impl fmt::Display for SecretLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretLocation::Keyring => f.write_str("system keyring"),
+            SecretLocation::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretsContents {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    coingecko_api_key: Option<ApiKey>,
+}
+
+/// JSON file holding secrets, readable by the owner only (mode 0600 on Unix)
+#[derive(Debug, Clone)]
+pub struct SecretsFile {
+    path: PathBuf,
+}
+
+impl SecretsFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The secrets file in the per-user config directory
+    pub fn default_location() -> Self {
+        Self::new(paths::config_dir().join(SECRETS_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the stored key; a missing file means no key
+    pub fn load_api_key(&self) -> io::Result<Option<ApiKey>> {
+        Ok(self.read()?.coingecko_api_key)
+    }
+
+    /// Stores `key`, or removes it when `None`
+    pub fn save_api_key(&self, key: Option<&ApiKey>) -> io::Result<()> {
+        let mut contents = self.read()?;
+        contents.coingecko_api_key = key.cloned();
+        self.write(&contents)
+    }
+
+    fn read(&self) -> io::Result<SecretsContents> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(SecretsContents::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write(&self, contents: &SecretsContents) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // `mode` only applies on creation; tighten files created by older versions too
+            if self.path.exists() {
+                std::fs::set_permissions(&self.path, std::fs::Permissions::from_mode(0o600))?;
+            }
+        }
+        options.open(&self.path)?.write_all(json.as_bytes())
+    }
+}
+
+/// Loads the CoinGecko API key from the keyring (with the `keyring` feature)
+/// or the secrets file. Errors are logged and treated as "no key".
+pub fn load_api_key() -> Option<ApiKey> {
+    #[cfg(feature = "keyring")]
+    match keyring_entry().and_then(|entry| entry.get_password()) {
+        Ok(json) => match serde_json::from_str(&json) {
+            Ok(key) => return Some(key),
+            Err(e) => warn!("Ignoring malformed API key in keyring: {e}"),
+        },
+        Err(keyring::Error::NoEntry) => {}
+        Err(e) => warn!("Keyring unavailable, falling back to secrets file: {e}"),
+    }
+
+    let file = SecretsFile::default_location();
+    file.load_api_key().unwrap_or_else(|e| {
+        warn!("Cannot read {}: {e}", file.path().display());
+        None
+    })
+}
+
+/// Stores (or with `None`, removes) the CoinGecko API key. Uses the keyring
+/// when the `keyring` feature is enabled and it works, the secrets file otherwise.
+pub fn save_api_key(key: Option<&ApiKey>) -> io::Result<SecretLocation> {
+    let file = SecretsFile::default_location();
+
+    #[cfg(feature = "keyring")]
+    {
+        let stored = keyring_entry().and_then(|entry| match key {
+            Some(key) => {
+                let json = serde_json::to_string(key).expect("API key serializes");
+                entry.set_password(&json)
+            }
+            None => match entry.delete_credential() {
+                Err(keyring::Error::NoEntry) => Ok(()),
+                other => other,
+            },
+        });
+        match stored {
+            Ok(()) => {
+                // Do not leave a plaintext copy behind
+                if file.path().exists() {
+                    file.save_api_key(None)?;
+                }
+                return Ok(SecretLocation::Keyring);
+            }
+            Err(e) => warn!("Keyring unavailable, falling back to secrets file: {e}"),
+        }
+    }
+
+    file.save_api_key(key)?;
+    Ok(SecretLocation::File(file.path().to_path_buf()))
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_entry() -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_ENTRY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiPlan;
+
+    fn temp_secrets() -> SecretsFile {
+        let nanos = std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        SecretsFile::new(std::env::temp_dir().join(format!(
+            "ifb-secrets-{}-{nanos}/secrets.json",
+            std::process::id()
+        )))
+    }
+
+    #[test]
+    fn api_key_round_trips_and_can_be_removed() {
+        let file = temp_secrets();
+        assert_eq!(file.load_api_key().unwrap(), None);
+
+        let key = ApiKey::new(ApiPlan::Pro, "CG-secret").unwrap();
+        file.save_api_key(Some(&key)).unwrap();
+        assert_eq!(file.load_api_key().unwrap(), Some(key));
+
+        file.save_api_key(None).unwrap();
+        assert_eq!(file.load_api_key().unwrap(), None);
+        let _ = std::fs::remove_dir_all(file.path().parent().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn secrets_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = temp_secrets();
+        std::fs::create_dir_all(file.path().parent().unwrap()).unwrap();
+        std::fs::write(file.path(), "{}").unwrap();
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let key = ApiKey::new(ApiPlan::Demo, "CG-secret").unwrap();
+        file.save_api_key(Some(&key)).unwrap();
+        let mode = std::fs::metadata(file.path()).unwrap().permissions().mode();
+        let _ = std::fs::remove_dir_all(file.path().parent().unwrap());
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,328 @@
+use futures::{SinkExt, Stream};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+use crate::metrics::METRICS;
+use crate::store::{PriceStore, SharedStore, STORE};
+
+/// Default localhost port for the embedded API server
+pub const DEFAULT_PORT: u16 = 8787;
+
+/// Upper bound for a request head; anything larger is rejected
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// Number of history entries returned when `limit` is not given
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// OpenAPI 3 description of the endpoints below, served at `/openapi.json`
+const OPENAPI_SPEC: &str = include_str!("../resources/openapi.json");
+
+/// Lifecycle events reported back to the UI
+#[derive(Debug, Clone)]
+pub enum ServerEvent {
+    /// Server is bound and accepting connections
+    Listening(SocketAddr),
+    /// Server could not start or stopped with an error
+    Failed(String),
+}
+
+/// Binds `127.0.0.1:port` and serves the global [`STORE`] until the stream is dropped.
+///
+/// Used as an iced subscription: disabling the server in Settings drops the
+/// subscription, which drops the accept loop and releases the port.
+pub fn run(port: u16) -> impl Stream<Item = ServerEvent> {
+    iced::stream::channel(1, async move |mut output| {
+        let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("API server failed to bind port {port}: {e}");
+                let _ = output.send(ServerEvent::Failed(e.to_string())).await;
+                return;
+            }
+        };
+
+        if let Ok(addr) = listener.local_addr() {
+            info!("API server listening on http://{addr}");
+            let _ = output.send(ServerEvent::Listening(addr)).await;
+        }
+
+        if let Err(e) = serve(listener, STORE.clone()).await {
+            warn!("API server stopped: {e}");
+            let _ = output.send(ServerEvent::Failed(e.to_string())).await;
+        }
+    })
+}
+
+/// Accept loop: one spawned task per connection, `Connection: close` semantics
+pub async fn serve(listener: TcpListener, store: SharedStore) -> io::Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let store = store.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &store).await {
+                debug!("API connection from {peer} failed: {e}");
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, store: &SharedStore) -> io::Result<()> {
+    let mut head = Vec::with_capacity(1024);
+    let mut buf = [0u8; 1024];
+
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD {
+            return write_response(&mut stream, Response::error(431, "request head too large"))
+                .await;
+        }
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => {
+            return write_response(&mut stream, Response::error(400, "malformed request line"))
+                .await
+        }
+    };
+
+    debug!("API request: {method} {target}");
+
+    let response = match store.read() {
+        Ok(store) => route(method, target, &store),
+        Err(_) => Response::error(500, "price store unavailable"),
+    };
+    write_response(&mut stream, response).await
+}
+
+async fn write_response(stream: &mut TcpStream, response: Response) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+// ── Routing ─────────────────────────────────────────────────────
+
+/// A fully-buffered HTTP response
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, value: &impl Serialize) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self {
+                status,
+                content_type: "application/json",
+                body,
+            },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CurrencyHistory<'a> {
+    currency: &'a str,
+    points: Vec<crate::history::SeriesPoint>,
+}
+
+fn route(method: &str, target: &str, store: &PriceStore) -> Response {
+    if method != "GET" {
+        return Response::error(405, "only GET is supported");
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = parse_query(query);
+
+    match path {
+        "/api/v1/latest" => match store.latest() {
+            Some(snapshot) => Response::json(200, snapshot),
+            None => Response::error(503, "no prices fetched yet"),
+        },
+        "/api/v1/history" => {
+            let limit = query
+                .get("limit")
+                .and_then(|l| l.parse::<usize>().ok())
+                .unwrap_or(DEFAULT_HISTORY_LIMIT);
+            match query.get("currency") {
+                Some(currency) => {
+                    let currency = currency.to_ascii_lowercase();
+                    let points = store.history().series(&currency, limit);
+                    Response::json(
+                        200,
+                        &CurrencyHistory {
+                            currency: &currency,
+                            points,
+                        },
+                    )
+                }
+                None => {
+                    let samples: Vec<_> =
+                        store.history().samples().rev().take(limit).rev().collect();
+                    Response::json(200, &samples)
+                }
+            }
+        }
+        "/api/v1/health" => Response::json(200, &store.health()),
+        "/metrics" => Response {
+            status: 200,
+            content_type: "text/plain; version=0.0.4",
+            body: METRICS.render(),
+        },
+        "/openapi.json" => Response {
+            status: 200,
+            content_type: "application/json",
+            body: OPENAPI_SPEC.to_string(),
+        },
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn parse_query(query: &str) -> HashMap<&str, &str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::country::{get_countries, CountryPrice};
+    use crate::store::Snapshot;
+    use std::sync::{Arc, RwLock};
+
+    async fn start_server(store: PriceStore) -> SocketAddr {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, Arc::new(RwLock::new(store))));
+        addr
+    }
+
+    async fn get(addr: SocketAddr, path: &str) -> (u16, serde_json::Value) {
+        let response = crate::http_client::client()
+            .get(format!("http://{addr}{path}"))
+            .send()
+            .await
+            .unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn serves_latest_history_and_health_on_ephemeral_port() {
+        let mut store = PriceStore::default();
+        let gbp = CountryPrice::new(
+            get_countries()
+                .iter()
+                .find(|c| c.currency == "gbp")
+                .cloned()
+                .unwrap(),
+            Some(51234.0),
+        );
+        store.record_success(&Snapshot {
+            fetched_at: 1_700_000_000,
+            provider_updated_at: Some(1_699_999_940),
+            usd: 65497.0,
+            prices: vec![gbp],
+            cross_rates: Default::default(),
+        });
+        let addr = start_server(store).await;
+
+        let (status, latest) = get(addr, "/api/v1/latest").await;
+        assert_eq!(status, 200);
+        assert_eq!(latest["usd"], 65497.0);
+        assert_eq!(latest["prices"][0]["country"]["currency"], "gbp");
+        assert_eq!(latest["prices"][0]["price"], 51234.0);
+
+        let (status, history) = get(addr, "/api/v1/history?currency=GBP").await;
+        assert_eq!(status, 200);
+        assert_eq!(history["currency"], "gbp");
+        assert_eq!(history["points"][0]["price"], 51234.0);
+
+        let (status, health) = get(addr, "/api/v1/health").await;
+        assert_eq!(status, 200);
+        assert_eq!(health["status"], "ok");
+        assert_eq!(health["samples"], 1);
+
+        let (status, spec) = get(addr, "/openapi.json").await;
+        assert_eq!(status, 200);
+        assert!(spec["paths"]["/api/v1/latest"].is_object());
+    }
+
+    #[tokio::test]
+    async fn serves_prometheus_metrics() {
+        let addr = start_server(PriceStore::default()).await;
+        let response = crate::http_client::client()
+            .get(format!("http://{addr}/metrics"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert!(response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        let body = response.text().await.unwrap();
+        assert!(body.contains("# TYPE btc_fetch_duration_seconds histogram"));
+        assert!(body.contains("flag_downloads_total{result=\"success\"}"));
+    }
+
+    #[tokio::test]
+    async fn empty_store_and_unknown_paths() {
+        let addr = start_server(PriceStore::default()).await;
+
+        let (status, body) = get(addr, "/api/v1/latest").await;
+        assert_eq!(status, 503);
+        assert!(body["error"].is_string());
+
+        let (status, _) = get(addr, "/nope").await;
+        assert_eq!(status, 404);
+
+        let (status, health) = get(addr, "/api/v1/health").await;
+        assert_eq!(status, 200);
+        assert_eq!(health["status"], "starting");
+    }
+}
@@ -0,0 +1,447 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use iced::event::{self, Event};
+use iced::keyboard::{self, key, Key, Modifiers};
+use iced::window;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::message::Message;
+use crate::paths;
+use crate::route::Route;
+
+const SHORTCUTS_FILE: &str = "shortcuts.json";
+
+/// Something the user can trigger from the keyboard or the command palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Refresh,
+    Search,
+    CommandPalette,
+    OpenSettings,
+    Navigate(Route),
+}
+
+impl Action {
+    /// Every action, in the order shown in settings and the palette
+    pub fn all() -> Vec<Action> {
+        let mut actions = vec![
+            Action::Refresh,
+            Action::Search,
+            Action::CommandPalette,
+            Action::OpenSettings,
+        ];
+        actions.extend(Route::all().into_iter().map(Action::Navigate));
+        actions
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Action::Refresh => "Refresh prices".to_string(),
+            Action::Search => "Search currencies".to_string(),
+            Action::CommandPalette => "Command palette".to_string(),
+            Action::OpenSettings => "Open settings".to_string(),
+            Action::Navigate(route) => format!("Go to {}", route.display_name()),
+        }
+    }
+}
+
+/// A key combination such as `Ctrl+Shift+K`.
+///
+/// `Ctrl` matches the platform command key (Cmd on macOS).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// Lowercase key character
+    pub key: char,
+}
+
+impl Shortcut {
+    pub const fn ctrl(key: char) -> Self {
+        Self {
+            ctrl: true,
+            alt: false,
+            shift: false,
+            key,
+        }
+    }
+
+    /// Whether a key press is this combination
+    pub fn matches(&self, key: &Key, modifiers: Modifiers) -> bool {
+        let Key::Character(c) = key else {
+            return false;
+        };
+        let mut chars = c.chars().flat_map(char::to_lowercase);
+        chars.next() == Some(self.key)
+            && chars.next().is_none()
+            && modifiers.command() == self.ctrl
+            && modifiers.alt() == self.alt
+            && modifiers.shift() == self.shift
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.alt {
+            f.write_str("Alt+")?;
+        }
+        if self.shift {
+            f.write_str("Shift+")?;
+        }
+        write!(f, "{}", self.key.to_uppercase())
+    }
+}
+
+/// Why a shortcut was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ShortcutError {
+    #[error("Unknown modifier {0:?} (use Ctrl, Alt or Shift)")]
+    UnknownModifier(String),
+    #[error("Shortcut must end with a single key")]
+    BadKey,
+    #[error("Shortcut needs Ctrl or Alt so it does not fire while typing")]
+    NoModifier,
+    #[error("Already used by \"{0}\"")]
+    Conflict(String),
+}
+
+impl FromStr for Shortcut {
+    type Err = ShortcutError;
+
+    /// Parses `Ctrl+R`, `ctrl+shift+k`, `Alt+,`, `Ctrl++`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (modifiers, key) = match s.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+
+        let mut chars = key.trim().chars();
+        let (Some(key), None) = (chars.next(), chars.next()) else {
+            return Err(ShortcutError::BadKey);
+        };
+        let mut shortcut = Shortcut {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            key: key.to_lowercase().next().unwrap_or(key),
+        };
+
+        for modifier in modifiers
+            .split('+')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+        {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" | "cmd" | "command" => shortcut.ctrl = true,
+                "alt" | "option" => shortcut.alt = true,
+                "shift" => shortcut.shift = true,
+                _ => return Err(ShortcutError::UnknownModifier(modifier.to_string())),
+            }
+        }
+        if !shortcut.ctrl && !shortcut.alt {
+            return Err(ShortcutError::NoModifier);
+        }
+        Ok(shortcut)
+    }
+}
+
+/// Shortcut assigned to each action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBindings {
+    bindings: Vec<(Action, Shortcut)>,
+}
+
+impl Default for KeyBindings {
+    /// Ctrl+R refresh, Ctrl+F search, Ctrl+K palette, Ctrl+, settings,
+    /// Ctrl+1..9 then Ctrl+0 for the pages
+    fn default() -> Self {
+        let mut bindings = vec![
+            (Action::Refresh, Shortcut::ctrl('r')),
+            (Action::Search, Shortcut::ctrl('f')),
+            (Action::CommandPalette, Shortcut::ctrl('k')),
+            (Action::OpenSettings, Shortcut::ctrl(',')),
+        ];
+        // Only ten digits: a test fails before an eleventh page goes unbound
+        bindings.extend(
+            Route::all()
+                .into_iter()
+                .zip(('1'..='9').chain(['0']))
+                .map(|(route, key)| (Action::Navigate(route), Shortcut::ctrl(key))),
+        );
+        Self { bindings }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> Option<Shortcut> {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, shortcut)| *shortcut)
+    }
+
+    /// Assigns `shortcut` to `action`, unless another action already uses it
+    pub fn set(&mut self, action: Action, shortcut: Shortcut) -> Result<(), ShortcutError> {
+        if let Some((other, _)) = self
+            .bindings
+            .iter()
+            .find(|(a, s)| *a != action && *s == shortcut)
+        {
+            return Err(ShortcutError::Conflict(other.label()));
+        }
+        match self.bindings.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, current)) => *current = shortcut,
+            None => self.bindings.push((action, shortcut)),
+        }
+        Ok(())
+    }
+
+    /// The action bound to a key press, if any
+    pub fn action_for(&self, key: &Key, modifiers: Modifiers) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, shortcut)| shortcut.matches(key, modifiers))
+            .map(|(action, _)| *action)
+    }
+
+    /// Saved shortcuts, then the defaults for actions the file leaves out.
+    /// Entries that no longer parse or clash with an earlier one are dropped.
+    fn from_saved(saved: &[SavedBinding]) -> Self {
+        let mut bindings = Self {
+            bindings: Vec::new(),
+        };
+        for entry in saved {
+            if let Ok(shortcut) = entry.shortcut.parse() {
+                let _ = bindings.set(entry.action, shortcut);
+            }
+        }
+        for (action, shortcut) in Self::default().bindings {
+            if bindings.get(action).is_none() {
+                let _ = bindings.set(action, shortcut);
+            }
+        }
+        bindings
+    }
+}
+
+// ── Storage ─────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedBinding {
+    action: Action,
+    /// As displayed, e.g. `Ctrl+Shift+K`
+    shortcut: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ShortcutsContents {
+    #[serde(default)]
+    bindings: Vec<SavedBinding>,
+}
+
+/// JSON file holding the shortcut of every action
+#[derive(Debug, Clone, Default)]
+pub struct ShortcutsFile {
+    path: PathBuf,
+}
+
+impl ShortcutsFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The shortcuts file in the per-user config directory
+    pub fn default_location() -> Self {
+        Self::new(paths::config_dir().join(SHORTCUTS_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Saved bindings; a missing file means the defaults
+    pub fn load(&self) -> io::Result<KeyBindings> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str::<ShortcutsContents>(&text)
+                .map(|contents| KeyBindings::from_saved(&contents.bindings))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(KeyBindings::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, bindings: &KeyBindings) -> io::Result<()> {
+        if let Some(parent) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents = ShortcutsContents {
+            bindings: bindings
+                .bindings
+                .iter()
+                .map(|(action, shortcut)| SavedBinding {
+                    action: *action,
+                    shortcut: shortcut.to_string(),
+                })
+                .collect(),
+        };
+        let json = serde_json::to_string_pretty(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(&self.path, json)
+    }
+}
+
+/// Keyboard filter for [`iced::event::listen_with`].
+///
+/// Forwards presses that can be shortcuts (with Ctrl or Alt) and the keys the
+/// command palette uses, even when a text input has captured them.
+pub fn key_event(event: Event, _status: event::Status, _window: window::Id) -> Option<Message> {
+    let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event else {
+        return None;
+    };
+    let palette_key = matches!(
+        key,
+        Key::Named(key::Named::Escape | key::Named::ArrowUp | key::Named::ArrowDown)
+    );
+    (palette_key || modifiers.command() || modifiers.alt())
+        .then_some(Message::KeyPressed(key, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(c: &str) -> Key {
+        Key::Character(c.into())
+    }
+
+    #[test]
+    fn parses_and_displays_shortcuts() {
+        assert_eq!("Ctrl+R".parse(), Ok(Shortcut::ctrl('r')));
+        assert_eq!("ctrl + ,".parse(), Ok(Shortcut::ctrl(',')));
+        assert_eq!("Ctrl++".parse(), Ok(Shortcut::ctrl('+')));
+        let shortcut: Shortcut = "cmd+shift+k".parse().unwrap();
+        assert_eq!(shortcut.to_string(), "Ctrl+Shift+K");
+
+        assert_eq!("R".parse::<Shortcut>(), Err(ShortcutError::NoModifier));
+        assert_eq!(
+            "Shift+R".parse::<Shortcut>(),
+            Err(ShortcutError::NoModifier)
+        );
+        assert_eq!("Ctrl+Enter".parse::<Shortcut>(), Err(ShortcutError::BadKey));
+        assert_eq!(
+            "Hyper+R".parse::<Shortcut>(),
+            Err(ShortcutError::UnknownModifier("Hyper".into()))
+        );
+    }
+
+    #[test]
+    fn default_bindings_resolve_key_presses() {
+        let bindings = KeyBindings::default();
+        let ctrl = Modifiers::COMMAND;
+
+        assert_eq!(
+            bindings.action_for(&press("r"), ctrl),
+            Some(Action::Refresh)
+        );
+        assert_eq!(
+            bindings.action_for(&press("R"), ctrl),
+            Some(Action::Refresh)
+        );
+        assert_eq!(
+            bindings.action_for(&press(","), ctrl),
+            Some(Action::OpenSettings)
+        );
+        assert_eq!(
+            bindings.action_for(&press("9"), ctrl),
+            Some(Action::Navigate(Route::Settings))
+        );
+        assert_eq!(
+            bindings.action_for(&press("0"), ctrl),
+            Some(Action::Navigate(Route::About))
+        );
+        assert_eq!(bindings.action_for(&press("r"), Modifiers::empty()), None);
+        assert_eq!(
+            bindings.action_for(&press("r"), ctrl | Modifiers::SHIFT),
+            None
+        );
+    }
+
+    #[test]
+    fn every_page_gets_a_digit_shortcut() {
+        assert!(Route::all().len() <= 10, "more pages than Ctrl+digit keys");
+
+        let bindings = KeyBindings::default();
+        for route in Route::all() {
+            assert!(
+                bindings.get(Action::Navigate(route)).is_some(),
+                "{route:?} has no default shortcut"
+            );
+        }
+    }
+
+    #[test]
+    fn rebinding_rejects_conflicts() {
+        let mut bindings = KeyBindings::default();
+        assert_eq!(
+            bindings.set(Action::Search, Shortcut::ctrl('r')),
+            Err(ShortcutError::Conflict("Refresh prices".into()))
+        );
+        assert_eq!(bindings.get(Action::Search), Some(Shortcut::ctrl('f')));
+
+        bindings.set(Action::Search, Shortcut::ctrl('/')).unwrap();
+        assert_eq!(bindings.get(Action::Search), Some(Shortcut::ctrl('/')));
+        // Re-assigning an action its own shortcut is not a conflict
+        bindings.set(Action::Search, Shortcut::ctrl('/')).unwrap();
+    }
+
+    #[test]
+    fn shortcuts_file_round_trips_and_fills_in_defaults() {
+        let dir = std::env::temp_dir().join(format!("ifb-shortcuts-{}", std::process::id()));
+        let file = ShortcutsFile::new(dir.join("shortcuts.json"));
+        assert_eq!(file.load().unwrap(), KeyBindings::default());
+
+        // Swapping two shortcuts survives a reload
+        let mut bindings = KeyBindings::default();
+        bindings.set(Action::Refresh, Shortcut::ctrl('x')).unwrap();
+        bindings.set(Action::Search, Shortcut::ctrl('r')).unwrap();
+        bindings.set(Action::Refresh, Shortcut::ctrl('f')).unwrap();
+        file.save(&bindings).unwrap();
+        assert_eq!(
+            file.load().unwrap().get(Action::Search),
+            Some(Shortcut::ctrl('r'))
+        );
+        assert_eq!(
+            file.load().unwrap().get(Action::Refresh),
+            Some(Shortcut::ctrl('f'))
+        );
+
+        // Unknown or clashing entries fall back to the defaults
+        std::fs::write(
+            file.path(),
+            r#"{"bindings": [
+                {"action": {"navigate": "about"}, "shortcut": "Ctrl+A"},
+                {"action": "search", "shortcut": "Ctrl+A"},
+                {"action": "refresh", "shortcut": "Hyper+R"}
+            ]}"#,
+        )
+        .unwrap();
+        let loaded = file.load().unwrap();
+        assert_eq!(
+            loaded.get(Action::Navigate(Route::About)),
+            Some(Shortcut::ctrl('a'))
+        );
+        assert_eq!(loaded.get(Action::Search), Some(Shortcut::ctrl('f')));
+        assert_eq!(loaded.get(Action::Refresh), Some(Shortcut::ctrl('r')));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,121 @@
+use serde::Serialize;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use crate::country::CountryPrice;
+use crate::fx_matrix::FxMatrix;
+use crate::history::{unix_now, PriceHistory, PriceSample};
+
+/// Price store shared between the UI and background services
+pub type SharedStore = Arc<RwLock<PriceStore>>;
+
+/// Process-wide store fed by every successful or failed price fetch.
+/// Background services (e.g. the local API server) read from it.
+pub static STORE: LazyLock<SharedStore> =
+    LazyLock::new(|| Arc::new(RwLock::new(PriceStore::default())));
+
+/// Latest successful fetch
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    /// Unix timestamp (seconds) of the fetch
+    pub fetched_at: u64,
+    /// Provider's own quote time (CoinGecko `last_updated_at`), if reported
+    pub provider_updated_at: Option<u64>,
+    /// BTC/USD price
+    pub usd: f64,
+    /// BTC price per supported currency
+    pub prices: Vec<CountryPrice>,
+    /// Cross rates implied by every BTC price in the response
+    #[serde(skip)]
+    pub cross_rates: FxMatrix,
+}
+
+impl Snapshot {
+    /// History sample of this snapshot: every quoted currency plus USD
+    pub fn to_sample(&self) -> PriceSample {
+        let mut prices: std::collections::HashMap<String, f64> = self
+            .prices
+            .iter()
+            .filter_map(|p| Some((p.country.currency.to_string(), p.price?)))
+            .collect();
+        prices.insert("usd".to_string(), self.usd);
+        PriceSample {
+            timestamp: self.fetched_at,
+            prices,
+        }
+    }
+}
+
+/// Fetch health summary
+#[derive(Debug, Clone, Serialize)]
+pub struct Health {
+    /// `ok`, `degraded` (last attempt failed) or `starting` (no data yet)
+    pub status: &'static str,
+    pub last_success_at: Option<u64>,
+    pub last_attempt_at: Option<u64>,
+    pub last_error: Option<String>,
+    pub samples: usize,
+    pub version: &'static str,
+}
+
+/// Latest snapshot, history and fetch health
+#[derive(Debug, Default)]
+pub struct PriceStore {
+    latest: Option<Snapshot>,
+    history: PriceHistory,
+    last_attempt_at: Option<u64>,
+    last_error: Option<String>,
+}
+
+impl PriceStore {
+    /// Records a successful fetch
+    pub fn record_success(&mut self, snapshot: &Snapshot) {
+        self.history.push(snapshot.to_sample());
+        self.latest = Some(snapshot.clone());
+        self.last_attempt_at = Some(snapshot.fetched_at);
+        self.last_error = None;
+    }
+
+    /// Records a failed fetch; the previous snapshot is kept
+    pub fn record_failure(&mut self, error: impl Into<String>) {
+        self.last_attempt_at = Some(unix_now());
+        self.last_error = Some(error.into());
+    }
+
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.latest.as_ref()
+    }
+
+    pub fn history(&self) -> &PriceHistory {
+        &self.history
+    }
+
+    pub fn health(&self) -> Health {
+        let status = match (&self.latest, &self.last_error) {
+            (None, None) => "starting",
+            (_, Some(_)) => "degraded",
+            (Some(_), None) => "ok",
+        };
+        Health {
+            status,
+            last_success_at: self.latest.as_ref().map(|s| s.fetched_at),
+            last_attempt_at: self.last_attempt_at,
+            last_error: self.last_error.clone(),
+            samples: self.history.len(),
+            version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+}
+
+/// Records a successful fetch in the global [`STORE`]
+pub fn record_success(snapshot: &Snapshot) {
+    if let Ok(mut store) = STORE.write() {
+        store.record_success(snapshot);
+    }
+}
+
+/// Records a failed fetch in the global [`STORE`]
+pub fn record_failure(error: impl Into<String>) {
+    if let Ok(mut store) = STORE.write() {
+        store.record_failure(error);
+    }
+}
@@ -0,0 +1,175 @@
+//! Block subsidy and coin supply from Bitcoin's consensus rules.
+//!
+//! Amounts are in satoshis. Every 210,000 blocks the subsidy halves by a
+//! right shift, starting from 50 BTC, so it reaches zero in the 34th era. The
+//! genesis block's unspendable 50 BTC is counted, as is usual for supply figures.
+
+/// Satoshis per bitcoin
+pub const COIN: u64 = 100_000_000;
+/// Blocks between halvings
+pub const HALVING_INTERVAL: u64 = 210_000;
+pub const INITIAL_SUBSIDY: u64 = 50 * COIN;
+/// Everything the subsidy schedule will ever issue
+pub const MAX_SUPPLY: u64 = 2_099_999_997_690_000;
+
+/// Number of halvings before `height`; era 0 pays 50 BTC
+pub fn era(height: u64) -> u64 {
+    height / HALVING_INTERVAL
+}
+
+/// New coins in block `height`
+pub fn subsidy(height: u64) -> u64 {
+    // Bitcoin Core stops at 64 halvings because larger shifts are undefined
+    match era(height) {
+        e if e >= 64 => 0,
+        e => INITIAL_SUBSIDY >> e,
+    }
+}
+
+/// Coins issued by blocks `0..=height`
+pub fn supply_after(height: u64) -> u64 {
+    let era = era(height);
+    let full_eras: u64 = (0..era.min(64))
+        .map(|e| HALVING_INTERVAL * subsidy(e * HALVING_INTERVAL))
+        .sum();
+    full_eras + (height % HALVING_INTERVAL + 1) * subsidy(height)
+}
+
+/// First block of the era after the one `height` is in
+pub fn next_halving(height: u64) -> u64 {
+    (era(height) + 1) * HALVING_INTERVAL
+}
+
+/// One stretch of 210,000 blocks paying the same subsidy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Era {
+    pub index: u64,
+    pub first_height: u64,
+    pub last_height: u64,
+    /// Per block
+    pub subsidy: u64,
+    /// Total issued once the era's last block is mined
+    pub supply_at_end: u64,
+}
+
+impl Era {
+    pub fn contains(&self, height: u64) -> bool {
+        (self.first_height..=self.last_height).contains(&height)
+    }
+}
+
+/// Every era that issues coins, oldest first
+pub fn schedule() -> Vec<Era> {
+    (0..)
+        .map(|index| {
+            let first_height = index * HALVING_INTERVAL;
+            let last_height = first_height + HALVING_INTERVAL - 1;
+            Era {
+                index,
+                first_height,
+                last_height,
+                subsidy: subsidy(first_height),
+                supply_at_end: supply_after(last_height),
+            }
+        })
+        .take_while(|era| era.subsidy > 0)
+        .collect()
+}
+
+/// When the next halving is expected, seen from the chain tip
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HalvingEstimate {
+    /// Height of the first block with the reduced subsidy
+    pub height: u64,
+    pub blocks_left: u64,
+    /// At the given average block interval
+    pub secs_left: u64,
+    pub subsidy_before: u64,
+    pub subsidy_after: u64,
+}
+
+/// Next halving after block `tip`, assuming blocks keep arriving every
+/// `avg_block_secs` seconds
+pub fn estimate_halving(tip: u64, avg_block_secs: f64) -> HalvingEstimate {
+    let height = next_halving(tip);
+    let blocks_left = height - tip;
+    HalvingEstimate {
+        height,
+        blocks_left,
+        secs_left: (blocks_left as f64 * avg_block_secs.max(0.0)).round() as u64,
+        subsidy_before: subsidy(tip),
+        subsidy_after: subsidy(height),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn subsidy_halves_every_era() {
+        assert_eq!(subsidy(0), 50 * COIN);
+        assert_eq!(subsidy(209_999), 50 * COIN);
+        assert_eq!(subsidy(210_000), 25 * COIN);
+        assert_eq!(subsidy(630_000), 625_000_000);
+        assert_eq!(subsidy(840_000), 312_500_000);
+        // 5,000,000,000 >> 32 is the last whole satoshi
+        assert_eq!(subsidy(32 * HALVING_INTERVAL), 1);
+        assert_eq!(subsidy(33 * HALVING_INTERVAL), 0);
+        assert_eq!(subsidy(u64::MAX), 0);
+    }
+
+    #[test]
+    fn supply_matches_known_milestones() {
+        assert_eq!(supply_after(0), 50 * COIN, "genesis");
+        assert_eq!(supply_after(209_999), 10_500_000 * COIN);
+        assert_eq!(supply_after(839_999), 19_687_500 * COIN);
+        assert_eq!(supply_after(840_000), 19_687_500 * COIN + 312_500_000);
+        assert_eq!(supply_after(u64::MAX), MAX_SUPPLY);
+    }
+
+    #[test]
+    fn schedule_covers_every_paying_era() {
+        let eras = schedule();
+        assert_eq!(eras.len(), 33);
+        assert_eq!(eras[0].subsidy, 50 * COIN);
+        assert_eq!(eras[4].first_height, 840_000);
+        assert_eq!(eras[4].supply_at_end, supply_after(1_049_999));
+        assert!(eras[4].contains(1_049_999) && !eras[4].contains(1_050_000));
+        assert_eq!(eras.last().unwrap().subsidy, 1);
+        assert_eq!(eras.last().unwrap().supply_at_end, MAX_SUPPLY);
+        for pair in eras.windows(2) {
+            assert_eq!(pair[0].last_height + 1, pair[1].first_height);
+        }
+    }
+
+    #[test]
+    fn halving_estimate_counts_blocks_from_the_tip() {
+        let estimate = estimate_halving(839_999, 600.0);
+        assert_eq!(estimate.height, 840_000);
+        assert_eq!(estimate.blocks_left, 1);
+        assert_eq!(estimate.secs_left, 600);
+        assert_eq!(
+            (estimate.subsidy_before, estimate.subsidy_after),
+            (625_000_000, 312_500_000)
+        );
+
+        let estimate = estimate_halving(840_000, 587.5);
+        assert_eq!(estimate.height, 1_050_000);
+        assert_eq!(estimate.blocks_left, 210_000);
+        assert_eq!(estimate.secs_left, 123_375_000);
+    }
+
+    proptest! {
+        #[test]
+        fn supply_grows_by_each_block_subsidy(height in 1..8_000_000u64) {
+            prop_assert_eq!(supply_after(height) - supply_after(height - 1), subsidy(height));
+        }
+
+        #[test]
+        fn supply_never_exceeds_the_cap(height in any::<u64>()) {
+            prop_assert!(supply_after(height) <= MAX_SUPPLY);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Shared helpers for GUI simulation tests.
+use iced::{Element, Settings, Size, Theme};
+use iced_test::Simulator;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Once};
+
+/// Same size as the real window, so snapshots look like the app
+pub const WINDOW_SIZE: Size = Size::new(700.0, 500.0);
+
+/// Builds a headless simulator of `element` on the CPU (tiny-skia) renderer
+pub fn simulator<'a, M>(element: impl Into<Element<'a, M>>) -> Simulator<'a, M> {
+    simulator_with_size(WINDOW_SIZE, element)
+}
+
+/// Like [`simulator`], with a custom viewport (e.g. to reach widgets below the fold)
+pub fn simulator_with_size<'a, M>(
+    size: Size,
+    element: impl Into<Element<'a, M>>,
+) -> Simulator<'a, M> {
+    pin_backend();
+    Simulator::with_size(Settings::default(), size, element)
+}
+
+/// Snapshots are compared pixel-for-pixel; pin the software renderer so
+/// results don't depend on the GPU of the machine running the tests. Set
+/// once per run rather than by every test thread.
+fn pin_backend() {
+    static PIN: Once = Once::new();
+    PIN.call_once(|| std::env::set_var("ICED_TEST_BACKEND", "tiny-skia"));
+}
+
+/// Configuration directory seen by every test in this run, in place of the
+/// developer's own: pages built by `App::boot` read their saved files from
+/// it, so snapshots never depend on what is installed locally
+pub fn config_dir() -> PathBuf {
+    static DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+        let dir = std::env::temp_dir().join(format!("ifb-test-config-{}", std::process::id()));
+        // A leftover from an earlier run with the same pid must not leak in
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    });
+    DIR.clone()
+}
+
+/// Renders `ui` and compares it with `tests/snapshots/<name>-tiny-skia.png`.
+///
+/// A missing baseline is written on first run; delete a baseline to regenerate it
+/// after an intentional UI change.
+pub fn assert_snapshot<M>(ui: &mut Simulator<'_, M>, theme: &Theme, name: &str) {
+    let path = format!("{}/tests/snapshots/{name}", env!("CARGO_MANIFEST_DIR"));
+    let snapshot = ui.snapshot(theme).expect("snapshot should render");
+    assert!(
+        snapshot.matches_image(&path).expect("snapshot should be comparable"),
+        "snapshot `{name}` differs from its baseline in tests/snapshots/"
+    );
+}
+
+/// Fixed fetch time used by [`sample_snapshot`]
+pub const SAMPLE_FETCHED_AT: u64 = 1_700_000_000;
+
+/// Deterministic snapshot for a handful of countries
+pub fn sample_snapshot() -> crate::store::Snapshot {
+    let quotes = [("usd", 65497.0), ("gbp", 51234.0), ("jpy", 9876543.21), ("krw", 89012345.5)];
+    let prices = quotes
+        .iter()
+        .map(|(currency, price)| {
+            let country = crate::country::get_countries()
+                .iter()
+                .find(|c| c.currency == *currency)
+                .cloned()
+                .expect("currency in catalogue");
+            crate::country::CountryPrice::new(country, Some(*price))
+        })
+        .collect();
+    let response = crate::api::CoinGeckoResponse {
+        bitcoin: quotes.iter().map(|(c, p)| (c.to_string(), *p)).collect(),
+        last_updated_at: Some(SAMPLE_FETCHED_AT - 45),
+    };
+    crate::store::Snapshot {
+        fetched_at: SAMPLE_FETCHED_AT,
+        provider_updated_at: response.last_updated_at,
+        usd: 65497.0,
+        prices,
+        cross_rates: crate::fx_matrix::FxMatrix::from_response(&response),
+    }
+}
@@ -1,14 +1,81 @@
-use iced::widget::{column, container, space, text};
-use iced::{Center, Fill};
+use iced::widget::{canvas, column, container, rule, scrollable, space, text, Column, Row};
+use iced::{Center, Fill, Font, Length};
 
 use crate::message::Message;
+use crate::supply::COIN;
+use crate::views::network_view::format_count;
+use crate::views::price_chart::SupplyChart;
+
+const SECTION_WIDTH: f32 = 600.0;
+const CHART_HEIGHT: f32 = 180.0;
+
+/// Next-halving estimate, once the chain tip is known
+pub struct CountdownProps {
+    pub height: u64,
+    pub blocks_left: u64,
+    /// Estimated wait at the recent block interval
+    pub secs_left: u64,
+    /// Estimated date, `YYYY-MM-DD HH:MM` UTC
+    pub date: String,
+    /// Recent average block interval, seconds
+    pub block_secs: u64,
+    pub subsidy_before: u64,
+    pub subsidy_after: u64,
+    /// Coins issued up to the tip
+    pub supply: u64,
+}
+
+/// One row of the subsidy schedule
+pub struct EraRow {
+    pub index: u64,
+    pub first_height: u64,
+    pub subsidy: u64,
+    pub supply_at_end: u64,
+    /// Whether the chain tip is in this era
+    pub current: bool,
+}
+
+/// Halving and supply section of the About page
+pub struct HalvingProps {
+    /// `None` while the tip height is unknown
+    pub countdown: Option<CountdownProps>,
+    /// Why there is no countdown yet
+    pub status: Option<String>,
+    pub eras: Vec<EraRow>,
+    pub chart: SupplyChart,
+}
 
 /// About view component
-pub struct AboutView;
+pub struct AboutView {
+    halving: HalvingProps,
+}
+
+/// Satoshis as BTC without trailing zeros, such as `3.125`
+pub fn format_btc(sats: u64) -> String {
+    let whole = format_count(sats / COIN);
+    let fraction = sats % COIN;
+    if fraction == 0 {
+        return whole;
+    }
+    let digits = format!("{fraction:08}");
+    format!("{whole}.{}", digits.trim_end_matches('0'))
+}
+
+/// Rough wait such as `1,427 days 22 hours`
+pub fn format_wait(secs: u64) -> String {
+    let plural = |n: u64, unit: &str| match n {
+        1 => format!("1 {unit}"),
+        n => format!("{} {unit}s", format_count(n)),
+    };
+    match secs / 86_400 {
+        0 => format!("{} {}", plural(secs / 3_600, "hour"), plural(secs % 3_600 / 60, "minute")),
+        days => format!("{} {}", plural(days, "day"), plural(secs % 86_400 / 3_600, "hour")),
+    }
+}
 
 impl AboutView {
-    pub fn new() -> Self {
-        Self
+    pub fn new(halving: HalvingProps) -> Self {
+        Self { halving }
     }
 
     pub fn view(self) -> iced::Element<'static, Message> {
@@ -47,23 +114,127 @@ impl AboutView {
         ]
         .spacing(5);
 
-        container(
-            column![
-                title,
-                space::vertical().height(20),
-                version_info,
-                space::vertical().height(20),
-                description,
-                space::vertical().height(20),
-                features,
-                space::vertical().height(20),
-                credits,
-            ]
-            .align_x(Center)
-            .spacing(10)
-            .padding(20)
+        scrollable(
+            container(
+                column![
+                    title,
+                    space::vertical().height(20),
+                    version_info,
+                    space::vertical().height(20),
+                    halving(self.halving),
+                    space::vertical().height(20),
+                    description,
+                    space::vertical().height(20),
+                    features,
+                    space::vertical().height(20),
+                    credits,
+                ]
+                .align_x(Center)
+                .spacing(10)
+                .padding(20)
+            )
+            .center_x(Fill)
         )
-        .center_x(Fill)
+        .height(Fill)
         .into()
     }
 }
+
+/// Countdown card, supply curve and subsidy table
+fn halving(props: HalvingProps) -> iced::Element<'static, Message> {
+    let HalvingProps { countdown, status, eras, chart } = props;
+
+    let mut section = Column::new()
+        .spacing(8)
+        .width(Length::Fixed(SECTION_WIDTH))
+        .push(text("Next halving").size(20));
+
+    if let Some(c) = countdown {
+        section = section
+            .push(text(format!("≈ {}", format_wait(c.secs_left))).size(28))
+            .push(
+                text(format!(
+                    "Block {} in {} blocks, around {} UTC at {}m {:02}s per block",
+                    format_count(c.height),
+                    format_count(c.blocks_left),
+                    c.date,
+                    c.block_secs / 60,
+                    c.block_secs % 60,
+                ))
+                .size(14),
+            )
+            .push(
+                text(format!(
+                    "Subsidy {} → {} BTC per block",
+                    format_btc(c.subsidy_before),
+                    format_btc(c.subsidy_after)
+                ))
+                .size(14),
+            )
+            .push(
+                text(format!(
+                    "{} BTC issued so far, {:.2}% of the final supply",
+                    format_btc(c.supply),
+                    c.supply as f64 / crate::supply::MAX_SUPPLY as f64 * 100.0
+                ))
+                .size(14),
+            );
+    }
+    if let Some(status) = status {
+        section = section.push(text(status).size(14));
+    }
+
+    section = section
+        .push(space::vertical().height(10))
+        .push(text("Circulating supply by block height").size(16))
+        .push(canvas(chart).width(Fill).height(Length::Fixed(CHART_HEIGHT)))
+        .push(space::vertical().height(10))
+        .push(text("Subsidy schedule").size(16));
+
+    let cell = |content: String, portion| {
+        text(content)
+            .size(13)
+            .font(Font::MONOSPACE)
+            .width(Length::FillPortion(portion))
+    };
+    let header = Row::new()
+        .push(cell("Era".into(), 1))
+        .push(cell("From block".into(), 3))
+        .push(cell("Subsidy (BTC)".into(), 3))
+        .push(cell("Supply after (BTC)".into(), 4));
+    let table = eras.into_iter().fold(
+        Column::new().spacing(4).push(header).push(rule::horizontal(1)),
+        |table, era| {
+            let marker = if era.current { " ◀ now" } else { "" };
+            table.push(
+                Row::new()
+                    .push(cell(era.index.to_string(), 1))
+                    .push(cell(format_count(era.first_height), 3))
+                    .push(cell(format_btc(era.subsidy), 3))
+                    .push(cell(format!("{}{marker}", format_btc(era.supply_at_end)), 4)),
+            )
+        },
+    );
+
+    section.push(table).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn btc_amounts_drop_trailing_zeros() {
+        assert_eq!(format_btc(50 * COIN), "50");
+        assert_eq!(format_btc(312_500_000), "3.125");
+        assert_eq!(format_btc(1), "0.00000001");
+        assert_eq!(format_btc(crate::supply::MAX_SUPPLY), "20,999,999.9769");
+    }
+
+    #[test]
+    fn waits_are_days_and_hours() {
+        assert_eq!(format_wait(123_375_000), "1,427 days 22 hours");
+        assert_eq!(format_wait(90_000), "1 day 1 hour");
+        assert_eq!(format_wait(5_400), "1 hour 30 minutes");
+    }
+}
@@ -0,0 +1,311 @@
+use iced::widget::{
+    button, checkbox, column, pick_list, row, scrollable, text, text_input, Column, Row,
+};
+use iced::{Center, Element, Fill, Font, Length};
+
+use crate::message::AlertsMessage;
+use crate::notify::{Channel, SmtpSecurity, WebhookPreset};
+use crate::pages::alerts_page::{AlertsTab, Field};
+
+const LABEL_WIDTH: f32 = 90.0;
+
+/// One text setting of the shown channel
+pub struct FieldRow {
+    pub label: &'static str,
+    pub field: Field,
+    pub value: String,
+    pub placeholder: &'static str,
+    /// Masked while typing
+    pub secure: bool,
+}
+
+/// One saved rule in the editor
+pub struct RuleRow {
+    pub source: String,
+    /// Parse or type error rendered under the rule text
+    pub error: Option<String>,
+    /// When the rule would have fired over recent history
+    pub preview: Option<String>,
+}
+
+/// The Rules tab
+pub struct RulesProps {
+    pub rules: Vec<RuleRow>,
+    pub new_rule: String,
+    pub new_rule_error: Option<String>,
+    pub can_add: bool,
+    /// Where the last save went, or why it failed
+    pub status: Option<String>,
+}
+
+/// A channel's tab
+pub struct ChannelProps {
+    pub channel: Channel,
+    pub enabled: bool,
+    /// What the channel sends and where
+    pub hint: String,
+    /// Webhook only: the preset matching the template, if any
+    pub preset: Option<Option<WebhookPreset>>,
+    /// Email only
+    pub security: Option<SmtpSecurity>,
+    pub fields: Vec<FieldRow>,
+    /// Test in flight or its outcome
+    pub status: Option<String>,
+    pub sending: bool,
+    /// Outcome of the last alert a rule sent
+    pub delivery: Option<String>,
+    pub save_status: Option<String>,
+}
+
+/// What the shown tab draws
+pub enum AlertsContent {
+    Rules(RulesProps),
+    Channel(ChannelProps),
+}
+
+/// Everything the alerts view draws
+pub struct AlertsProps {
+    pub tab: AlertsTab,
+    /// Switched-on channels, marked on their tabs
+    pub enabled_channels: Vec<Channel>,
+    pub content: AlertsContent,
+}
+
+/// Alerts page view: a rule editor tab, then one tab per delivery channel
+/// with its settings
+pub struct AlertsView {
+    props: AlertsProps,
+}
+
+impl AlertsView {
+    pub fn new(props: AlertsProps) -> Self {
+        Self { props }
+    }
+
+    pub fn view<'a>(self) -> Element<'a, AlertsMessage> {
+        let AlertsProps {
+            tab,
+            enabled_channels,
+            content,
+        } = self.props;
+
+        let tab_button = |label: String, target: AlertsTab| {
+            button(text(label).size(14))
+                .style(if target == tab {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .on_press(AlertsMessage::TabSelected(target))
+                .into()
+        };
+        let tabs = Row::with_children(
+            std::iter::once(tab_button("Rules".to_string(), AlertsTab::Rules)).chain(
+                Channel::ALL.into_iter().map(|c| {
+                    let label = if enabled_channels.contains(&c) {
+                        format!("{} ●", c.label())
+                    } else {
+                        c.label().to_string()
+                    };
+                    tab_button(label, AlertsTab::Channel(c))
+                }),
+            ),
+        )
+        .spacing(6);
+
+        let body = match content {
+            AlertsContent::Rules(props) => rules_view(props),
+            AlertsContent::Channel(props) => channel_view(props),
+        };
+
+        scrollable(
+            column![text("Alerts").size(20), tabs, body]
+                .spacing(12)
+                .padding([10, 30])
+                .width(Fill),
+        )
+        .height(Fill)
+        .into()
+    }
+}
+
+/// Rule errors line their carets up under the rule text
+fn error_text<'a>(rendered: String) -> Element<'a, AlertsMessage> {
+    text(rendered)
+        .size(13)
+        .font(Font::MONOSPACE)
+        .style(text::danger)
+        .into()
+}
+
+fn rules_view<'a>(props: RulesProps) -> Element<'a, AlertsMessage> {
+    let RulesProps {
+        rules,
+        new_rule,
+        new_rule_error,
+        can_add,
+        status,
+    } = props;
+
+    let mut list = Column::new().spacing(10);
+    for (
+        index,
+        RuleRow {
+            source,
+            error,
+            preview,
+        },
+    ) in rules.into_iter().enumerate()
+    {
+        let mut item = column![row![
+            text_input("usd > 100000", &source)
+                .on_input(move |source| AlertsMessage::RuleChanged(index, source))
+                .font(Font::MONOSPACE)
+                .size(14),
+            button(text("Remove").size(14))
+                .style(button::secondary)
+                .on_press(AlertsMessage::RemoveRule(index)),
+        ]
+        .spacing(10)
+        .align_y(Center)]
+        .spacing(4);
+        if let Some(error) = error {
+            item = item.push(error_text(error));
+        }
+        if let Some(preview) = preview {
+            item = item.push(text(preview).size(13));
+        }
+        list = list.push(item);
+    }
+
+    let mut add = button(text("Add").size(14));
+    if can_add {
+        add = add.on_press(AlertsMessage::AddRule);
+    }
+    let mut form = column![
+        text(
+            "Conditions over the fetched prices, such as `usd > 100000 and change_24h > 5%` \
+             or `krw premium > 3% for 10m`. A rule alerts the enabled channels when it fires, \
+             while price alerts are enabled in Settings."
+        )
+        .size(13),
+        list,
+        row![
+            text_input("New rule", &new_rule)
+                .on_input(AlertsMessage::NewRuleChanged)
+                .on_submit(AlertsMessage::AddRule)
+                .font(Font::MONOSPACE)
+                .size(14),
+            add,
+        ]
+        .spacing(10)
+        .align_y(Center),
+    ]
+    .spacing(12);
+    if let Some(error) = new_rule_error {
+        form = form.push(error_text(error));
+    }
+    form = form.push(
+        button(text("Save rules").size(14))
+            .style(button::secondary)
+            .on_press(AlertsMessage::SaveRules),
+    );
+    if let Some(status) = status {
+        form = form.push(text(status).size(13));
+    }
+    form.into()
+}
+
+fn channel_view<'a>(props: ChannelProps) -> Element<'a, AlertsMessage> {
+    let ChannelProps {
+        channel,
+        enabled,
+        hint,
+        preset,
+        security,
+        fields,
+        status,
+        sending,
+        delivery,
+        save_status,
+    } = props;
+
+    let labelled = |label: &'a str, control: Element<'a, AlertsMessage>| {
+        row![
+            text(label).size(14).width(Length::Fixed(LABEL_WIDTH)),
+            control
+        ]
+        .spacing(10)
+        .align_y(Center)
+    };
+
+    let mut form = column![
+        text(hint).size(13),
+        checkbox(enabled)
+            .label(format!("Send alerts by {}", channel.label()))
+            .on_toggle(AlertsMessage::EnabledToggled),
+    ]
+    .spacing(12);
+    if let Some(preset) = preset {
+        form = form.push(labelled(
+            "Preset",
+            pick_list(WebhookPreset::ALL, preset, AlertsMessage::PresetSelected)
+                .placeholder("Custom")
+                .text_size(14)
+                .into(),
+        ));
+    }
+    if let Some(security) = security {
+        form = form.push(labelled(
+            "Security",
+            pick_list(
+                SmtpSecurity::ALL,
+                Some(security),
+                AlertsMessage::SecuritySelected,
+            )
+            .text_size(14)
+            .into(),
+        ));
+    }
+    for FieldRow {
+        label,
+        field,
+        value,
+        placeholder,
+        secure,
+    } in fields
+    {
+        form = form.push(labelled(
+            label,
+            text_input(placeholder, &value)
+                .on_input(move |value| AlertsMessage::FieldChanged(field, value))
+                .secure(secure)
+                .size(14)
+                .into(),
+        ));
+    }
+
+    let mut test = button(text("Send test").size(14));
+    if !sending {
+        test = test.on_press(AlertsMessage::SendTest);
+    }
+    form = form.push(
+        row![
+            test,
+            button(text("Save").size(14))
+                .style(button::secondary)
+                .on_press(AlertsMessage::Save),
+        ]
+        .spacing(10),
+    );
+    if let Some(status) = status {
+        form = form.push(text(status).size(14));
+    }
+    if let Some(delivery) = delivery {
+        form = form.push(text(delivery).size(13));
+    }
+    if let Some(save_status) = save_status {
+        form = form.push(text(save_status).size(13));
+    }
+    form.into()
+}
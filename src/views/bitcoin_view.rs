@@ -1,21 +1,67 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use iced::widget::{image, svg, Column, Container, Row, Scrollable, Text};
+use iced::widget::{
+    canvas, image, svg, text_input, tooltip, Column, Container, Row, Scrollable, Text,
+};
 use iced::{widget, Center, Fill, Length};
 
 use crate::country::CountryPrice;
+use crate::history::PriceHistory;
 use crate::message::BitcoinMessage;
+use crate::views::error_panel::ErrorPanel;
+use crate::views::sparkline::Sparkline;
 
 /// Default SVG content for missing flag images
 const DEFAULT_SVG: &[u8] = br#"<svg width="40" height="30" xmlns="http://www.w3.org/2000/svg"><rect width="100%" height="100%" fill="gray"/></svg>"#;
 
+/// Number of recent samples drawn in each row's sparkline
+pub const SPARKLINE_POINTS: usize = 30;
+
+/// Id of the currency filter input, focused by the search shortcut
+pub const SEARCH_INPUT: &str = "currency-search";
+
+/// Data age information shown in the status bar
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Freshness {
+    /// When the prices were fetched (Unix seconds)
+    pub fetched_at: Option<u64>,
+    /// Provider's own quote time (Unix seconds)
+    pub provider_updated_at: Option<u64>,
+    /// Current wall-clock time (Unix seconds)
+    pub now: u64,
+    /// Age in seconds after which prices count as stale
+    pub stale_after: u64,
+}
+
+impl Freshness {
+    pub fn is_stale(&self) -> bool {
+        self.fetched_at
+            .is_some_and(|t| self.now.saturating_sub(t) > self.stale_after)
+    }
+}
+
+/// Formats an age in seconds as a short relative time ("42s ago", "3h ago")
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        0..5 => "just now".to_string(),
+        5..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
 /// View component for displaying Bitcoin prices
 pub struct BitcoinView<'a> {
     price_usd: f64,
     vs_currencies: &'a [CountryPrice],
     svg_map: &'a HashMap<String, svg::Handle>,
     is_loading: bool,
-    error_message: Option<&'a str>,
+    error_panel: Option<ErrorPanel<'a>>,
+    supported: Option<&'a HashSet<String>>,
+    freshness: Freshness,
+    search: &'a str,
+    history: Option<&'a PriceHistory>,
 }
 
 impl<'a> BitcoinView<'a> {
@@ -24,23 +70,42 @@ impl<'a> BitcoinView<'a> {
         vs_currencies: &'a [CountryPrice],
         svg_map: &'a HashMap<String, svg::Handle>,
         is_loading: bool,
-        error_message: Option<&'a str>,
+        error_panel: Option<ErrorPanel<'a>>,
+        supported: Option<&'a HashSet<String>>,
+        freshness: Freshness,
     ) -> Self {
         Self {
             price_usd,
             vs_currencies,
             svg_map,
             is_loading,
-            error_message,
+            error_panel,
+            supported,
+            freshness,
+            search: "",
+            history: None,
         }
     }
 
-    pub fn view(self) -> iced::Element<'a, BitcoinMessage> {
+    /// Draws a trend sparkline per row from the recent samples in `history`
+    pub fn history(mut self, history: &'a PriceHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Only lists currencies whose code or country contains `search`
+    pub fn search(mut self, search: &'a str) -> Self {
+        self.search = search;
+        self
+    }
+
+    pub fn view(mut self) -> iced::Element<'a, BitcoinMessage> {
         let header = self.build_header();
         let controls = self.build_controls();
+        let status = self.build_status_bar();
         let content = self.build_content();
 
-        widget::column![header, controls, content]
+        widget::column![header, controls, status, content]
             .width(Fill)
             .spacing(15)
             .padding([10, 50])
@@ -75,20 +140,60 @@ impl<'a> BitcoinView<'a> {
                 .on_press(BitcoinMessage::Refetch)
         };
 
-        Container::new(fetch_button)
+        let search = text_input("Search currencies…", self.search)
+            .id(SEARCH_INPUT)
+            .on_input(BitcoinMessage::SearchChanged)
+            .width(Length::Fixed(220.0));
+
+        Container::new(Row::new().spacing(10).align_y(Center).push(fetch_button).push(search))
             .center_x(Fill)
             .into()
     }
 
-    fn build_content(&self) -> iced::Element<'a, BitcoinMessage> {
-        if let Some(error) = self.error_message {
-            return Container::new(
-                Text::new(format!("Error: {}", error))
-                    .size(16)
-                    .color([1.0, 0.0, 0.0]),
-            )
-            .center_x(Fill)
-            .into();
+    fn build_status_bar(&self) -> iced::Element<'a, BitcoinMessage> {
+        let Freshness {
+            fetched_at,
+            provider_updated_at,
+            now,
+            ..
+        } = self.freshness;
+
+        let Some(fetched_at) = fetched_at else {
+            return Text::new("No data yet").size(13).into();
+        };
+
+        let mut status = Row::new().spacing(12).align_y(Center).push(
+            Text::new(format!("Updated {}", format_age(now.saturating_sub(fetched_at)))).size(13),
+        );
+
+        if let Some(quoted_at) = provider_updated_at {
+            status = status.push(
+                Text::new(format!(
+                    "CoinGecko quote {}",
+                    format_age(now.saturating_sub(quoted_at))
+                ))
+                .size(13),
+            );
+        }
+
+        if self.freshness.is_stale() {
+            status = status.push(Text::new("Stale").size(13).style(widget::text::danger));
+        }
+
+        status.into()
+    }
+
+    fn build_content(&mut self) -> iced::Element<'a, BitcoinMessage> {
+        if let Some(panel) = self.error_panel.take() {
+            let panel = panel.view();
+
+            // Keep showing the last good prices underneath the error
+            if self.vs_currencies.is_empty() {
+                return panel;
+            }
+            return widget::column![panel, self.build_currency_list()]
+                .spacing(10)
+                .into();
         }
 
         if self.is_loading && self.vs_currencies.is_empty() {
@@ -111,12 +216,16 @@ impl<'a> BitcoinView<'a> {
             .push(Text::new("Flag").width(Length::Fixed(40.0)).size(14))
             .push(Text::new("Country").width(Length::FillPortion(6)).size(14))
             .push(Text::new("Currency").width(Length::FillPortion(2)).size(14))
-            .push(Text::new("Price").width(Length::FillPortion(4)).size(14));
+            .push(Text::new("Price").width(Length::FillPortion(4)).size(14))
+            .push(Text::new("Trend").width(Length::Fixed(100.0)).size(14));
 
         column = column.push(header_row);
         column = column.push(widget::rule::horizontal(1));
 
-        for country_price in self.vs_currencies {
+        let query = self.search.trim().to_lowercase();
+        for country_price in self.vs_currencies.iter().filter(|p| {
+            p.country.currency.contains(&query) || p.country.name.to_lowercase().contains(&query)
+        }) {
             column = column.push(self.build_currency_row(country_price));
         }
 
@@ -127,13 +236,23 @@ impl<'a> BitcoinView<'a> {
     }
 
     fn build_currency_row(&self, country_price: &'a CountryPrice) -> Row<'a, BitcoinMessage> {
-        let country_text = Text::new(country_price.country.name).size(16);
+        let country_text = Text::new(&country_price.country.name).size(16);
         let currency_text = Text::new(country_price.country.currency.to_uppercase()).size(16);
-        let price_text = Text::new(format!("{:.2}", country_price.price)).size(16);
+        let price: iced::Element<'a, BitcoinMessage> = match country_price.price {
+            Some(price) => Text::new(format!("{price:.2}")).size(16).into(),
+            None => tooltip(
+                Text::new("N/A").size(16),
+                Container::new(Text::new(self.missing_reason(country_price)).size(13))
+                    .padding(6)
+                    .style(widget::container::rounded_box),
+                tooltip::Position::Top,
+            )
+            .into(),
+        };
 
         let handle = self
             .svg_map
-            .get(country_price.country.country_code)
+            .get(&country_price.country.country_code)
             .cloned()
             .unwrap_or_else(|| svg::Handle::from_memory(DEFAULT_SVG.to_vec()));
         let svg_image = svg(handle).width(40).height(30);
@@ -145,6 +264,80 @@ impl<'a> BitcoinView<'a> {
             .push(svg_image.width(Length::Fixed(40.0)))
             .push(country_text.width(Length::FillPortion(6)))
             .push(currency_text.width(Length::FillPortion(2)))
-            .push(price_text.width(Length::FillPortion(4)))
+            .push(Container::new(price).width(Length::FillPortion(4)))
+            .push(self.build_sparkline(&country_price.country.currency))
+    }
+
+    fn build_sparkline(&self, currency: &str) -> iced::Element<'a, BitcoinMessage> {
+        let values = self
+            .history
+            .map(|h| h.series(currency, SPARKLINE_POINTS))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| p.price)
+            .collect();
+        canvas(Sparkline::new(values))
+            .width(Length::Fixed(100.0))
+            .height(Length::Fixed(30.0))
+            .into()
+    }
+
+    /// Tooltip text explaining why a price is missing
+    fn missing_reason(&self, country_price: &CountryPrice) -> String {
+        let currency = &country_price.country.currency;
+        if self.supported.is_some_and(|s| !s.contains(currency)) {
+            format!("CoinGecko does not support {}", currency.to_uppercase())
+        } else {
+            format!("No {} quote in the last response", currency.to_uppercase())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_relative_ages() {
+        assert_eq!(format_age(0), "just now");
+        assert_eq!(format_age(42), "42s ago");
+        assert_eq!(format_age(600), "10m ago");
+        assert_eq!(format_age(7200), "2h ago");
+        assert_eq!(format_age(3 * 86400 + 5), "3d ago");
+    }
+
+    #[test]
+    fn missing_reason_distinguishes_unsupported_currencies() {
+        let prices = crate::testing::sample_snapshot().prices;
+        let svg_map = HashMap::new();
+        let supported: HashSet<String> = ["usd", "jpy"].map(String::from).into();
+        let view = |supported| {
+            BitcoinView::new(0.0, &prices, &svg_map, false, None, supported, Freshness::default())
+        };
+
+        assert_eq!(
+            view(Some(&supported)).missing_reason(&prices[1]),
+            "CoinGecko does not support GBP"
+        );
+        assert_eq!(
+            view(Some(&supported)).missing_reason(&prices[2]),
+            "No JPY quote in the last response"
+        );
+        assert_eq!(view(None).missing_reason(&prices[1]), "No GBP quote in the last response");
+    }
+
+    #[test]
+    fn stale_only_past_threshold() {
+        let mut freshness = Freshness {
+            fetched_at: Some(1000),
+            provider_updated_at: None,
+            now: 1090,
+            stale_after: 90,
+        };
+        assert!(!freshness.is_stale());
+        freshness.now = 1091;
+        assert!(freshness.is_stale());
+        freshness.fetched_at = None;
+        assert!(!freshness.is_stale());
     }
 }
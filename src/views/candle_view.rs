@@ -0,0 +1,90 @@
+use iced::widget::{button, canvas, column, pick_list, row, space, text, Row};
+use iced::{Center, Element, Fill, Length};
+
+use crate::message::CandleMessage;
+use crate::pages::candle_page::CandleInterval;
+use crate::views::price_chart::CandleChart;
+
+const CHART_HEIGHT: f32 = 420.0;
+
+/// Everything the candles view draws
+pub struct CandleProps {
+    pub interval: CandleInterval,
+    /// Uppercase code of the charted currency
+    pub currency: String,
+    pub currencies: Vec<String>,
+    /// Loading, error or empty-chart message shown instead of the candle count
+    pub status: Option<String>,
+    pub count: usize,
+    pub chart: CandleChart,
+}
+
+/// Candles page view: interval and currency pickers over the candlestick chart
+pub struct CandleView {
+    props: CandleProps,
+}
+
+impl CandleView {
+    pub fn new(props: CandleProps) -> Self {
+        Self { props }
+    }
+
+    pub fn view<'a>(self) -> Element<'a, CandleMessage> {
+        let CandleProps {
+            interval,
+            currency,
+            currencies,
+            status,
+            count,
+            chart,
+        } = self.props;
+
+        let intervals = |all: &[CandleInterval]| {
+            Row::with_children(all.iter().map(|&i| {
+                button(text(i.label()).size(14))
+                    .style(if i == interval {
+                        button::primary
+                    } else {
+                        button::secondary
+                    })
+                    .on_press(CandleMessage::IntervalSelected(i))
+                    .into()
+            }))
+            .spacing(6)
+        };
+
+        let controls = row![
+            text("Recorded").size(14),
+            intervals(&CandleInterval::RECORDED),
+            text("CoinGecko").size(14),
+            intervals(&CandleInterval::COINGECKO),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let caption = text(status.unwrap_or_else(|| match count {
+            1 => "1 candle".to_string(),
+            n => format!("{n} candles"),
+        }))
+        .size(13);
+
+        column![
+            controls,
+            row![
+                caption,
+                space::horizontal(),
+                text("Currency").size(14),
+                pick_list(currencies, Some(currency), CandleMessage::CurrencySelected)
+                    .text_size(14),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            canvas(chart)
+                .width(Fill)
+                .height(Length::Fixed(CHART_HEIGHT)),
+        ]
+        .spacing(12)
+        .padding([10, 30])
+        .into()
+    }
+}
@@ -0,0 +1,142 @@
+use iced::widget::{
+    button, canvas, checkbox, column, pick_list, row, scrollable, space, text, Row,
+};
+use iced::{Center, Element, Fill, Length, Theme};
+
+use crate::message::ChartMessage;
+use crate::pages::chart_page::{ChartRange, Overlay, Pane};
+use crate::views::price_chart::{overlay_color, PaneChart, PriceChart};
+
+const CHART_HEIGHT: f32 = 320.0;
+const PANE_HEIGHT: f32 = 120.0;
+
+/// Everything the chart view draws
+pub struct ChartProps<'a> {
+    pub range: ChartRange,
+    /// Uppercase code of the charted currency
+    pub currency: String,
+    pub currencies: Vec<String>,
+    pub overlays: &'a [Overlay],
+    pub panes: &'a [Pane],
+    /// Loading, error or empty-chart message shown instead of the point count
+    pub status: Option<String>,
+    pub points: usize,
+    pub chart: PriceChart,
+    pub pane_charts: Vec<PaneChart>,
+}
+
+/// Chart page view: range and indicator controls over the price chart and
+/// one pane per enabled sub-indicator
+pub struct ChartView<'a> {
+    props: ChartProps<'a>,
+}
+
+impl<'a> ChartView<'a> {
+    pub fn new(props: ChartProps<'a>) -> Self {
+        Self { props }
+    }
+
+    pub fn view(self) -> Element<'a, ChartMessage> {
+        let ChartProps {
+            range,
+            currency,
+            currencies,
+            overlays,
+            panes,
+            status,
+            points,
+            chart,
+            pane_charts,
+        } = self.props;
+
+        let ranges = Row::with_children(ChartRange::ALL.into_iter().map(|r| {
+            button(text(r.label()).size(14))
+                .style(if r == range {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .on_press(ChartMessage::RangeSelected(r))
+                .into()
+        }))
+        .spacing(6);
+
+        let controls = row![
+            ranges,
+            space::horizontal(),
+            text("Currency").size(14),
+            pick_list(currencies, Some(currency), ChartMessage::CurrencySelected).text_size(14),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let overlay_toggles = Row::with_children(Overlay::ALL.into_iter().map(|o| {
+            checkbox(overlays.contains(&o))
+                .label(o.label())
+                .on_toggle(move |on| ChartMessage::OverlayToggled(o, on))
+                .size(16)
+                .text_size(14)
+                .into()
+        }))
+        .spacing(16);
+
+        let pane_toggles = Row::with_children(Pane::ALL.into_iter().map(|p| {
+            checkbox(panes.contains(&p))
+                .label(p.label())
+                .on_toggle(move |on| ChartMessage::PaneToggled(p, on))
+                .size(16)
+                .text_size(14)
+                .into()
+        }))
+        .spacing(16);
+
+        let caption = text(status.unwrap_or_else(|| format!("{points} prices"))).size(13);
+
+        let legend = Row::with_children(
+            std::iter::once(legend_entry("Price", |theme| theme.palette().primary)).chain(
+                overlays
+                    .iter()
+                    .map(|&o| legend_entry(o.label(), move |theme| overlay_color(o, theme))),
+            ),
+        )
+        .spacing(16);
+
+        let mut content = column![
+            controls,
+            row![
+                text("Overlays").size(14).width(Length::Fixed(70.0)),
+                overlay_toggles
+            ]
+            .align_y(Center),
+            row![
+                text("Panes").size(14).width(Length::Fixed(70.0)),
+                pane_toggles
+            ]
+            .align_y(Center),
+            row![caption, space::horizontal(), legend].align_y(Center),
+            canvas(chart)
+                .width(Fill)
+                .height(Length::Fixed(CHART_HEIGHT)),
+        ]
+        .spacing(12)
+        .padding([10, 30]);
+
+        for pane in pane_charts {
+            content = content.push(canvas(pane).width(Fill).height(Length::Fixed(PANE_HEIGHT)));
+        }
+
+        scrollable(content).height(Fill).into()
+    }
+}
+
+fn legend_entry<'a>(
+    label: &'a str,
+    color: impl Fn(&Theme) -> iced::Color + 'a,
+) -> Element<'a, ChartMessage> {
+    text(format!("— {label}"))
+        .size(13)
+        .style(move |theme: &Theme| text::Style {
+            color: Some(color(theme)),
+        })
+        .into()
+}
@@ -0,0 +1,72 @@
+use iced::widget::{button, column, container, opaque, row, text, text_input, Column};
+use iced::{Center, Element, Fill, Length};
+
+use crate::message::PaletteMessage;
+
+/// Id of the palette query input, focused when the palette opens
+pub const QUERY_INPUT: &str = "command-palette-query";
+
+/// One palette result: label and optional shortcut hint
+pub struct PaletteEntry {
+    pub label: String,
+    pub hint: Option<String>,
+}
+
+/// Ctrl+K command palette: query input over the best matching results
+pub struct CommandPaletteView<'a> {
+    query: &'a str,
+    entries: Vec<PaletteEntry>,
+    selected: usize,
+}
+
+impl<'a> CommandPaletteView<'a> {
+    pub fn new(query: &'a str, entries: Vec<PaletteEntry>, selected: usize) -> Self {
+        Self {
+            query,
+            entries,
+            selected,
+        }
+    }
+
+    pub fn view(self) -> Element<'a, PaletteMessage> {
+        let input = text_input("Type a command or currency…", self.query)
+            .id(QUERY_INPUT)
+            .on_input(PaletteMessage::QueryChanged)
+            .on_submit(PaletteMessage::Submit)
+            .padding(10);
+
+        let results: Element<'a, PaletteMessage> = if self.entries.is_empty() {
+            text("No matches").size(14).into()
+        } else {
+            Column::with_children(self.entries.into_iter().enumerate().map(|(i, entry)| {
+                let hint = text(entry.hint.unwrap_or_default()).size(13);
+                button(row![text(entry.label).width(Fill), hint].align_y(Center))
+                    .width(Fill)
+                    .style(if i == self.selected {
+                        button::primary
+                    } else {
+                        button::text
+                    })
+                    .on_press(PaletteMessage::Select(i))
+                    .into()
+            }))
+            .spacing(2)
+            .into()
+        };
+
+        let panel = container(
+            column![
+                input,
+                results,
+                text("↑↓ to choose · Enter to run · Esc to close").size(12),
+            ]
+            .spacing(10),
+        )
+        .padding(12)
+        .width(Length::Fixed(480.0))
+        .style(container::bordered_box);
+
+        // Clicks on the panel stay in the panel; the backdrop closes it
+        opaque(panel)
+    }
+}
@@ -0,0 +1,75 @@
+use iced::widget::{button, column, container, row, text, Column};
+use iced::{Center, Fill};
+
+use crate::api::ApiError;
+use crate::message::BitcoinMessage;
+
+/// Fetch error panel: friendly category, retry, and expandable technical details
+pub struct ErrorPanel<'a> {
+    error: &'a ApiError,
+    expanded: bool,
+    can_retry: bool,
+}
+
+impl<'a> ErrorPanel<'a> {
+    pub fn new(error: &'a ApiError, expanded: bool, can_retry: bool) -> Self {
+        Self {
+            error,
+            expanded,
+            can_retry,
+        }
+    }
+
+    pub fn view(self) -> iced::Element<'a, BitcoinMessage> {
+        let category = self.error.category();
+
+        let retry = button("Retry").style(button::primary);
+        let retry = if self.can_retry {
+            retry.on_press(BitcoinMessage::Refetch)
+        } else {
+            retry
+        };
+        let toggle = button(if self.expanded {
+            "Hide details"
+        } else {
+            "Show details"
+        })
+        .style(button::secondary)
+        .on_press(BitcoinMessage::ToggleErrorDetails);
+        let copy = button("Copy diagnostics")
+            .style(button::secondary)
+            .on_press(BitcoinMessage::CopyDiagnostics);
+
+        let mut panel = column![
+            text(category.title()).size(18).style(text::danger),
+            text(category.hint()).size(14),
+            row![retry, toggle, copy].spacing(10),
+        ]
+        .spacing(8)
+        .align_x(Center);
+
+        if self.expanded {
+            panel = panel.push(self.build_details());
+        }
+
+        container(panel)
+            .padding(12)
+            .center_x(Fill)
+            .style(container::bordered_box)
+            .into()
+    }
+
+    fn build_details(&self) -> iced::Element<'a, BitcoinMessage> {
+        let mut details = Column::new()
+            .spacing(4)
+            .push(text(self.error.to_string()).size(13));
+        if let Some(status) = self.error.status() {
+            details = details.push(text(format!("Status: {status}")).size(13));
+        }
+        details = details.push(text(format!("URL: {}", self.error.url())).size(13));
+        if let Some(body) = self.error.body_excerpt() {
+            details = details.push(text(format!("Response: {body}")).size(13));
+        }
+        details.width(Fill).into()
+    }
+}
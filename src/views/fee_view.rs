@@ -0,0 +1,168 @@
+use iced::widget::{
+    column, pick_list, row, rule, scrollable, space, text, text_input, Column, Row,
+};
+use iced::{Center, Element, Fill, Length};
+
+use crate::message::FeeMessage;
+use crate::pages::fee_page::ScriptChoice;
+use crate::views::network_view::format_fee_rate;
+
+const COUNT_WIDTH: f32 = 70.0;
+
+/// Cost of the transaction at one recommended fee rate
+pub struct FeeQuote {
+    pub label: &'static str,
+    /// Roughly when a transaction paying this rate confirms
+    pub target: &'static str,
+    /// sat/vB
+    pub rate: f64,
+    pub sats: String,
+    /// In the selected currency, when a price is known
+    pub fiat: Option<String>,
+}
+
+/// Everything the fee calculator draws
+pub struct FeeProps {
+    pub script: ScriptChoice,
+    /// Counts as typed
+    pub inputs: String,
+    pub outputs: String,
+    /// Signatures and keys as typed, for multisig scripts
+    pub multisig: Option<(String, String)>,
+    /// Why the typed shape cannot be sized
+    pub error: Option<String>,
+    /// Virtual size and weight
+    pub size: Option<String>,
+    /// Missing fee rates or price
+    pub notice: Option<String>,
+    /// Uppercase codes with a price in the latest sample
+    pub currencies: Vec<String>,
+    pub currency: Option<String>,
+    /// Fastest first
+    pub quotes: Vec<FeeQuote>,
+}
+
+/// Fee calculator view: transaction shape inputs over a fee table
+pub struct FeeView {
+    props: FeeProps,
+}
+
+impl FeeView {
+    pub fn new(props: FeeProps) -> Self {
+        Self { props }
+    }
+
+    pub fn view<'a>(self) -> Element<'a, FeeMessage> {
+        let FeeProps {
+            script,
+            inputs,
+            outputs,
+            multisig,
+            error,
+            size,
+            notice,
+            currencies,
+            currency,
+            quotes,
+        } = self.props;
+
+        let count = |label: &'a str, value: &str, on_input: fn(String) -> FeeMessage| {
+            row![
+                text(label).size(14),
+                text_input("1", value)
+                    .on_input(on_input)
+                    .size(14)
+                    .width(Length::Fixed(COUNT_WIDTH)),
+            ]
+            .spacing(8)
+            .align_y(Center)
+        };
+
+        let header = row![
+            text("Transaction cost").size(20),
+            space::horizontal(),
+            pick_list(currencies, currency, FeeMessage::CurrencySelected).text_size(14),
+        ]
+        .align_y(Center);
+
+        let mut shape = Row::new()
+            .spacing(16)
+            .align_y(Center)
+            .push(
+                pick_list(ScriptChoice::ALL, Some(script), FeeMessage::ScriptSelected)
+                    .text_size(14),
+            )
+            .push(count("Inputs", &inputs, FeeMessage::InputsChanged))
+            .push(count("Outputs", &outputs, FeeMessage::OutputsChanged));
+        if let Some((required, keys)) = multisig {
+            shape = shape.push(
+                row![
+                    text_input("m", &required)
+                        .on_input(FeeMessage::RequiredChanged)
+                        .size(14)
+                        .width(Length::Fixed(44.0)),
+                    text("of").size(14),
+                    text_input("n", &keys)
+                        .on_input(FeeMessage::KeysChanged)
+                        .size(14)
+                        .width(Length::Fixed(44.0)),
+                ]
+                .spacing(6)
+                .align_y(Center),
+            );
+        }
+
+        let mut content = column![
+            header,
+            text("Every input and output uses the chosen script type; signatures are sized for the worst case")
+                .size(13),
+            shape.wrap(),
+        ]
+        .spacing(12);
+
+        if let Some(error) = error {
+            content = content.push(text(error).size(14));
+        }
+        if let Some(size) = size {
+            content = content.push(text(size).size(18));
+        }
+        if let Some(notice) = notice {
+            content = content.push(text(notice).size(14));
+        }
+
+        if !quotes.is_empty() {
+            let cell = |content: String, portion| {
+                text(content).size(14).width(Length::FillPortion(portion))
+            };
+            let header = Row::new()
+                .spacing(10)
+                .push(cell("Priority".into(), 3))
+                .push(cell("Rate".into(), 3))
+                .push(cell("Fee".into(), 3))
+                .push(cell("Value".into(), 3))
+                .push(cell("Confirms".into(), 4));
+            let table = quotes.into_iter().fold(
+                Column::new()
+                    .spacing(8)
+                    .push(header)
+                    .push(rule::horizontal(1)),
+                |table, q| {
+                    table.push(
+                        Row::new()
+                            .spacing(10)
+                            .push(cell(q.label.to_string(), 3))
+                            .push(cell(format_fee_rate(q.rate), 3))
+                            .push(cell(q.sats, 3))
+                            .push(cell(q.fiat.unwrap_or_else(|| "—".to_string()), 3))
+                            .push(cell(q.target.to_string(), 4)),
+                    )
+                },
+            );
+            content = content.push(table);
+        }
+
+        scrollable(content.padding([10, 30]).width(Fill))
+            .height(Fill)
+            .into()
+    }
+}
@@ -0,0 +1,314 @@
+use iced::widget::{
+    button, column, container, row, rule, scrollable, space, text, text_input, Column, Row,
+};
+use iced::{Center, Color, Element, Fill, Length, Theme};
+
+use crate::message::MarketsMessage;
+use crate::pages::markets_page::MarketsTab;
+
+/// Premiums within this distance of zero are shown without colour
+const NEUTRAL_PREMIUM: f64 = 0.01;
+/// Change since the last fetch at which a heatmap cell is fully coloured
+const FULL_SCALE_CHANGE: f64 = 0.005;
+const CELL_WIDTH: f32 = 96.0;
+
+/// One currency's BTC price against the FX-converted USD price
+pub struct PremiumRow {
+    pub country: String,
+    /// Uppercase currency code
+    pub currency: String,
+    pub local: f64,
+    pub implied: f64,
+    pub premium: f64,
+}
+
+/// Premium tab contents
+pub struct PremiumProps {
+    /// Reference rate provider and age, once rates are loaded
+    pub source: Option<String>,
+    /// Loading, error or waiting message
+    pub status: Option<String>,
+    pub loading: bool,
+    /// Highest premium first
+    pub rows: Vec<PremiumRow>,
+}
+
+/// One cell of the cross-rate heatmap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatrixCell {
+    /// Units of the column currency per row currency
+    pub rate: Option<f64>,
+    /// Relative move since the previous fetch
+    pub change: Option<f64>,
+}
+
+/// FX matrix tab contents
+pub struct MatrixProps {
+    /// Currency list as typed
+    pub input: String,
+    /// Uppercase codes shown as both rows and columns
+    pub currencies: Vec<String>,
+    /// Typed codes without a BTC price
+    pub unknown: Vec<String>,
+    /// `cells[row][column]`
+    pub cells: Vec<Vec<MatrixCell>>,
+    /// Waiting message, or why there are no changes to colour yet
+    pub status: Option<String>,
+}
+
+pub enum MarketsContent {
+    Premiums(PremiumProps),
+    Matrix(MatrixProps),
+}
+
+/// Markets page view: local premiums or the BTC-implied cross-rate heatmap
+pub struct MarketsView {
+    tab: MarketsTab,
+    content: MarketsContent,
+}
+
+/// Signed percentage such as `+4.20%`
+pub fn format_premium(premium: f64) -> String {
+    format!("{:+.2}%", premium * 100.0)
+}
+
+/// Exchange rate with four significant digits
+pub fn format_rate(rate: f64) -> String {
+    if rate <= 0.0 || !rate.is_finite() {
+        return "—".to_string();
+    }
+    let decimals = (3 - rate.log10().floor() as i32).clamp(0, 8) as usize;
+    format!("{rate:.decimals$}")
+}
+
+impl MarketsView {
+    pub fn new(tab: MarketsTab, content: MarketsContent) -> Self {
+        Self { tab, content }
+    }
+
+    pub fn view<'a>(self) -> Element<'a, MarketsMessage> {
+        let tabs = Row::with_children(MarketsTab::ALL.into_iter().map(|t| {
+            button(text(t.label()).size(14))
+                .style(if t == self.tab {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .on_press(MarketsMessage::TabSelected(t))
+                .into()
+        }))
+        .spacing(6);
+
+        let body = match self.content {
+            MarketsContent::Premiums(props) => premiums(props),
+            MarketsContent::Matrix(props) => matrix(props),
+        };
+
+        scrollable(column![tabs, body].spacing(12).padding([10, 30]))
+            .height(Fill)
+            .into()
+    }
+}
+
+fn premiums<'a>(props: PremiumProps) -> Element<'a, MarketsMessage> {
+    let PremiumProps {
+        source,
+        status,
+        loading,
+        rows,
+    } = props;
+
+    let refresh = button(text("Refresh FX rates").size(14))
+        .style(button::secondary)
+        .on_press_maybe((!loading).then_some(MarketsMessage::RefreshRates));
+
+    let header = row![
+        column![
+            text("Local premiums").size(20),
+            text(source.unwrap_or_default()).size(13),
+        ]
+        .spacing(4),
+        space::horizontal(),
+        refresh,
+    ]
+    .align_y(Center);
+
+    let mut content = column![
+        header,
+        text(
+            "BTC price in each currency compared with the USD price converted at the \
+             reference rate"
+        )
+        .size(13),
+    ]
+    .spacing(12);
+
+    if let Some(status) = status {
+        content = content.push(text(status).size(14));
+    }
+
+    if !rows.is_empty() {
+        let cell =
+            |content: String, portion| text(content).size(14).width(Length::FillPortion(portion));
+        let header = Row::new()
+            .spacing(10)
+            .push(cell("Country".into(), 5))
+            .push(cell("Currency".into(), 2))
+            .push(cell("BTC price".into(), 4))
+            .push(cell("USD × FX".into(), 4))
+            .push(cell("Premium".into(), 3));
+
+        let table = rows.into_iter().fold(
+            Column::new()
+                .spacing(8)
+                .push(header)
+                .push(rule::horizontal(1)),
+            |table, r| {
+                let premium = r.premium;
+                table.push(
+                    Row::new()
+                        .spacing(10)
+                        .push(cell(r.country, 5))
+                        .push(cell(r.currency, 2))
+                        .push(cell(format!("{:.2}", r.local), 4))
+                        .push(cell(format!("{:.2}", r.implied), 4))
+                        .push(
+                            text(format_premium(premium))
+                                .size(14)
+                                .width(Length::FillPortion(3))
+                                .style(move |theme: &Theme| premium_style(theme, premium)),
+                        ),
+                )
+            },
+        );
+        content = content.push(table);
+    }
+
+    content.into()
+}
+
+fn premium_style(theme: &Theme, premium: f64) -> text::Style {
+    let palette = theme.extended_palette();
+    text::Style {
+        color: match premium {
+            p if p >= NEUTRAL_PREMIUM => Some(palette.success.base.color),
+            p if p <= -NEUTRAL_PREMIUM => Some(palette.danger.base.color),
+            _ => None,
+        },
+    }
+}
+
+fn matrix<'a>(props: MatrixProps) -> Element<'a, MarketsMessage> {
+    let MatrixProps {
+        input,
+        currencies,
+        unknown,
+        cells,
+        status,
+    } = props;
+
+    let mut content = column![
+        text("Cross rates").size(20),
+        text(
+            "Units of the column currency per row currency, implied by BTC prices; \
+             colour shows the move since the previous fetch"
+        )
+        .size(13),
+        row![
+            text("Currencies").size(14),
+            text_input("usd, gbp, jpy", &input)
+                .on_input(MarketsMessage::MatrixCurrenciesChanged)
+                .size(14)
+                .width(Length::Fixed(320.0)),
+        ]
+        .spacing(10)
+        .align_y(Center),
+    ]
+    .spacing(12);
+
+    if !unknown.is_empty() {
+        content = content.push(text(format!("No BTC price for {}", unknown.join(", "))).size(13));
+    }
+    if let Some(status) = status {
+        content = content.push(text(status).size(14));
+    }
+    if currencies.is_empty() {
+        return content.into();
+    }
+
+    let label = |code: String| text(code).size(14).width(Length::Fixed(CELL_WIDTH / 2.0));
+    let header = currencies.iter().fold(
+        Row::new().spacing(4).push(label(String::new())),
+        |header, code| header.push(text(code.clone()).size(14).width(Length::Fixed(CELL_WIDTH))),
+    );
+    let grid = currencies.into_iter().zip(cells).fold(
+        Column::new().spacing(4).push(header),
+        |grid, (code, cells)| {
+            grid.push(cells.into_iter().fold(
+                Row::new().spacing(4).align_y(Center).push(label(code)),
+                |row, cell| row.push(heat_cell(cell)),
+            ))
+        },
+    );
+
+    content.push(grid).into()
+}
+
+fn heat_cell<'a>(cell: MatrixCell) -> Element<'a, MarketsMessage> {
+    let change = cell.change.map(format_premium).unwrap_or_default();
+    container(
+        column![
+            text(cell.rate.map(format_rate).unwrap_or_else(|| "—".into())).size(13),
+            text(change).size(11),
+        ]
+        .spacing(2),
+    )
+    .padding(6)
+    .width(Length::Fixed(CELL_WIDTH))
+    .style(move |theme: &Theme| heat_style(theme, cell.change))
+    .into()
+}
+
+/// Green for a rising rate, red for a falling one, stronger for bigger moves
+fn heat_style(theme: &Theme, change: Option<f64>) -> container::Style {
+    let palette = theme.extended_palette();
+    let background = match change {
+        Some(change) if change != 0.0 => {
+            let base = if change > 0.0 {
+                palette.success.base.color
+            } else {
+                palette.danger.base.color
+            };
+            let strength = (change.abs() / FULL_SCALE_CHANGE).min(1.0) as f32;
+            Color {
+                a: 0.15 + 0.6 * strength,
+                ..base
+            }
+        }
+        _ => palette.background.weak.color,
+    };
+    container::Style::default()
+        .background(background)
+        .border(iced::Border::default().rounded(4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn premiums_are_signed_percentages() {
+        assert_eq!(format_premium(0.042), "+4.20%");
+        assert_eq!(format_premium(-0.07), "-7.00%");
+        assert_eq!(format_premium(0.0), "+0.00%");
+    }
+
+    #[test]
+    fn rates_keep_four_significant_digits() {
+        assert_eq!(format_rate(1340.2), "1340");
+        assert_eq!(format_rate(1.0), "1.000");
+        assert_eq!(format_rate(0.79213), "0.7921");
+        assert_eq!(format_rate(0.000746), "0.0007460");
+        assert_eq!(format_rate(0.0), "—");
+    }
+}
@@ -1,8 +1,32 @@
 /// Bitcoin-related view components
 pub mod bitcoin_view;
+/// Chart page view components
+pub mod chart_view;
+/// Candles page view components
+pub mod candle_view;
+/// Markets page view components
+pub mod markets_view;
+/// Network page view components
+pub mod network_view;
+/// Fees page view components
+pub mod fee_view;
+/// Wallet page view components
+pub mod wallet_view;
+/// Alerts page view components
+pub mod alerts_view;
 /// Settings view components
 pub mod settings_view;
 /// About view components
 pub mod about_view;
 /// Navigation components
 pub mod navigation;
+/// Fetch error panel component
+pub mod error_panel;
+/// Offline banner component
+pub mod offline_banner;
+/// Command palette overlay
+pub mod command_palette;
+/// Per-currency trend sparkline
+pub mod sparkline;
+/// Canvas price chart and indicator panes
+pub mod price_chart;
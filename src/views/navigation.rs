@@ -40,6 +40,8 @@ impl<'a> Navigation<'a> {
                     .spacing(10)
                     .align_y(Center)
                     .padding(10)
+                    .wrap()
+                    .vertical_spacing(10)
             )
             .width(Fill)
             .style(container::rounded_box),
@@ -48,3 +50,19 @@ impl<'a> Navigation<'a> {
         .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::simulator;
+
+    #[test]
+    fn inactive_route_navigates_and_active_route_is_inert() {
+        let mut ui = simulator(Navigation::new(&Route::Bitcoin).view());
+        ui.click("Bitcoin Prices").expect("active button is rendered");
+        ui.click("About").expect("About button");
+
+        let messages: Vec<_> = ui.into_messages().collect();
+        assert!(matches!(messages.as_slice(), [Message::Navigate(Route::About)]));
+    }
+}
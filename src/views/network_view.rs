@@ -0,0 +1,214 @@
+use iced::widget::{
+    button, column, container, progress_bar, row, scrollable, space, text, text_input, Column,
+};
+use iced::{Center, Element, Fill, Length};
+
+use crate::message::NetworkMessage;
+
+const CARD_WIDTH: f32 = 300.0;
+
+/// One recommended fee rate
+pub struct FeeRow {
+    pub label: &'static str,
+    /// Roughly when a transaction paying this rate confirms
+    pub target: &'static str,
+    /// sat/vB
+    pub rate: f64,
+}
+
+/// Mempool card lines
+pub struct MempoolProps {
+    pub transactions: String,
+    pub size: String,
+    pub fees: String,
+}
+
+/// Difficulty adjustment card
+pub struct DifficultyProps {
+    /// Share of the epoch mined, 0–100
+    pub progress: f32,
+    pub remaining: String,
+    /// Signed estimated change
+    pub change: String,
+    pub retarget: String,
+    /// Average block interval this epoch
+    pub block_time: String,
+}
+
+/// Everything the network view draws; data fields are `None` until loaded
+pub struct NetworkProps {
+    /// Base URL as typed
+    pub url_input: String,
+    pub url_error: Option<String>,
+    /// Loading or error message
+    pub notice: Option<String>,
+    pub loading: bool,
+    pub tip_height: Option<String>,
+    /// Fast, medium and slow
+    pub fees: Vec<FeeRow>,
+    pub mempool: Option<MempoolProps>,
+    pub difficulty: Option<DifficultyProps>,
+}
+
+/// Network page view: server picker over tip, fee, mempool and difficulty cards
+pub struct NetworkView {
+    props: NetworkProps,
+}
+
+/// Integer with thousands separators, such as `840,000`
+pub fn format_count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Whole sat/vB without decimals, fractional rates with one
+pub fn format_fee_rate(rate: f64) -> String {
+    if rate.fract() == 0.0 {
+        format!("{rate:.0} sat/vB")
+    } else {
+        format!("{rate:.1} sat/vB")
+    }
+}
+
+/// Minutes and seconds, such as `9m 48s`
+pub fn format_duration(secs: u64) -> String {
+    format!("{}m {:02}s", secs / 60, secs % 60)
+}
+
+impl NetworkView {
+    pub fn new(props: NetworkProps) -> Self {
+        Self { props }
+    }
+
+    pub fn view<'a>(self) -> Element<'a, NetworkMessage> {
+        let NetworkProps {
+            url_input,
+            url_error,
+            notice,
+            loading,
+            tip_height,
+            fees,
+            mempool,
+            difficulty,
+        } = self.props;
+
+        let server = row![
+            text("Server").size(14),
+            text_input(crate::mempool::DEFAULT_BASE_URL, &url_input)
+                .on_input(NetworkMessage::BaseUrlChanged)
+                .on_submit(NetworkMessage::ApplyBaseUrl)
+                .size(14)
+                .width(Fill),
+            button(text("Apply").size(14))
+                .style(button::secondary)
+                .on_press(NetworkMessage::ApplyBaseUrl),
+            button(text("Refresh").size(14))
+                .style(button::secondary)
+                .on_press_maybe((!loading).then_some(NetworkMessage::Refresh)),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let mut content = column![server].spacing(12);
+        if let Some(error) = url_error {
+            content = content.push(text(error).size(13));
+        }
+        if let Some(notice) = notice {
+            content = content.push(text(notice).size(14));
+        }
+
+        if let Some(height) = tip_height {
+            content = content.push(
+                row![
+                    card("Block height", column![text(height).size(28)]),
+                    card(
+                        "Fee rates",
+                        fees.into_iter().fold(Column::new().spacing(4), |col, fee| {
+                            col.push(
+                                row![
+                                    text(fee.label).size(14).width(Length::Fixed(70.0)),
+                                    text(format_fee_rate(fee.rate)).size(14).width(Fill),
+                                    text(fee.target).size(12),
+                                ]
+                                .align_y(Center),
+                            )
+                        }),
+                    ),
+                ]
+                .spacing(12),
+            );
+        }
+
+        let mempool = mempool.map(|m| {
+            card(
+                "Mempool",
+                column![
+                    text(m.transactions).size(16),
+                    text(m.size).size(14),
+                    text(m.fees).size(14),
+                ]
+                .spacing(4),
+            )
+        });
+        let difficulty = difficulty.map(|d| {
+            card(
+                "Difficulty adjustment",
+                column![
+                    progress_bar(0.0..=100.0, d.progress).girth(8),
+                    text(d.remaining).size(13),
+                    row![
+                        text("Estimated change").size(13),
+                        space::horizontal(),
+                        text(d.change).size(13)
+                    ],
+                    row![
+                        text("Average block").size(13),
+                        space::horizontal(),
+                        text(d.block_time).size(13)
+                    ],
+                    text(d.retarget).size(13),
+                ]
+                .spacing(6),
+            )
+        });
+        if let (Some(mempool), Some(difficulty)) = (mempool, difficulty) {
+            content = content.push(row![mempool, difficulty].spacing(12));
+        }
+
+        scrollable(content.padding([10, 30])).height(Fill).into()
+    }
+}
+
+/// Titled box of fixed width
+fn card<'a>(
+    title: &'a str,
+    body: impl Into<Element<'a, NetworkMessage>>,
+) -> Element<'a, NetworkMessage> {
+    container(column![text(title).size(13), body.into()].spacing(8))
+        .padding(12)
+        .width(Length::Fixed(CARD_WIDTH))
+        .style(container::rounded_box)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_grouped_and_rates_trimmed() {
+        assert_eq!(format_count(840_000), "840,000");
+        assert_eq!(format_count(672), "672");
+        assert_eq!(format_count(1_234_567), "1,234,567");
+        assert_eq!(format_fee_rate(32.0), "32 sat/vB");
+        assert_eq!(format_fee_rate(1.5), "1.5 sat/vB");
+        assert_eq!(format_duration(587), "9m 47s");
+    }
+}
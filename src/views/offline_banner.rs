@@ -0,0 +1,13 @@
+use iced::widget::{container, text};
+use iced::Fill;
+
+/// Banner shown under the navigation bar while the network is unreachable
+pub fn view<'a, M: 'a>() -> iced::Element<'a, M> {
+    container(
+        text("Offline — prices will refresh automatically when the connection returns").size(14),
+    )
+    .padding([6, 12])
+    .center_x(Fill)
+    .style(container::warning)
+    .into()
+}
@@ -0,0 +1,690 @@
+use iced::alignment;
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke, Text};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
+
+use crate::api::Candle;
+use crate::candles::format_utc;
+use crate::indicators::{Band, Macd};
+use crate::pages::chart_page::{Overlay, Pane};
+
+/// Space on the right for value labels
+const AXIS_WIDTH: f32 = 72.0;
+/// Inner margin above and below the plotted values
+const PADDING: f32 = 8.0;
+const LABEL_SIZE: f32 = 11.0;
+/// Strip above an indicator pane's plot holding its title
+const TITLE_HEIGHT: f32 = 14.0;
+/// Share of a candle chart's height given to the volume bars
+const VOLUME_SHARE: f32 = 0.25;
+/// Widest candle body, so a handful of candles don't turn into blocks
+const MAX_BODY_WIDTH: f32 = 16.0;
+
+/// Colour of an overlay line, shared by the chart and its legend
+pub fn overlay_color(overlay: Overlay, theme: &Theme) -> Color {
+    let palette = theme.extended_palette();
+    match overlay {
+        Overlay::Sma50 => palette.success.base.color,
+        Overlay::Sma200 => palette.danger.base.color,
+        Overlay::Ema20 => palette.warning.base.color,
+        Overlay::Bollinger => palette.secondary.base.color,
+    }
+}
+
+/// Maps sample indices and values into a plot rectangle
+#[derive(Debug, Clone, Copy)]
+struct Scale {
+    min: f64,
+    max: f64,
+    count: usize,
+    area: Rectangle,
+}
+
+impl Scale {
+    fn x(&self, index: usize) -> f32 {
+        if self.count > 1 {
+            self.area.x + self.area.width * index as f32 / (self.count - 1) as f32
+        } else {
+            self.area.x + self.area.width / 2.0
+        }
+    }
+
+    fn y(&self, value: f64) -> f32 {
+        let t = if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)) as f32
+        } else {
+            0.5
+        };
+        self.area.y + self.area.height * (1.0 - t)
+    }
+}
+
+/// Lowest and highest of `values`, or `None` when there are none
+pub fn value_range(values: impl IntoIterator<Item = f64>) -> Option<(f64, f64)> {
+    values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .fold(None, |range, v| match range {
+            None => Some((v, v)),
+            Some((lo, hi)) => Some((v.min(lo), v.max(hi))),
+        })
+}
+
+/// Connects consecutive values; a `None` starts a new segment
+fn polyline(values: &[Option<f64>], scale: &Scale) -> Path {
+    Path::new(|builder| {
+        let mut drawing = false;
+        for (i, value) in values.iter().enumerate() {
+            match value {
+                Some(v) => {
+                    let point = Point::new(scale.x(i), scale.y(*v));
+                    if drawing {
+                        builder.line_to(point);
+                    } else {
+                        builder.move_to(point);
+                    }
+                    drawing = true;
+                }
+                None => drawing = false,
+            }
+        }
+    })
+}
+
+fn plot_area(bounds: &Rectangle) -> Rectangle {
+    Rectangle {
+        x: 0.0,
+        y: PADDING,
+        width: (bounds.width - AXIS_WIDTH).max(1.0),
+        height: (bounds.height - 2.0 * PADDING).max(1.0),
+    }
+}
+
+fn muted(theme: &Theme) -> Color {
+    theme.extended_palette().background.strong.color
+}
+
+/// Horizontal guide at `value` with its label in the axis column
+fn guide(frame: &mut Frame, scale: &Scale, value: f64, label: String, theme: &Theme) {
+    let y = scale.y(value);
+    let right = scale.area.x + scale.area.width;
+    frame.stroke(
+        &Path::line(Point::new(scale.area.x, y), Point::new(right, y)),
+        Stroke::default().with_color(muted(theme)).with_width(1.0),
+    );
+    frame.fill_text(Text {
+        content: label,
+        position: Point::new(right + 6.0, y),
+        color: theme.palette().text,
+        size: LABEL_SIZE.into(),
+        align_y: alignment::Vertical::Center,
+        ..Text::default()
+    });
+}
+
+fn title(frame: &mut Frame, content: &str, theme: &Theme) {
+    frame.fill_text(Text {
+        content: content.to_string(),
+        position: Point::new(4.0, 2.0),
+        color: theme.palette().text,
+        size: LABEL_SIZE.into(),
+        ..Text::default()
+    });
+}
+
+/// Compact price label: no decimals for large prices
+fn format_value(value: f64) -> String {
+    if value.abs() >= 1000.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+/// Price line with moving-average overlays and optional Bollinger bands
+pub struct PriceChart {
+    pub prices: Vec<f64>,
+    pub lines: Vec<(Overlay, Vec<Option<f64>>)>,
+    pub band: Option<Vec<Option<Band>>>,
+}
+
+impl PriceChart {
+    fn range(&self) -> Option<(f64, f64)> {
+        let lines = self.lines.iter().flat_map(|(_, l)| l.iter().flatten());
+        let band = self
+            .band
+            .iter()
+            .flatten()
+            .flatten()
+            .flat_map(|b| [b.lower, b.upper]);
+        value_range(self.prices.iter().chain(lines).copied().chain(band))
+    }
+}
+
+impl<Message> canvas::Program<Message> for PriceChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let Some((min, max)) = self.range() else {
+            return vec![frame.into_geometry()];
+        };
+        let scale = Scale {
+            min,
+            max,
+            count: self.prices.len(),
+            area: plot_area(&bounds),
+        };
+
+        for value in [min, (min + max) / 2.0, max] {
+            guide(&mut frame, &scale, value, format_value(value), theme);
+        }
+
+        if let Some(band) = &self.band {
+            let color = overlay_color(Overlay::Bollinger, theme);
+            // Shade each step between consecutive readings
+            for (i, pair) in band.windows(2).enumerate() {
+                if let [Some(a), Some(b)] = pair {
+                    let shape = Path::new(|p| {
+                        p.move_to(Point::new(scale.x(i), scale.y(a.upper)));
+                        p.line_to(Point::new(scale.x(i + 1), scale.y(b.upper)));
+                        p.line_to(Point::new(scale.x(i + 1), scale.y(b.lower)));
+                        p.line_to(Point::new(scale.x(i), scale.y(a.lower)));
+                        p.close();
+                    });
+                    frame.fill(&shape, Color { a: 0.12, ..color });
+                }
+            }
+            for edge in [
+                band.iter().map(|b| b.map(|b| b.upper)).collect::<Vec<_>>(),
+                band.iter().map(|b| b.map(|b| b.lower)).collect(),
+            ] {
+                frame.stroke(
+                    &polyline(&edge, &scale),
+                    Stroke::default().with_color(color).with_width(1.0),
+                );
+            }
+        }
+
+        let prices: Vec<Option<f64>> = self.prices.iter().copied().map(Some).collect();
+        frame.stroke(
+            &polyline(&prices, &scale),
+            Stroke::default()
+                .with_color(theme.palette().primary)
+                .with_width(1.5),
+        );
+
+        for (overlay, values) in &self.lines {
+            frame.stroke(
+                &polyline(values, &scale),
+                Stroke::default()
+                    .with_color(overlay_color(*overlay, theme))
+                    .with_width(1.5),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// An indicator drawn in its own pane under the price chart
+pub enum PaneChart {
+    /// RSI readings on a fixed 0–100 scale
+    Rsi(Vec<Option<f64>>),
+    Macd(Vec<Option<Macd>>),
+    /// Annualised volatility as a fraction (0.5 = 50%)
+    Volatility(Vec<Option<f64>>),
+}
+
+impl PaneChart {
+    fn title(&self) -> &'static str {
+        match self {
+            PaneChart::Rsi(_) => Pane::Rsi.label(),
+            PaneChart::Macd(_) => Pane::Macd.label(),
+            PaneChart::Volatility(_) => Pane::Volatility.label(),
+        }
+    }
+}
+
+impl<Message> canvas::Program<Message> for PaneChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let area = plot_area(&bounds);
+        let area = Rectangle {
+            y: area.y + TITLE_HEIGHT,
+            height: (area.height - TITLE_HEIGHT).max(1.0),
+            ..area
+        };
+        let line = |color: Color| Stroke::default().with_color(color).with_width(1.5);
+
+        match self {
+            PaneChart::Rsi(values) => {
+                let scale = Scale {
+                    min: 0.0,
+                    max: 100.0,
+                    count: values.len(),
+                    area,
+                };
+                for level in [30.0, 70.0] {
+                    guide(&mut frame, &scale, level, format!("{level:.0}"), theme);
+                }
+                frame.stroke(&polyline(values, &scale), line(palette.primary.base.color));
+            }
+            PaneChart::Macd(readings) => {
+                let extent = value_range(
+                    readings
+                        .iter()
+                        .flatten()
+                        .flat_map(|m| [m.macd.abs(), m.signal.abs(), m.histogram.abs()]),
+                )
+                .map_or(1.0, |(_, hi)| hi.max(f64::EPSILON));
+                let scale = Scale {
+                    min: -extent,
+                    max: extent,
+                    count: readings.len(),
+                    area,
+                };
+                guide(&mut frame, &scale, 0.0, "0".into(), theme);
+
+                let bar = (area.width / readings.len().max(1) as f32 * 0.6).max(1.0);
+                for (i, reading) in readings.iter().enumerate() {
+                    let Some(reading) = reading else { continue };
+                    let (top, bottom) = (
+                        scale.y(reading.histogram.max(0.0)),
+                        scale.y(reading.histogram.min(0.0)),
+                    );
+                    let color = if reading.histogram >= 0.0 {
+                        palette.success.base.color
+                    } else {
+                        palette.danger.base.color
+                    };
+                    frame.fill_rectangle(
+                        Point::new(scale.x(i) - bar / 2.0, top),
+                        Size::new(bar, (bottom - top).max(0.5)),
+                        Color { a: 0.5, ..color },
+                    );
+                }
+                let macd: Vec<_> = readings.iter().map(|r| r.map(|r| r.macd)).collect();
+                let signal: Vec<_> = readings.iter().map(|r| r.map(|r| r.signal)).collect();
+                frame.stroke(&polyline(&macd, &scale), line(palette.primary.base.color));
+                frame.stroke(&polyline(&signal, &scale), line(palette.warning.base.color));
+            }
+            PaneChart::Volatility(values) => {
+                let max = value_range(values.iter().flatten().copied())
+                    .map_or(1.0, |(_, hi)| hi.max(f64::EPSILON));
+                let scale = Scale {
+                    min: 0.0,
+                    max,
+                    count: values.len(),
+                    area,
+                };
+                for level in [0.0, max] {
+                    guide(
+                        &mut frame,
+                        &scale,
+                        level,
+                        format!("{:.0}%", level * 100.0),
+                        theme,
+                    );
+                }
+                frame.stroke(&polyline(values, &scale), line(palette.primary.base.color));
+            }
+        }
+
+        title(&mut frame, self.title(), theme);
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Volume label such as `21.3B`
+fn format_volume(volume: f64) -> String {
+    match volume.abs() {
+        v if v >= 1e9 => format!("{:.1}B", volume / 1e9),
+        v if v >= 1e6 => format!("{:.1}M", volume / 1e6),
+        v if v >= 1e3 => format!("{:.1}K", volume / 1e3),
+        _ => format!("{volume:.0}"),
+    }
+}
+
+/// Candlesticks over volume bars, with a readout of the hovered candle
+pub struct CandleChart {
+    pub candles: Vec<Candle>,
+}
+
+impl CandleChart {
+    /// Width given to each candle
+    fn slot(&self, area: &Rectangle) -> f32 {
+        area.width / self.candles.len().max(1) as f32
+    }
+
+    /// Index of the candle whose slot contains `x`
+    fn candle_at(&self, x: f32, area: &Rectangle) -> Option<usize> {
+        if self.candles.is_empty() || x < area.x || x >= area.x + area.width {
+            return None;
+        }
+        Some((((x - area.x) / self.slot(area)) as usize).min(self.candles.len() - 1))
+    }
+
+    /// Text shown for the hovered candle
+    pub fn readout(candle: &Candle) -> String {
+        let mut readout = format!(
+            "{} UTC   O {}  H {}  L {}  C {}",
+            format_utc(candle.time),
+            format_value(candle.open),
+            format_value(candle.high),
+            format_value(candle.low),
+            format_value(candle.close),
+        );
+        if let Some(volume) = candle.volume {
+            readout.push_str(&format!("  Vol 24h {}", format_volume(volume)));
+        }
+        readout
+    }
+}
+
+impl<Message> canvas::Program<Message> for CandleChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let Some((min, max)) = value_range(self.candles.iter().flat_map(|c| [c.low, c.high]))
+        else {
+            return vec![frame.into_geometry()];
+        };
+
+        // Readout strip on top, candles, then the volume strip with its title
+        let area = plot_area(&bounds);
+        let volume_height = area.height * VOLUME_SHARE;
+        let prices = Rectangle {
+            y: area.y + TITLE_HEIGHT,
+            height: (area.height - volume_height - 2.0 * TITLE_HEIGHT).max(1.0),
+            ..area
+        };
+        let volumes = Rectangle {
+            y: area.y + area.height - volume_height + TITLE_HEIGHT,
+            height: (volume_height - TITLE_HEIGHT).max(1.0),
+            ..area
+        };
+        let scale = Scale {
+            min,
+            max,
+            count: self.candles.len(),
+            area: prices,
+        };
+        for value in [min, (min + max) / 2.0, max] {
+            guide(&mut frame, &scale, value, format_value(value), theme);
+        }
+
+        let slot = self.slot(&area);
+        let body = (slot * 0.6).clamp(1.0, MAX_BODY_WIDTH);
+        let center = |i: usize| area.x + slot * (i as f32 + 0.5);
+        let direction = |c: &Candle| {
+            if c.close >= c.open {
+                palette.success.base.color
+            } else {
+                palette.danger.base.color
+            }
+        };
+
+        for (i, candle) in self.candles.iter().enumerate() {
+            let color = direction(candle);
+            let x = center(i);
+            frame.stroke(
+                &Path::line(
+                    Point::new(x, scale.y(candle.high)),
+                    Point::new(x, scale.y(candle.low)),
+                ),
+                Stroke::default().with_color(color).with_width(1.0),
+            );
+            let top = scale.y(candle.open.max(candle.close));
+            let bottom = scale.y(candle.open.min(candle.close));
+            frame.fill_rectangle(
+                Point::new(x - body / 2.0, top),
+                Size::new(body, (bottom - top).max(1.0)),
+                color,
+            );
+        }
+
+        let volume_title = Point::new(4.0, volumes.y - TITLE_HEIGHT);
+        let max_volume = value_range(self.candles.iter().filter_map(|c| c.volume));
+        let label = match max_volume {
+            Some((_, hi)) if hi > 0.0 => {
+                let scale = Scale {
+                    min: 0.0,
+                    max: hi,
+                    count: self.candles.len(),
+                    area: volumes,
+                };
+                guide(&mut frame, &scale, hi, format_volume(hi), theme);
+                for (i, candle) in self.candles.iter().enumerate() {
+                    let Some(volume) = candle.volume else {
+                        continue;
+                    };
+                    let top = scale.y(volume);
+                    frame.fill_rectangle(
+                        Point::new(center(i) - body / 2.0, top),
+                        Size::new(body, (volumes.y + volumes.height - top).max(0.5)),
+                        Color {
+                            a: 0.5,
+                            ..direction(candle)
+                        },
+                    );
+                }
+                "24h volume"
+            }
+            _ => "No volume for recorded prices",
+        };
+        frame.fill_text(Text {
+            content: label.to_string(),
+            position: volume_title,
+            color: theme.palette().text,
+            size: LABEL_SIZE.into(),
+            ..Text::default()
+        });
+
+        let hovered = cursor
+            .position_in(bounds)
+            .and_then(|position| self.candle_at(position.x, &area));
+        if let Some(i) = hovered {
+            let x = center(i);
+            frame.stroke(
+                &Path::line(
+                    Point::new(x, prices.y),
+                    Point::new(x, volumes.y + volumes.height),
+                ),
+                Stroke::default().with_color(muted(theme)).with_width(1.0),
+            );
+            frame.fill_text(Text {
+                content: Self::readout(&self.candles[i]),
+                position: Point::new(4.0, area.y),
+                color: theme.palette().text,
+                size: LABEL_SIZE.into(),
+                ..Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Coins issued against block height, with a marker at the chain tip
+pub struct SupplyChart {
+    /// Supply in BTC at evenly spaced heights, starting at block 0
+    pub supply: Vec<f64>,
+    /// Blocks between consecutive `supply` values
+    pub step: u64,
+    /// Chain tip, when known
+    pub tip: Option<u64>,
+}
+
+impl<Message> canvas::Program<Message> for SupplyChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let Some((_, max)) = value_range(self.supply.iter().copied()) else {
+            return vec![frame.into_geometry()];
+        };
+        let scale = Scale {
+            min: 0.0,
+            max,
+            count: self.supply.len(),
+            area: plot_area(&bounds),
+        };
+        for value in [0.0, max / 2.0, max] {
+            guide(&mut frame, &scale, value, format_volume(value), theme);
+        }
+
+        let color = theme.palette().primary;
+        let values: Vec<Option<f64>> = self.supply.iter().copied().map(Some).collect();
+        let area = Path::new(|p| {
+            p.move_to(Point::new(scale.x(0), scale.y(0.0)));
+            for (i, v) in self.supply.iter().enumerate() {
+                p.line_to(Point::new(scale.x(i), scale.y(*v)));
+            }
+            p.line_to(Point::new(scale.x(self.supply.len() - 1), scale.y(0.0)));
+            p.close();
+        });
+        frame.fill(&area, Color { a: 0.15, ..color });
+        frame.stroke(
+            &polyline(&values, &scale),
+            Stroke::default().with_color(color).with_width(1.5),
+        );
+
+        if let Some(tip) = self.tip {
+            let index = (tip / self.step.max(1)) as usize;
+            if let Some(value) = self.supply.get(index) {
+                let x = scale.x(index);
+                frame.stroke(
+                    &Path::line(
+                        Point::new(x, scale.area.y),
+                        Point::new(x, scale.area.y + scale.area.height),
+                    ),
+                    Stroke::default()
+                        .with_color(theme.extended_palette().warning.base.color)
+                        .with_width(1.0),
+                );
+                frame.fill(
+                    &Path::circle(Point::new(x, scale.y(*value)), 3.5),
+                    theme.extended_palette().warning.base.color,
+                );
+                frame.fill_text(Text {
+                    content: "now".to_string(),
+                    position: Point::new(x + 5.0, scale.area.y + scale.area.height - 4.0),
+                    color: theme.palette().text,
+                    size: LABEL_SIZE.into(),
+                    align_y: alignment::Vertical::Bottom,
+                    ..Text::default()
+                });
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_range_skips_non_finite_values() {
+        assert_eq!(value_range([3.0, f64::NAN, -1.0, 7.5]), Some((-1.0, 7.5)));
+        assert_eq!(value_range([]), None);
+    }
+
+    #[test]
+    fn price_range_covers_overlays_and_bands() {
+        let chart = PriceChart {
+            prices: vec![10.0, 12.0, 11.0],
+            lines: vec![(Overlay::Sma50, vec![None, Some(9.0), None])],
+            band: Some(vec![
+                None,
+                None,
+                Some(Band {
+                    lower: 8.5,
+                    middle: 11.0,
+                    upper: 13.5,
+                }),
+            ]),
+        };
+        assert_eq!(chart.range(), Some((8.5, 13.5)));
+    }
+
+    #[test]
+    fn scale_maps_oldest_left_and_highest_up() {
+        let scale = Scale {
+            min: 10.0,
+            max: 20.0,
+            count: 3,
+            area: Rectangle::new(Point::ORIGIN, Size::new(100.0, 50.0)),
+        };
+        assert_eq!((scale.x(0), scale.x(2)), (0.0, 100.0));
+        assert_eq!(
+            (scale.y(20.0), scale.y(10.0), scale.y(15.0)),
+            (0.0, 50.0, 25.0)
+        );
+
+        let flat = Scale {
+            min: 5.0,
+            max: 5.0,
+            ..scale
+        };
+        assert_eq!(flat.y(5.0), 25.0);
+    }
+
+    #[test]
+    fn hovered_candle_follows_cursor() {
+        let candle = Candle {
+            time: 1_711_356_300,
+            open: 65_012.5,
+            high: 65_480.0,
+            low: 64_910.2,
+            close: 65_497.0,
+            volume: Some(2.13e10),
+        };
+        let chart = CandleChart {
+            candles: vec![candle; 4],
+        };
+        let area = Rectangle::new(Point::new(0.0, 8.0), Size::new(100.0, 50.0));
+        assert_eq!(chart.candle_at(0.0, &area), Some(0));
+        assert_eq!(chart.candle_at(60.0, &area), Some(2));
+        assert_eq!(chart.candle_at(100.0, &area), None);
+        assert_eq!(
+            CandleChart::readout(&candle),
+            "2024-03-25 08:45 UTC   O 65012  H 65480  L 64910  C 65497  Vol 24h 21.3B"
+        );
+    }
+}
@@ -1,7 +1,14 @@
-use iced::widget::{button, checkbox, column, container, pick_list, row, slider, text, toggler};
-use iced::{Center, Fill, Length, Theme};
+use iced::widget::{
+    button, checkbox, column, container, pick_list, row, scrollable, slider, text, text_input,
+    themer, toggler,
+};
+use iced::{Background, Center, Color, Fill, Length, Theme};
 
-use crate::message::SettingsMessage;
+use crate::api::ApiPlan;
+use crate::custom_theme::{self, ThemeColor};
+use crate::message::{HttpField, SettingsMessage};
+use crate::server::ServerEvent;
+use crate::shortcuts::Action;
 
 /// Available themes for the pick-list
 const THEME_LIST: &[Theme] = &[
@@ -18,30 +25,99 @@ const THEME_LIST: &[Theme] = &[
     Theme::CatppuccinMocha,
 ];
 
+/// Local API server state shown in the settings view
+pub struct ApiServerProps<'a> {
+    pub enabled: bool,
+    pub port_input: &'a str,
+    pub status: Option<&'a ServerEvent>,
+}
+
+/// HTTP client form shown in the Network section
+pub struct NetworkProps<'a> {
+    pub proxy: &'a str,
+    pub proxy_user: &'a str,
+    pub proxy_password: &'a str,
+    pub ca_bundle: &'a str,
+    pub connect_timeout: &'a str,
+    pub timeout: &'a str,
+    /// Outcome of the last "Apply"
+    pub status: Option<&'a Result<(), String>>,
+}
+
+/// CoinGecko API key form
+pub struct ApiKeyProps<'a> {
+    pub plan: ApiPlan,
+    pub key: &'a str,
+    /// Outcome of the last "Save key"
+    pub status: Option<&'a Result<String, String>>,
+}
+
+/// Keyboard shortcut inputs
+pub struct ShortcutProps<'a> {
+    /// Shortcut text per action, in display order
+    pub inputs: &'a [(Action, String)],
+    /// Action whose last edit was rejected, with the reason
+    pub error: Option<&'a (Action, String)>,
+}
+
+/// Refresh, theme and notification preferences
+pub struct GeneralProps {
+    pub auto_refresh_interval: u32,
+    pub auto_refresh_enabled: bool,
+    pub stale_multiple: u32,
+    pub notifications_enabled: bool,
+}
+
+/// Theme choice, OS-following and the custom palette editor
+pub struct ThemeProps<'a> {
+    pub selected: &'a Theme,
+    /// Custom themes offered after the built-in ones
+    pub custom: Vec<Theme>,
+    pub follow_system: bool,
+    pub light: &'a Theme,
+    pub dark: &'a Theme,
+    pub editor_name: &'a str,
+    /// Hex text per colour
+    pub editor_colors: &'a [(ThemeColor, String)],
+    /// Theme built from the editor, or why it cannot be built
+    pub preview: Result<Theme, String>,
+    /// Import / export file path
+    pub file: &'a str,
+    /// Outcome of the last editor action
+    pub status: Option<&'a Result<String, String>>,
+}
+
 /// Settings view component
-pub struct SettingsView {
-    auto_refresh_interval: u32,
-    auto_refresh_enabled: bool,
-    selected_theme: Theme,
-    notifications_enabled: bool,
+pub struct SettingsView<'a> {
+    general: GeneralProps,
+    theme: ThemeProps<'a>,
+    api_server: ApiServerProps<'a>,
+    network: NetworkProps<'a>,
+    api_key: ApiKeyProps<'a>,
+    shortcuts: ShortcutProps<'a>,
 }
 
-impl SettingsView {
+impl<'a> SettingsView<'a> {
     pub fn new(
-        auto_refresh_interval: u32,
-        auto_refresh_enabled: bool,
-        selected_theme: &Theme,
-        notifications_enabled: bool,
+        general: GeneralProps,
+        theme: ThemeProps<'a>,
+        api_server: ApiServerProps<'a>,
+        network: NetworkProps<'a>,
+        api_key: ApiKeyProps<'a>,
+        shortcuts: ShortcutProps<'a>,
     ) -> Self {
         Self {
-            auto_refresh_interval,
-            auto_refresh_enabled,
-            selected_theme: selected_theme.clone(),
-            notifications_enabled,
+            general,
+            theme,
+            api_server,
+            network,
+            api_key,
+            shortcuts,
         }
     }
 
-    pub fn view(self) -> iced::Element<'static, SettingsMessage> {
+    pub fn view(self) -> iced::Element<'a, SettingsMessage> {
+        let general = &self.general;
         let title = text("Settings").size(32);
 
         // ── Auto-Refresh Section ────────────────────────────────
@@ -49,7 +125,7 @@ impl SettingsView {
             text("Auto Refresh").size(18),
             row![
                 text("Enable auto-refresh").width(Fill),
-                toggler(self.auto_refresh_enabled)
+                toggler(general.auto_refresh_enabled)
                     .on_toggle(SettingsMessage::AutoRefreshToggled)
                     .size(25),
             ]
@@ -58,58 +134,335 @@ impl SettingsView {
             row![
                 slider(
                     5..=300,
-                    self.auto_refresh_interval,
+                    general.auto_refresh_interval,
                     SettingsMessage::AutoRefreshIntervalChanged
                 )
                 .step(5u32),
-                text(format!("{}s", self.auto_refresh_interval))
+                text(format!("{}s", general.auto_refresh_interval))
                     .width(Length::Fixed(50.0))
             ]
             .spacing(10)
             .align_y(Center),
+            row![
+                text("Flag prices as stale after").width(Length::Shrink),
+                slider(2..=10, general.stale_multiple, SettingsMessage::StaleMultipleChanged),
+                text(format!("{}× interval", general.stale_multiple))
+                    .width(Length::Fixed(80.0))
+            ]
+            .spacing(10)
+            .align_y(Center),
         ]
         .spacing(10);
 
         // ── Theme Section ───────────────────────────────────────
-        let theme_section = column![
-            text("Theme").size(18),
-            pick_list(
-                THEME_LIST,
-                Some(self.selected_theme),
-                SettingsMessage::ThemeChanged
-            )
-            .placeholder("Select theme...")
-            .width(200)
-        ]
-        .spacing(10);
+        let theme_section = Self::theme_section(self.theme);
 
         // ── Notifications Section ───────────────────────────────
         let notifications_section = column![
             text("Notifications").size(18),
-            checkbox(self.notifications_enabled)
+            checkbox(general.notifications_enabled)
                 .label("Enable price alerts")
                 .on_toggle(SettingsMessage::NotificationsToggled)
                 .size(20),
         ]
         .spacing(10);
 
+        // ── Local API Server Section ────────────────────────────
+        let api_server_status = match (&self.api_server.status, self.api_server.enabled) {
+            (_, false) => text("Stopped").size(14),
+            (Some(ServerEvent::Listening(addr)), true) => {
+                text(format!("Listening on http://{addr}  (see /openapi.json)")).size(14)
+            }
+            (Some(ServerEvent::Failed(e)), true) => text(format!("Failed: {e}"))
+                .size(14)
+                .style(text::danger),
+            (None, true) => text("Starting...").size(14),
+        };
+
+        let api_server_section = column![
+            text("Local API Server").size(18),
+            row![
+                text("Serve latest prices as JSON on localhost").width(Fill),
+                toggler(self.api_server.enabled)
+                    .on_toggle(SettingsMessage::ApiServerToggled)
+                    .size(25),
+            ]
+            .align_y(Center)
+            .spacing(10),
+            row![
+                text("Port"),
+                text_input("8787", self.api_server.port_input)
+                    .on_input(SettingsMessage::ApiServerPortChanged)
+                    .width(Length::Fixed(100.0)),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            api_server_status,
+        ]
+        .spacing(10);
+
+        // ── CoinGecko API Key Section ───────────────────────────
+        let api_key_status = match self.api_key.status {
+            None => text(""),
+            Some(Ok(message)) => text(message.clone()).size(14).style(text::success),
+            Some(Err(e)) => text(e.clone()).size(14).style(text::danger),
+        };
+        let api_key_input = text_input("CG-...", self.api_key.key).secure(true);
+        let api_key_input = if self.api_key.plan == ApiPlan::Public {
+            api_key_input
+        } else {
+            api_key_input.on_input(SettingsMessage::ApiKeyChanged)
+        };
+
+        let api_key_section = column![
+            text("CoinGecko API").size(18),
+            row![
+                text("Plan").width(Length::Fixed(150.0)),
+                pick_list(ApiPlan::ALL, Some(self.api_key.plan), SettingsMessage::ApiPlanChanged)
+                    .width(200),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            row![
+                text("API key").width(Length::Fixed(150.0)),
+                api_key_input,
+            ]
+            .spacing(10)
+            .align_y(Center),
+            row![
+                button("Save key").on_press(SettingsMessage::SaveApiKey),
+                api_key_status,
+            ]
+            .spacing(10)
+            .align_y(Center),
+        ]
+        .spacing(10);
+
+        // ── Network Section ─────────────────────────────────────
+        let network = &self.network;
+        let field = |label: &'static str, placeholder: &str, value: &str, field: HttpField| {
+            row![
+                text(label).width(Length::Fixed(150.0)),
+                text_input(placeholder, value)
+                    .on_input(move |v| SettingsMessage::HttpFieldChanged(field, v)),
+            ]
+            .spacing(10)
+            .align_y(Center)
+        };
+        let network_status = match network.status {
+            None => text(""),
+            Some(Ok(())) => text("Applied").size(14).style(text::success),
+            Some(Err(e)) => text(format!("Failed: {e}")).size(14).style(text::danger),
+        };
+
+        let network_section = column![
+            text("Network").size(18),
+            field("Proxy URL", "system default", network.proxy, HttpField::Proxy),
+            field("Proxy user", "", network.proxy_user, HttpField::ProxyUser),
+            row![
+                text("Proxy password").width(Length::Fixed(150.0)),
+                text_input("", network.proxy_password)
+                    .secure(true)
+                    .on_input(|v| SettingsMessage::HttpFieldChanged(HttpField::ProxyPassword, v)),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            field("CA bundle (PEM)", "built-in roots only", network.ca_bundle, HttpField::CaBundle),
+            field("Connect timeout (s)", "10", network.connect_timeout, HttpField::ConnectTimeout),
+            field("Request timeout (s)", "30", network.timeout, HttpField::Timeout),
+            row![
+                button("Apply").on_press(SettingsMessage::ApplyHttpConfig),
+                network_status,
+            ]
+            .spacing(10)
+            .align_y(Center),
+        ]
+        .spacing(10);
+
+        // ── Keyboard Shortcuts Section ──────────────────────────
+        let mut shortcuts_section = column![
+            text("Keyboard Shortcuts").size(18),
+            text("Combine Ctrl (Cmd on macOS), Alt or Shift with one key, e.g. Ctrl+R")
+                .size(14),
+        ]
+        .spacing(10);
+        for (action, input) in self.shortcuts.inputs {
+            let action = *action;
+            shortcuts_section = shortcuts_section.push(
+                row![
+                    text(action.label()).width(Length::Fixed(150.0)),
+                    text_input("", input)
+                        .on_input(move |v| SettingsMessage::ShortcutChanged(action, v))
+                        .width(Length::Fixed(150.0)),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            );
+            if let Some((_, e)) = self.shortcuts.error.filter(|(a, _)| *a == action) {
+                shortcuts_section = shortcuts_section.push(text(e.clone()).size(14).style(text::danger));
+            }
+        }
+
         // ── Reset Button ────────────────────────────────────────
         let reset_button = button("Reset to Defaults")
             .on_press(SettingsMessage::ResetToDefaults)
             .style(button::danger);
 
-        container(
+        scrollable(container(
             column![
                 title,
                 refresh_section,
                 theme_section,
                 notifications_section,
+                api_server_section,
+                api_key_section,
+                network_section,
+                shortcuts_section,
                 reset_button
             ]
             .spacing(30)
             .padding(20),
         )
-        .center_x(Fill)
+        .center_x(Fill))
         .into()
     }
+
+    fn theme_section(theme: ThemeProps<'a>) -> iced::widget::Column<'a, SettingsMessage> {
+        let options: Vec<Theme> = THEME_LIST.iter().cloned().chain(theme.custom).collect();
+        let picker = |selected: &Theme, on_select: fn(Theme) -> SettingsMessage| {
+            pick_list(options.clone(), Some(selected.clone()), on_select)
+                .placeholder("Select theme...")
+                .width(200)
+        };
+
+        let choice = if theme.follow_system {
+            column![
+                row![
+                    text("Light").width(Length::Fixed(150.0)),
+                    picker(theme.light, SettingsMessage::LightThemeChanged),
+                ]
+                .spacing(10)
+                .align_y(Center),
+                row![
+                    text("Dark").width(Length::Fixed(150.0)),
+                    picker(theme.dark, SettingsMessage::DarkThemeChanged),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            ]
+            .spacing(10)
+        } else {
+            column![picker(theme.selected, SettingsMessage::ThemeChanged)]
+        };
+
+        // ── Custom palette editor ──
+        let mut editor = column![
+            row![
+                text("Custom theme").size(16).width(Fill),
+                button("Edit current")
+                    .style(button::secondary)
+                    .on_press(SettingsMessage::EditCurrentTheme),
+            ]
+            .align_y(Center),
+            row![
+                text("Name").width(Length::Fixed(150.0)),
+                text_input("My Theme", theme.editor_name).on_input(SettingsMessage::ThemeNameChanged),
+            ]
+            .spacing(10)
+            .align_y(Center),
+        ]
+        .spacing(10);
+        for (which, hex) in theme.editor_colors {
+            let which = *which;
+            let swatch = custom_theme::parse_hex(hex).unwrap_or(Color::TRANSPARENT);
+            editor = editor.push(
+                row![
+                    text(which.label()).width(Length::Fixed(150.0)),
+                    text_input("#rrggbb", hex)
+                        .on_input(move |v| SettingsMessage::ThemeColorChanged(which, v))
+                        .width(Length::Fixed(120.0)),
+                    container(text(""))
+                        .width(24)
+                        .height(24)
+                        .style(move |t: &Theme| container::Style {
+                            background: Some(Background::Color(swatch)),
+                            ..container::bordered_box(t)
+                        }),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            );
+        }
+
+        let preview: iced::Element<'a, SettingsMessage> =
+            match theme.preview {
+                Ok(preview_theme) => themer(
+                    Some(preview_theme),
+                    container(
+                        column![
+                            text("Preview").size(16),
+                            text("BTC/USD 65497.00"),
+                            row![
+                                text("+2.4%").style(text::success),
+                                text("-1.3%").style(text::danger),
+                            ]
+                            .spacing(10),
+                            row![
+                                button("Apply")
+                                    .style(button::primary)
+                                    .on_press(SettingsMessage::ApplyCustomTheme),
+                                button("Save")
+                                    .style(button::secondary)
+                                    .on_press(SettingsMessage::SaveCustomTheme),
+                            ]
+                            .spacing(10),
+                        ]
+                        .spacing(8),
+                    )
+                    .padding(12)
+                    .width(Fill)
+                    .style(|t: &Theme| {
+                        let palette = t.palette();
+                        container::Style {
+                            background: Some(Background::Color(palette.background)),
+                            text_color: Some(palette.text),
+                            ..container::bordered_box(t)
+                        }
+                    }),
+                )
+                .into(),
+                Err(e) => text(e).size(14).style(text::danger).into(),
+            };
+
+        let status = match theme.status {
+            None => text(""),
+            Some(Ok(message)) => text(message.clone()).size(14).style(text::success),
+            Some(Err(e)) => text(e.clone()).size(14).style(text::danger),
+        };
+
+        column![
+            text("Theme").size(18),
+            row![
+                text("Follow system light/dark").width(Fill),
+                toggler(theme.follow_system)
+                    .on_toggle(SettingsMessage::FollowSystemThemeToggled)
+                    .size(25),
+            ]
+            .align_y(Center)
+            .spacing(10),
+            choice,
+            editor,
+            preview,
+            row![
+                text_input("path/to/theme.json", theme.file)
+                    .on_input(SettingsMessage::ThemeFileChanged),
+                button("Import").on_press(SettingsMessage::ImportTheme),
+                button("Export").on_press(SettingsMessage::ExportTheme),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            status,
+        ]
+        .spacing(10)
+    }
 }
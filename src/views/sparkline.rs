@@ -0,0 +1,155 @@
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
+
+/// Inner margin so the line and markers are not clipped at the edges
+const PADDING: f32 = 3.0;
+
+/// Overall movement of a series, first point to last
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Flat,
+}
+
+/// Direction of `values`; fewer than two points is flat
+pub fn direction(values: &[f64]) -> Direction {
+    match (values.first(), values.last()) {
+        (Some(first), Some(last)) if values.len() > 1 && last > first => Direction::Up,
+        (Some(first), Some(last)) if values.len() > 1 && last < first => Direction::Down,
+        _ => Direction::Flat,
+    }
+}
+
+/// Maps `values` onto `size`: oldest on the left, the min/max band spans the
+/// full padded height. A constant series is drawn through the middle.
+pub fn plot(values: &[f64], size: Size) -> Vec<Point> {
+    let (min, max) = bounds(values);
+    let width = (size.width - 2.0 * PADDING).max(0.0);
+    let height = (size.height - 2.0 * PADDING).max(0.0);
+    let step = if values.len() > 1 {
+        width / (values.len() - 1) as f32
+    } else {
+        0.0
+    };
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            let t = if max > min {
+                ((v - min) / (max - min)) as f32
+            } else {
+                0.5
+            };
+            Point::new(PADDING + step * i as f32, PADDING + height * (1.0 - t))
+        })
+        .collect()
+}
+
+fn bounds(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        })
+}
+
+/// Small trend line with a shaded min/max band, coloured by direction
+pub struct Sparkline {
+    values: Vec<f64>,
+}
+
+impl Sparkline {
+    pub fn new(values: Vec<f64>) -> Self {
+        Self { values }
+    }
+}
+
+impl<Message> canvas::Program<Message> for Sparkline {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+
+        if self.values.len() < 2 {
+            let y = bounds.height / 2.0;
+            frame.stroke(
+                &Path::line(
+                    Point::new(PADDING, y),
+                    Point::new(bounds.width - PADDING, y),
+                ),
+                Stroke::default()
+                    .with_color(palette.background.strong.color)
+                    .with_width(1.0),
+            );
+            return vec![frame.into_geometry()];
+        }
+
+        let color = match direction(&self.values) {
+            Direction::Up => palette.success.base.color,
+            Direction::Down => palette.danger.base.color,
+            Direction::Flat => palette.background.strong.text,
+        };
+
+        // Band between the lowest and highest sample
+        frame.fill_rectangle(
+            Point::new(PADDING, PADDING),
+            Size::new(bounds.width - 2.0 * PADDING, bounds.height - 2.0 * PADDING),
+            Color { a: 0.12, ..color },
+        );
+
+        let points = plot(&self.values, bounds.size());
+        let line = Path::new(|builder| {
+            builder.move_to(points[0]);
+            for point in &points[1..] {
+                builder.line_to(*point);
+            }
+        });
+        frame.stroke(&line, Stroke::default().with_color(color).with_width(1.5));
+        if let Some(last) = points.last() {
+            frame.fill(&Path::circle(*last, 2.0), color);
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direction_compares_first_and_last() {
+        assert_eq!(direction(&[1.0, 5.0, 2.0]), Direction::Up);
+        assert_eq!(direction(&[3.0, 5.0, 2.0]), Direction::Down);
+        assert_eq!(direction(&[2.0, 5.0, 2.0]), Direction::Flat);
+        assert_eq!(direction(&[2.0]), Direction::Flat);
+        assert_eq!(direction(&[]), Direction::Flat);
+    }
+
+    #[test]
+    fn plot_spans_the_min_max_band() {
+        let size = Size::new(106.0, 26.0);
+        let points = plot(&[10.0, 30.0, 20.0], size);
+        assert_eq!(
+            points[0],
+            Point::new(PADDING, 23.0),
+            "minimum at the bottom"
+        );
+        assert_eq!(points[1], Point::new(53.0, PADDING), "maximum at the top");
+        assert_eq!(points[2], Point::new(103.0, 13.0));
+
+        let flat = plot(&[5.0, 5.0], size);
+        assert!(flat.iter().all(|p| p.y == 13.0));
+    }
+}
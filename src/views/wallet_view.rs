@@ -0,0 +1,145 @@
+use iced::widget::{
+    button, column, container, pick_list, row, rule, scrollable, space, text, text_input, Column,
+};
+use iced::{Center, Element, Fill, Length};
+
+use crate::message::WalletMessage;
+
+/// One watched address or key
+pub struct WalletRow {
+    pub label: String,
+    /// Address, or an extended key shortened to fit
+    pub target: String,
+    /// Address type or key prefix
+    pub kind: String,
+    /// Total balance, once loaded
+    pub btc: Option<String>,
+    /// Total in the selected currency, when a price is known
+    pub fiat: Option<String>,
+    /// Transaction summary, loading note or error
+    pub detail: String,
+    /// Full address or key, identifying the entry for removal
+    pub key: String,
+}
+
+/// Everything the wallet view draws
+pub struct WalletProps {
+    /// Address or key as typed
+    pub input: String,
+    pub label_input: String,
+    pub input_error: Option<String>,
+    /// Empty-list or missing-price message
+    pub notice: Option<String>,
+    /// Which API balances come from
+    pub source: String,
+    pub loading: bool,
+    /// Uppercase codes with a price in the latest sample
+    pub currencies: Vec<String>,
+    pub currency: Option<String>,
+    pub rows: Vec<WalletRow>,
+    /// Sum over all loaded balances
+    pub total: Option<String>,
+}
+
+/// Wallet page view: add form, watched entries and their combined total
+pub struct WalletView {
+    props: WalletProps,
+}
+
+impl WalletView {
+    pub fn new(props: WalletProps) -> Self {
+        Self { props }
+    }
+
+    pub fn view<'a>(self) -> Element<'a, WalletMessage> {
+        let WalletProps {
+            input,
+            label_input,
+            input_error,
+            notice,
+            source,
+            loading,
+            currencies,
+            currency,
+            rows,
+            total,
+        } = self.props;
+
+        let header = row![
+            column![text("Watch-only wallets").size(20), text(source).size(13)].spacing(4),
+            space::horizontal(),
+            pick_list(currencies, currency, WalletMessage::CurrencySelected).text_size(14),
+            button(text("Refresh").size(14))
+                .style(button::secondary)
+                .on_press_maybe((!loading && !rows.is_empty()).then_some(WalletMessage::Refresh)),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let form = row![
+            text_input("Address, xpub, ypub or zpub", &input)
+                .on_input(WalletMessage::InputChanged)
+                .on_submit(WalletMessage::Add)
+                .size(14)
+                .width(Fill),
+            text_input("Label", &label_input)
+                .on_input(WalletMessage::LabelChanged)
+                .on_submit(WalletMessage::Add)
+                .size(14)
+                .width(Length::Fixed(140.0)),
+            button(text("Add").size(14)).on_press(WalletMessage::Add),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let mut content = column![header, form].spacing(12);
+        if let Some(error) = input_error {
+            content = content.push(text(error).size(13));
+        }
+        if let Some(notice) = notice {
+            content = content.push(text(notice).size(14));
+        }
+
+        let list = rows.into_iter().fold(Column::new().spacing(8), |list, r| {
+            let title = if r.label.is_empty() {
+                r.kind.clone()
+            } else {
+                format!("{} · {}", r.label, r.kind)
+            };
+            list.push(
+                container(
+                    row![
+                        column![
+                            text(title).size(15),
+                            text(r.target).size(12),
+                            text(r.detail).size(12),
+                        ]
+                        .spacing(4)
+                        .width(Fill),
+                        column![
+                            text(r.btc.unwrap_or_else(|| "—".to_string())).size(15),
+                            text(r.fiat.unwrap_or_default()).size(13),
+                        ]
+                        .spacing(4)
+                        .align_x(iced::Right),
+                        button(text("Remove").size(13))
+                            .style(button::secondary)
+                            .on_press(WalletMessage::Remove(r.key)),
+                    ]
+                    .spacing(12)
+                    .align_y(Center),
+                )
+                .padding(10)
+                .width(Fill)
+                .style(container::rounded_box),
+            )
+        });
+        content = content.push(list);
+
+        if let Some(total) = total {
+            content = content.push(rule::horizontal(1)).push(text(total).size(16));
+        }
+
+        scrollable(content.padding([10, 30])).height(Fill).into()
+    }
+}
@@ -0,0 +1,338 @@
+//! Transaction size estimates from input and output counts (BIP 141 weight).
+//!
+//! ECDSA signatures are assumed at 72 bytes with the sighash byte, their
+//! largest usual size. About half encode in 71 bytes, so an estimate can come
+//! out up to 1 vB per input high. Schnorr signatures are 64 bytes with the
+//! default sighash; one that names its sighash type carries a 65th byte.
+
+use thiserror::Error;
+
+/// Version and lock time
+const TX_FIXED_BYTES: u64 = 8;
+/// Segwit marker and flag, counted at witness weight
+const SEGWIT_MARKER_WEIGHT: u64 = 2;
+const WITNESS_SCALE: u64 = 4;
+/// Previous output reference and sequence
+const OUTPOINT_AND_SEQUENCE: u64 = 36 + 4;
+const ECDSA_SIGNATURE: u64 = 72;
+const SCHNORR_SIGNATURE: u64 = 64;
+const COMPRESSED_KEY: u64 = 33;
+/// Output amount
+const VALUE_BYTES: u64 = 8;
+/// OP_CHECKMULTISIG accepts at most 20 keys
+pub const MAX_MULTISIG_KEYS: u8 = 20;
+/// P2SH redeem scripts are limited to 520 bytes, which fits 15 keys
+pub const MAX_P2SH_MULTISIG_KEYS: u8 = 15;
+
+/// How the coins being spent and created are locked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptType {
+    P2pkh,
+    P2wpkh,
+    /// Key-path spend
+    P2tr,
+    /// Bare `m`-of-`n` CHECKMULTISIG inside P2SH
+    P2shMultisig {
+        m: u8,
+        n: u8,
+    },
+    /// `m`-of-`n` CHECKMULTISIG inside P2WSH
+    P2wshMultisig {
+        m: u8,
+        n: u8,
+    },
+}
+
+/// Why a transaction shape cannot be estimated
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SizeError {
+    #[error("A transaction needs at least one input")]
+    NoInputs,
+    #[error("A transaction needs at least one output")]
+    NoOutputs,
+    #[error("Multisig needs 1 ≤ m ≤ n signatures, not {m} of {n}")]
+    BadThreshold { m: u8, n: u8 },
+    #[error("At most {max} keys fit this multisig script")]
+    TooManyKeys { max: u8 },
+    #[error("Too many inputs or outputs to estimate")]
+    TooLarge,
+}
+
+impl ScriptType {
+    fn validate(self) -> Result<(), SizeError> {
+        let (m, n, max) = match self {
+            ScriptType::P2shMultisig { m, n } => (m, n, MAX_P2SH_MULTISIG_KEYS),
+            ScriptType::P2wshMultisig { m, n } => (m, n, MAX_MULTISIG_KEYS),
+            _ => return Ok(()),
+        };
+        if m == 0 || m > n {
+            return Err(SizeError::BadThreshold { m, n });
+        }
+        if n > max {
+            return Err(SizeError::TooManyKeys { max });
+        }
+        Ok(())
+    }
+
+    fn is_segwit(self) -> bool {
+        !matches!(self, ScriptType::P2pkh | ScriptType::P2shMultisig { .. })
+    }
+
+    /// Bytes of one input outside and inside the witness
+    fn input_bytes(self) -> (u64, u64) {
+        match self {
+            ScriptType::P2pkh => {
+                let script_sig = push(ECDSA_SIGNATURE) + push(COMPRESSED_KEY);
+                (OUTPOINT_AND_SEQUENCE + with_length(script_sig), 0)
+            }
+            ScriptType::P2wpkh => (
+                OUTPOINT_AND_SEQUENCE + 1,
+                witness(&[ECDSA_SIGNATURE, COMPRESSED_KEY]),
+            ),
+            ScriptType::P2tr => (OUTPOINT_AND_SEQUENCE + 1, witness(&[SCHNORR_SIGNATURE])),
+            ScriptType::P2shMultisig { m, n } => {
+                // OP_0 works around the CHECKMULTISIG off-by-one
+                let script_sig = 1 + m as u64 * push(ECDSA_SIGNATURE) + push(multisig_script(n));
+                (OUTPOINT_AND_SEQUENCE + with_length(script_sig), 0)
+            }
+            ScriptType::P2wshMultisig { m, n } => {
+                let mut items = vec![0];
+                items.extend(std::iter::repeat_n(ECDSA_SIGNATURE, m as usize));
+                items.push(multisig_script(n));
+                (OUTPOINT_AND_SEQUENCE + 1, witness(&items))
+            }
+        }
+    }
+
+    /// Bytes of one output paying to this type
+    fn output_bytes(self) -> u64 {
+        let script_pubkey = match self {
+            ScriptType::P2pkh => 25,
+            ScriptType::P2wpkh => 22,
+            ScriptType::P2shMultisig { .. } => 23,
+            ScriptType::P2tr | ScriptType::P2wshMultisig { .. } => 34,
+        };
+        VALUE_BYTES + with_length(script_pubkey)
+    }
+}
+
+/// Size of a transaction in the units fees are charged in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxSize {
+    /// Weight units: non-witness bytes count four times
+    pub weight: u64,
+    /// Virtual bytes, weight / 4 rounded up
+    pub vsize: u64,
+}
+
+/// Size of a transaction spending `inputs` coins and creating `outputs`,
+/// all of `script` type
+pub fn estimate(script: ScriptType, inputs: u64, outputs: u64) -> Result<TxSize, SizeError> {
+    script.validate()?;
+    if inputs == 0 {
+        return Err(SizeError::NoInputs);
+    }
+    if outputs == 0 {
+        return Err(SizeError::NoOutputs);
+    }
+
+    let (input_base, input_witness) = script.input_bytes();
+    // Counts come straight from the user, so guard every step against overflow
+    let base = inputs
+        .checked_mul(input_base)
+        .zip(outputs.checked_mul(script.output_bytes()))
+        .and_then(|(inputs, outputs)| inputs.checked_add(outputs))
+        .and_then(|bytes| {
+            bytes.checked_add(TX_FIXED_BYTES + compact_size(inputs) + compact_size(outputs))
+        })
+        .ok_or(SizeError::TooLarge)?;
+    let witness = if script.is_segwit() {
+        inputs
+            .checked_mul(input_witness)
+            .and_then(|bytes| bytes.checked_add(SEGWIT_MARKER_WEIGHT))
+            .ok_or(SizeError::TooLarge)?
+    } else {
+        0
+    };
+    let weight = base
+        .checked_mul(WITNESS_SCALE)
+        .and_then(|weight| weight.checked_add(witness))
+        .ok_or(SizeError::TooLarge)?;
+    Ok(TxSize {
+        weight,
+        vsize: weight.div_ceil(WITNESS_SCALE),
+    })
+}
+
+/// Sats paid at `rate` sat/vB, rounded up to a whole sat
+pub fn fee(vsize: u64, rate: f64) -> u64 {
+    (vsize as f64 * rate.max(0.0)).ceil() as u64
+}
+
+/// Bytes of Bitcoin's variable-length integer encoding
+fn compact_size(n: u64) -> u64 {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// `len` bytes preceded by their compact-size length
+fn with_length(len: u64) -> u64 {
+    compact_size(len) + len
+}
+
+/// Script push of `len` bytes, opcode included
+fn push(len: u64) -> u64 {
+    match len {
+        0..=75 => 1 + len,
+        76..=0xff => 2 + len,
+        _ => 3 + len,
+    }
+}
+
+/// Witness stack of items with the given sizes
+fn witness(items: &[u64]) -> u64 {
+    compact_size(items.len() as u64) + items.iter().map(|&len| with_length(len)).sum::<u64>()
+}
+
+/// `OP_m <n keys> OP_n OP_CHECKMULTISIG`
+fn multisig_script(n: u8) -> u64 {
+    3 + n as u64 * push(COMPRESSED_KEY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn vsize(script: ScriptType, inputs: u64, outputs: u64) -> u64 {
+        estimate(script, inputs, outputs).unwrap().vsize
+    }
+
+    /// Estimated weight of `inputs` coins of `script` type paying `outputs`
+    fn weight_paying(script: ScriptType, inputs: u64, outputs: &[ScriptType]) -> u64 {
+        let count = outputs.len() as u64;
+        let same = estimate(script, inputs, count).unwrap().weight;
+        let paid: u64 = outputs.iter().map(|output| output.output_bytes()).sum();
+        same - count * script.output_bytes() * WITNESS_SCALE + paid * WITNESS_SCALE
+    }
+
+    #[test]
+    fn typical_payments_match_well_known_sizes() {
+        // One coin to a payee plus change
+        assert_eq!(vsize(ScriptType::P2pkh, 1, 2), 226);
+        assert_eq!(vsize(ScriptType::P2wpkh, 1, 2), 141);
+        assert_eq!(vsize(ScriptType::P2wpkh, 1, 1), 110);
+        assert_eq!(vsize(ScriptType::P2tr, 1, 1), 111);
+        assert_eq!(vsize(ScriptType::P2tr, 1, 2), 154);
+        assert_eq!(estimate(ScriptType::P2wpkh, 1, 2).unwrap().weight, 562);
+    }
+
+    #[test]
+    fn multisig_inputs_match_well_known_sizes() {
+        // 2-of-3: 297-byte P2SH input, 104.5 vB P2WSH input
+        let (base, witness) = ScriptType::P2shMultisig { m: 2, n: 3 }.input_bytes();
+        assert_eq!((base, witness), (297, 0));
+        let (base, witness) = ScriptType::P2wshMultisig { m: 2, n: 3 }.input_bytes();
+        assert_eq!(base * 4 + witness, 418);
+        assert_eq!(vsize(ScriptType::P2wshMultisig { m: 2, n: 3 }, 1, 2), 201);
+        assert_eq!(vsize(ScriptType::P2shMultisig { m: 2, n: 3 }, 1, 2), 371);
+    }
+
+    #[test]
+    fn mainnet_p2pkh_spend() {
+        // fc12dfcb4723715a456c6984e298e00c479706067da81be969e8085544b0ba08:
+        // three inputs, two of them signed in 71 bytes, paying two P2PKH outputs
+        let weight = weight_paying(ScriptType::P2pkh, 3, &[ScriptType::P2pkh; 2]);
+        assert_eq!(weight, 2080 + 2 * WITNESS_SCALE);
+        assert_eq!(weight.div_ceil(WITNESS_SCALE), 520 + 2);
+    }
+
+    #[test]
+    fn mainnet_p2wpkh_spend() {
+        // 6c972fe677b6ed76e51c6eb9eb3f50958932919f1858948bbc1be0b1474ccca9:
+        // one input signed in 71 bytes, paying P2SH and P2WPKH
+        let outputs = [ScriptType::P2shMultisig { m: 1, n: 1 }, ScriptType::P2wpkh];
+        let weight = weight_paying(ScriptType::P2wpkh, 1, &outputs);
+        assert_eq!(weight, 565 + 1);
+        assert_eq!(weight.div_ceil(WITNESS_SCALE), 142);
+    }
+
+    #[test]
+    fn mainnet_p2tr_spend() {
+        // 9ba9d23c3e21d1d2f3fd658cd60043553c80cdae7885b5ee29c10cda4b5b715f:
+        // one key-path input whose signature names SIGHASH_ALL, paying P2TR
+        // and P2WSH
+        let outputs = [ScriptType::P2tr, ScriptType::P2wshMultisig { m: 1, n: 1 }];
+        let weight = weight_paying(ScriptType::P2tr, 1, &outputs);
+        assert_eq!(weight, 617 - 1);
+        assert_eq!(weight.div_ceil(WITNESS_SCALE), 155 - 1);
+    }
+
+    #[test]
+    fn mainnet_p2wsh_multisig_spend() {
+        // 37090d6fafabd4cd0ed9d240b566ce3d055ca4e539adfc70a30a71992e8c3e5e:
+        // one 2-of-3 input with two 71-byte signatures, paying P2PKH and P2WSH
+        let script = ScriptType::P2wshMultisig { m: 2, n: 3 };
+        let weight = weight_paying(script, 1, &[ScriptType::P2pkh, script]);
+        assert_eq!(weight, 766 + 2);
+        assert_eq!(weight.div_ceil(WITNESS_SCALE), 192);
+    }
+
+    #[test]
+    fn counts_above_252_use_longer_length_prefixes() {
+        let small = estimate(ScriptType::P2pkh, 252, 1).unwrap();
+        let large = estimate(ScriptType::P2pkh, 253, 1).unwrap();
+        assert_eq!(large.vsize - small.vsize, 148 + 2);
+    }
+
+    #[test]
+    fn invalid_shapes_are_rejected() {
+        assert_eq!(estimate(ScriptType::P2tr, 0, 1), Err(SizeError::NoInputs));
+        assert_eq!(estimate(ScriptType::P2tr, 1, 0), Err(SizeError::NoOutputs));
+        assert_eq!(
+            estimate(ScriptType::P2wshMultisig { m: 3, n: 2 }, 1, 1),
+            Err(SizeError::BadThreshold { m: 3, n: 2 })
+        );
+        assert_eq!(
+            estimate(ScriptType::P2shMultisig { m: 2, n: 16 }, 1, 1),
+            Err(SizeError::TooManyKeys { max: 15 })
+        );
+        assert!(estimate(ScriptType::P2wshMultisig { m: 2, n: 16 }, 1, 1).is_ok());
+        assert_eq!(
+            estimate(ScriptType::P2pkh, u64::MAX, 1),
+            Err(SizeError::TooLarge)
+        );
+        assert_eq!(
+            estimate(ScriptType::P2wpkh, 1, u64::MAX),
+            Err(SizeError::TooLarge)
+        );
+        // Fits in bytes, but not in weight units
+        assert_eq!(
+            estimate(ScriptType::P2pkh, u64::MAX / 4 / 148 + 1, 1),
+            Err(SizeError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn fees_round_up_to_whole_sats() {
+        assert_eq!(fee(141, 10.0), 1_410);
+        assert_eq!(fee(141, 1.5), 212);
+        assert_eq!(fee(141, -1.0), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn every_input_and_output_adds_size(inputs in 1..500u64, outputs in 1..500u64) {
+            for script in [ScriptType::P2pkh, ScriptType::P2wpkh, ScriptType::P2tr] {
+                let size = estimate(script, inputs, outputs).unwrap();
+                prop_assert!(estimate(script, inputs + 1, outputs).unwrap().vsize > size.vsize);
+                prop_assert!(estimate(script, inputs, outputs + 1).unwrap().vsize > size.vsize);
+                prop_assert!(size.vsize * 4 >= size.weight && size.vsize * 4 < size.weight + 4);
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Watch-only addresses and extended public keys, saved between runs.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::address::Address;
+use crate::paths;
+use crate::xpub::{self, ExtendedPubKey};
+
+/// Name of the watchlist file inside [`paths::config_dir`]
+const WATCHLIST_FILE: &str = "watchlist.json";
+
+/// What a watchlist entry tracks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchTarget {
+    Address(Address),
+    /// Every used receive and change address of an account
+    Xpub(ExtendedPubKey),
+}
+
+impl WatchTarget {
+    /// An address or an `xpub`/`ypub`/`zpub`, checksums verified
+    pub fn parse(input: &str) -> Result<Self, String> {
+        if xpub::is_extended_key(input) {
+            ExtendedPubKey::parse(input)
+                .map(WatchTarget::Xpub)
+                .map_err(|e| e.to_string())
+        } else {
+            Address::parse(input)
+                .map(WatchTarget::Address)
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    /// Short type name, such as `P2WPKH` or `zpub`
+    pub fn kind_label(&self) -> String {
+        match self {
+            WatchTarget::Address(address) => address.kind().label(),
+            WatchTarget::Xpub(key) => key.kind().prefix().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for WatchTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatchTarget::Address(address) => address.fmt(f),
+            WatchTarget::Xpub(key) => key.fmt(f),
+        }
+    }
+}
+
+/// One watched address or key with the user's name for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchEntry {
+    pub label: String,
+    pub target: WatchTarget,
+}
+
+/// On-disk form; targets are kept as text and checked again on load
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WatchlistContents {
+    #[serde(default)]
+    entries: Vec<StoredEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredEntry {
+    #[serde(default)]
+    label: String,
+    target: String,
+}
+
+/// JSON file listing the watched entries
+#[derive(Debug, Clone)]
+pub struct WatchlistFile {
+    path: PathBuf,
+}
+
+impl WatchlistFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The watchlist in the per-user config directory
+    pub fn default_location() -> Self {
+        Self::new(paths::config_dir().join(WATCHLIST_FILE))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Saved entries; a missing file means none, and entries that no longer
+    /// validate are skipped with a warning
+    pub fn load(&self) -> io::Result<Vec<WatchEntry>> {
+        let contents: WatchlistContents = match std::fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => WatchlistContents::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(contents
+            .entries
+            .into_iter()
+            .filter_map(|stored| match WatchTarget::parse(&stored.target) {
+                Ok(target) => Some(WatchEntry {
+                    label: stored.label,
+                    target,
+                }),
+                Err(e) => {
+                    warn!("Skipping watchlist entry {}: {e}", stored.target);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    pub fn save(&self, entries: &[WatchEntry]) -> io::Result<()> {
+        if let Some(parent) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents = WatchlistContents {
+            entries: entries
+                .iter()
+                .map(|entry| StoredEntry {
+                    label: entry.label.clone(),
+                    target: entry.target.to_string(),
+                })
+                .collect(),
+        };
+        let json = serde_json::to_string_pretty(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(&self.path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    #[test]
+    fn targets_are_told_apart() {
+        let address = WatchTarget::parse(" BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4 ").unwrap();
+        assert_eq!(address.kind_label(), "P2WPKH");
+        assert_eq!(
+            address.to_string(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(WatchTarget::parse(ZPUB).unwrap().kind_label(), "zpub");
+        assert_eq!(
+            WatchTarget::parse("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Err("Checksum does not match; check for typos".to_string())
+        );
+    }
+
+    #[test]
+    fn entries_round_trip_and_invalid_ones_are_skipped() {
+        let nanos = std::time::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("ifb-watchlist-{}-{nanos}", std::process::id()));
+        let file = WatchlistFile::new(dir.join("watchlist.json"));
+        assert_eq!(file.load().unwrap(), Vec::new());
+
+        let entries = vec![
+            WatchEntry {
+                label: "Cold storage".into(),
+                target: WatchTarget::parse(ZPUB).unwrap(),
+            },
+            WatchEntry {
+                label: String::new(),
+                target: WatchTarget::parse("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").unwrap(),
+            },
+        ];
+        file.save(&entries).unwrap();
+        assert_eq!(file.load().unwrap(), entries);
+
+        std::fs::write(
+            file.path(),
+            r#"{"entries":[{"label":"typo","target":"1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb"},
+                           {"target":"3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy"}]}"#,
+        )
+        .unwrap();
+        let loaded = file.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].target.kind_label(), "P2SH");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,272 @@
+//! Extended public keys (BIP 32) and the receive and change addresses
+//! derived from them, without any private key material.
+//!
+//! The version prefix picks the script type, as wallets do: `xpub` for
+//! P2PKH (BIP 44), `ypub` for P2SH-wrapped P2WPKH (BIP 49) and `zpub` for
+//! native P2WPKH (BIP 84).
+
+use hmac::{Hmac, Mac};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::PrimeField;
+use k256::{FieldBytes, ProjectivePoint, PublicKey, Scalar};
+use sha2::Sha512;
+use thiserror::Error;
+
+use crate::address::{self, Address, AddressError};
+
+/// Serialized length: version, depth, parent fingerprint, child number,
+/// chain code and compressed key
+const SERIALIZED_LENGTH: usize = 78;
+/// Child numbers from here on are hardened and need the private key
+const HARDENED: u32 = 1 << 31;
+const PRIVATE_VERSIONS: [u32; 3] = [0x0488_ade4, 0x049d_7878, 0x04b2_430c];
+
+/// Chain of receive addresses; 1 is the change chain
+pub const RECEIVE_CHAIN: u32 = 0;
+pub const CHANGE_CHAIN: u32 = 1;
+
+/// Why an extended key was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum XpubError {
+    #[error(transparent)]
+    Encoding(#[from] AddressError),
+    #[error("Extended keys are {SERIALIZED_LENGTH} bytes long")]
+    BadLength,
+    #[error("That is a private key; only xpub, ypub and zpub are accepted")]
+    PrivateKey,
+    #[error("Only mainnet xpub, ypub and zpub keys are supported")]
+    UnknownVersion,
+    #[error("Not a valid public key")]
+    InvalidKey,
+    #[error("Hardened child {0} cannot be derived from a public key")]
+    Hardened(u32),
+}
+
+/// Address type of everything derived from a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    /// `xpub`
+    P2pkh,
+    /// `ypub`
+    P2shP2wpkh,
+    /// `zpub`
+    P2wpkh,
+}
+
+impl ScriptKind {
+    fn version(self) -> u32 {
+        match self {
+            ScriptKind::P2pkh => 0x0488_b21e,
+            ScriptKind::P2shP2wpkh => 0x049d_7cb2,
+            ScriptKind::P2wpkh => 0x04b2_4746,
+        }
+    }
+
+    /// Prefix of the serialized key, such as `zpub`
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ScriptKind::P2pkh => "xpub",
+            ScriptKind::P2shP2wpkh => "ypub",
+            ScriptKind::P2wpkh => "zpub",
+        }
+    }
+
+    fn from_version(version: u32) -> Option<Self> {
+        [
+            ScriptKind::P2pkh,
+            ScriptKind::P2shP2wpkh,
+            ScriptKind::P2wpkh,
+        ]
+        .into_iter()
+        .find(|kind| kind.version() == version)
+    }
+}
+
+/// A BIP 32 extended public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedPubKey {
+    kind: ScriptKind,
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+    chain_code: [u8; 32],
+    /// Compressed SEC1 encoding
+    key: [u8; 33],
+}
+
+/// Whether `input` looks like an extended key rather than an address
+pub fn is_extended_key(input: &str) -> bool {
+    let input = input.trim();
+    input.len() > 100
+        && ["xpub", "ypub", "zpub", "xprv", "yprv", "zprv"]
+            .iter()
+            .any(|prefix| input.starts_with(prefix))
+}
+
+impl ExtendedPubKey {
+    pub fn parse(input: &str) -> Result<Self, XpubError> {
+        let data = address::base58check_decode(input.trim())?;
+        if data.len() != SERIALIZED_LENGTH {
+            return Err(XpubError::BadLength);
+        }
+        let version = u32::from_be_bytes(data[0..4].try_into().unwrap());
+        if PRIVATE_VERSIONS.contains(&version) {
+            return Err(XpubError::PrivateKey);
+        }
+        let kind = ScriptKind::from_version(version).ok_or(XpubError::UnknownVersion)?;
+        let key: [u8; 33] = data[45..78].try_into().unwrap();
+        PublicKey::from_sec1_bytes(&key).map_err(|_| XpubError::InvalidKey)?;
+        Ok(Self {
+            kind,
+            depth: data[4],
+            parent_fingerprint: data[5..9].try_into().unwrap(),
+            child_number: u32::from_be_bytes(data[9..13].try_into().unwrap()),
+            chain_code: data[13..45].try_into().unwrap(),
+            key,
+        })
+    }
+
+    pub fn kind(&self) -> ScriptKind {
+        self.kind
+    }
+
+    /// Non-hardened child `index` (CKDpub)
+    pub fn child(&self, index: u32) -> Result<Self, XpubError> {
+        if index >= HARDENED {
+            return Err(XpubError::Hardened(index));
+        }
+        let mut mac = Hmac::<Sha512>::new_from_slice(&self.chain_code)
+            .expect("HMAC accepts keys of any length");
+        mac.update(&self.key);
+        mac.update(&index.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+        let (tweak, chain_code) = digest.split_at(32);
+
+        // A tweak at or above the curve order, or a child at infinity, is
+        // invalid; BIP 32 says to skip to the next index, which no wallet has
+        // ever had to do
+        let tweak = Option::<Scalar>::from(Scalar::from_repr(FieldBytes::clone_from_slice(tweak)))
+            .ok_or(XpubError::InvalidKey)?;
+        let parent = PublicKey::from_sec1_bytes(&self.key).map_err(|_| XpubError::InvalidKey)?;
+        let point = ProjectivePoint::GENERATOR * tweak + parent.to_projective();
+        let child = PublicKey::from_affine(point.to_affine()).map_err(|_| XpubError::InvalidKey)?;
+
+        Ok(Self {
+            kind: self.kind,
+            depth: self.depth.saturating_add(1),
+            parent_fingerprint: address::hash160(&self.key)[..4].try_into().unwrap(),
+            child_number: index,
+            chain_code: chain_code.try_into().unwrap(),
+            key: child
+                .to_encoded_point(true)
+                .as_bytes()
+                .try_into()
+                .expect("compressed points are 33 bytes"),
+        })
+    }
+
+    /// Address of this key itself, in the key's script type
+    pub fn address(&self) -> Address {
+        let key_hash = address::hash160(&self.key);
+        match self.kind {
+            ScriptKind::P2pkh => Address::p2pkh(&key_hash),
+            ScriptKind::P2shP2wpkh => {
+                let mut redeem_script = vec![0x00, 0x14];
+                redeem_script.extend_from_slice(&key_hash);
+                Address::p2sh(&address::hash160(&redeem_script))
+            }
+            ScriptKind::P2wpkh => Address::p2wpkh(&key_hash),
+        }
+    }
+
+    /// Address `index` on `chain` ([`RECEIVE_CHAIN`] or [`CHANGE_CHAIN`])
+    pub fn derive(&self, chain: u32, index: u32) -> Result<Address, XpubError> {
+        Ok(self.child(chain)?.child(index)?.address())
+    }
+}
+
+impl std::fmt::Display for ExtendedPubKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut data = Vec::with_capacity(SERIALIZED_LENGTH);
+        data.extend_from_slice(&self.kind.version().to_be_bytes());
+        data.push(self.depth);
+        data.extend_from_slice(&self.parent_fingerprint);
+        data.extend_from_slice(&self.child_number.to_be_bytes());
+        data.extend_from_slice(&self.chain_code);
+        data.extend_from_slice(&self.key);
+        f.write_str(&address::base58check_encode(&data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// BIP 84 test vector account key, from the "abandon … about" mnemonic
+    const ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    #[test]
+    fn ckdpub_matches_bip32_test_vector_1() {
+        // m/0'/1 from m/0'
+        let parent = ExtendedPubKey::parse("xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw").unwrap();
+        assert_eq!(
+            parent.child(1).unwrap().to_string(),
+            "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ"
+        );
+        assert_eq!(parent.child(HARDENED), Err(XpubError::Hardened(HARDENED)));
+    }
+
+    #[test]
+    fn zpub_derives_native_segwit_addresses() {
+        let key = ExtendedPubKey::parse(ZPUB).unwrap();
+        assert_eq!(key.kind(), ScriptKind::P2wpkh);
+        assert_eq!(key.to_string(), ZPUB);
+        let address = |chain, index| key.derive(chain, index).unwrap().to_string();
+        assert_eq!(
+            address(RECEIVE_CHAIN, 0),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(
+            address(RECEIVE_CHAIN, 1),
+            "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g"
+        );
+        assert_eq!(
+            address(CHANGE_CHAIN, 0),
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+        );
+    }
+
+    #[test]
+    fn xpub_derives_legacy_addresses() {
+        // BIP 44 account of the same mnemonic
+        let key = ExtendedPubKey::parse("xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj").unwrap();
+        assert_eq!(key.kind(), ScriptKind::P2pkh);
+        assert_eq!(
+            key.derive(RECEIVE_CHAIN, 0).unwrap().to_string(),
+            "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"
+        );
+    }
+
+    #[test]
+    fn malformed_keys_are_rejected() {
+        let mut typo = ZPUB.to_string();
+        typo.replace_range(20..21, "A");
+        assert_eq!(
+            ExtendedPubKey::parse(&typo),
+            Err(XpubError::Encoding(AddressError::BadChecksum))
+        );
+        assert_eq!(
+            ExtendedPubKey::parse("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"),
+            Err(XpubError::BadLength)
+        );
+        // BIP 32 test vector 1 master private key
+        assert_eq!(
+            ExtendedPubKey::parse("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi"),
+            Err(XpubError::PrivateKey)
+        );
+        assert!(is_extended_key(ZPUB));
+        assert!(!is_extended_key(
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        ));
+    }
+}